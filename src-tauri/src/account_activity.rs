@@ -0,0 +1,121 @@
+use crate::config::get_config_file;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const ACCOUNT_ACTIVITY_FILE: &str = "account-activity.json";
+const ACCOUNT_ACTIVITY_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct AccountActivity {
+    /// Unix ms of the last successful switch to this account.
+    #[serde(default, rename = "lastUsedAt", skip_serializing_if = "Option::is_none")]
+    pub last_used_at: Option<i64>,
+}
+
+/// Per-account usage bookkeeping, keyed by profile id and stored next to the app config.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountActivityStore {
+    #[serde(default)]
+    pub version: u32,
+    #[serde(default)]
+    pub accounts: BTreeMap<String, AccountActivity>,
+}
+
+impl Default for AccountActivityStore {
+    fn default() -> Self {
+        Self {
+            version: ACCOUNT_ACTIVITY_VERSION,
+            accounts: BTreeMap::new(),
+        }
+    }
+}
+
+impl AccountActivityStore {
+    pub fn get(&self, profile_id: &str) -> AccountActivity {
+        self.accounts.get(profile_id).cloned().unwrap_or_default()
+    }
+
+    /// Moves the entry for `from` to `to` unless `to` already has one.
+    pub fn rename_id(&mut self, from: &str, to: &str) {
+        if from == to || self.accounts.contains_key(to) {
+            return;
+        }
+        if let Some(activity) = self.accounts.remove(from) {
+            self.accounts.insert(to.to_string(), activity);
+        }
+    }
+}
+
+pub fn get_account_activity_file() -> PathBuf {
+    get_config_file().with_file_name(ACCOUNT_ACTIVITY_FILE)
+}
+
+fn load_account_activity_from(path: &Path) -> AccountActivityStore {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<AccountActivityStore>(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_account_activity_to(path: &Path, store: &AccountActivityStore) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+
+    let next = AccountActivityStore {
+        version: ACCOUNT_ACTIVITY_VERSION,
+        accounts: store.accounts.clone(),
+    };
+    let content = serde_json::to_string_pretty(&next)
+        .map_err(|e| format!("Failed to serialize account activity: {}", e))?;
+    fs::write(path, content).map_err(|e| format!("Failed to write account activity: {}", e))
+}
+
+pub fn load_account_activity() -> AccountActivityStore {
+    load_account_activity_from(&get_account_activity_file())
+}
+
+/// Runs `update` against the stored activity and persists the result.
+pub fn update_account_activity(update: impl FnOnce(&mut AccountActivityStore)) -> Result<(), String> {
+    let path = get_account_activity_file();
+    let mut store = load_account_activity_from(&path);
+    update(&mut store);
+    save_account_activity_to(&path, &store)
+}
+
+pub fn record_account_used(profile_id: &str, used_at: i64) -> Result<(), String> {
+    update_account_activity(|store| {
+        store
+            .accounts
+            .entry(profile_id.to_string())
+            .or_default()
+            .last_used_at = Some(used_at);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load_account_activity_from, save_account_activity_to, AccountActivity, AccountActivityStore};
+
+    #[test]
+    fn round_trips_last_used_timestamps() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let path = temp.path().join("account-activity.json");
+        assert_eq!(load_account_activity_from(&path).get("user-1"), AccountActivity::default());
+
+        let mut store = AccountActivityStore::default();
+        store.accounts.insert(
+            "user-1".to_string(),
+            AccountActivity {
+                last_used_at: Some(1_700_000_000_000),
+            },
+        );
+        save_account_activity_to(&path, &store).expect("save");
+
+        let loaded = load_account_activity_from(&path);
+        assert_eq!(loaded.get("user-1").last_used_at, Some(1_700_000_000_000));
+        assert_eq!(loaded.get("user-2").last_used_at, None);
+    }
+}
@@ -0,0 +1,118 @@
+use crate::account_files::ParsedAccountFile;
+use crate::account_status::evaluate_token_status;
+use crate::{extract_info_from_auth, extract_profile_id_from_auth, AuthInfo};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+struct CachedAccountFile {
+    file: ParsedAccountFile,
+    info: AuthInfo,
+    profile_id: String,
+}
+
+/// Parsed profiles and their decoded JWT claims, keyed by path and valid while the mtime is unchanged.
+#[derive(Default)]
+pub struct AccountScanCache {
+    entries: Mutex<HashMap<PathBuf, CachedAccountFile>>,
+}
+
+impl AccountScanCache {
+    pub fn get(&self, path: &Path, modified_at: i64) -> Option<ParsedAccountFile> {
+        let entries = self.entries.lock().ok()?;
+        entries
+            .get(path)
+            .filter(|entry| entry.file.modified_at == modified_at)
+            .map(|entry| entry.file.clone())
+    }
+
+    pub fn insert(&self, file: &ParsedAccountFile) {
+        let info = extract_info_from_auth(&file.auth, 0);
+        let profile_id = extract_profile_id_from_auth(&file.auth, Some(&file.path));
+        self.store(file, info, profile_id);
+    }
+
+    fn store(&self, file: &ParsedAccountFile, info: AuthInfo, profile_id: String) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert(
+                file.path.clone(),
+                CachedAccountFile {
+                    file: file.clone(),
+                    info,
+                    profile_id,
+                },
+            );
+        }
+    }
+
+    /// Decoded info and profile id for `file`, decoding only when the cached entry is stale.
+    pub fn auth_info(&self, file: &ParsedAccountFile, refresh_threshold_minutes: i64) -> (AuthInfo, String) {
+        let cached = self.entries.lock().ok().and_then(|entries| {
+            entries
+                .get(&file.path)
+                .filter(|entry| entry.file.modified_at == file.modified_at)
+                .map(|entry| (entry.info.clone(), entry.profile_id.clone()))
+        });
+
+        let (mut info, profile_id) = match cached {
+            Some(value) => value,
+            None => {
+                let info = extract_info_from_auth(&file.auth, refresh_threshold_minutes);
+                let profile_id = extract_profile_id_from_auth(&file.auth, Some(&file.path));
+                self.store(file, info.clone(), profile_id.clone());
+                (info, profile_id)
+            }
+        };
+        // Token status depends on the clock, so it is re-evaluated on every read.
+        info.token_status =
+            evaluate_token_status(info.expires_at, chrono::Utc::now().timestamp(), refresh_threshold_minutes);
+        (info, profile_id)
+    }
+
+    /// Drops entries for files that were not seen by the latest scan.
+    pub fn retain_paths(&self, seen: &HashSet<PathBuf>) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.retain(|path, _| seen.contains(path));
+        }
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.entries.lock().map(|entries| entries.len()).unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AccountScanCache;
+    use crate::account_files::ParsedAccountFile;
+    use std::collections::HashSet;
+    use std::path::PathBuf;
+
+    fn parsed(path: &str, modified_at: i64) -> ParsedAccountFile {
+        let auth = serde_json::from_str(
+            r#"{"OPENAI_API_KEY":null,"last_refresh":"","tokens":{"access_token":"a","account_id":"acc","id_token":"i","refresh_token":"r"}}"#,
+        )
+        .expect("auth");
+        ParsedAccountFile {
+            path: PathBuf::from(path),
+            modified_at,
+            auth,
+            file_size: 0,
+            content_hash: None,
+        }
+    }
+
+    #[test]
+    fn entries_are_invalidated_by_mtime_and_missing_files() {
+        let cache = AccountScanCache::default();
+        let file = parsed("/accounts/a.json", 100);
+        cache.insert(&file);
+
+        assert!(cache.get(&file.path, 100).is_some());
+        assert!(cache.get(&file.path, 101).is_none());
+
+        cache.retain_paths(&HashSet::new());
+        assert_eq!(cache.len(), 0);
+    }
+}
@@ -0,0 +1,156 @@
+use crate::account_files::validate_readable_account_path;
+use crate::account_usage::{read_auth_file, UsageError, UsageErrorKind};
+use crate::http_client::http_client;
+use crate::http_retry::retry_after_header;
+use crate::{get_accounts_dir, get_codex_auth_file};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+const CREDIT_GRANTS_URL: &str = "https://api.openai.com/dashboard/billing/credit_grants";
+const CREDITS_TIMEOUT: Duration = Duration::from_secs(12);
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CreditGrant {
+    #[serde(rename = "grantAmount")]
+    pub grant_amount: f64,
+    #[serde(rename = "usedAmount")]
+    pub used_amount: f64,
+    /// Unix seconds.
+    #[serde(rename = "effectiveAt")]
+    pub effective_at: Option<i64>,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: Option<i64>,
+}
+
+/// Credit balance in dollars.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CreditInfo {
+    pub granted: f64,
+    pub used: f64,
+    pub remaining: f64,
+    /// Earliest expiry among grants that still have a balance.
+    #[serde(rename = "expiresAt")]
+    pub expires_at: Option<i64>,
+    pub grants: Vec<CreditGrant>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiCreditGrant {
+    #[serde(default)]
+    grant_amount: f64,
+    #[serde(default)]
+    used_amount: f64,
+    effective_at: Option<f64>,
+    expires_at: Option<f64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ApiCreditGrantList {
+    #[serde(default)]
+    data: Vec<ApiCreditGrant>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiCreditSummary {
+    #[serde(default)]
+    total_granted: f64,
+    #[serde(default)]
+    total_used: f64,
+    total_available: Option<f64>,
+    #[serde(default)]
+    grants: ApiCreditGrantList,
+}
+
+fn credits_from_summary(summary: ApiCreditSummary) -> CreditInfo {
+    let grants: Vec<CreditGrant> = summary
+        .grants
+        .data
+        .into_iter()
+        .map(|grant| CreditGrant {
+            grant_amount: grant.grant_amount,
+            used_amount: grant.used_amount,
+            effective_at: grant.effective_at.map(|at| at as i64),
+            expires_at: grant.expires_at.map(|at| at as i64),
+        })
+        .collect();
+    let expires_at = grants
+        .iter()
+        .filter(|grant| grant.used_amount < grant.grant_amount)
+        .filter_map(|grant| grant.expires_at)
+        .min();
+
+    CreditInfo {
+        granted: summary.total_granted,
+        used: summary.total_used,
+        remaining: summary
+            .total_available
+            .unwrap_or((summary.total_granted - summary.total_used).max(0.0)),
+        expires_at,
+        grants,
+    }
+}
+
+/// Credit grants for the profile at `file_path`, using its API key or else its access token.
+/// Accounts without credits (404/403, e.g. ChatGPT plans) yield `None` rather than an error.
+#[tauri::command]
+pub async fn fetch_credits(file_path: String) -> Result<Option<CreditInfo>, UsageError> {
+    let validated_path = validate_readable_account_path(&file_path, &get_accounts_dir(), &get_codex_auth_file())?;
+    let auth = read_auth_file(&validated_path).map_err(UsageError::local)?;
+    let client = http_client(CREDITS_TIMEOUT)
+        .map_err(|e| UsageError::local(format!("Failed to build credits client: {}", e.message)))?;
+
+    let request = match (auth.api_key(), auth.tokens.as_ref()) {
+        (Some(api_key), _) => client.get(CREDIT_GRANTS_URL).bearer_auth(api_key),
+        (None, Some(tokens)) => {
+            let request = client.get(CREDIT_GRANTS_URL).bearer_auth(&tokens.access_token);
+            if tokens.account_id.is_empty() {
+                request
+            } else {
+                request.header("ChatGPT-Account-Id", &tokens.account_id)
+            }
+        }
+        (None, None) => return Err(UsageError::local("Profile has no credentials")),
+    };
+    let network_error = |e: reqwest::Error| UsageError::new(UsageErrorKind::Network, format!("Credits request failed: {}", e));
+    let response = request.header("Accept", "application/json").send().await.map_err(network_error)?;
+    let status = response.status();
+    if matches!(status, reqwest::StatusCode::NOT_FOUND | reqwest::StatusCode::FORBIDDEN) {
+        return Ok(None);
+    }
+    let retry_after = retry_after_header(response.headers());
+    let body = response.text().await.map_err(network_error)?;
+    if !status.is_success() {
+        return Err(UsageError::from_status(status, retry_after, format!("Credits request failed: HTTP {}", status)).with_body(&body));
+    }
+
+    let summary = serde_json::from_str::<ApiCreditSummary>(&body).map_err(|e| {
+        UsageError::new(UsageErrorKind::Parse, format!("Failed to parse credits response: {}", e)).with_body(&body)
+    })?;
+    Ok(Some(credits_from_summary(summary)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{credits_from_summary, ApiCreditSummary};
+
+    #[test]
+    fn summarizes_grants_and_earliest_open_expiry() {
+        let summary: ApiCreditSummary = serde_json::from_str(
+            r#"{
+                "object": "credit_summary",
+                "total_granted": 25.0,
+                "total_used": 7.5,
+                "grants": {"data": [
+                    {"grant_amount": 5.0, "used_amount": 5.0, "effective_at": 1690000000.0, "expires_at": 1700000000.0},
+                    {"grant_amount": 20.0, "used_amount": 2.5, "effective_at": 1695000000.0, "expires_at": 1720000000.0}
+                ]}
+            }"#,
+        )
+        .expect("summary");
+
+        let credits = credits_from_summary(summary);
+        assert_eq!(credits.remaining, 17.5);
+        assert_eq!(credits.expires_at, Some(1_720_000_000));
+        assert_eq!(credits.grants.len(), 2);
+    }
+}
@@ -0,0 +1,200 @@
+use crate::account_files::{
+    archived_accounts_dir, resolve_managed_account_path, scan_account_files, write_private_file_atomic,
+};
+use crate::account_meta::ACCOUNT_META_FILE;
+use crate::{get_accounts_dir, ScanError};
+use serde::Serialize;
+use std::fs;
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{AesMode, CompressionMethod, ZipWriter};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchiveExportResult {
+    pub count: usize,
+    /// Size of the written archive in bytes.
+    pub size: u64,
+    pub skipped: Vec<ScanError>,
+}
+
+/// `content` with `tokens.refresh_token` blanked, so the copy works until the access token expires
+/// but cannot mint new sessions.
+fn without_refresh_token(content: &str) -> Result<String, String> {
+    let mut value: serde_json::Value =
+        serde_json::from_str(content).map_err(|e| format!("Failed to parse authentication file: {}", e))?;
+    if let Some(refresh_token) = value.pointer_mut("/tokens/refresh_token") {
+        *refresh_token = serde_json::Value::String(String::new());
+    }
+    serde_json::to_string_pretty(&value).map_err(|e| format!("Failed to serialize: {}", e))
+}
+
+/// Copies one profile to `target_path`, creating parent folders. Returns the bytes written.
+#[tauri::command]
+pub fn export_account(
+    file_path: String,
+    target_path: String,
+    exclude_refresh_token: Option<bool>,
+    overwrite: Option<bool>,
+) -> Result<u64, String> {
+    let source = resolve_managed_account_path(&file_path, &get_accounts_dir())?;
+    if !source.is_file() {
+        return Err("Account file not found".to_string());
+    }
+    let target = PathBuf::from(target_path.trim());
+    if target.as_os_str().is_empty() || target.is_dir() {
+        return Err("Export target must be a file path".to_string());
+    }
+    if target.exists() && !overwrite.unwrap_or(false) {
+        return Err("Export target already exists".to_string());
+    }
+
+    let mut content = fs::read_to_string(&source).map_err(|e| format!("Failed to read authentication file: {}", e))?;
+    if exclude_refresh_token.unwrap_or(false) {
+        content = without_refresh_token(&content)?;
+    }
+    if let Some(parent) = target.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    write_private_file_atomic(&target, content.as_bytes()).map_err(|e| format!("Failed to write export: {}", e))?;
+    Ok(content.len() as u64)
+}
+
+/// Archive entry name for `path`: relative to the accounts folder, always `/`-separated.
+fn archive_entry_name(path: &Path, accounts_dir: &Path) -> Option<String> {
+    let relative = path.strip_prefix(accounts_dir).ok()?;
+    let parts: Vec<String> = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().to_string())
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+/// Zips `entries` sorted by name so the same accounts always produce the same entry order.
+fn build_archive(mut entries: Vec<(String, Vec<u8>)>, passphrase: Option<&str>) -> zip::result::ZipResult<Vec<u8>> {
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .unix_permissions(0o600);
+    for (name, content) in &entries {
+        match passphrase {
+            Some(passphrase) => writer.start_file(name, options.with_aes_encryption(AesMode::Aes256, passphrase))?,
+            None => writer.start_file(name, options)?,
+        }
+        writer.write_all(content)?;
+    }
+    Ok(writer.finish()?.into_inner())
+}
+
+/// Writes every profile plus `meta.json` into one zip at `target_path`, keeping group folders.
+/// Profiles that cannot be parsed or read are reported in `skipped` instead of failing the export.
+#[tauri::command]
+pub fn export_accounts_archive(
+    target_path: String,
+    include_archived: Option<bool>,
+    passphrase: Option<String>,
+    overwrite: Option<bool>,
+) -> Result<ArchiveExportResult, String> {
+    let target = PathBuf::from(target_path.trim());
+    if target.as_os_str().is_empty() || target.is_dir() {
+        return Err("Export target must be a file path".to_string());
+    }
+    if target.exists() && !overwrite.unwrap_or(false) {
+        return Err("Export target already exists".to_string());
+    }
+    let passphrase = passphrase.filter(|passphrase| !passphrase.is_empty());
+
+    let accounts_dir = get_accounts_dir();
+    let (mut files, mut skipped) = scan_account_files(&accounts_dir, None, None)?;
+    if include_archived.unwrap_or(false) {
+        let (archived, archived_errors) = scan_account_files(&archived_accounts_dir(&accounts_dir), None, None)?;
+        files.extend(archived);
+        skipped.extend(archived_errors);
+    }
+
+    let mut paths: Vec<PathBuf> = files.into_iter().map(|file| file.path).collect();
+    let meta_file = accounts_dir.join(ACCOUNT_META_FILE);
+    if meta_file.is_file() {
+        paths.push(meta_file);
+    }
+    let mut entries = Vec::with_capacity(paths.len());
+    for path in paths {
+        let Some(name) = archive_entry_name(&path, &accounts_dir) else {
+            continue;
+        };
+        match fs::read(&path) {
+            Ok(content) => entries.push((name, content)),
+            Err(error) => skipped.push(ScanError {
+                file_path: path.to_string_lossy().to_string(),
+                reason: format!("Failed to read file: {}", error),
+            }),
+        }
+    }
+
+    let count = entries.len();
+    let archive = build_archive(entries, passphrase.as_deref()).map_err(|e| format!("Failed to build archive: {}", e))?;
+    if let Some(parent) = target.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    write_private_file_atomic(&target, &archive).map_err(|e| format!("Failed to write archive: {}", e))?;
+    Ok(ArchiveExportResult {
+        count,
+        size: archive.len() as u64,
+        skipped,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{archive_entry_name, build_archive, without_refresh_token};
+    use std::io::{Cursor, Read};
+    use std::path::Path;
+
+    #[test]
+    fn blanks_only_the_refresh_token() {
+        let content = r#"{"tokens":{"access_token":"a","account_id":"acc","id_token":"i","refresh_token":"secret"},"extra":1}"#;
+        let stripped: serde_json::Value = serde_json::from_str(&without_refresh_token(content).expect("strip")).expect("json");
+        assert_eq!(stripped["tokens"]["refresh_token"], "");
+        assert_eq!(stripped["tokens"]["access_token"], "a");
+        assert_eq!(stripped["extra"], 1);
+
+        let api_key_only = without_refresh_token(r#"{"OPENAI_API_KEY":"sk"}"#).expect("api key");
+        assert!(api_key_only.contains("\"sk\""));
+    }
+
+    #[test]
+    fn builds_sorted_archive_with_optional_encryption() {
+        let accounts_dir = Path::new("/accounts");
+        assert_eq!(
+            archive_entry_name(&accounts_dir.join("team").join("work.json"), accounts_dir).as_deref(),
+            Some("team/work.json")
+        );
+        assert_eq!(archive_entry_name(Path::new("/elsewhere/a.json"), accounts_dir), None);
+
+        let entries = vec![
+            ("team/work.json".to_string(), b"{\"w\":1}".to_vec()),
+            ("meta.json".to_string(), b"{}".to_vec()),
+            ("personal.json".to_string(), b"{\"p\":1}".to_vec()),
+        ];
+        let plain = build_archive(entries.clone(), None).expect("plain");
+        let mut archive = zip::ZipArchive::new(Cursor::new(plain)).expect("open");
+        let names: Vec<&str> = (0..archive.len()).filter_map(|index| archive.name_for_index(index)).collect();
+        assert_eq!(names, ["meta.json", "personal.json", "team/work.json"]);
+        let mut content = String::new();
+        archive.by_name("team/work.json").expect("entry").read_to_string(&mut content).expect("read");
+        assert_eq!(content, "{\"w\":1}");
+
+        let encrypted = build_archive(entries, Some("hunter2")).expect("encrypted");
+        let mut archive = zip::ZipArchive::new(Cursor::new(encrypted)).expect("open encrypted");
+        assert!(archive.by_name("personal.json").is_err());
+        let mut content = String::new();
+        archive
+            .by_name_decrypt("personal.json", b"hunter2")
+            .expect("decrypt")
+            .read_to_string(&mut content)
+            .expect("read");
+        assert_eq!(content, "{\"p\":1}");
+        assert!(archive.by_name_decrypt("personal.json", b"wrong").is_err());
+    }
+}
@@ -1,19 +1,31 @@
-use crate::CodexAuthFile;
+use crate::account_cache::AccountScanCache;
+use crate::account_meta::is_account_meta_file;
+use crate::error::{AppError, AppResult};
+use crate::webdav_sync::hash_content;
+use crate::{extract_info_from_auth, CodexAuthFile, ScanError};
 use std::fs;
-use std::path::PathBuf;
+use std::io::{ErrorKind, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Subfolder of the accounts dir holding profiles that are kept but hidden from normal use.
+pub const ARCHIVED_DIR: &str = "archived";
 
 #[derive(Debug, Clone)]
 pub struct ParsedAccountFile {
     pub path: PathBuf,
     pub modified_at: i64,
     pub auth: CodexAuthFile,
+    pub file_size: u64,
+    /// SHA-256 of the bytes that were parsed; `None` for entries not read from disk.
+    pub content_hash: Option<String>,
 }
 
-fn is_json_file(path: &PathBuf) -> bool {
+fn is_json_file(path: &Path) -> bool {
     path.extension().and_then(|s| s.to_str()) == Some("json")
 }
 
-fn file_modified_at(path: &PathBuf) -> Result<i64, String> {
+pub fn file_modified_at(path: &PathBuf) -> Result<i64, String> {
     let metadata = fs::metadata(path)
         .map_err(|e| format!("Failed to read account file metadata: {}", e))?;
     metadata
@@ -24,53 +36,292 @@ fn file_modified_at(path: &PathBuf) -> Result<i64, String> {
         .map(|duration| duration.as_millis() as i64)
 }
 
-fn parse_account_file(path: &PathBuf) -> Result<Option<ParsedAccountFile>, String> {
-    if !path.is_file() || !is_json_file(path) {
-        return Ok(None);
+/// Writes `content` to a temporary file next to `target` and renames it into place, so readers
+/// never observe a partially written file. On failure `target` is left untouched.
+pub fn write_file_atomic(target: &Path, content: &[u8]) -> std::io::Result<()> {
+    write_atomic(target, content, false)
+}
+
+static TEMP_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// `<target>.<pid>-<n>.tmp`, distinct for every call so concurrent writers never share one.
+fn unique_temp_path(target: &Path) -> PathBuf {
+    let mut temp_name = target.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(format!(
+        ".{}-{}.tmp",
+        std::process::id(),
+        TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    target.with_file_name(temp_name)
+}
+
+fn write_atomic(target: &Path, content: &[u8], private: bool) -> std::io::Result<()> {
+    let temp_path = unique_temp_path(target);
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    // Private files are owner-only from the moment they exist, not just after the rename.
+    #[cfg(unix)]
+    if private {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    #[cfg(not(unix))]
+    let _ = private;
+    let mut file = options.open(&temp_path)?;
+
+    let result = file.write_all(content).and_then(|()| {
+        drop(file);
+        fs::rename(&temp_path, target)
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Limits `path` to its owner (0600) on Unix; a no-op elsewhere.
+pub fn restrict_permissions(path: &Path) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+/// The permission bits of `path` when its group or others can read it; always `None` off Unix.
+pub fn exposed_permission_mode(path: &Path) -> std::io::Result<Option<u32>> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(path)?.permissions().mode() & 0o777;
+        Ok((mode & 0o044 != 0).then_some(mode))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        Ok(None)
+    }
+}
+
+/// `write_file_atomic` for files holding tokens: the temporary file is created owner-only (0600)
+/// on Unix, so the content is never readable by others.
+pub fn write_private_file_atomic(target: &Path, content: &[u8]) -> std::io::Result<()> {
+    write_atomic(target, content, true)
+}
+
+/// Points `target` at `source` through a symlink created beside it and renamed into place,
+/// replacing whatever file or link was there before.
+pub fn link_file_atomic(target: &Path, source: &Path) -> std::io::Result<()> {
+    let source = fs::canonicalize(source)?;
+    let temp_path = unique_temp_path(target);
+
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(&source, &temp_path)?;
+    #[cfg(windows)]
+    std::os::windows::fs::symlink_file(&source, &temp_path)?;
+    #[cfg(not(any(unix, windows)))]
+    return Err(std::io::Error::new(ErrorKind::Unsupported, "symlinks are not supported"));
+
+    #[cfg(any(unix, windows))]
+    fs::rename(&temp_path, target).inspect_err(|_| {
+        let _ = fs::remove_file(&temp_path);
+    })
+}
+
+/// Whether both paths name the same directory entry. Unlike `paths_match` the final component is
+/// not resolved, so a symlink and the file it points to are different entries.
+pub fn same_dir_entry(a: &Path, b: &Path) -> bool {
+    if a == b {
+        return true;
+    }
+    let parents = (
+        a.parent().and_then(|parent| fs::canonicalize(parent).ok()),
+        b.parent().and_then(|parent| fs::canonicalize(parent).ok()),
+    );
+    let (Some(a_parent), Some(b_parent)) = parents else {
+        return false;
+    };
+    let (Some(a_name), Some(b_name)) = (a.file_name(), b.file_name()) else {
+        return false;
+    };
+
+    #[cfg(target_os = "windows")]
+    {
+        a_parent == b_parent && a_name.to_string_lossy().eq_ignore_ascii_case(&b_name.to_string_lossy())
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        a_parent == b_parent && a_name == b_name
+    }
+}
+
+fn scan_error(path: &Path, reason: impl Into<String>) -> ScanError {
+    ScanError {
+        file_path: path.to_string_lossy().to_string(),
+        reason: reason.into(),
+    }
+}
+
+fn parse_account_file(
+    path: &PathBuf,
+    cache: Option<&AccountScanCache>,
+) -> Option<Result<ParsedAccountFile, ScanError>> {
+    if !path.is_file() || !is_json_file(path) || is_account_meta_file(path) {
+        return None;
+    }
+
+    let modified_at = match file_modified_at(path) {
+        Ok(modified_at) => modified_at,
+        Err(error) => return Some(Err(scan_error(path, error))),
+    };
+    if let Some(cached) = cache.and_then(|cache| cache.get(path, modified_at)) {
+        return Some(Ok(cached));
     }
 
     let content = match fs::read_to_string(path) {
         Ok(content) => content,
-        Err(_) => return Ok(None),
+        Err(error) if error.kind() == ErrorKind::InvalidData => {
+            return Some(Err(scan_error(path, "File is not valid UTF-8")));
+        }
+        Err(error) => return Some(Err(scan_error(path, format!("Failed to read file: {}", error)))),
     };
     let auth = match serde_json::from_str::<CodexAuthFile>(&content) {
         Ok(auth) => auth,
-        Err(_) => return Ok(None),
+        Err(error) => return Some(Err(scan_error(path, format!("Invalid account JSON: {}", error)))),
     };
-
-    Ok(Some(ParsedAccountFile {
+    if !auth.has_credentials() {
+        return Some(Err(scan_error(path, "Account file has neither tokens nor OPENAI_API_KEY")));
+    }
+    let parsed = ParsedAccountFile {
         path: path.clone(),
-        modified_at: file_modified_at(path)?,
+        modified_at,
         auth,
-    }))
+        file_size: content.len() as u64,
+        content_hash: Some(hash_content(content.as_bytes())),
+    };
+    if let Some(cache) = cache {
+        cache.insert(&parsed);
+    }
+    Some(Ok(parsed))
 }
 
-pub fn collect_account_files(
+/// How many folder levels below the scanned dir are searched for profiles.
+pub const MAX_ACCOUNT_DIR_DEPTH: usize = 3;
+
+/// Whether a subfolder of the accounts dir should be searched for profiles.
+pub fn is_account_group_dir(path: &Path, depth: usize) -> bool {
+    let Some(name) = path.file_name().and_then(|value| value.to_str()) else {
+        return false;
+    };
+    path.is_dir() && !name.starts_with('.') && !(depth == 0 && name == ARCHIVED_DIR)
+}
+
+fn scan_dir_into(
     dir: &PathBuf,
+    depth: usize,
     skip_path: Option<&PathBuf>,
-) -> Result<Vec<ParsedAccountFile>, String> {
-    if !dir.exists() {
-        return Ok(Vec::new());
-    }
-
-    let mut parsed_files = Vec::new();
-    let entries = fs::read_dir(dir).map_err(|e| format!("Failed to read accounts directory: {}", e))?;
+    cache: Option<&AccountScanCache>,
+    parsed_files: &mut Vec<ParsedAccountFile>,
+    errors: &mut Vec<ScanError>,
+) -> Result<(), String> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(error) if depth == 0 => return Err(format!("Failed to read accounts directory: {}", error)),
+        Err(error) => {
+            errors.push(scan_error(dir, format!("Failed to read folder: {}", error)));
+            return Ok(());
+        }
+    };
 
     for entry in entries.flatten() {
         let path = entry.path();
-        if skip_path.is_some_and(|skip| paths_match(&path, skip)) {
+        if skip_path.is_some_and(|skip| same_dir_entry(&path, skip)) {
+            continue;
+        }
+
+        if is_account_group_dir(&path, depth) {
+            if depth < MAX_ACCOUNT_DIR_DEPTH {
+                scan_dir_into(&path, depth + 1, skip_path, cache, parsed_files, errors)?;
+            }
             continue;
         }
 
-        if let Some(parsed) = parse_account_file(&path)? {
-            parsed_files.push(parsed);
+        match parse_account_file(&path, cache) {
+            Some(Ok(parsed)) => parsed_files.push(parsed),
+            Some(Err(error)) => errors.push(error),
+            None => {}
         }
     }
 
-    Ok(parsed_files)
+    Ok(())
 }
 
-pub fn resolve_available_account_target(dir: &PathBuf, file_stem: &str) -> PathBuf {
+/// Like `collect_account_files`, but also reports files that look like profiles and could not be read.
+/// Files whose mtime matches an entry in `cache` are not re-read.
+pub fn scan_account_files(
+    dir: &PathBuf,
+    skip_path: Option<&PathBuf>,
+    cache: Option<&AccountScanCache>,
+) -> Result<(Vec<ParsedAccountFile>, Vec<ScanError>), String> {
+    if !dir.exists() {
+        return Ok((Vec::new(), Vec::new()));
+    }
+
+    let mut parsed_files = Vec::new();
+    let mut errors = Vec::new();
+    scan_dir_into(dir, 0, skip_path, cache, &mut parsed_files, &mut errors)?;
+    Ok((parsed_files, errors))
+}
+
+/// Folder of `path` relative to `base` using `/` separators, or `None` for files directly inside it.
+pub fn account_group(path: &Path, base: &Path) -> Option<String> {
+    let parent = path.parent()?;
+    let relative = match parent.strip_prefix(base) {
+        Ok(relative) => relative.to_path_buf(),
+        Err(_) => {
+            let canonical_parent = fs::canonicalize(parent).ok()?;
+            let canonical_base = fs::canonicalize(base).ok()?;
+            canonical_parent.strip_prefix(&canonical_base).ok()?.to_path_buf()
+        }
+    };
+    let group = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<_>>()
+        .join("/");
+    (!group.is_empty()).then_some(group)
+}
+
+pub fn collect_account_files(
+    dir: &PathBuf,
+    skip_path: Option<&PathBuf>,
+) -> Result<Vec<ParsedAccountFile>, String> {
+    scan_account_files(dir, skip_path, None).map(|(parsed_files, _)| parsed_files)
+}
+
+/// Active profiles followed by archived ones, for checks that must see every stored profile.
+pub fn collect_all_account_files(accounts_dir: &PathBuf) -> Result<Vec<ParsedAccountFile>, String> {
+    let mut files = collect_account_files(accounts_dir, None)?;
+    files.extend(collect_account_files(&archived_accounts_dir(accounts_dir), None)?);
+    Ok(files)
+}
+
+pub fn archived_accounts_dir(accounts_dir: &Path) -> PathBuf {
+    accounts_dir.join(ARCHIVED_DIR)
+}
+
+pub fn is_archived_account_path(path: &Path, accounts_dir: &Path) -> bool {
+    let archived_dir = archived_accounts_dir(accounts_dir);
+    path.ancestors()
+        .skip(1)
+        .any(|ancestor| paths_match(&ancestor.to_path_buf(), &archived_dir))
+}
+
+pub fn resolve_available_account_target(dir: &Path, file_stem: &str) -> PathBuf {
     let safe_stem = if file_stem.trim().is_empty() {
         "account"
     } else {
@@ -91,6 +342,117 @@ pub fn resolve_available_account_target(dir: &PathBuf, file_stem: &str) -> PathB
     }
 }
 
+/// Profiles sharing this key describe the same upstream account. Falls back to the email when
+/// `account_id` is empty.
+pub fn duplicate_key(account_id: &str, email: &str) -> Option<String> {
+    let account_id = account_id.trim();
+    if !account_id.is_empty() {
+        return Some(format!("account:{}", account_id));
+    }
+
+    let email = email.trim().to_lowercase();
+    if email.is_empty() || email == "unknown" {
+        return None;
+    }
+    Some(format!("email:{}", email))
+}
+
+/// The stored profile describing the same upstream account as `auth`, by `duplicate_key`.
+pub fn find_duplicate_profile<'a>(files: &'a [ParsedAccountFile], auth: &CodexAuthFile) -> Option<&'a ParsedAccountFile> {
+    let email_of = |auth: &CodexAuthFile| extract_info_from_auth(auth, 0).email;
+    let key = duplicate_key(auth.account_id(), &email_of(auth))?;
+    files
+        .iter()
+        .find(|file| duplicate_key(file.auth.account_id(), &email_of(&file.auth)).as_ref() == Some(&key))
+}
+
+/// SHA-256 of the auth JSON with volatile fields removed, so a re-saved copy still matches its profile.
+pub fn auth_fingerprint(auth: &CodexAuthFile) -> Option<String> {
+    use sha2::{Digest, Sha256};
+
+    let mut value = serde_json::to_value(auth).ok()?;
+    value.as_object_mut()?.remove("last_refresh");
+    let canonical = serde_json::to_string(&value).ok()?;
+    Some(format!("{:x}", Sha256::digest(canonical.as_bytes())))
+}
+
+/// Profile whose file stem (case-insensitive) or upstream account id equals `name_or_id`.
+/// Several matches are reported as an error naming each candidate relative to `base`.
+pub fn find_profile_by_name(
+    account_files: &[ParsedAccountFile],
+    base: &Path,
+    name_or_id: &str,
+) -> Result<PathBuf, String> {
+    let query = name_or_id.trim();
+    if query.is_empty() {
+        return Err("Account name cannot be empty".to_string());
+    }
+
+    let matches: Vec<&PathBuf> = account_files
+        .iter()
+        .filter(|file| {
+            file.path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .is_some_and(|stem| stem.eq_ignore_ascii_case(query))
+                || file.auth.account_id() == query
+        })
+        .map(|file| &file.path)
+        .collect();
+
+    match matches.as_slice() {
+        [] => Err(format!("No account named '{}'", query)),
+        [path] => Ok((*path).clone()),
+        candidates => {
+            let mut names: Vec<String> = candidates
+                .iter()
+                .map(|path| {
+                    path.strip_prefix(base)
+                        .unwrap_or(path)
+                        .to_string_lossy()
+                        .replace('\\', "/")
+                })
+                .collect();
+            names.sort();
+            Err(format!("'{}' matches several accounts: {}", query, names.join(", ")))
+        }
+    }
+}
+
+/// Picks the profile matching `active_auth` by fingerprint or access token, for auth files without an
+/// `account_id`. When several profiles match, `preferred` wins if it is one of them.
+pub fn match_active_profile(
+    account_files: &[ParsedAccountFile],
+    active_auth: &CodexAuthFile,
+    preferred: Option<&PathBuf>,
+) -> Option<PathBuf> {
+    let fingerprint = auth_fingerprint(active_auth);
+    let access_token = active_auth
+        .tokens
+        .as_ref()
+        .map(|tokens| tokens.access_token.as_str())
+        .filter(|token| !token.is_empty());
+
+    let matches: Vec<&PathBuf> = account_files
+        .iter()
+        .filter(|file| {
+            (fingerprint.is_some() && auth_fingerprint(&file.auth) == fingerprint)
+                || access_token.is_some_and(|token| {
+                    file.auth
+                        .tokens
+                        .as_ref()
+                        .is_some_and(|tokens| tokens.access_token == token)
+                })
+        })
+        .map(|file| &file.path)
+        .collect();
+
+    preferred
+        .and_then(|preferred| matches.iter().find(|path| paths_match(path, preferred)).copied())
+        .or_else(|| matches.first().copied())
+        .cloned()
+}
+
 pub fn files_have_same_content(left: &PathBuf, right: &PathBuf) -> bool {
     match (fs::read(left), fs::read(right)) {
         (Ok(left_bytes), Ok(right_bytes)) => left_bytes == right_bytes,
@@ -120,32 +482,75 @@ pub fn paths_match(a: &PathBuf, b: &PathBuf) -> bool {
     }
 }
 
-pub fn resolve_managed_account_path(file_path: &str, accounts_dir: &PathBuf) -> Result<PathBuf, String> {
+const OUTSIDE_ACCOUNTS_ERROR: &str = "Account path is outside the managed accounts directory";
+
+/// Canonicalizes a path sent by the webview and checks it names a `.json` file inside
+/// `accounts_dir`. Relative (including Windows drive-relative) paths and `..` components are
+/// refused up front; symlinks resolving outside the folder are refused after canonicalization.
+pub fn validate_account_path(file_path: &str, accounts_dir: &Path) -> AppResult<PathBuf> {
     let path = PathBuf::from(file_path);
+    if !path.is_absolute() || path.components().any(|component| component == Component::ParentDir) {
+        return Err(AppError::path_outside_accounts(OUTSIDE_ACCOUNTS_ERROR));
+    }
     if path.extension().and_then(|s| s.to_str()) != Some("json") {
-        return Err("Only .json account files are supported".to_string());
+        return Err(AppError::parse("Only .json account files are supported"));
     }
 
-    let resolved_path = fs::canonicalize(&path)
-        .map_err(|e| format!("Failed to resolve account path: {}", e))?;
+    let resolved_path =
+        fs::canonicalize(&path).map_err(|e| AppError::not_found(format!("Failed to resolve account path: {}", e)))?;
     let resolved_accounts_dir = if accounts_dir.exists() {
         fs::canonicalize(accounts_dir)
-            .map_err(|e| format!("Failed to resolve accounts directory: {}", e))?
+            .map_err(|e| AppError::io(format!("Failed to resolve accounts directory: {}", e)))?
     } else {
-        accounts_dir.clone()
+        accounts_dir.to_path_buf()
     };
 
     if !resolved_path.starts_with(&resolved_accounts_dir) {
-        return Err("Account path is outside the managed accounts directory".to_string());
+        return Err(AppError::path_outside_accounts(OUTSIDE_ACCOUNTS_ERROR));
     }
 
     Ok(resolved_path)
 }
 
+/// Like `validate_account_path`, but also accepts the live `auth.json` for read-only commands.
+pub fn validate_readable_account_path(file_path: &str, accounts_dir: &Path, codex_auth: &Path) -> AppResult<PathBuf> {
+    let error = match validate_account_path(file_path, accounts_dir) {
+        Ok(path) => return Ok(path),
+        Err(error) => error,
+    };
+    let requested = fs::canonicalize(file_path).ok();
+    match (requested, fs::canonicalize(codex_auth).ok()) {
+        (Some(requested), Some(live)) if requested == live => Ok(requested),
+        _ => Err(error),
+    }
+}
+
+pub fn resolve_managed_account_path(file_path: &str, accounts_dir: &Path) -> Result<PathBuf, String> {
+    validate_account_path(file_path, accounts_dir).map_err(|error| error.message)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::resolve_managed_account_path;
+    use super::{
+        account_group,
+        archived_accounts_dir,
+        duplicate_key,
+        exposed_permission_mode,
+        find_duplicate_profile,
+        find_profile_by_name,
+        is_archived_account_path,
+        link_file_atomic,
+        match_active_profile,
+        resolve_managed_account_path,
+        scan_account_files,
+        validate_account_path,
+        validate_readable_account_path,
+        write_file_atomic,
+        write_private_file_atomic,
+        ParsedAccountFile,
+    };
     use std::fs;
+    use std::path::PathBuf;
 
     #[test]
     fn rejects_paths_outside_accounts_directory() {
@@ -163,4 +568,251 @@ mod tests {
         assert!(resolve_managed_account_path(inside_file.to_string_lossy().as_ref(), &accounts_dir).is_ok());
         assert!(resolve_managed_account_path(outside_file.to_string_lossy().as_ref(), &accounts_dir).is_err());
     }
+
+    #[test]
+    fn rejects_parent_components_relative_and_drive_relative_paths() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let accounts_dir = temp.path().join("accounts");
+        fs::create_dir_all(&accounts_dir).expect("accounts dir");
+        fs::write(temp.path().join("outside.json"), "{}").expect("outside file");
+        fs::write(accounts_dir.join("inside.json"), "{}").expect("inside file");
+
+        let escaping = accounts_dir.join("..").join("outside.json");
+        let looping_back = accounts_dir.join("..").join("accounts").join("inside.json");
+        for path in [
+            escaping.to_string_lossy().to_string(),
+            looping_back.to_string_lossy().to_string(),
+            "inside.json".to_string(),
+            "C:inside.json".to_string(),
+            "C:..\\outside.json".to_string(),
+        ] {
+            let error = validate_account_path(&path, &accounts_dir).expect_err(&path);
+            assert_eq!(error.code, crate::error::AppErrorCode::PathOutsideAccounts, "{}", path);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_symlinks_escaping_accounts_directory() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let accounts_dir = temp.path().join("accounts");
+        fs::create_dir_all(accounts_dir.join("team")).expect("accounts dir");
+        let secret = temp.path().join("secret.json");
+        fs::write(&secret, "{}").expect("secret");
+        std::os::unix::fs::symlink(&secret, accounts_dir.join("link.json")).expect("file link");
+        std::os::unix::fs::symlink(temp.path(), accounts_dir.join("escape")).expect("dir link");
+
+        for path in [accounts_dir.join("link.json"), accounts_dir.join("escape").join("secret.json")] {
+            let error = validate_account_path(path.to_string_lossy().as_ref(), &accounts_dir).expect_err("escape");
+            assert_eq!(error.code, crate::error::AppErrorCode::PathOutsideAccounts);
+        }
+
+        let live = temp.path().join("auth.json");
+        fs::write(&live, "{}").expect("live auth");
+        let live_path = live.to_string_lossy().to_string();
+        assert!(validate_readable_account_path(&live_path, &accounts_dir, &live).is_ok());
+        assert!(validate_account_path(&live_path, &accounts_dir).is_err());
+        assert!(validate_readable_account_path(secret.to_string_lossy().as_ref(), &accounts_dir, &live).is_err());
+    }
+
+    #[test]
+    fn reports_unreadable_account_files_without_aborting_scan() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let accounts_dir = temp.path().join("accounts");
+        fs::create_dir_all(&accounts_dir).expect("accounts dir");
+
+        let valid = r#"{"OPENAI_API_KEY":null,"last_refresh":"","tokens":{"access_token":"a","account_id":"acc","id_token":"i","refresh_token":"r"}}"#;
+        fs::write(accounts_dir.join("good.json"), valid).expect("good file");
+        fs::write(accounts_dir.join("truncated.json"), "{\"tokens\": {").expect("truncated file");
+        fs::write(accounts_dir.join("binary.json"), [0xff, 0xfe, 0x00]).expect("binary file");
+        fs::write(accounts_dir.join("notes.txt"), "ignored").expect("notes file");
+
+        let (files, mut errors) = scan_account_files(&accounts_dir, None, None).expect("scan");
+        errors.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].file_size, valid.len() as u64);
+        assert_eq!(files[0].content_hash, Some(crate::webdav_sync::hash_content(valid.as_bytes())));
+        assert_eq!(files[0].content_hash.as_deref().map(str::len), Some(64));
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].file_path.ends_with("binary.json"));
+        assert!(errors[0].reason.contains("UTF-8"));
+        assert!(errors[1].file_path.ends_with("truncated.json"));
+        assert!(errors[1].reason.starts_with("Invalid account JSON"));
+    }
+
+    #[test]
+    fn duplicate_key_prefers_account_id_and_falls_back_to_email() {
+        assert_eq!(duplicate_key("acc-1", "a@example.com"), Some("account:acc-1".to_string()));
+        assert_eq!(duplicate_key(" ", "A@Example.com"), Some("email:a@example.com".to_string()));
+        assert_eq!(duplicate_key("", "Unknown"), None);
+    }
+
+    #[test]
+    fn archived_profiles_are_kept_out_of_the_main_scan() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let accounts_dir = temp.path().join("accounts");
+        let archived_dir = archived_accounts_dir(&accounts_dir);
+        fs::create_dir_all(&archived_dir).expect("archived dir");
+
+        let valid = r#"{"OPENAI_API_KEY":null,"last_refresh":"","tokens":{"access_token":"a","account_id":"acc","id_token":"i","refresh_token":"r"}}"#;
+        fs::write(accounts_dir.join("live.json"), valid).expect("live file");
+        fs::write(archived_dir.join("old.json"), valid).expect("archived file");
+
+        let (files, errors) = scan_account_files(&accounts_dir, None, None).expect("scan");
+        assert_eq!(files.len(), 1);
+        assert!(errors.is_empty());
+        assert!(is_archived_account_path(&archived_dir.join("old.json"), &accounts_dir));
+        assert!(!is_archived_account_path(&accounts_dir.join("live.json"), &accounts_dir));
+    }
+
+    #[test]
+    fn scans_group_folders_up_to_the_depth_limit() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let accounts_dir = temp.path().join("accounts");
+        let work_dir = accounts_dir.join("work").join("team");
+        let too_deep = accounts_dir.join("a").join("b").join("c").join("d");
+        fs::create_dir_all(&work_dir).expect("work dir");
+        fs::create_dir_all(&too_deep).expect("deep dir");
+
+        let valid = r#"{"OPENAI_API_KEY":null,"last_refresh":"","tokens":{"access_token":"a","account_id":"acc","id_token":"i","refresh_token":"r"}}"#;
+        fs::write(accounts_dir.join("top.json"), valid).expect("top file");
+        fs::write(work_dir.join("nested.json"), valid).expect("nested file");
+        fs::write(too_deep.join("hidden.json"), valid).expect("deep file");
+
+        let (files, errors) = scan_account_files(&accounts_dir, None, None).expect("scan");
+        assert!(errors.is_empty());
+        assert_eq!(files.len(), 2);
+
+        let nested = files.iter().find(|file| file.path.ends_with("nested.json")).expect("nested");
+        assert_eq!(account_group(&nested.path, &accounts_dir).as_deref(), Some("work/team"));
+        assert_eq!(account_group(&accounts_dir.join("top.json"), &accounts_dir), None);
+    }
+
+    fn parsed(path: &str, account_id: &str, access_token: &str, last_refresh: &str) -> ParsedAccountFile {
+        let content = format!(
+            r#"{{"OPENAI_API_KEY":null,"last_refresh":"{}","tokens":{{"access_token":"{}","account_id":"{}","id_token":"i","refresh_token":"r"}}}}"#,
+            last_refresh, access_token, account_id
+        );
+        ParsedAccountFile {
+            path: PathBuf::from(path),
+            modified_at: 0,
+            auth: serde_json::from_str(&content).expect("auth"),
+            file_size: content.len() as u64,
+            content_hash: None,
+        }
+    }
+
+    #[test]
+    fn finds_duplicates_by_account_id() {
+        let files = vec![parsed("/a/one.json", "acc-1", "t1", ""), parsed("/a/two.json", "acc-2", "t2", "")];
+        let same_account = parsed("/new.json", "acc-2", "fresh", "");
+        let other_account = parsed("/new.json", "acc-3", "fresh", "");
+        let anonymous = parsed("/new.json", "", "fresh", "");
+
+        assert_eq!(
+            find_duplicate_profile(&files, &same_account.auth).map(|file| file.path.clone()),
+            Some(PathBuf::from("/a/two.json"))
+        );
+        assert!(find_duplicate_profile(&files, &other_account.auth).is_none());
+        assert!(find_duplicate_profile(&files, &anonymous.auth).is_none());
+    }
+
+    #[test]
+    fn matches_active_profile_without_account_id() {
+        let files = vec![parsed("/a/one.json", "", "token-1", "2025-01-01"), parsed("/a/two.json", "", "token-2", "")];
+        let active = parsed("/codex/auth.json", "", "token-2", "2025-03-01");
+
+        assert_eq!(match_active_profile(&files, &active.auth, None), Some(PathBuf::from("/a/two.json")));
+
+        let unrelated = parsed("/codex/auth.json", "", "token-3", "");
+        assert_eq!(match_active_profile(&files, &unrelated.auth, None), None);
+    }
+
+    #[test]
+    fn prefers_the_configured_profile_when_tokens_are_identical() {
+        let files = vec![parsed("/a/one.json", "", "shared", ""), parsed("/a/two.json", "", "shared", "")];
+        let active = parsed("/codex/auth.json", "", "shared", "");
+        let preferred = PathBuf::from("/a/two.json");
+
+        assert_eq!(match_active_profile(&files, &active.auth, Some(&preferred)), Some(preferred.clone()));
+        assert_eq!(match_active_profile(&files, &active.auth, None), Some(PathBuf::from("/a/one.json")));
+    }
+
+    #[test]
+    fn atomic_write_replaces_target_or_leaves_it_untouched() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let target = temp.path().join("auth.json");
+        fs::write(&target, "old").expect("seed");
+
+        write_file_atomic(&target, b"new").expect("write");
+        assert_eq!(fs::read_to_string(&target).expect("read"), "new");
+        let entries = || {
+            let mut names: Vec<_> = fs::read_dir(temp.path())
+                .expect("list")
+                .map(|entry| entry.expect("entry").file_name().to_string_lossy().to_string())
+                .collect();
+            names.sort();
+            names
+        };
+        assert_eq!(entries(), ["auth.json"]);
+
+        // A non-empty directory at the target makes the rename fail; the temp file is removed.
+        let blocked = temp.path().join("blocked");
+        fs::create_dir(&blocked).expect("blocker");
+        fs::write(blocked.join("keep"), "").expect("keep");
+        assert!(write_file_atomic(&blocked, b"newer").is_err());
+        assert!(blocked.join("keep").is_file());
+        assert_eq!(entries(), ["auth.json", "blocked"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn private_writes_are_owner_only() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let target = temp.path().join("auth.json");
+        write_file_atomic(&target, b"{}").expect("write");
+
+        std::fs::set_permissions(&target, std::os::unix::fs::PermissionsExt::from_mode(0o644)).expect("chmod");
+        assert_eq!(exposed_permission_mode(&target).expect("mode"), Some(0o644));
+        write_private_file_atomic(&target, b"{}").expect("private write");
+        assert_eq!(exposed_permission_mode(&target).expect("mode"), None);
+    }
+
+    #[test]
+    fn finds_profiles_by_stem_or_account_id() {
+        let files = vec![
+            parsed("/a/Work-Pro.json", "acc-1", "t1", ""),
+            parsed("/a/team/personal.json", "acc-2", "t2", ""),
+            parsed("/a/personal.json", "acc-3", "t3", ""),
+        ];
+        let base = std::path::Path::new("/a");
+
+        assert_eq!(find_profile_by_name(&files, base, "work-pro"), Ok(PathBuf::from("/a/Work-Pro.json")));
+        assert_eq!(find_profile_by_name(&files, base, "acc-2"), Ok(PathBuf::from("/a/team/personal.json")));
+        assert!(find_profile_by_name(&files, base, "missing").is_err());
+
+        let error = find_profile_by_name(&files, base, "Personal").expect_err("ambiguous");
+        assert!(error.ends_with("personal.json, team/personal.json"), "{}", error);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn linked_runtime_file_is_skipped_but_its_target_is_scanned() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let profile = temp.path().join("work.json");
+        let runtime = temp.path().join("auth.json");
+        let valid = r#"{"OPENAI_API_KEY":null,"last_refresh":"","tokens":{"access_token":"a","account_id":"acc","id_token":"i","refresh_token":"r"}}"#;
+        fs::write(&profile, valid).expect("profile");
+        fs::write(&runtime, "stale").expect("runtime");
+
+        link_file_atomic(&runtime, &profile).expect("link");
+        assert!(fs::symlink_metadata(&runtime).expect("meta").file_type().is_symlink());
+        assert_eq!(fs::read_to_string(&runtime).expect("read"), valid);
+
+        let (files, errors) = scan_account_files(&temp.path().to_path_buf(), Some(&runtime), None).expect("scan");
+        assert!(errors.is_empty());
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, profile);
+    }
 }
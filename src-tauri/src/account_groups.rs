@@ -0,0 +1,157 @@
+use crate::account_files::{collect_account_files, is_account_group_dir, ARCHIVED_DIR, MAX_ACCOUNT_DIR_DEPTH};
+use crate::accounts::is_live_account;
+use crate::trash_bin::remove_path;
+use crate::{get_accounts_dir, AccountInfo};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GroupInfo {
+    /// Last folder name, for display.
+    pub name: String,
+    /// Folder below the accounts dir, `/`-separated; matches `AccountInfo::group`.
+    pub path: String,
+    /// Non-archived profiles stored directly in this folder.
+    #[serde(rename = "accountCount")]
+    pub account_count: usize,
+}
+
+fn collect_group_dirs(dir: &Path, prefix: &str, depth: usize, groups: &mut Vec<String>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !is_account_group_dir(&path, depth) {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        let relative = if prefix.is_empty() {
+            name
+        } else {
+            format!("{}/{}", prefix, name)
+        };
+        if depth + 1 < MAX_ACCOUNT_DIR_DEPTH {
+            collect_group_dirs(&path, &relative, depth + 1, groups);
+        }
+        groups.push(relative);
+    }
+}
+
+/// Every group folder under `accounts_dir`, including empty ones, sorted by path.
+pub fn list_account_groups(accounts_dir: &Path, accounts: &[AccountInfo]) -> Vec<GroupInfo> {
+    let mut paths = Vec::new();
+    collect_group_dirs(accounts_dir, "", 0, &mut paths);
+    paths.sort();
+
+    paths
+        .into_iter()
+        .map(|path| GroupInfo {
+            name: path.rsplit('/').next().unwrap_or(&path).to_string(),
+            account_count: accounts
+                .iter()
+                .filter(|account| !account.is_archived && account.group.as_deref() == Some(path.as_str()))
+                .count(),
+            path,
+        })
+        .collect()
+}
+
+/// Resolves a `/`-separated group name to a folder inside `accounts_dir`, rejecting anything
+/// that would escape it, collide with the archive, or sit deeper than scans reach.
+pub fn resolve_group_dir(accounts_dir: &Path, name: &str) -> Result<PathBuf, String> {
+    let segments: Vec<&str> = name.trim().trim_matches('/').split('/').map(str::trim).collect();
+    if segments.iter().any(|segment| segment.is_empty()) {
+        return Err("Group name cannot be empty".to_string());
+    }
+    if segments.len() > MAX_ACCOUNT_DIR_DEPTH {
+        return Err(format!("Groups can be nested at most {} levels deep", MAX_ACCOUNT_DIR_DEPTH));
+    }
+    for segment in &segments {
+        if segment.starts_with('.') || segment.contains(['\\', ':']) {
+            return Err(format!("Invalid group name '{}'", segment));
+        }
+    }
+    if segments[0] == ARCHIVED_DIR {
+        return Err(format!("'{}' is reserved for archived accounts", ARCHIVED_DIR));
+    }
+
+    Ok(segments.iter().fold(accounts_dir.to_path_buf(), |dir, segment| dir.join(segment)))
+}
+
+#[tauri::command]
+pub fn create_account_group(name: String) -> Result<(), String> {
+    let dir = resolve_group_dir(&get_accounts_dir(), &name)?;
+    if dir.exists() {
+        return Err("A group with this name already exists".to_string());
+    }
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create group: {}", e))
+}
+
+/// Fails for groups that still contain files unless `force` is set, which moves the folder and
+/// its profiles to the trash. A group holding the active account is never deleted.
+#[tauri::command]
+pub fn delete_account_group(name: String, force: Option<bool>) -> Result<(), String> {
+    let accounts_dir = get_accounts_dir();
+    let dir = resolve_group_dir(&accounts_dir, &name)?;
+    if !dir.is_dir() {
+        return Err("Group does not exist".to_string());
+    }
+
+    if force.unwrap_or(false) {
+        if let Some(active) = collect_account_files(&dir, None)?
+            .into_iter()
+            .find(|file| is_live_account(&file.path, &accounts_dir))
+        {
+            let name = active.path.file_stem().and_then(|s| s.to_str()).unwrap_or("A profile");
+            return Err(format!("'{}' in this group is the active account; switch away first", name));
+        }
+        return remove_path(&dir, false);
+    }
+    let is_empty = fs::read_dir(&dir)
+        .map_err(|e| format!("Failed to read group: {}", e))?
+        .next()
+        .is_none();
+    if !is_empty {
+        return Err("Group is not empty".to_string());
+    }
+    fs::remove_dir(&dir).map_err(|e| format!("Failed to delete group: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{list_account_groups, resolve_group_dir};
+    use crate::AccountInfo;
+    use std::fs;
+
+    #[test]
+    fn resolves_only_safe_group_names() {
+        let base = std::path::Path::new("/accounts");
+        assert_eq!(resolve_group_dir(base, "work/team").expect("nested"), base.join("work").join("team"));
+        assert!(resolve_group_dir(base, "../outside").is_err());
+        assert!(resolve_group_dir(base, "work//team").is_err());
+        assert!(resolve_group_dir(base, "archived").is_err());
+        assert!(resolve_group_dir(base, "a\\b").is_err());
+        assert!(resolve_group_dir(base, "a/b/c/d").is_err());
+    }
+
+    #[test]
+    fn lists_empty_and_populated_groups() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        fs::create_dir_all(temp.path().join("work").join("team")).expect("work");
+        fs::create_dir_all(temp.path().join("empty")).expect("empty");
+        fs::create_dir_all(temp.path().join("archived")).expect("archived");
+        let accounts = vec![AccountInfo {
+            group: Some("work/team".to_string()),
+            ..Default::default()
+        }];
+
+        let groups = list_account_groups(temp.path(), &accounts);
+        let summary: Vec<(&str, &str, usize)> = groups
+            .iter()
+            .map(|group| (group.name.as_str(), group.path.as_str(), group.account_count))
+            .collect();
+        assert_eq!(summary, vec![("empty", "empty", 0), ("work", "work", 0), ("team", "work/team", 1)]);
+    }
+}
@@ -0,0 +1,269 @@
+use crate::account_cache::AccountScanCache;
+use crate::account_meta::{update_account_meta, AccountMetaStore, ACCOUNT_META_FILE};
+use crate::accounts::{account_info_for_path, store_profile, validate_auth_value};
+use crate::error::{AppError, AppErrorCode, AppResult};
+use crate::{get_accounts_dir, AccountInfo, CodexAuthFile, ScanError};
+use serde::Serialize;
+use std::fs;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use zip::result::ZipError;
+
+/// Largest archive entry that is read; auth files and meta.json are a few KB.
+const MAX_ARCHIVE_ENTRY_SIZE: u64 = 1024 * 1024;
+
+/// Parses an auth file as exported by Codex or another machine, tolerating a UTF-8 BOM.
+fn parse_auth_document(content: &[u8]) -> AppResult<CodexAuthFile> {
+    let content = content.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(content);
+    let text = std::str::from_utf8(content).map_err(|_| AppError::parse("File is not a text JSON file"))?;
+    let value: serde_json::Value =
+        serde_json::from_str(text).map_err(|e| AppError::parse(format!("File is not valid JSON: {}", e)))?;
+    validate_auth_value(&value).map_err(AppError::parse)?;
+    serde_json::from_value(value).map_err(|e| AppError::parse(format!("Not a Codex auth file: {}", e)))
+}
+
+fn read_auth_document(path: &Path) -> AppResult<CodexAuthFile> {
+    let metadata = fs::metadata(path).map_err(|e| AppError::not_found(format!("Cannot open file: {}", e)))?;
+    if metadata.is_dir() {
+        return Err(AppError::parse("Expected an auth JSON file but got a directory"));
+    }
+    let content = fs::read(path).map_err(|e| AppError::io(format!("Failed to read file: {}", e)))?;
+    parse_auth_document(&content)
+}
+
+/// Adds the auth file at `path` (from the file dialog or drag & drop) as a new profile named
+/// `name`, or after its email.
+#[tauri::command]
+pub fn import_account_file(
+    cache: tauri::State<'_, AccountScanCache>,
+    path: String,
+    name: Option<String>,
+) -> AppResult<AccountInfo> {
+    let auth = read_auth_document(Path::new(path.trim()))?;
+    let result = store_profile(&auth, name.as_deref().unwrap_or_default(), false, false)?;
+    account_info_for_path(&cache, &PathBuf::from(result.file_path)).map_err(AppError::io)
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct ImportSummary {
+    /// Profiles written, as paths in the accounts dir.
+    pub imported: Vec<String>,
+    /// Source files describing an account that is already stored, with the reason.
+    pub skipped: Vec<ScanError>,
+    pub errors: Vec<ScanError>,
+}
+
+impl ImportSummary {
+    fn record(&mut self, file_path: String, result: AppResult<String>) {
+        match result {
+            Ok(stored) => self.imported.push(stored),
+            Err(error) if error.code == AppErrorCode::DuplicateAccount => self.skipped.push(ScanError {
+                file_path,
+                reason: error.message,
+            }),
+            Err(error) => self.errors.push(ScanError {
+                file_path,
+                reason: error.message,
+            }),
+        }
+    }
+}
+
+fn import_files(paths: Vec<PathBuf>, overwrite: bool) -> ImportSummary {
+    let mut summary = ImportSummary::default();
+    for path in paths {
+        let result = read_auth_document(&path)
+            .and_then(|auth| store_profile(&auth, "", false, overwrite))
+            .map(|stored| stored.file_path);
+        summary.record(path.to_string_lossy().to_string(), result);
+    }
+    summary
+}
+
+/// Imports every `*.json` directly inside `path`, naming profiles after their emails. Accounts
+/// that are already stored are skipped unless `overwrite` is set; bad files are reported per file.
+#[tauri::command]
+pub fn import_accounts_from_dir(path: String, overwrite: Option<bool>) -> AppResult<ImportSummary> {
+    let dir = PathBuf::from(path.trim());
+    if !dir.is_dir() {
+        return Err(AppError::not_found("Import folder does not exist"));
+    }
+    let mut paths: Vec<PathBuf> = fs::read_dir(&dir)
+        .map_err(|e| AppError::io(format!("Failed to read import folder: {}", e)))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            path.extension()
+                .and_then(|extension| extension.to_str())
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("json"))
+        })
+        .collect();
+    paths.sort();
+    Ok(import_files(paths, overwrite.unwrap_or(false)))
+}
+
+/// The relative path an archive entry would extract to, or `None` for absolute paths and entries
+/// climbing out with `..` (zip-slip).
+fn archive_entry_path(name: &str) -> Option<PathBuf> {
+    let normalized = name.replace('\\', "/");
+    if normalized.starts_with('/') || normalized.contains(':') {
+        return None;
+    }
+    let mut path = PathBuf::new();
+    for component in Path::new(&normalized).components() {
+        match component {
+            Component::Normal(part) => path.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    (!path.as_os_str().is_empty()).then_some(path)
+}
+
+/// Reads at most `limit` bytes, failing instead of truncating so a lying size header cannot
+/// slip a huge entry through.
+fn read_capped(reader: impl Read, limit: u64) -> AppResult<Vec<u8>> {
+    let mut content = Vec::new();
+    reader
+        .take(limit + 1)
+        .read_to_end(&mut content)
+        .map_err(|e| AppError::io(format!("Failed to read entry: {}", e)))?;
+    if content.len() as u64 > limit {
+        return Err(AppError::parse(format!("Entry is larger than {} bytes", limit)));
+    }
+    Ok(content)
+}
+
+fn archive_error(error: ZipError) -> AppError {
+    match error {
+        ZipError::InvalidPassword => AppError::auth("Incorrect archive passphrase"),
+        ZipError::UnsupportedArchive(ZipError::PASSWORD_REQUIRED) => {
+            AppError::auth("Archive is encrypted; a passphrase is required")
+        }
+        error => AppError::parse(format!("Failed to read archive: {}", error)),
+    }
+}
+
+/// Imports the `*.json` profiles of an archive made by `export_accounts_archive`, at any depth,
+/// naming them after their file names, and merges its `meta.json`. Unsafe, oversized or invalid
+/// entries are reported per entry; a missing or wrong passphrase fails the whole import.
+#[tauri::command]
+pub fn import_accounts_archive(
+    path: String,
+    overwrite: Option<bool>,
+    passphrase: Option<String>,
+) -> AppResult<ImportSummary> {
+    let overwrite = overwrite.unwrap_or(false);
+    let file = fs::File::open(path.trim()).map_err(|e| AppError::not_found(format!("Cannot open archive: {}", e)))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(archive_error)?;
+    let passphrase = passphrase.filter(|passphrase| !passphrase.is_empty());
+
+    let mut summary = ImportSummary::default();
+    let mut meta = None;
+    for index in 0..archive.len() {
+        let entry = match passphrase.as_deref() {
+            Some(passphrase) => archive.by_index_decrypt(index, passphrase.as_bytes()),
+            None => archive.by_index(index),
+        };
+        let entry = entry.map_err(archive_error)?;
+        let name = entry.name().to_string();
+        if entry.is_dir() {
+            continue;
+        }
+        let Some(entry_path) = archive_entry_path(&name) else {
+            summary.record(name, Err(AppError::forbidden("Entry path escapes the archive")));
+            continue;
+        };
+        let is_json = entry_path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+        if !is_json {
+            continue;
+        }
+        if entry.size() > MAX_ARCHIVE_ENTRY_SIZE {
+            summary.record(
+                name,
+                Err(AppError::parse(format!("Entry is larger than {} bytes", MAX_ARCHIVE_ENTRY_SIZE))),
+            );
+            continue;
+        }
+        let content = match read_capped(entry, MAX_ARCHIVE_ENTRY_SIZE) {
+            Ok(content) => content,
+            Err(error) => {
+                summary.record(name, Err(error));
+                continue;
+            }
+        };
+
+        if entry_path == Path::new(ACCOUNT_META_FILE) {
+            match serde_json::from_slice::<AccountMetaStore>(&content) {
+                Ok(store) => meta = Some(store),
+                Err(error) => summary.record(name, Err(AppError::parse(format!("Invalid meta.json: {}", error)))),
+            }
+            continue;
+        }
+        let profile_name = entry_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let result = parse_auth_document(&content)
+            .and_then(|auth| store_profile(&auth, &profile_name, false, overwrite))
+            .map(|stored| stored.file_path);
+        summary.record(name, result);
+    }
+
+    if let Some(meta) = meta {
+        update_account_meta(&get_accounts_dir(), |store| store.merge(meta, overwrite)).map_err(AppError::io)?;
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{archive_entry_path, parse_auth_document, read_auth_document, read_capped};
+    use crate::error::AppErrorCode;
+    use std::path::PathBuf;
+
+    const AUTH: &str = r#"{"OPENAI_API_KEY":null,"tokens":{"access_token":"a","account_id":"acc-1","id_token":"i","refresh_token":"r"}}"#;
+
+    #[test]
+    fn parses_bom_prefixed_auth_files() {
+        let mut content = b"\xEF\xBB\xBF".to_vec();
+        content.extend_from_slice(AUTH.as_bytes());
+        assert_eq!(parse_auth_document(&content).expect("auth").account_id(), "acc-1");
+    }
+
+    #[test]
+    fn rejects_directories_and_non_json_content() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let error = read_auth_document(temp.path()).expect_err("directory");
+        assert!(error.message.contains("directory"));
+
+        let error = parse_auth_document(b"not json").expect_err("text");
+        assert_eq!(error.code, AppErrorCode::Parse);
+        assert!(error.message.starts_with("File is not valid JSON"));
+        assert!(parse_auth_document(&[0xff, 0xfe, 0x00]).is_err());
+        assert!(parse_auth_document(br#"{"tokens":{"access_token":"a"}}"#).is_err());
+    }
+
+    #[test]
+    fn rejects_entries_escaping_the_archive() {
+        assert_eq!(archive_entry_path("team/work.json"), Some(PathBuf::from("team").join("work.json")));
+        assert_eq!(archive_entry_path("./work.json"), Some(PathBuf::from("work.json")));
+        assert_eq!(archive_entry_path("../work.json"), None);
+        assert_eq!(archive_entry_path("team/../../work.json"), None);
+        assert_eq!(archive_entry_path("..\\work.json"), None);
+        assert_eq!(archive_entry_path("/etc/work.json"), None);
+        assert_eq!(archive_entry_path("C:/work.json"), None);
+        assert_eq!(archive_entry_path(""), None);
+    }
+
+    #[test]
+    fn caps_entry_size_regardless_of_header() {
+        assert_eq!(read_capped(&b"12345"[..], 5).expect("fits"), b"12345");
+        let error = read_capped(&b"123456"[..], 5).expect_err("too large");
+        assert_eq!(error.code, AppErrorCode::Parse);
+    }
+}
@@ -0,0 +1,370 @@
+use crate::account_files::resolve_managed_account_path;
+use crate::{extract_profile_id_from_auth, get_accounts_dir, CodexAuthFile};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Sidecar file in the accounts directory, keyed by profile id so entries survive renames.
+pub const ACCOUNT_META_FILE: &str = "meta.json";
+const ACCOUNT_META_VERSION: u32 = 1;
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct AccountMeta {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, rename = "switchCount", skip_serializing_if = "is_zero")]
+    pub switch_count: u64,
+    /// Total ms this account was active, excluding the current stint.
+    #[serde(default, rename = "activeDurationMs", skip_serializing_if = "is_zero")]
+    pub active_duration_ms: u64,
+    /// Unix ms of the switch that made this account active; cleared when switching away.
+    #[serde(default, rename = "activeSince", skip_serializing_if = "Option::is_none")]
+    pub active_since: Option<i64>,
+    /// TOML merged into `~/.codex/config.toml` when switching to this account.
+    #[serde(default, rename = "configOverride", skip_serializing_if = "Option::is_none")]
+    pub config_override: Option<String>,
+    /// The last refresh was rejected as expired, reused or invalidated; cleared by a later success.
+    #[serde(default, rename = "refreshDead", skip_serializing_if = "is_false")]
+    pub refresh_dead: bool,
+    /// Unix seconds when an exhausted rate-limit window resets; ignored once it has passed.
+    #[serde(default, rename = "coolingDownUntil", skip_serializing_if = "Option::is_none")]
+    pub cooling_down_until: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AccountStats {
+    #[serde(rename = "profileId")]
+    pub profile_id: String,
+    #[serde(rename = "switchCount")]
+    pub switch_count: u64,
+    /// Includes the time since the current switch for the active account.
+    #[serde(rename = "activeDurationMs")]
+    pub active_duration_ms: u64,
+}
+
+impl AccountMeta {
+    pub fn is_empty(&self) -> bool {
+        self == &AccountMeta::default()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountMetaStore {
+    #[serde(default)]
+    pub version: u32,
+    #[serde(default)]
+    pub accounts: BTreeMap<String, AccountMeta>,
+}
+
+impl Default for AccountMetaStore {
+    fn default() -> Self {
+        Self {
+            version: ACCOUNT_META_VERSION,
+            accounts: BTreeMap::new(),
+        }
+    }
+}
+
+impl AccountMetaStore {
+    /// Closes the running stint of whichever account was active and starts one for `profile_id`.
+    pub fn record_switch(&mut self, profile_id: &str, now: i64) {
+        for meta in self.accounts.values_mut() {
+            if let Some(active_since) = meta.active_since.take() {
+                meta.active_duration_ms += now.saturating_sub(active_since).max(0) as u64;
+            }
+        }
+
+        let meta = self.accounts.entry(profile_id.to_string()).or_default();
+        meta.switch_count += 1;
+        meta.active_since = Some(now);
+    }
+
+    pub fn stats(&self, now: i64) -> Vec<AccountStats> {
+        self.accounts
+            .iter()
+            .filter(|(_, meta)| meta.switch_count > 0 || meta.active_duration_ms > 0 || meta.active_since.is_some())
+            .map(|(profile_id, meta)| AccountStats {
+                profile_id: profile_id.clone(),
+                switch_count: meta.switch_count,
+                active_duration_ms: meta.active_duration_ms
+                    + meta
+                        .active_since
+                        .map(|since| now.saturating_sub(since).max(0) as u64)
+                        .unwrap_or(0),
+            })
+            .collect()
+    }
+
+    pub fn reset_stats(&mut self) {
+        for meta in self.accounts.values_mut() {
+            meta.switch_count = 0;
+            meta.active_duration_ms = 0;
+            meta.active_since = None;
+        }
+        self.accounts.retain(|_, meta| !meta.is_empty());
+    }
+
+    /// Moves the entry for `from` to `to` unless `to` already has one.
+    pub fn rename_id(&mut self, from: &str, to: &str) {
+        if from == to || self.accounts.contains_key(to) {
+            return;
+        }
+        if let Some(meta) = self.accounts.remove(from) {
+            self.accounts.insert(to.to_string(), meta);
+        }
+    }
+
+    /// Adds the entries of `other` (e.g. from a backup), replacing existing ones only when
+    /// `overwrite` is set. Running stints are dropped since they belong to another session.
+    pub fn merge(&mut self, other: AccountMetaStore, overwrite: bool) {
+        for (profile_id, mut meta) in other.accounts {
+            if !overwrite && self.accounts.contains_key(&profile_id) {
+                continue;
+            }
+            meta.active_since = None;
+            self.set(&profile_id, meta);
+        }
+    }
+
+    pub fn get(&self, profile_id: &str) -> AccountMeta {
+        self.accounts.get(profile_id).cloned().unwrap_or_default()
+    }
+
+    pub fn set(&mut self, profile_id: &str, meta: AccountMeta) {
+        if meta.is_empty() {
+            self.accounts.remove(profile_id);
+        } else {
+            self.accounts.insert(profile_id.to_string(), meta);
+        }
+    }
+}
+
+pub fn is_account_meta_file(path: &Path) -> bool {
+    path.file_name().and_then(|name| name.to_str()) == Some(ACCOUNT_META_FILE)
+}
+
+pub fn account_meta_path(accounts_dir: &Path) -> PathBuf {
+    accounts_dir.join(ACCOUNT_META_FILE)
+}
+
+pub fn load_account_meta(accounts_dir: &Path) -> AccountMetaStore {
+    fs::read_to_string(account_meta_path(accounts_dir))
+        .ok()
+        .and_then(|content| serde_json::from_str::<AccountMetaStore>(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_account_meta(accounts_dir: &Path, store: &AccountMetaStore) -> Result<(), String> {
+    let next = AccountMetaStore {
+        version: ACCOUNT_META_VERSION,
+        accounts: store.accounts.clone(),
+    };
+    let content = serde_json::to_string_pretty(&next)
+        .map_err(|e| format!("Failed to serialize account metadata: {}", e))?;
+    fs::write(account_meta_path(accounts_dir), content)
+        .map_err(|e| format!("Failed to write account metadata: {}", e))
+}
+
+/// Runs `update` against the stored metadata and persists the result.
+pub fn update_account_meta(
+    accounts_dir: &Path,
+    update: impl FnOnce(&mut AccountMetaStore),
+) -> Result<(), String> {
+    let mut store = load_account_meta(accounts_dir);
+    update(&mut store);
+    save_account_meta(accounts_dir, &store)
+}
+
+/// Records the outcome of a token refresh; meta.json is only rewritten when the flag flips.
+pub fn set_refresh_dead(accounts_dir: &Path, profile_id: &str, dead: bool) -> Result<(), String> {
+    if load_account_meta(accounts_dir).get(profile_id).refresh_dead == dead {
+        return Ok(());
+    }
+    update_account_meta(accounts_dir, |store| {
+        let mut meta = store.get(profile_id);
+        meta.refresh_dead = dead;
+        store.set(profile_id, meta);
+    })
+}
+
+/// Records when the account's rate limit resets (`None` when it is usable); only rewrites meta.json
+/// when the value changes.
+pub fn set_cooldown(accounts_dir: &Path, profile_id: &str, until: Option<i64>) -> Result<(), String> {
+    if load_account_meta(accounts_dir).get(profile_id).cooling_down_until == until {
+        return Ok(());
+    }
+    update_account_meta(accounts_dir, |store| {
+        let mut meta = store.get(profile_id);
+        meta.cooling_down_until = until;
+        store.set(profile_id, meta);
+    })
+}
+
+pub fn profile_id_for_file(path: &PathBuf) -> Result<String, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read account file: {}", e))?;
+    let auth: CodexAuthFile =
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse account file: {}", e))?;
+    Ok(extract_profile_id_from_auth(&auth, Some(path)))
+}
+
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let trimmed = tag.trim();
+        if trimmed.is_empty() || normalized.iter().any(|existing| existing.eq_ignore_ascii_case(trimmed)) {
+            continue;
+        }
+        normalized.push(trimmed.to_string());
+    }
+    normalized
+}
+
+#[tauri::command]
+pub fn set_account_meta(file_path: String, notes: Option<String>, tags: Vec<String>) -> Result<(), String> {
+    let accounts_dir = get_accounts_dir();
+    let path = resolve_managed_account_path(&file_path, &accounts_dir)?;
+    let profile_id = profile_id_for_file(&path)?;
+
+    update_account_meta(&accounts_dir, |store| {
+        let mut meta = store.get(&profile_id);
+        meta.notes = notes
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty());
+        meta.tags = normalize_tags(tags);
+        store.set(&profile_id, meta);
+    })
+}
+
+#[tauri::command]
+pub fn get_account_stats() -> Vec<AccountStats> {
+    load_account_meta(&get_accounts_dir()).stats(chrono::Utc::now().timestamp_millis())
+}
+
+#[tauri::command]
+pub fn reset_account_stats() -> Result<(), String> {
+    update_account_meta(&get_accounts_dir(), |store| store.reset_stats())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load_account_meta, normalize_tags, save_account_meta, set_refresh_dead, AccountMeta, AccountMetaStore};
+
+    #[test]
+    fn normalizes_tags_and_drops_empty_entries() {
+        let tags = normalize_tags(vec![" work ".to_string(), "".to_string(), "Work".to_string(), "客户".to_string()]);
+        assert_eq!(tags, vec!["work".to_string(), "客户".to_string()]);
+    }
+
+    #[test]
+    fn round_trips_store_and_removes_empty_entries() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let mut store = AccountMetaStore::default();
+        store.set(
+            "user-1",
+            AccountMeta {
+                notes: Some("client X".to_string()),
+                tags: vec!["work".to_string()],
+                ..Default::default()
+            },
+        );
+        store.set("user-2", AccountMeta::default());
+        save_account_meta(temp.path(), &store).expect("save");
+
+        let loaded = load_account_meta(temp.path());
+        assert_eq!(loaded.accounts.len(), 1);
+        assert_eq!(loaded.get("user-1").notes.as_deref(), Some("client X"));
+        assert!(loaded.get("missing").is_empty());
+    }
+
+    #[test]
+    fn tracks_switch_counts_and_active_durations() {
+        let mut store = AccountMetaStore::default();
+        store.record_switch("a", 1_000);
+        store.record_switch("b", 4_000);
+        store.record_switch("a", 5_000);
+
+        let stats = store.stats(6_000);
+        assert_eq!(stats.len(), 2);
+        assert_eq!((stats[0].switch_count, stats[0].active_duration_ms), (2, 4_000));
+        assert_eq!((stats[1].switch_count, stats[1].active_duration_ms), (1, 1_000));
+
+        store.reset_stats();
+        assert!(store.stats(7_000).is_empty());
+    }
+
+    #[test]
+    fn flags_and_clears_dead_refresh_tokens() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        set_refresh_dead(temp.path(), "user-1", true).expect("flag");
+        assert!(load_account_meta(temp.path()).get("user-1").refresh_dead);
+
+        set_refresh_dead(temp.path(), "user-1", false).expect("clear");
+        assert!(load_account_meta(temp.path()).accounts.is_empty());
+    }
+
+    #[test]
+    fn renames_ids_without_clobbering_existing_entries() {
+        let mut store = AccountMetaStore::default();
+        let noted = |notes: &str| AccountMeta {
+            notes: Some(notes.to_string()),
+            ..Default::default()
+        };
+        store.set("path:/a.json", noted("a"));
+        store.set("path:/c.json", noted("c"));
+
+        store.rename_id("path:/a.json", "path:/b.json");
+        assert_eq!(store.get("path:/b.json").notes.as_deref(), Some("a"));
+        assert!(store.get("path:/a.json").is_empty());
+
+        store.rename_id("path:/b.json", "path:/c.json");
+        assert_eq!(store.get("path:/c.json").notes.as_deref(), Some("c"));
+        assert_eq!(store.get("path:/b.json").notes.as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn merge_keeps_local_entries_unless_overwriting() {
+        let mut local = AccountMetaStore::default();
+        local.set(
+            "a",
+            AccountMeta {
+                notes: Some("local".to_string()),
+                ..AccountMeta::default()
+            },
+        );
+        let mut backup = AccountMetaStore::default();
+        backup.set(
+            "a",
+            AccountMeta {
+                notes: Some("backup".to_string()),
+                ..AccountMeta::default()
+            },
+        );
+        backup.set(
+            "b",
+            AccountMeta {
+                switch_count: 2,
+                active_since: Some(1),
+                ..AccountMeta::default()
+            },
+        );
+
+        local.merge(backup.clone(), false);
+        assert_eq!(local.get("a").notes.as_deref(), Some("local"));
+        assert_eq!(local.get("b").switch_count, 2);
+        assert_eq!(local.get("b").active_since, None);
+
+        local.merge(backup, true);
+        assert_eq!(local.get("a").notes.as_deref(), Some("backup"));
+    }
+}
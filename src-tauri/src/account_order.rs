@@ -0,0 +1,188 @@
+use crate::AccountInfo;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum AccountSortKey {
+    #[default]
+    Name,
+    Email,
+    PlanType,
+    ExpiresAt,
+    AuthUpdatedAt,
+    LastUsedAt,
+    /// The user's saved order, see `assign_sort_indices`.
+    Custom,
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum SortValue {
+    Text(String),
+    Number(i64),
+}
+
+fn text_value(value: &str, placeholder: &str) -> Option<SortValue> {
+    let trimmed = value.trim();
+    if trimmed.is_empty() || trimmed.eq_ignore_ascii_case(placeholder) {
+        return None;
+    }
+    Some(SortValue::Text(trimmed.to_lowercase()))
+}
+
+fn sort_value(account: &AccountInfo, key: AccountSortKey) -> Option<SortValue> {
+    match key {
+        AccountSortKey::Name => text_value(&account.name, ""),
+        AccountSortKey::Email => text_value(&account.email, "unknown"),
+        AccountSortKey::PlanType => text_value(&account.plan_type, "unknown"),
+        AccountSortKey::ExpiresAt => account.expires_at.map(SortValue::Number),
+        AccountSortKey::AuthUpdatedAt => Some(SortValue::Number(account.auth_updated_at)),
+        AccountSortKey::LastUsedAt => account.last_used_at.map(SortValue::Number),
+        AccountSortKey::Custom => Some(SortValue::Number(account.sort_index as i64)),
+    }
+}
+
+/// Sorts in place; accounts without a value for `key` always go last, whatever the direction.
+pub fn sort_accounts(accounts: &mut [AccountInfo], key: AccountSortKey, descending: bool) {
+    accounts.sort_by(|a, b| {
+        let primary = match (sort_value(a, key), sort_value(b, key)) {
+            (Some(left), Some(right)) if descending => right.cmp(&left),
+            (Some(left), Some(right)) => left.cmp(&right),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        };
+
+        primary
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+            .then_with(|| a.file_path.cmp(&b.file_path))
+    });
+}
+
+/// Drops ids from the saved order that no longer belong to any stored profile.
+pub fn prune_account_order(order: &[String], known_ids: &HashSet<String>) -> Vec<String> {
+    order.iter().filter(|id| known_ids.contains(*id)).cloned().collect()
+}
+
+/// Sets `sort_index` from the saved order; accounts missing from it follow in name order.
+pub fn assign_sort_indices(accounts: &mut [AccountInfo], order: &[String]) {
+    let positions: HashMap<&str, usize> = order
+        .iter()
+        .enumerate()
+        .map(|(index, id)| (id.as_str(), index))
+        .collect();
+
+    let mut unordered = Vec::new();
+    for (index, account) in accounts.iter_mut().enumerate() {
+        match positions.get(account.id.as_str()) {
+            Some(position) => account.sort_index = *position,
+            None => unordered.push(index),
+        }
+    }
+
+    unordered.sort_by(|a, b| {
+        let (left, right) = (&accounts[*a], &accounts[*b]);
+        left.name
+            .to_lowercase()
+            .cmp(&right.name.to_lowercase())
+            .then_with(|| left.file_path.cmp(&right.file_path))
+    });
+    for (offset, index) in unordered.into_iter().enumerate() {
+        accounts[index].sort_index = order.len() + offset;
+    }
+}
+
+/// Accounts to try when cycling forward from the active one: those after it in `accounts`' order,
+/// then wrapping around. Without an active account the whole list is returned.
+pub fn rotation_after_active(accounts: &[AccountInfo]) -> Vec<&AccountInfo> {
+    let start = accounts
+        .iter()
+        .position(|account| account.is_active)
+        .map_or(0, |index| index + 1);
+    accounts[start..]
+        .iter()
+        .chain(&accounts[..start])
+        .filter(|account| !account.is_active && !account.is_archived)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{assign_sort_indices, prune_account_order, rotation_after_active, sort_accounts, AccountSortKey};
+    use std::collections::HashSet;
+    use crate::AccountInfo;
+
+    fn account(name: &str, expires_at: Option<i64>) -> AccountInfo {
+        AccountInfo {
+            name: name.to_string(),
+            email: format!("{}@example.com", name),
+            file_path: format!("/accounts/{}.json", name),
+            expires_at,
+            ..Default::default()
+        }
+    }
+
+    fn names(accounts: &[AccountInfo]) -> Vec<&str> {
+        accounts.iter().map(|account| account.name.as_str()).collect()
+    }
+
+    #[test]
+    fn sorts_by_name_case_insensitively() {
+        let mut accounts = vec![account("beta", None), account("Alpha", None), account("gamma", None)];
+        sort_accounts(&mut accounts, AccountSortKey::Name, false);
+        assert_eq!(names(&accounts), vec!["Alpha", "beta", "gamma"]);
+    }
+
+    #[test]
+    fn missing_values_sort_last_in_both_directions() {
+        let mut accounts = vec![account("a", None), account("b", Some(20)), account("c", Some(10))];
+
+        sort_accounts(&mut accounts, AccountSortKey::ExpiresAt, false);
+        assert_eq!(names(&accounts), vec!["c", "b", "a"]);
+
+        sort_accounts(&mut accounts, AccountSortKey::ExpiresAt, true);
+        assert_eq!(names(&accounts), vec!["b", "c", "a"]);
+    }
+
+    #[test]
+    fn custom_order_keeps_saved_ids_first_and_appends_new_accounts() {
+        let mut accounts = vec![account("b", None), account("a", None), account("c", None)];
+        for account in accounts.iter_mut() {
+            account.id = format!("id-{}", account.name);
+        }
+
+        let known: HashSet<String> = accounts.iter().map(|account| account.id.clone()).collect();
+        let order = prune_account_order(&["id-c".to_string(), "id-gone".to_string()], &known);
+        assert_eq!(order, vec!["id-c".to_string()]);
+
+        assign_sort_indices(&mut accounts, &order);
+        sort_accounts(&mut accounts, AccountSortKey::Custom, false);
+        assert_eq!(names(&accounts), vec!["c", "a", "b"]);
+        assert_eq!(accounts[2].sort_index, 2);
+    }
+
+    #[test]
+    fn rotates_past_the_active_account_and_wraps() {
+        let account = |name: &str, is_active: bool| AccountInfo {
+            name: name.to_string(),
+            is_active,
+            ..Default::default()
+        };
+        let names = |accounts: &[AccountInfo]| -> Vec<String> {
+            rotation_after_active(accounts)
+                .iter()
+                .map(|account| account.name.clone())
+                .collect()
+        };
+
+        let accounts = vec![account("a", false), account("b", true), account("c", false)];
+        assert_eq!(names(&accounts), vec!["c", "a"]);
+
+        let accounts = vec![account("a", false), account("b", false), account("c", true)];
+        assert_eq!(names(&accounts), vec!["a", "b"]);
+
+        let accounts = vec![account("a", false), account("b", false)];
+        assert_eq!(names(&accounts), vec!["a", "b"]);
+    }
+}
@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PlanKind {
+    Free,
+    Plus,
+    Pro,
+    Team,
+    Business,
+    Enterprise,
+    Edu,
+    #[default]
+    Unknown,
+}
+
+impl PlanKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PlanKind::Free => "free",
+            PlanKind::Plus => "plus",
+            PlanKind::Pro => "pro",
+            PlanKind::Team => "team",
+            PlanKind::Business => "business",
+            PlanKind::Enterprise => "enterprise",
+            PlanKind::Edu => "edu",
+            PlanKind::Unknown => "unknown",
+        }
+    }
+
+    /// Relative usage allowance, higher is more generous; breaks ties when recommending accounts.
+    pub fn tier(self) -> u8 {
+        match self {
+            PlanKind::Pro => 5,
+            PlanKind::Enterprise | PlanKind::Business => 4,
+            PlanKind::Team => 3,
+            PlanKind::Plus | PlanKind::Edu => 2,
+            PlanKind::Free => 1,
+            PlanKind::Unknown => 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct PlanInfo {
+    /// `chatgpt_plan_type` exactly as found in the token.
+    pub raw: String,
+    pub kind: PlanKind,
+    #[serde(rename = "displayName")]
+    pub display_name: String,
+    #[serde(rename = "isTeamOrEnterprise")]
+    pub is_team_or_enterprise: bool,
+}
+
+pub fn plan_info(raw: &str) -> PlanInfo {
+    let normalized = raw.trim().to_ascii_lowercase();
+    let (kind, display_name) = match normalized.as_str() {
+        "free" => (PlanKind::Free, "Free"),
+        "plus" => (PlanKind::Plus, "Plus"),
+        "pro" => (PlanKind::Pro, "Pro"),
+        "team" => (PlanKind::Team, "Team"),
+        "business" => (PlanKind::Business, "Business"),
+        "enterprise" => (PlanKind::Enterprise, "Enterprise"),
+        "edu" | "education" => (PlanKind::Edu, "Edu"),
+        "" | "unknown" => (PlanKind::Unknown, "Unknown"),
+        // Plans we don't know yet still show their raw name rather than a blank.
+        _ => (PlanKind::Unknown, raw.trim()),
+    };
+
+    PlanInfo {
+        raw: raw.to_string(),
+        kind,
+        display_name: display_name.to_string(),
+        is_team_or_enterprise: matches!(kind, PlanKind::Team | PlanKind::Business | PlanKind::Enterprise),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{plan_info, PlanKind};
+
+    #[test]
+    fn maps_known_plan_strings() {
+        let cases = [
+            ("free", PlanKind::Free, "Free", false),
+            ("plus", PlanKind::Plus, "Plus", false),
+            ("pro", PlanKind::Pro, "Pro", false),
+            ("team", PlanKind::Team, "Team", true),
+            ("business", PlanKind::Business, "Business", true),
+            ("Enterprise", PlanKind::Enterprise, "Enterprise", true),
+            ("edu", PlanKind::Edu, "Edu", false),
+            ("unknown", PlanKind::Unknown, "Unknown", false),
+        ];
+
+        for (raw, kind, display_name, is_team) in cases {
+            let plan = plan_info(raw);
+            assert_eq!(plan.raw, raw);
+            assert_eq!(plan.kind, kind, "{}", raw);
+            assert_eq!(plan.display_name, display_name);
+            assert_eq!(plan.is_team_or_enterprise, is_team);
+        }
+    }
+
+    #[test]
+    fn unrecognized_plans_fall_through_to_the_raw_value() {
+        let plan = plan_info("galaxy_brain_2099");
+        assert_eq!(plan.kind, PlanKind::Unknown);
+        assert_eq!(plan.display_name, "galaxy_brain_2099");
+        assert!(!plan.is_team_or_enterprise);
+    }
+}
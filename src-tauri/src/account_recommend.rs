@@ -0,0 +1,192 @@
+use crate::account_cache::AccountScanCache;
+use crate::account_plan::PlanKind;
+use crate::account_usage::{
+    fetch_usage_from_auth_path, UsageCache, UsageErrorKind, UsageInfo, DEFAULT_USAGE_CONCURRENCY,
+    DEFAULT_USAGE_MAX_AGE_SECS,
+};
+use crate::accounts::scan_accounts_with_cache;
+use crate::error::{AppError, AppResult};
+use crate::{AccountInfo, AccountKind};
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::Manager;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountRecommendation {
+    /// 1 is the best account to use now.
+    pub rank: usize,
+    #[serde(rename = "profileId")]
+    pub profile_id: String,
+    pub name: String,
+    pub email: String,
+    #[serde(rename = "filePath")]
+    pub file_path: String,
+    pub plan: PlanKind,
+    /// `PlanKind::tier`, the last tie-breaker.
+    pub tier: u8,
+    /// 100 minus the window's `usedPercent`; 100 when the account reports no such window.
+    #[serde(rename = "primaryRemainingPercent")]
+    pub primary_remaining_percent: Option<f64>,
+    #[serde(rename = "secondaryRemainingPercent")]
+    pub secondary_remaining_percent: Option<f64>,
+    pub usage: Option<UsageInfo>,
+    pub error: Option<String>,
+    #[serde(rename = "errorKind")]
+    pub error_kind: Option<UsageErrorKind>,
+}
+
+impl AccountRecommendation {
+    fn new(account: &AccountInfo, result: Result<UsageInfo, (String, UsageErrorKind)>) -> Self {
+        let remaining = |usage: &UsageInfo, primary: bool| {
+            let window = if primary { &usage.primary_window } else { &usage.secondary_window };
+            100.0 - window.as_ref().map(|window| window.used_percent).unwrap_or(0.0)
+        };
+        let (usage, error) = match result {
+            Ok(usage) => (Some(usage), None),
+            Err(error) => (None, Some(error)),
+        };
+
+        Self {
+            rank: 0,
+            profile_id: account.id.clone(),
+            name: account.name.clone(),
+            email: account.email.clone(),
+            file_path: account.file_path.clone(),
+            plan: account.plan.kind,
+            tier: account.plan.kind.tier(),
+            primary_remaining_percent: usage.as_ref().map(|usage| remaining(usage, true)),
+            secondary_remaining_percent: usage.as_ref().map(|usage| remaining(usage, false)),
+            usage,
+            error: error.as_ref().map(|(message, _)| message.clone()),
+            error_kind: error.map(|(_, kind)| kind),
+        }
+    }
+}
+
+/// Most primary-window headroom first, then secondary-window headroom, then plan tier. Accounts
+/// whose usage could not be fetched come last.
+fn compare_recommendations(a: &AccountRecommendation, b: &AccountRecommendation) -> Ordering {
+    let headroom = |recommendation: &AccountRecommendation| {
+        recommendation
+            .primary_remaining_percent
+            .zip(recommendation.secondary_remaining_percent)
+    };
+    match (headroom(a), headroom(b)) {
+        (Some((a_primary, a_secondary)), Some((b_primary, b_secondary))) => b_primary
+            .total_cmp(&a_primary)
+            .then(b_secondary.total_cmp(&a_secondary))
+            .then(b.tier.cmp(&a.tier)),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => b.tier.cmp(&a.tier),
+    }
+}
+
+fn rank_recommendations(recommendations: &mut [AccountRecommendation]) {
+    recommendations.sort_by(compare_recommendations);
+    for (index, recommendation) in recommendations.iter_mut().enumerate() {
+        recommendation.rank = index + 1;
+    }
+}
+
+/// Ranks usable accounts (token-based, not expired, archived, dead or cooling down) by remaining
+/// capacity. Usage comes from the cache when at most five minutes old, otherwise it is fetched.
+#[tauri::command]
+pub async fn recommend_account(app: tauri::AppHandle) -> AppResult<Vec<AccountRecommendation>> {
+    let scan = scan_accounts_with_cache(&app.state::<AccountScanCache>(), None, None, None).map_err(AppError::io)?;
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let candidates: Vec<AccountInfo> = scan
+        .accounts
+        .into_iter()
+        .filter(|account| {
+            account.kind == AccountKind::ChatGpt
+                && !account.is_archived
+                && !account.refresh_dead
+                && !account.is_cooling_down
+                && account.duplicate_of.is_none()
+                && account.expires_at.is_none_or(|expires_at| expires_at > now_ms / 1000)
+        })
+        .collect();
+
+    let permits = Arc::new(tokio::sync::Semaphore::new(DEFAULT_USAGE_CONCURRENCY as usize));
+    let mut tasks = tokio::task::JoinSet::new();
+    for account in candidates {
+        let permits = permits.clone();
+        let app = app.clone();
+        tasks.spawn(async move {
+            let cache = app.state::<UsageCache>();
+            let cached = cache.get_fresh(&account.id, DEFAULT_USAGE_MAX_AGE_SECS, now_ms);
+            let result = match cached {
+                Some(usage) => Ok(usage),
+                None => {
+                    let _permit = permits.acquire_owned().await;
+                    let result = fetch_usage_from_auth_path(&app, &PathBuf::from(&account.file_path)).await;
+                    if let Ok(usage) = &result {
+                        cache.store(&account.id, usage);
+                    }
+                    result.map_err(|error| (error.message, error.kind))
+                }
+            };
+            AccountRecommendation::new(&account, result)
+        });
+    }
+
+    let mut recommendations = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        recommendations.push(joined.map_err(|e| AppError::external(format!("Usage task failed: {}", e)))?);
+    }
+    rank_recommendations(&mut recommendations);
+    Ok(recommendations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rank_recommendations, AccountRecommendation};
+    use crate::account_plan::PlanKind;
+
+    fn recommendation(name: &str, headroom: Option<(f64, f64)>, plan: PlanKind) -> AccountRecommendation {
+        AccountRecommendation {
+            rank: 0,
+            profile_id: name.to_string(),
+            name: name.to_string(),
+            email: String::new(),
+            file_path: String::new(),
+            plan,
+            tier: plan.tier(),
+            primary_remaining_percent: headroom.map(|(primary, _)| primary),
+            secondary_remaining_percent: headroom.map(|(_, secondary)| secondary),
+            usage: None,
+            error: headroom.is_none().then(|| "offline".to_string()),
+            error_kind: None,
+        }
+    }
+
+    #[test]
+    fn ranks_by_primary_then_secondary_then_tier_with_failures_last() {
+        let mut recommendations = vec![
+            recommendation("failed-pro", None, PlanKind::Pro),
+            recommendation("plus-busy", Some((20.0, 90.0)), PlanKind::Plus),
+            recommendation("plus-free-week", Some((80.0, 70.0)), PlanKind::Plus),
+            recommendation("pro-free-week", Some((80.0, 70.0)), PlanKind::Pro),
+            recommendation("team-fresh", Some((80.0, 95.0)), PlanKind::Team),
+        ];
+        rank_recommendations(&mut recommendations);
+
+        let order: Vec<(&str, usize)> = recommendations
+            .iter()
+            .map(|recommendation| (recommendation.name.as_str(), recommendation.rank))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                ("team-fresh", 1),
+                ("pro-free-week", 2),
+                ("plus-free-week", 3),
+                ("plus-busy", 4),
+                ("failed-pro", 5),
+            ]
+        );
+    }
+}
@@ -0,0 +1,130 @@
+use crate::account_cache::AccountScanCache;
+use crate::account_status::TokenStatus;
+use crate::accounts::scan_accounts_with_cache;
+use crate::AccountInfo;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct AccountFilters {
+    /// Matches either the raw `planType` or the normalized plan kind, case-insensitively.
+    #[serde(default, rename = "planType")]
+    pub plan_type: Option<String>,
+    #[serde(default, rename = "activeOnly")]
+    pub active_only: bool,
+    #[serde(default, rename = "expiredOnly")]
+    pub expired_only: bool,
+    #[serde(default, rename = "includeArchived")]
+    pub include_archived: bool,
+}
+
+fn matches_query(account: &AccountInfo, query: &str) -> bool {
+    if query.is_empty() {
+        return true;
+    }
+
+    account.name.to_lowercase().contains(query)
+        || account.email.to_lowercase().contains(query)
+        || account.tags.iter().any(|tag| tag.to_lowercase().contains(query))
+}
+
+fn matches_filters(account: &AccountInfo, filters: &AccountFilters) -> bool {
+    if filters.active_only && !account.is_active {
+        return false;
+    }
+    if filters.expired_only && account.token_status != TokenStatus::Expired {
+        return false;
+    }
+
+    match filters.plan_type.as_deref().map(str::trim).filter(|value| !value.is_empty()) {
+        Some(plan_type) => {
+            account.plan_type.eq_ignore_ascii_case(plan_type) || account.plan.kind.as_str().eq_ignore_ascii_case(plan_type)
+        }
+        None => true,
+    }
+}
+
+pub fn filter_accounts(accounts: Vec<AccountInfo>, query: &str, filters: &AccountFilters) -> Vec<AccountInfo> {
+    let query = query.trim().to_lowercase();
+    accounts
+        .into_iter()
+        .filter(|account| matches_query(account, &query) && matches_filters(account, filters))
+        .collect()
+}
+
+/// Case-insensitive substring search over name, email and tags; an empty query returns every account.
+#[tauri::command]
+pub fn search_accounts(
+    cache: tauri::State<'_, AccountScanCache>,
+    query: Option<String>,
+    filters: Option<AccountFilters>,
+) -> Result<Vec<AccountInfo>, String> {
+    let filters = filters.unwrap_or_default();
+    let scan = scan_accounts_with_cache(&cache, None, None, Some(filters.include_archived))?;
+    Ok(filter_accounts(scan.accounts, query.as_deref().unwrap_or(""), &filters))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{filter_accounts, AccountFilters};
+    use crate::account_plan::plan_info;
+    use crate::account_status::TokenStatus;
+    use crate::AccountInfo;
+
+    fn account(name: &str, email: &str, plan_type: &str, tags: &[&str]) -> AccountInfo {
+        AccountInfo {
+            name: name.to_string(),
+            email: email.to_string(),
+            plan_type: plan_type.to_string(),
+            plan: plan_info(plan_type),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn names(accounts: &[AccountInfo]) -> Vec<&str> {
+        accounts.iter().map(|account| account.name.as_str()).collect()
+    }
+
+    #[test]
+    fn matches_name_email_and_tags_including_unicode() {
+        let accounts = vec![
+            account("工作账号", "work@example.com", "team", &[]),
+            account("Personal", "ME@Example.com", "plus", &["Side"]),
+            account("spare", "spare@example.com", "free", &["备用"]),
+        ];
+
+        assert_eq!(filter_accounts(accounts.clone(), "", &AccountFilters::default()).len(), 3);
+        assert_eq!(names(&filter_accounts(accounts.clone(), "工作", &AccountFilters::default())), vec!["工作账号"]);
+        assert_eq!(names(&filter_accounts(accounts.clone(), "me@EXAMPLE", &AccountFilters::default())), vec!["Personal"]);
+        assert_eq!(names(&filter_accounts(accounts.clone(), "side", &AccountFilters::default())), vec!["Personal"]);
+        assert_eq!(names(&filter_accounts(accounts, "备用", &AccountFilters::default())), vec!["spare"]);
+    }
+
+    #[test]
+    fn applies_plan_active_and_expired_filters() {
+        let mut accounts = vec![
+            account("a", "a@example.com", "team", &[]),
+            account("b", "b@example.com", "plus", &[]),
+        ];
+        accounts[0].is_active = true;
+        accounts[1].token_status = TokenStatus::Expired;
+
+        let plan_filter = AccountFilters {
+            plan_type: Some("PLUS".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(names(&filter_accounts(accounts.clone(), "", &plan_filter)), vec!["b"]);
+
+        let active_filter = AccountFilters {
+            active_only: true,
+            ..Default::default()
+        };
+        assert_eq!(names(&filter_accounts(accounts.clone(), "", &active_filter)), vec!["a"]);
+
+        let expired_filter = AccountFilters {
+            expired_only: true,
+            ..Default::default()
+        };
+        assert_eq!(names(&filter_accounts(accounts, "", &expired_filter)), vec!["b"]);
+    }
+}
@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenStatus {
+    Valid,
+    Expiring,
+    Expired,
+    #[default]
+    Unknown,
+}
+
+impl TokenStatus {
+    pub fn needs_refresh(self) -> bool {
+        matches!(self, TokenStatus::Expiring | TokenStatus::Expired)
+    }
+}
+
+/// `expires_at` and `now` are Unix seconds, matching the JWT `exp` claim.
+pub fn evaluate_token_status(expires_at: Option<i64>, now: i64, refresh_threshold_minutes: i64) -> TokenStatus {
+    let Some(expires_at) = expires_at else {
+        return TokenStatus::Unknown;
+    };
+
+    if expires_at <= now {
+        TokenStatus::Expired
+    } else if expires_at - now <= refresh_threshold_minutes.max(0) * 60 {
+        TokenStatus::Expiring
+    } else {
+        TokenStatus::Valid
+    }
+}
+
+/// `last_refresh` as written by the Codex CLI (RFC 3339) in Unix ms; `None` when missing or malformed.
+pub fn parse_last_refresh(value: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc3339(value.trim())
+        .ok()
+        .map(|timestamp| timestamp.timestamp_millis())
+}
+
+/// `chatgpt_subscription_active_until` in Unix seconds. The claim has been issued as epoch
+/// seconds (number or string), epoch ms, RFC 3339, and bare dates over time.
+pub fn parse_subscription_end(value: &str) -> Option<i64> {
+    let value = value.trim();
+    if let Ok(epoch) = value.parse::<f64>() {
+        if !epoch.is_finite() || epoch <= 0.0 {
+            return None;
+        }
+        let seconds = if epoch >= 1e12 { epoch / 1000.0 } else { epoch };
+        return Some(seconds as i64);
+    }
+    if let Ok(timestamp) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Some(timestamp.timestamp());
+    }
+    if let Ok(naive) = chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f") {
+        return Some(naive.and_utc().timestamp());
+    }
+    chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|midnight| midnight.and_utc().timestamp())
+}
+
+/// Whole days until the subscription lapses, floored and never negative.
+pub fn subscription_days_left(ends_at: Option<i64>, now: i64) -> Option<i64> {
+    ends_at.map(|ends_at| (ends_at - now).max(0) / (24 * 60 * 60))
+}
+
+/// Profiles with an unknown refresh time are not flagged. A huge `stale_after_days` (config.json is
+/// hand-editable) means never stale rather than overflowing.
+pub fn is_stale(last_refresh_at: Option<i64>, now_ms: i64, stale_after_days: i64) -> bool {
+    let stale_after_ms = stale_after_days.max(0).saturating_mul(24 * 60 * 60 * 1000);
+    last_refresh_at.is_some_and(|refreshed_at| now_ms.saturating_sub(refreshed_at) > stale_after_ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        evaluate_token_status,
+        is_stale,
+        parse_last_refresh,
+        parse_subscription_end,
+        subscription_days_left,
+        TokenStatus,
+    };
+
+    #[test]
+    fn classifies_expiry_against_threshold() {
+        let now = 1_700_000_000;
+        assert_eq!(evaluate_token_status(None, now, 30), TokenStatus::Unknown);
+        assert_eq!(evaluate_token_status(Some(now - 1), now, 30), TokenStatus::Expired);
+        assert_eq!(evaluate_token_status(Some(now + 10 * 60), now, 30), TokenStatus::Expiring);
+        assert_eq!(evaluate_token_status(Some(now + 31 * 60), now, 30), TokenStatus::Valid);
+        assert!(TokenStatus::Expired.needs_refresh());
+        assert!(!TokenStatus::Unknown.needs_refresh());
+    }
+
+    #[test]
+    fn parses_last_refresh_and_flags_stale_profiles() {
+        let refreshed_at = parse_last_refresh("2025-01-01T00:00:00.123456Z").expect("timestamp");
+        assert_eq!(refreshed_at, 1_735_689_600_123);
+        assert_eq!(parse_last_refresh("2025-01-01T08:00:00+08:00"), Some(1_735_689_600_000));
+        assert_eq!(parse_last_refresh(""), None);
+        assert_eq!(parse_last_refresh("yesterday"), None);
+
+        let day_ms = 24 * 60 * 60 * 1000;
+        assert!(!is_stale(Some(refreshed_at), refreshed_at + 6 * day_ms, 7));
+        assert!(is_stale(Some(refreshed_at), refreshed_at + 8 * day_ms, 7));
+        assert!(!is_stale(None, refreshed_at, 7));
+        assert!(!is_stale(Some(refreshed_at), refreshed_at + 8 * day_ms, i64::MAX));
+    }
+
+    #[test]
+    fn parses_subscription_end_formats() {
+        let expected = 1_893_456_000; // 2030-01-01T00:00:00Z
+        assert_eq!(parse_subscription_end("2030-01-01T00:00:00Z"), Some(expected));
+        assert_eq!(parse_subscription_end("2030-01-01T08:00:00+08:00"), Some(expected));
+        assert_eq!(parse_subscription_end("2030-01-01T00:00:00.000"), Some(expected));
+        assert_eq!(parse_subscription_end("2030-01-01"), Some(expected));
+        assert_eq!(parse_subscription_end("1893456000"), Some(expected));
+        assert_eq!(parse_subscription_end("1893456000000"), Some(expected));
+        assert_eq!(parse_subscription_end(" 1893456000.5 "), Some(expected));
+        assert_eq!(parse_subscription_end(""), None);
+        assert_eq!(parse_subscription_end("0"), None);
+        assert_eq!(parse_subscription_end("NaN"), None);
+        assert_eq!(parse_subscription_end("next month"), None);
+        assert_eq!(parse_subscription_end("01/01/2030"), None);
+    }
+
+    #[test]
+    fn counts_subscription_days_left() {
+        let day = 24 * 60 * 60;
+        let now = 1_700_000_000;
+        assert_eq!(subscription_days_left(None, now), None);
+        assert_eq!(subscription_days_left(Some(now + 3 * day + 10), now), Some(3));
+        assert_eq!(subscription_days_left(Some(now + day - 1), now), Some(0));
+        assert_eq!(subscription_days_left(Some(now - 5 * day), now), Some(0));
+    }
+}
@@ -1,12 +1,209 @@
-use crate::account_files::{files_have_same_content, paths_match, resolve_managed_account_path};
-use crate::config::load_config;
-use crate::{get_accounts_dir, get_codex_auth_file, CodexAuthFile};
+use crate::account_files::{
+    collect_account_files,
+    file_modified_at,
+    files_have_same_content,
+    paths_match,
+    resolve_managed_account_path,
+    ParsedAccountFile,
+    restrict_permissions,
+    validate_account_path,
+    write_private_file_atomic,
+};
+use crate::account_meta::set_refresh_dead;
+use crate::account_status::parse_last_refresh;
+use crate::auth_backups::backup_active_auth;
+use crate::config::{load_config, validate_auth_url};
+use crate::error::{AppError, AppResult};
+use crate::http_client::http_client;
+use crate::{http_retry, trace};
+use crate::{
+    extract_info_from_auth, extract_profile_id_from_auth, get_accounts_dir, get_codex_auth_file, AccountInfo, CodexAuthFile,
+    CodexTokens,
+};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+
+pub const TOKEN_REFRESH_PROGRESS_EVENT: &str = "token-refresh-progress";
+
+/// Refreshes `refresh_all_tokens` runs at once, to stay polite to the auth endpoint.
+const BULK_REFRESH_CONCURRENCY: usize = 3;
+
+/// Where a profile's token refresh stands, as seen by this process.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RefreshState {
+    Queued,
+    InProgress,
+    Succeeded,
+    Failed,
+}
+
+#[derive(Debug, Clone)]
+struct RefreshStatus {
+    state: RefreshState,
+    last_error: Option<String>,
+}
+
+/// One async lock per profile, held around read-refresh-write so two refreshes of the same file
+/// cannot race and persist an already-rotated refresh token. Also tracks each profile's latest
+/// refresh state for the account list.
+#[derive(Default)]
+pub struct RefreshLocks {
+    locks: Mutex<HashMap<PathBuf, Arc<tokio::sync::Mutex<()>>>>,
+    statuses: Mutex<HashMap<PathBuf, RefreshStatus>>,
+}
+
+fn refresh_key(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+impl RefreshLocks {
+    fn lock_for(&self, path: &Path) -> Arc<tokio::sync::Mutex<()>> {
+        let mut locks = self.locks.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        locks.entry(refresh_key(path)).or_default().clone()
+    }
+
+    /// Records `state`; `error` replaces the last error on success or failure and is kept otherwise.
+    pub(crate) fn set_state(&self, path: &Path, state: RefreshState, error: Option<String>) {
+        let mut statuses = self.statuses.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let status = statuses.entry(refresh_key(path)).or_insert(RefreshStatus {
+            state,
+            last_error: None,
+        });
+        status.state = state;
+        if matches!(state, RefreshState::Succeeded | RefreshState::Failed) {
+            status.last_error = error;
+        }
+    }
+
+    /// Marks `path` queued unless a refresh of it is already queued or running.
+    pub fn try_queue(&self, path: &Path) -> bool {
+        let key = refresh_key(path);
+        let mut statuses = self.statuses.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        match statuses.get_mut(&key) {
+            Some(status) if matches!(status.state, RefreshState::Queued | RefreshState::InProgress) => false,
+            Some(status) => {
+                status.state = RefreshState::Queued;
+                true
+            }
+            None => {
+                statuses.insert(
+                    key,
+                    RefreshStatus {
+                        state: RefreshState::Queued,
+                        last_error: None,
+                    },
+                );
+                true
+            }
+        }
+    }
+
+    /// Copies the tracked refresh state onto scanned accounts.
+    pub fn annotate(&self, accounts: &mut [AccountInfo]) {
+        let statuses = self.statuses.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if statuses.is_empty() {
+            return;
+        }
+        for account in accounts {
+            if let Some(status) = statuses.get(&refresh_key(Path::new(&account.file_path))) {
+                account.refresh_state = Some(status.state);
+                account.last_refresh_error = status.last_error.clone();
+            }
+        }
+    }
+}
+
+fn read_last_refresh(path: &PathBuf) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str::<CodexAuthFile>(&content).ok().map(|auth| auth.last_refresh)
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RefreshErrorKind {
+    Expired,
+    Reused,
+    Invalidated,
+    Network,
+    Other,
+}
+
+#[derive(Debug, Clone)]
+pub struct RefreshError {
+    pub kind: RefreshErrorKind,
+    pub message: String,
+}
+
+impl RefreshError {
+    fn other(message: impl Into<String>) -> Self {
+        Self {
+            kind: RefreshErrorKind::Other,
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for RefreshError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.message.fmt(f)
+    }
+}
+
+impl From<RefreshError> for AppError {
+    /// Dead refresh tokens surface as `REFRESH_DEAD` so the UI can ask for a new login.
+    fn from(error: RefreshError) -> Self {
+        match error.kind {
+            RefreshErrorKind::Expired | RefreshErrorKind::Reused | RefreshErrorKind::Invalidated => {
+                AppError::refresh_dead(error.message)
+            }
+            RefreshErrorKind::Network => AppError::network(error.message),
+            RefreshErrorKind::Other => AppError::external(error.message),
+        }
+    }
+}
+
+/// State of a profile after `refresh_account_token`.
+#[derive(Debug, Clone, Serialize)]
+pub struct RefreshResult {
+    /// Access token `exp` (Unix seconds).
+    #[serde(rename = "expiresAt")]
+    pub expires_at: Option<i64>,
+    #[serde(rename = "lastRefresh")]
+    pub last_refresh: String,
+    /// Whether the server issued a new refresh token.
+    #[serde(rename = "refreshTokenRotated")]
+    pub refresh_token_rotated: bool,
+    pub email: String,
+    #[serde(rename = "planType")]
+    pub plan_type: String,
+    /// Whether `~/.codex/auth.json` was rewritten as well.
+    #[serde(rename = "activeSessionUpdated")]
+    pub active_session_updated: bool,
+}
+
+impl RefreshResult {
+    fn from_auth(auth: &CodexAuthFile, refresh_token_rotated: bool, active_session_updated: bool) -> Self {
+        let info = extract_info_from_auth(auth, 0);
+        Self {
+            expires_at: info.expires_at,
+            last_refresh: auth.last_refresh.clone(),
+            refresh_token_rotated,
+            email: info.email,
+            plan_type: info.plan_type,
+            active_session_updated,
+        }
+    }
+}
 
 #[derive(Debug, Serialize)]
 struct TokenRefreshRequest {
-    client_id: &'static str,
+    client_id: String,
     grant_type: &'static str,
     refresh_token: String,
     scope: &'static str,
@@ -19,12 +216,248 @@ struct TokenRefreshResponse {
     refresh_token: Option<String>,
 }
 
-const CODEX_CLIENT_ID: &str = "app_EMoamEEZ73f0CkXaXp7hrann";
-const TOKEN_REFRESH_URL: &str = "https://auth.openai.com/oauth/token";
+const REFRESH_MAX_ATTEMPTS: u32 = 3;
+const REFRESH_RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+const REFRESH_REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+/// Upper bound for one refresh including all retries.
+const REFRESH_TOTAL_TIMEOUT: Duration = Duration::from_secs(45);
+
+fn is_dead_refresh_token(kind: RefreshErrorKind) -> bool {
+    matches!(
+        kind,
+        RefreshErrorKind::Expired | RefreshErrorKind::Reused | RefreshErrorKind::Invalidated
+    )
+}
+
+/// Maps a failed token response to an error; the auth server reports dead refresh tokens as
+/// `error.code` (or a top-level `code`).
+fn classify_refresh_failure(status: reqwest::StatusCode, body: &str) -> RefreshError {
+    if let Some(error_json) = serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .filter(|error_json| error_json.get("error").is_some())
+    {
+        let error_code = error_json["error"]
+            .get("code")
+            .or_else(|| error_json.get("code"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown");
+        let (kind, dead_reason) = match error_code {
+            "refresh_token_expired" => (RefreshErrorKind::Expired, "expired"),
+            "refresh_token_reused" => (RefreshErrorKind::Reused, "reused"),
+            "refresh_token_invalidated" => (RefreshErrorKind::Invalidated, "invalidated"),
+            _ => return RefreshError::other(format!("Refresh failed: {} - {}", status, body)),
+        };
+        return RefreshError {
+            kind,
+            message: format!("Refresh Token {}, please log in to Codex CLI again", dead_reason),
+        };
+    }
+
+    let kind = if status.is_server_error() {
+        RefreshErrorKind::Network
+    } else {
+        RefreshErrorKind::Other
+    };
+    RefreshError {
+        kind,
+        message: format!("Refresh failed: HTTP {} - {}", status, body),
+    }
+}
+
+/// Copies the refreshed tokens over `auth`, leaving every other field untouched.
+fn apply_refreshed_tokens(auth: &CodexAuthFile, response: TokenRefreshResponse, refreshed_at: String) -> CodexAuthFile {
+    let mut updated_auth = auth.clone();
+    if let Some(updated_tokens) = updated_auth.tokens.as_mut() {
+        if let Some(new_access_token) = response.access_token {
+            updated_tokens.access_token = new_access_token;
+        }
+        if let Some(new_id_token) = response.id_token {
+            updated_tokens.id_token = new_id_token;
+        }
+        if let Some(new_refresh_token) = response.refresh_token {
+            updated_tokens.refresh_token = new_refresh_token;
+        }
+    }
+
+    updated_auth.last_refresh = refreshed_at;
+    updated_auth
+}
+
+/// Whether the live auth file belongs to the same user as `auth`, so refreshed tokens should be
+/// mirrored there. Compares profile ids: teammates share the upstream workspace account id.
+fn live_session_matches(live_path: &PathBuf, auth: &CodexAuthFile) -> bool {
+    let profile_id = extract_profile_id_from_auth(auth, None);
+    !profile_id.trim().is_empty()
+        && fs::read_to_string(live_path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<CodexAuthFile>(&content).ok())
+            .is_some_and(|live| extract_profile_id_from_auth(&live, None) == profile_id)
+}
 
 #[tauri::command]
-pub async fn refresh_account_token(file_path: String) -> Result<String, String> {
+pub async fn refresh_account_token(
+    locks: tauri::State<'_, RefreshLocks>,
+    file_path: String,
+) -> AppResult<RefreshResult> {
+    let validated_path = validate_account_path(&file_path, &get_accounts_dir())?;
+    Ok(refresh_profile_file(&locks, &validated_path).await?)
+}
+
+/// `<profile>.json.bak`, holding the profile as it was before the last refresh write.
+fn rollback_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    path.with_file_name(name)
+}
+
+/// Writes `updated` over `path`, keeping `previous` in the rollback file until the written
+/// content has been read back intact. On failure the rollback file is left for
+/// `restore_account_backup`.
+fn write_with_rollback(path: &Path, previous: &str, updated: &str) -> Result<(), String> {
+    let rollback = rollback_path(path);
+    write_private_file_atomic(&rollback, previous.as_bytes())
+        .map_err(|e| format!("Failed to write rollback copy: {}", e))?;
+    write_private_file_atomic(path, updated.as_bytes()).map_err(|e| {
+        format!("Failed to write file: {}; previous tokens kept in {}", e, rollback.display())
+    })?;
+    if fs::read_to_string(path).ok().as_deref() != Some(updated) {
+        return Err(format!(
+            "Refreshed tokens did not persist; previous tokens kept in {}",
+            rollback.display()
+        ));
+    }
+    let _ = fs::remove_file(&rollback);
+    Ok(())
+}
+
+/// Puts back the copy a failed refresh left in `<profile>.json.bak`.
+#[tauri::command]
+pub async fn restore_account_backup(
+    locks: tauri::State<'_, RefreshLocks>,
+    file_path: String,
+) -> Result<(), String> {
     let validated_path = resolve_managed_account_path(&file_path, &get_accounts_dir())?;
+    let lock = locks.lock_for(&validated_path);
+    let _guard = lock.lock().await;
+    let rollback = rollback_path(&validated_path);
+    let content = fs::read_to_string(&rollback).map_err(|_| "No rollback copy exists for this account".to_string())?;
+    serde_json::from_str::<CodexAuthFile>(&content).map_err(|e| format!("Rollback copy is not valid: {}", e))?;
+    write_private_file_atomic(&validated_path, content.as_bytes())
+        .map_err(|e| format!("Failed to restore account: {}", e))?;
+    fs::remove_file(&rollback).map_err(|e| format!("Restored, but failed to remove rollback copy: {}", e))
+}
+
+/// Refreshes a chatgpt profile whose access token expires within `window_minutes`, so a switch
+/// never activates a dead session. API-key profiles and tokens without an `exp` claim are left alone.
+pub(crate) async fn refresh_before_switch(
+    locks: &RefreshLocks,
+    path: &PathBuf,
+    window_minutes: i64,
+) -> Result<(), String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read authentication file: {}", e))?;
+    let auth: CodexAuthFile =
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse authentication file: {}", e))?;
+    if auth.tokens.is_none() || !extract_info_from_auth(&auth, window_minutes).needs_refresh() {
+        return Ok(());
+    }
+
+    refresh_profile_file(locks, path)
+        .await
+        .map(|_| ())
+        .map_err(|error| format!("Could not refresh the token before switching: {}", error))
+}
+
+/// Posts the refresh request, retrying network failures, 429 and 5xx with backoff. Auth errors
+/// are returned immediately; a rejected refresh token will not recover by retrying.
+async fn request_token_refresh(url: &str, request: &TokenRefreshRequest) -> Result<TokenRefreshResponse, RefreshError> {
+    let deadline = tokio::time::Instant::now() + REFRESH_TOTAL_TIMEOUT;
+    let client = http_client(REFRESH_REQUEST_TIMEOUT).map_err(|e| RefreshError::other(e.message))?;
+    let mut attempt = 1;
+    loop {
+        let timeout = deadline.saturating_duration_since(tokio::time::Instant::now());
+        let (error, retry_after) = match client
+            .post(url)
+            .header("Content-Type", "application/json")
+            .timeout(timeout.min(REFRESH_REQUEST_TIMEOUT))
+            .json(request)
+            .send()
+            .await
+        {
+            Ok(response) if response.status().is_success() => {
+                return response
+                    .json()
+                    .await
+                    .map_err(|e| RefreshError::other(format!("Failed to parse response: {}", e)));
+            }
+            Ok(response) => {
+                let status = response.status();
+                let retry_after = http_retry::retry_after_header(response.headers());
+                let body = response.text().await.unwrap_or_default();
+                let error = classify_refresh_failure(status, &body);
+                if !http_retry::is_retryable_status(status) || is_dead_refresh_token(error.kind) {
+                    return Err(error);
+                }
+                (error, retry_after)
+            }
+            Err(e) => (
+                RefreshError {
+                    kind: RefreshErrorKind::Network,
+                    message: format!("Request failed: {}", e),
+                },
+                None,
+            ),
+        };
+
+        let delay = http_retry::backoff_delay(attempt, REFRESH_RETRY_BASE_DELAY, retry_after, http_retry::jitter_seed());
+        if attempt >= REFRESH_MAX_ATTEMPTS || tokio::time::Instant::now() + delay >= deadline {
+            trace::emit(
+                "token_refresh",
+                "gave_up",
+                serde_json::json!({ "attempts": attempt, "error": error.message }),
+            );
+            return Err(RefreshError {
+                message: format!("{} (after {} attempts)", error.message, attempt),
+                ..error
+            });
+        }
+        trace::emit(
+            "token_refresh",
+            "retry",
+            serde_json::json!({ "attempt": attempt, "delayMs": delay.as_millis() as u64, "error": error.message }),
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// Refreshes the profile at `validated_path` and mirrors the result into `~/.codex/auth.json`
+/// when that profile is active. A caller that had to wait for another refresh of the same file
+/// skips the network call and gets the profile's current state.
+pub(crate) async fn refresh_profile_file(locks: &RefreshLocks, validated_path: &PathBuf) -> Result<RefreshResult, RefreshError> {
+    locks.set_state(validated_path, RefreshState::InProgress, None);
+    let result = refresh_profile_file_locked(locks, validated_path).await;
+    match &result {
+        Ok(_) => locks.set_state(validated_path, RefreshState::Succeeded, None),
+        Err(error) => locks.set_state(validated_path, RefreshState::Failed, Some(error.message.clone())),
+    }
+    result
+}
+
+async fn refresh_profile_file_locked(
+    locks: &RefreshLocks,
+    validated_path: &PathBuf,
+) -> Result<RefreshResult, RefreshError> {
+    let observed_refresh = read_last_refresh(validated_path);
+    let lock = locks.lock_for(validated_path);
+    let _guard = lock.lock().await;
+    if read_last_refresh(validated_path) != observed_refresh {
+        let content = fs::read_to_string(validated_path)
+            .map_err(|e| RefreshError::other(format!("Failed to read authentication file: {}", e)))?;
+        let auth: CodexAuthFile = serde_json::from_str(&content)
+            .map_err(|e| RefreshError::other(format!("Failed to parse authentication file: {}", e)))?;
+        return Ok(RefreshResult::from_auth(&auth, false, false));
+    }
+
     let active_auth_path = get_codex_auth_file();
     let active_accounts_dir = get_accounts_dir();
     let configured_active_path = load_config()
@@ -32,90 +465,389 @@ pub async fn refresh_account_token(file_path: String) -> Result<String, String>
         .and_then(|value| resolve_managed_account_path(&value, &active_accounts_dir).ok());
     let was_active_source = configured_active_path
         .as_ref()
-        .is_some_and(|active_path| paths_match(active_path, &validated_path))
+        .is_some_and(|active_path| paths_match(active_path, validated_path))
         || (active_auth_path.exists()
-            && !paths_match(&active_auth_path, &validated_path)
-            && files_have_same_content(&validated_path, &active_auth_path));
-    let content = fs::read_to_string(&validated_path)
-        .map_err(|e| format!("Failed to read authentication file: {}", e))?;
+            && !paths_match(&active_auth_path, validated_path)
+            && files_have_same_content(validated_path, &active_auth_path));
+    let content = fs::read_to_string(validated_path)
+        .map_err(|e| RefreshError::other(format!("Failed to read authentication file: {}", e)))?;
 
     let auth: CodexAuthFile = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse authentication file: {}", e))?;
+        .map_err(|e| RefreshError::other(format!("Failed to parse authentication file: {}", e)))?;
 
+    let tokens = auth.require_tokens("Token refresh").map_err(RefreshError::other)?;
+    let profile_id = extract_profile_id_from_auth(&auth, Some(validated_path));
+
+    let config = load_config();
+    let token_url = validate_auth_url(&config.token_refresh_url, config.allow_insecure_auth_url)
+        .map_err(|error| RefreshError::other(error.message))?;
     let refresh_request = TokenRefreshRequest {
-        client_id: CODEX_CLIENT_ID,
+        client_id: config.oauth_client_id,
         grant_type: "refresh_token",
-        refresh_token: auth.tokens.refresh_token.clone(),
+        refresh_token: tokens.refresh_token.clone(),
         scope: "openid profile email",
     };
 
-    let client = reqwest::Client::new();
+    let refresh_response = match request_token_refresh(&token_url, &refresh_request).await {
+        Ok(refresh_response) => refresh_response,
+        Err(error) => {
+            if is_dead_refresh_token(error.kind) {
+                let _ = set_refresh_dead(&active_accounts_dir, &profile_id, true);
+            }
+            return Err(error);
+        }
+    };
+
+    let updated_auth = apply_refreshed_tokens(&auth, refresh_response, chrono::Utc::now().to_rfc3339());
+    let refresh_token_rotated = updated_auth
+        .tokens
+        .as_ref()
+        .is_some_and(|updated| updated.refresh_token != tokens.refresh_token);
+
+    let updated_content = serde_json::to_string_pretty(&updated_auth)
+        .map_err(|e| RefreshError::other(format!("Failed to serialize: {}", e)))?;
+    write_with_rollback(validated_path, &content, &updated_content).map_err(RefreshError::other)?;
+    let _ = set_refresh_dead(&active_accounts_dir, &profile_id, false);
+
+    let mut synced_active_auth = false;
+    let is_live_session = was_active_source || live_session_matches(&active_auth_path, &auth);
+    if is_live_session && active_auth_path.exists() && !paths_match(&active_auth_path, validated_path) {
+        backup_active_auth().map_err(|e| RefreshError::other(format!("Failed to back up auth.json: {}", e)))?;
+        write_private_file_atomic(&active_auth_path, updated_content.as_bytes())
+            .map_err(|e| RefreshError::other(format!("Failed to update active authentication file: {}", e)))?;
+        synced_active_auth = true;
+    }
+
+    Ok(RefreshResult::from_auth(&updated_auth, refresh_token_rotated, synced_active_auth))
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RevocationOutcome {
+    Revoked,
+    /// The auth server answered but refused the revocation.
+    Rejected,
+    /// The auth server could not be reached.
+    NetworkError,
+    /// No token of this type was stored.
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenRevocation {
+    pub outcome: RevocationOutcome,
+    pub message: Option<String>,
+}
+
+/// The revocation endpoint next to the configured token endpoint (`.../oauth/revoke`).
+fn revoke_url(token_url: &str) -> Result<String, String> {
+    reqwest::Url::parse(token_url)
+        .and_then(|url| url.join("revoke"))
+        .map(|url| url.to_string())
+        .map_err(|e| format!("Invalid auth URL: {}", e))
+}
+
+/// Revokes `token` per RFC 7009 at the endpoint derived from the configured token URL.
+pub(crate) async fn revoke_token(token: &str, token_type_hint: &str) -> TokenRevocation {
+    if token.trim().is_empty() {
+        return TokenRevocation {
+            outcome: RevocationOutcome::Skipped,
+            message: None,
+        };
+    }
+    let config = load_config();
+    let url = match validate_auth_url(&config.token_refresh_url, config.allow_insecure_auth_url)
+        .map_err(|error| error.message)
+        .and_then(|token_url| revoke_url(&token_url))
+    {
+        Ok(url) => url,
+        Err(message) => {
+            return TokenRevocation {
+                outcome: RevocationOutcome::Rejected,
+                message: Some(message),
+            }
+        }
+    };
+
+    let client = match http_client(REFRESH_REQUEST_TIMEOUT) {
+        Ok(client) => client,
+        Err(error) => {
+            return TokenRevocation {
+                outcome: RevocationOutcome::NetworkError,
+                message: Some(error.message),
+            }
+        }
+    };
     let response = client
-        .post(TOKEN_REFRESH_URL)
-        .header("Content-Type", "application/json")
-        .json(&refresh_request)
+        .post(&url)
+        .form(&[
+            ("token", token),
+            ("token_type_hint", token_type_hint),
+            ("client_id", config.oauth_client_id.as_str()),
+        ])
         .send()
-        .await
-        .map_err(|e| format!("Request failed: {}", e))?;
-
-    let status = response.status();
-    if !status.is_success() {
-        let body = response.text().await.unwrap_or_default();
-
-        if let Ok(error_json) = serde_json::from_str::<serde_json::Value>(&body) {
-            if let Some(error) = error_json.get("error") {
-                let error_code = error
-                    .get("code")
-                    .or_else(|| error_json.get("code"))
-                    .and_then(|v| v.as_str())
-                    .unwrap_or("unknown");
-
-                return match error_code {
-                    "refresh_token_expired" => Err("Refresh Token expired, please log in to Codex CLI again".to_string()),
-                    "refresh_token_reused" => Err("Refresh Token reused, please log in to Codex CLI again".to_string()),
-                    "refresh_token_invalidated" => Err("Refresh Token invalidated, please log in to Codex CLI again".to_string()),
-                    _ => Err(format!("Refresh failed: {} - {}", status, body)),
-                };
+        .await;
+    match response {
+        Ok(response) if response.status().is_success() => TokenRevocation {
+            outcome: RevocationOutcome::Revoked,
+            message: None,
+        },
+        Ok(response) => {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            TokenRevocation {
+                outcome: RevocationOutcome::Rejected,
+                message: Some(format!("Revocation failed: HTTP {} - {}", status, body)),
             }
         }
+        Err(error) => TokenRevocation {
+            outcome: RevocationOutcome::NetworkError,
+            message: Some(format!("Request failed: {}", error)),
+        },
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BulkRefreshOutcome {
+    Refreshed,
+    Skipped,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkRefreshEntry {
+    pub name: String,
+    #[serde(rename = "filePath")]
+    pub file_path: String,
+    pub outcome: BulkRefreshOutcome,
+    pub error: Option<String>,
+    #[serde(rename = "errorKind")]
+    pub error_kind: Option<RefreshErrorKind>,
+    /// Access token `exp` (Unix seconds) after the refresh, or the current one when skipped.
+    #[serde(rename = "expiresAt")]
+    pub expires_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkRefreshProgress {
+    pub done: usize,
+    pub total: usize,
+    pub name: String,
+}
+
+pub(crate) fn token_expires_at(path: &PathBuf) -> Option<i64> {
+    let content = fs::read_to_string(path).ok()?;
+    let auth: CodexAuthFile = serde_json::from_str(&content).ok()?;
+    extract_info_from_auth(&auth, 0).expires_at
+}
 
-        return Err(format!("Refresh failed: HTTP {} - {}", status, body));
+async fn refresh_entry(app: tauri::AppHandle, path: PathBuf, name: String) -> BulkRefreshEntry {
+    let result = refresh_profile_file(&app.state::<RefreshLocks>(), &path).await;
+    let (outcome, error) = match result {
+        Ok(_) => (BulkRefreshOutcome::Refreshed, None),
+        Err(error) => (BulkRefreshOutcome::Failed, Some(error)),
+    };
+    BulkRefreshEntry {
+        expires_at: token_expires_at(&path),
+        name,
+        file_path: path.to_string_lossy().to_string(),
+        outcome,
+        error_kind: error.as_ref().map(|error| error.kind),
+        error: error.map(|error| error.message),
     }
+}
 
-    let refresh_response: TokenRefreshResponse = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
+/// Refreshes every token-based profile, at most `BULK_REFRESH_CONCURRENCY` at a time. With
+/// `only_expiring`, profiles outside the refresh threshold are reported as skipped. Emits
+/// `token-refresh-progress` after each profile; one failure does not stop the rest.
+#[tauri::command]
+pub async fn refresh_all_tokens(
+    app: tauri::AppHandle,
+    only_expiring: Option<bool>,
+) -> Result<Vec<BulkRefreshEntry>, String> {
+    let only_expiring = only_expiring.unwrap_or(false);
+    let threshold_minutes = load_config().token_refresh_threshold_minutes;
+    let mut report = Vec::new();
+    let mut pending = Vec::new();
+    for file in collect_account_files(&get_accounts_dir(), None)? {
+        if file.auth.tokens.is_none() {
+            continue;
+        }
+        let name = file.path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        let info = extract_info_from_auth(&file.auth, threshold_minutes);
+        if only_expiring && !info.needs_refresh() {
+            report.push(BulkRefreshEntry {
+                name,
+                file_path: file.path.to_string_lossy().to_string(),
+                outcome: BulkRefreshOutcome::Skipped,
+                error: None,
+                error_kind: None,
+                expires_at: info.expires_at,
+            });
+            continue;
+        }
+        pending.push((file.path, name));
+    }
 
-    let mut updated_auth = auth.clone();
-    if let Some(new_access_token) = refresh_response.access_token {
-        updated_auth.tokens.access_token = new_access_token;
+    let total = pending.len();
+    let permits = Arc::new(tokio::sync::Semaphore::new(BULK_REFRESH_CONCURRENCY));
+    let mut tasks = tokio::task::JoinSet::new();
+    for (path, name) in pending {
+        let permits = permits.clone();
+        let app = app.clone();
+        tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            refresh_entry(app, path, name).await
+        });
     }
-    if let Some(new_id_token) = refresh_response.id_token {
-        updated_auth.tokens.id_token = new_id_token;
+    let mut done = 0;
+    while let Some(joined) = tasks.join_next().await {
+        let entry = joined.map_err(|e| format!("Refresh task failed: {}", e))?;
+        done += 1;
+        let _ = app.emit(
+            TOKEN_REFRESH_PROGRESS_EVENT,
+            BulkRefreshProgress {
+                done,
+                total,
+                name: entry.name.clone(),
+            },
+        );
+        report.push(entry);
     }
-    if let Some(new_refresh_token) = refresh_response.refresh_token {
-        updated_auth.tokens.refresh_token = new_refresh_token;
+
+    report.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.file_path.cmp(&b.file_path)));
+    Ok(report)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ActiveSyncResult {
+    /// Profile that received the live tokens, if any.
+    #[serde(rename = "filePath")]
+    pub file_path: Option<String>,
+    /// Token fields that changed, e.g. `refresh_token`.
+    #[serde(rename = "updatedFields")]
+    pub updated_fields: Vec<String>,
+}
+
+fn changed_token_fields(stored: &CodexTokens, live: &CodexTokens) -> Vec<String> {
+    [
+        ("access_token", &stored.access_token, &live.access_token),
+        ("id_token", &stored.id_token, &live.id_token),
+        ("refresh_token", &stored.refresh_token, &live.refresh_token),
+    ]
+    .into_iter()
+    .filter(|(_, stored, live)| stored != live)
+    .map(|(field, _, _)| field.to_string())
+    .collect()
+}
+
+/// `profile` with the live tokens copied in, when they differ and the live file is newer by
+/// `last_refresh` (or by mtime when either side lacks one). Never returns older data.
+fn merge_live_tokens(
+    profile: &CodexAuthFile,
+    profile_modified_at: i64,
+    live: &CodexAuthFile,
+    live_modified_at: i64,
+) -> Option<(CodexAuthFile, Vec<String>)> {
+    let (Some(stored_tokens), Some(live_tokens)) = (profile.tokens.as_ref(), live.tokens.as_ref()) else {
+        return None;
+    };
+    let changed = changed_token_fields(stored_tokens, live_tokens);
+    if changed.is_empty() {
+        return None;
+    }
+    let live_is_newer = match (parse_last_refresh(&live.last_refresh), parse_last_refresh(&profile.last_refresh)) {
+        (Some(live_refresh), Some(stored_refresh)) => live_refresh > stored_refresh,
+        _ => live_modified_at > profile_modified_at,
+    };
+    if !live_is_newer {
+        return None;
     }
 
-    updated_auth.last_refresh = chrono::Utc::now().to_rfc3339();
+    let mut updated = profile.clone();
+    if let Some(tokens) = updated.tokens.as_mut() {
+        tokens.access_token = live_tokens.access_token.clone();
+        tokens.id_token = live_tokens.id_token.clone();
+        tokens.refresh_token = live_tokens.refresh_token.clone();
+    }
+    if !live.last_refresh.is_empty() {
+        updated.last_refresh = live.last_refresh.clone();
+    }
+    Some((updated, changed))
+}
 
-    let updated_content =
-        serde_json::to_string_pretty(&updated_auth).map_err(|e| format!("Failed to serialize: {}", e))?;
-    fs::write(&validated_path, &updated_content).map_err(|e| format!("Failed to write file: {}", e))?;
+/// The profile among `candidates` (all holding the live user) that receives the live tokens: the
+/// switched-to one, else the only one. Several unrelated copies are ambiguous and left alone.
+fn pick_sync_target<'a>(
+    candidates: &'a [ParsedAccountFile],
+    configured_active: Option<&PathBuf>,
+) -> Result<Option<&'a ParsedAccountFile>, String> {
+    if let Some(active) = candidates
+        .iter()
+        .find(|file| configured_active.is_some_and(|active| paths_match(&file.path, active)))
+    {
+        return Ok(Some(active));
+    }
+    match candidates {
+        [] => Ok(None),
+        [only] => Ok(Some(only)),
+        _ => Err(format!(
+            "The live tokens match {} profiles; none was updated",
+            candidates.len()
+        )),
+    }
+}
 
-    let mut synced_active_auth = false;
-    if was_active_source && active_auth_path.exists() && !paths_match(&active_auth_path, &validated_path) {
-        fs::write(&active_auth_path, &updated_content)
-            .map_err(|e| format!("Failed to update active authentication file: {}", e))?;
-        synced_active_auth = true;
+/// Copies tokens that Codex CLI rotated in `~/.codex/auth.json` back into the matching stored
+/// profile, so switching away does not discard the newest refresh token.
+pub(crate) fn sync_active_tokens_to_profile() -> Result<ActiveSyncResult, String> {
+    let mut result = ActiveSyncResult {
+        file_path: None,
+        updated_fields: Vec::new(),
+    };
+    let live_path = get_codex_auth_file();
+    // A linked runtime file already is the profile.
+    if fs::symlink_metadata(&live_path).map_or(true, |metadata| metadata.file_type().is_symlink()) {
+        return Ok(result);
+    }
+    let Some(live) = fs::read_to_string(&live_path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<CodexAuthFile>(&content).ok())
+    else {
+        return Ok(result);
+    };
+    let profile_id = extract_profile_id_from_auth(&live, None);
+    if profile_id.trim().is_empty() {
+        return Ok(result);
     }
 
-    if synced_active_auth {
-        Ok("Token refresh successful and active auth updated".to_string())
-    } else {
-        Ok("Token refresh successful".to_string())
+    let accounts_dir = get_accounts_dir();
+    let configured_active = load_config()
+        .active_account_file
+        .and_then(|value| resolve_managed_account_path(&value, &accounts_dir).ok());
+    let candidates: Vec<_> = collect_account_files(&accounts_dir, None)?
+        .into_iter()
+        .filter(|file| extract_profile_id_from_auth(&file.auth, Some(&file.path)) == profile_id)
+        .collect();
+    let Some(profile) = pick_sync_target(&candidates, configured_active.as_ref())? else {
+        return Ok(result);
+    };
+
+    let live_modified_at = file_modified_at(&live_path)?;
+    if let Some((updated, fields)) = merge_live_tokens(&profile.auth, profile.modified_at, &live, live_modified_at) {
+        let content = serde_json::to_string_pretty(&updated).map_err(|e| format!("Failed to serialize: {}", e))?;
+        write_private_file_atomic(&profile.path, content.as_bytes())
+            .map_err(|e| format!("Failed to update profile: {}", e))?;
+        result.file_path = Some(profile.path.to_string_lossy().to_string());
+        result.updated_fields = fields;
     }
+    Ok(result)
+}
+
+#[tauri::command]
+pub fn sync_active_to_profile() -> Result<ActiveSyncResult, String> {
+    sync_active_tokens_to_profile()
 }
 
 #[tauri::command]
@@ -144,8 +876,240 @@ pub fn import_default_account() -> Result<bool, String> {
     if !target_path.exists() {
         fs::copy(&codex_auth, &target_path)
             .map_err(|e| format!("Failed to copy default account: {}", e))?;
+        let _ = restrict_permissions(&target_path);
         return Ok(true);
     }
 
     Ok(false)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        apply_refreshed_tokens,
+        classify_refresh_failure,
+        live_session_matches,
+        merge_live_tokens,
+        pick_sync_target,
+        revoke_url,
+        rollback_path,
+        write_with_rollback,
+        RefreshErrorKind,
+        RefreshLocks,
+        RefreshState,
+        TokenRefreshResponse,
+    };
+    use crate::error::{AppError, AppErrorCode};
+    use crate::{AccountInfo, CodexAuthFile};
+    use base64::Engine;
+
+    fn auth_with(refresh_token: &str, last_refresh: &str) -> CodexAuthFile {
+        serde_json::from_value(serde_json::json!({
+            "OPENAI_API_KEY": null,
+            "last_refresh": last_refresh,
+            "tokens": {
+                "access_token": "access",
+                "account_id": "acc",
+                "id_token": "id",
+                "refresh_token": refresh_token,
+            },
+            "auth_mode": "chatgpt",
+        }))
+        .expect("auth")
+    }
+
+    /// A workspace profile whose id token belongs to `subject`.
+    fn workspace_member(subject: &str) -> CodexAuthFile {
+        let encode = |value: &[u8]| base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(value);
+        let claims = serde_json::json!({ "sub": subject });
+        let mut auth = auth_with("refresh", "");
+        auth.tokens.as_mut().expect("tokens").id_token =
+            format!("{}.{}.sig", encode(br#"{"alg":"none"}"#), encode(claims.to_string().as_bytes()));
+        auth
+    }
+
+    #[test]
+    fn refresh_only_changes_token_fields() {
+        let original = r#"{
+  "OPENAI_API_KEY": null,
+  "last_refresh": "2025-01-01T00:00:00Z",
+  "tokens": {
+    "access_token": "old-access",
+    "account_id": "acc",
+    "id_token": "old-id",
+    "refresh_token": "old-refresh",
+    "workspace_id": "ws-1"
+  },
+  "auth_mode": "chatgpt"
+}"#;
+        let auth: CodexAuthFile = serde_json::from_str(original).expect("parse");
+        let response = TokenRefreshResponse {
+            id_token: None,
+            access_token: Some("new-access".to_string()),
+            refresh_token: Some("new-refresh".to_string()),
+        };
+
+        let updated = apply_refreshed_tokens(&auth, response, "2025-02-01T00:00:00Z".to_string());
+        let expected = original
+            .replace("old-access", "new-access")
+            .replace("old-refresh", "new-refresh")
+            .replace("2025-01-01", "2025-02-01");
+        assert_eq!(serde_json::to_string_pretty(&updated).expect("serialize"), expected);
+    }
+
+    #[test]
+    fn merges_only_newer_live_tokens() {
+        let profile = auth_with("old-refresh", "2025-01-01T00:00:00Z");
+        let live = auth_with("new-refresh", "2025-02-01T00:00:00Z");
+
+        let (updated, fields) = merge_live_tokens(&profile, 0, &live, 0).expect("newer live tokens");
+        assert_eq!(fields, vec!["refresh_token".to_string()]);
+        assert_eq!(updated.tokens.as_ref().map(|t| t.refresh_token.as_str()), Some("new-refresh"));
+        assert_eq!(updated.last_refresh, "2025-02-01T00:00:00Z");
+        assert!(updated.extra.contains_key("auth_mode"));
+
+        assert!(merge_live_tokens(&live, 0, &profile, 0).is_none());
+        assert!(merge_live_tokens(&profile, 0, &profile, 10).is_none());
+
+        let undated_profile = auth_with("old-refresh", "");
+        assert!(merge_live_tokens(&undated_profile, 100, &live, 200).is_some());
+        assert!(merge_live_tokens(&undated_profile, 300, &live, 200).is_none());
+    }
+
+    #[test]
+    fn classifies_refresh_failures() {
+        let dead = classify_refresh_failure(
+            reqwest::StatusCode::UNAUTHORIZED,
+            r#"{"error":{"code":"refresh_token_reused","message":"reused"}}"#,
+        );
+        assert_eq!(dead.kind, RefreshErrorKind::Reused);
+        assert!(dead.message.contains("reused"));
+        assert_eq!(AppError::from(dead).code, AppErrorCode::RefreshDead);
+
+        let top_level = classify_refresh_failure(
+            reqwest::StatusCode::BAD_REQUEST,
+            r#"{"error":"invalid_grant","code":"refresh_token_expired"}"#,
+        );
+        assert_eq!(top_level.kind, RefreshErrorKind::Expired);
+
+        let unknown = classify_refresh_failure(reqwest::StatusCode::BAD_REQUEST, r#"{"error":{"code":"nope"}}"#);
+        assert_eq!(unknown.kind, RefreshErrorKind::Other);
+        let gateway = classify_refresh_failure(reqwest::StatusCode::BAD_GATEWAY, "<html>");
+        assert_eq!(gateway.kind, RefreshErrorKind::Network);
+        assert_eq!(AppError::from(gateway).code, AppErrorCode::Network);
+    }
+
+    #[test]
+    fn derives_revoke_url_from_token_url() {
+        assert_eq!(
+            revoke_url("https://auth.openai.com/oauth/token").as_deref(),
+            Ok("https://auth.openai.com/oauth/revoke")
+        );
+        assert_eq!(
+            revoke_url("http://127.0.0.1:9000/token?x=1").as_deref(),
+            Ok("http://127.0.0.1:9000/revoke")
+        );
+        assert!(revoke_url("not a url").is_err());
+    }
+
+    #[test]
+    fn matches_live_session_by_profile_id() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let live_path = temp.path().join("auth.json");
+        let profile = auth_with("refresh", "");
+
+        assert!(!live_session_matches(&live_path, &profile));
+        std::fs::write(&live_path, serde_json::to_string(&auth_with("other", "")).unwrap()).expect("live");
+        assert!(live_session_matches(&live_path, &profile));
+
+        let mut other_account = serde_json::to_value(auth_with("refresh", "")).unwrap();
+        other_account["tokens"]["account_id"] = "someone-else".into();
+        std::fs::write(&live_path, other_account.to_string()).expect("live");
+        assert!(!live_session_matches(&live_path, &profile));
+
+        // Teammates share the workspace account id but are different users.
+        std::fs::write(&live_path, serde_json::to_string(&workspace_member("user-a")).unwrap()).expect("live");
+        assert!(live_session_matches(&live_path, &workspace_member("user-a")));
+        assert!(!live_session_matches(&live_path, &workspace_member("user-b")));
+    }
+
+    #[test]
+    fn syncs_live_tokens_only_into_an_unambiguous_profile() {
+        let parsed = |name: &str| crate::account_files::ParsedAccountFile {
+            path: std::path::PathBuf::from(name),
+            modified_at: 0,
+            auth: workspace_member("user-a"),
+            file_size: 0,
+            content_hash: None,
+        };
+        let work = parsed("work.json");
+        let copy = parsed("copy.json");
+
+        assert!(pick_sync_target(&[], None).unwrap().is_none());
+        let only = [work.clone()];
+        assert_eq!(pick_sync_target(&only, None).unwrap().map(|file| &file.path), Some(&work.path));
+        let both = [work.clone(), copy.clone()];
+        assert!(pick_sync_target(&both, None).is_err());
+        assert_eq!(pick_sync_target(&both, Some(&copy.path)).unwrap().map(|file| &file.path), Some(&copy.path));
+    }
+
+    #[test]
+    fn shares_one_lock_per_profile_path() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let work = temp.path().join("work.json");
+        std::fs::write(&work, "{}").expect("work");
+        let locks = RefreshLocks::default();
+
+        let first = locks.lock_for(&work);
+        let via_dot = locks.lock_for(&temp.path().join(".").join("work.json"));
+        assert!(std::sync::Arc::ptr_eq(&first, &via_dot));
+        assert!(!std::sync::Arc::ptr_eq(&first, &locks.lock_for(&temp.path().join("other.json"))));
+
+        let _held = first.try_lock().expect("free");
+        assert!(via_dot.try_lock().is_err());
+    }
+
+    #[test]
+    fn tracks_queue_and_failure_state_per_profile() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let work = temp.path().join("work.json");
+        std::fs::write(&work, "{}").expect("work");
+        let locks = RefreshLocks::default();
+        let mut accounts = vec![AccountInfo {
+            file_path: work.to_string_lossy().to_string(),
+            ..Default::default()
+        }];
+
+        assert!(locks.try_queue(&work));
+        assert!(!locks.try_queue(&work));
+        locks.set_state(&work, RefreshState::Failed, Some("Refresh Token reused".to_string()));
+        locks.annotate(&mut accounts);
+        assert_eq!(accounts[0].refresh_state, Some(RefreshState::Failed));
+        assert_eq!(accounts[0].last_refresh_error.as_deref(), Some("Refresh Token reused"));
+
+        assert!(locks.try_queue(&work));
+        locks.set_state(&work, RefreshState::InProgress, None);
+        locks.annotate(&mut accounts);
+        assert_eq!(accounts[0].refresh_state, Some(RefreshState::InProgress));
+        assert!(accounts[0].last_refresh_error.is_some());
+
+        locks.set_state(&work, RefreshState::Succeeded, None);
+        locks.annotate(&mut accounts);
+        assert_eq!(accounts[0].last_refresh_error, None);
+    }
+
+    #[test]
+    fn removes_rollback_copy_once_the_update_is_verified() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let profile = temp.path().join("work.json");
+        std::fs::write(&profile, "old").expect("profile");
+        assert_eq!(rollback_path(&profile), temp.path().join("work.json.bak"));
+
+        write_with_rollback(&profile, "old", "new").expect("write");
+        assert_eq!(std::fs::read_to_string(&profile).unwrap(), "new");
+        assert!(!rollback_path(&profile).exists());
+
+        let missing_dir = temp.path().join("missing").join("work.json");
+        assert!(write_with_rollback(&missing_dir, "old", "new").is_err());
+    }
+}
@@ -1,9 +1,28 @@
-use crate::account_files::resolve_managed_account_path;
-use crate::{get_accounts_dir, get_codex_auth_file, CodexAuthFile};
+use crate::account_files::{
+    collect_account_files,
+    paths_match,
+    resolve_managed_account_path,
+    validate_readable_account_path,
+};
+use crate::account_meta::set_cooldown;
+use crate::account_tokens::{refresh_profile_file, RefreshLocks};
+use crate::command_guard::is_read_only;
+use crate::config::{get_config_file, load_config, usage_endpoints};
+use crate::error::{AppError, AppErrorCode};
+use crate::http_client::http_client;
+use crate::http_retry::retry_after_header;
+use crate::trace;
+use crate::usage_alerts::check_usage_thresholds;
+use crate::usage_history::record_usage_sample;
+use crate::{extract_info_from_auth, extract_profile_id_from_auth, get_accounts_dir, get_codex_auth_file, CodexAuthFile};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
+use tauri::{Emitter, Manager};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RateLimitWindow {
@@ -23,6 +42,34 @@ pub struct UsageInfo {
     pub secondary_window: Option<RateLimitWindow>,
     #[serde(rename = "planType")]
     pub plan_type: Option<String>,
+    /// When this usage was fetched (Unix ms); cached results keep their original time.
+    #[serde(default, rename = "fetchedAt")]
+    pub fetched_at: Option<i64>,
+    /// Endpoint that answered; only set when the raw response was requested.
+    #[serde(default, rename = "endpointUsed", skip_serializing_if = "Option::is_none")]
+    pub endpoint_used: Option<String>,
+    /// The endpoint's JSON response as received; only set when requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Ok,
+    Unauthorized,
+    RateLimited,
+    NetworkError,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountHealth {
+    pub status: HealthStatus,
+    #[serde(rename = "httpStatus")]
+    pub http_status: Option<u16>,
+    #[serde(rename = "latencyMs")]
+    pub latency_ms: u64,
+    pub message: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -69,92 +116,900 @@ fn usage_from_api_response(api_response: ApiUsageResponse) -> Result<UsageInfo,
         primary_window: primary,
         secondary_window: secondary,
         plan_type: api_response.plan_type,
+        fetched_at: None,
+        endpoint_used: None,
+        raw: None,
+    })
+}
+
+/// First of `keys` present on `value`, so snake and camel case spellings both match.
+fn value_field<'a>(value: &'a serde_json::Value, keys: &[&str]) -> Option<&'a serde_json::Value> {
+    keys.iter().find_map(|key| value.get(*key)).filter(|field| !field.is_null())
+}
+
+fn window_from_value(value: &serde_json::Value) -> Option<ApiRateLimitWindow> {
+    let used_percent = value_field(value, &["used_percent", "usedPercent"])?.as_f64()?;
+    let limit_window_seconds = value_field(value, &["limit_window_seconds", "limitWindowSeconds", "window_seconds"])
+        .and_then(|seconds| seconds.as_f64())
+        .map(|seconds| seconds as i32);
+    let reset_at = value_field(value, &["reset_at", "resetAt", "resets_at", "resetsAt"])
+        .and_then(|reset_at| reset_at.as_f64())
+        .map(|reset_at| reset_at as i64);
+    Some(ApiRateLimitWindow {
+        used_percent,
+        limit_window_seconds,
+        reset_at,
+    })
+}
+
+/// Best-effort extraction for responses that no longer match `ApiUsageResponse`, accepting
+/// `rate_limit`/`rate_limits` and camel case field names.
+fn usage_from_value(value: &serde_json::Value) -> Result<UsageInfo, String> {
+    let details = value_field(value, &["rate_limit", "rate_limits", "rateLimit", "rateLimits"])
+        .ok_or_else(|| "Usage response did not include any rate limit windows".to_string())?;
+    let window = |keys: &[&str]| value_field(details, keys).and_then(window_from_value);
+    let plan_type = value_field(value, &["plan_type", "planType"])
+        .and_then(|plan_type| plan_type.as_str())
+        .map(str::to_string);
+    usage_from_api_response(ApiUsageResponse {
+        rate_limit: Some(ApiRateLimitDetails {
+            primary_window: window(&["primary_window", "primaryWindow", "primary"]),
+            secondary_window: window(&["secondary_window", "secondaryWindow", "secondary"]),
+        }),
+        plan_type,
     })
 }
 
-pub(crate) async fn fetch_usage_from_auth_path(auth_path: &Path) -> Result<UsageInfo, String> {
+/// Parses a usage response body, falling back to `usage_from_value` when the typed parse fails.
+fn parse_usage_body(body: &str) -> Result<(UsageInfo, serde_json::Value), String> {
+    let value = serde_json::from_str::<serde_json::Value>(body)
+        .map_err(|e| format!("Failed to parse API response: {}", e))?;
+    let typed = serde_json::from_value::<ApiUsageResponse>(value.clone())
+        .map_err(|e| format!("Failed to parse API response: {}", e))
+        .and_then(usage_from_api_response);
+    match typed {
+        Ok(usage) => Ok((usage, value)),
+        Err(error) => match usage_from_value(&value) {
+            Ok(usage) => {
+                trace::emit("usage", "fallback_parse", json!({ "error": error }));
+                Ok((usage, value))
+            }
+            Err(_) => Err(error),
+        },
+    }
+}
+
+/// Remembers the endpoint that last answered, next to `config.json`.
+const PREFERRED_USAGE_ENDPOINT_FILE: &str = "usage_endpoint";
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(8);
+pub(crate) const DEFAULT_USAGE_MAX_AGE_SECS: u64 = 300;
+/// Backoff after a 429 that carried no `Retry-After`.
+const DEFAULT_RATE_LIMIT_BACKOFF_SECS: u64 = 60;
+pub(crate) const DEFAULT_USAGE_CONCURRENCY: u32 = 4;
+const MAX_USAGE_CONCURRENCY: u32 = 16;
+
+pub const USAGE_PROGRESS_EVENT: &str = "usage-progress";
+
+fn usage_request(
+    client: &reqwest::Client,
+    url: &str,
+    access_token: &str,
+    account_id: &str,
+) -> reqwest::RequestBuilder {
+    let request = client
+        .get(url)
+        .header("Authorization", format!("Bearer {}", access_token))
+        .header("Accept", "application/json")
+        .header("Cache-Control", "no-cache")
+        .header("Pragma", "no-cache")
+        .header(
+            "User-Agent",
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+        )
+        .header("Origin", "https://chatgpt.com");
+
+    if account_id.is_empty() {
+        request
+    } else {
+        request.header("ChatGPT-Account-Id", account_id)
+    }
+}
+
+/// Last usage per profile id, so renamed or moved profiles share one entry. Also holds the Unix ms
+/// until which a profile answered 429 and must not be asked again.
+#[derive(Default)]
+pub struct UsageCache {
+    entries: Mutex<HashMap<String, UsageInfo>>,
+    rate_limited_until: Mutex<HashMap<String, i64>>,
+}
+
+impl UsageCache {
+    /// Seconds left on the profile's 429 backoff, if any.
+    fn backoff_remaining(&self, profile_id: &str, now_ms: i64) -> Option<u64> {
+        let mut backoffs = self.rate_limited_until.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        match backoffs.get(profile_id) {
+            Some(until) if *until > now_ms => Some(((until - now_ms + 999) / 1000) as u64),
+            Some(_) => {
+                backoffs.remove(profile_id);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn back_off(&self, profile_id: &str, until_ms: i64) {
+        if profile_id.is_empty() {
+            return;
+        }
+        let mut backoffs = self.rate_limited_until.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        backoffs.insert(profile_id.to_string(), until_ms);
+    }
+
+    pub(crate) fn get_fresh(&self, profile_id: &str, max_age_secs: u64, now_ms: i64) -> Option<UsageInfo> {
+        let entries = self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        entries
+            .get(profile_id)
+            .filter(|usage| {
+                usage
+                    .fetched_at
+                    .is_some_and(|fetched_at| now_ms - fetched_at <= (max_age_secs as i64).saturating_mul(1000))
+            })
+            .cloned()
+    }
+
+    pub(crate) fn store(&self, profile_id: &str, usage: &UsageInfo) {
+        if profile_id.is_empty() {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        entries.insert(
+            profile_id.to_string(),
+            UsageInfo {
+                endpoint_used: None,
+                raw: None,
+                ..usage.clone()
+            },
+        );
+    }
+}
+
+/// Profile id that usage, its cache, 429 backoff, history and alerts are keyed by; the live auth.json counts as the
+/// active profile. Teammates share an upstream account id, so that cannot be the key.
+pub(crate) fn usage_profile_id(auth_path: &Path, auth: &CodexAuthFile) -> String {
+    extract_profile_id_from_auth(auth, Some(&profile_for_auth_path(auth_path, auth)))
+}
+
+fn usage_cache_key(auth_path: &Path) -> Option<String> {
+    read_auth_file(auth_path)
+        .ok()
+        .map(|auth| usage_profile_id(auth_path, &auth))
+        .filter(|profile_id| !profile_id.trim().is_empty())
+}
+
+fn preferred_usage_endpoint_file() -> PathBuf {
+    get_config_file().with_file_name(PREFERRED_USAGE_ENDPOINT_FILE)
+}
+
+/// Moves `preferred` to the front when it is one of `endpoints`.
+fn prefer_endpoint(endpoints: &mut [String], preferred: Option<&str>) {
+    if let Some(index) = preferred.and_then(|preferred| endpoints.iter().position(|url| url == preferred)) {
+        endpoints[..=index].rotate_right(1);
+    }
+}
+
+/// The configured endpoints, starting with the one that last answered.
+fn ordered_usage_endpoints() -> Result<Vec<String>, String> {
+    let mut endpoints = usage_endpoints(&load_config()).map_err(|error| error.message)?;
+    let preferred = fs::read_to_string(preferred_usage_endpoint_file()).ok();
+    prefer_endpoint(&mut endpoints, preferred.as_deref().map(str::trim));
+    Ok(endpoints)
+}
+
+/// Saves the endpoint that answered, unless read-only mode is on.
+fn remember_usage_endpoint(url: &str) {
+    let path = preferred_usage_endpoint_file();
+    if is_read_only() || fs::read_to_string(&path).is_ok_and(|current| current.trim() == url) {
+        return;
+    }
+    if let Err(error) = fs::write(&path, url) {
+        trace::emit("usage", "endpoint_write_failed", json!({ "error": error.to_string() }));
+    }
+}
+
+/// Why a usage fetch failed, so the UI can offer the matching fix.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageErrorKind {
+    /// 401: the access token was rejected and a refresh did not help.
+    Unauthorized,
+    /// 403: the account is blocked or lacks access.
+    Forbidden,
+    RateLimited,
+    ServerError,
+    /// Any other unexpected HTTP status, e.g. a moved endpoint.
+    HttpError,
+    Network,
+    Parse,
+    /// The auth file could not be read or holds no tokens.
+    Local,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageError {
+    pub code: AppErrorCode,
+    pub kind: UsageErrorKind,
+    pub message: String,
+    #[serde(rename = "httpStatus")]
+    pub http_status: Option<u16>,
+    #[serde(rename = "retryAfterSecs")]
+    pub retry_after_secs: Option<u64>,
+    /// Response body of the endpoint the error came from, truncated.
+    pub body: Option<String>,
+}
+
+const MAX_ERROR_BODY_CHARS: usize = 2000;
+
+impl UsageError {
+    pub(crate) fn new(kind: UsageErrorKind, message: impl Into<String>) -> Self {
+        let code = match kind {
+            UsageErrorKind::Unauthorized => AppErrorCode::Auth,
+            UsageErrorKind::Forbidden => AppErrorCode::Forbidden,
+            UsageErrorKind::RateLimited | UsageErrorKind::Network => AppErrorCode::Network,
+            UsageErrorKind::ServerError | UsageErrorKind::HttpError => AppErrorCode::External,
+            UsageErrorKind::Parse => AppErrorCode::Parse,
+            UsageErrorKind::Local => AppErrorCode::Io,
+        };
+        Self {
+            code,
+            kind,
+            message: message.into(),
+            http_status: None,
+            retry_after_secs: None,
+            body: None,
+        }
+    }
+
+    pub(crate) fn local(message: impl Into<String>) -> Self {
+        Self::new(UsageErrorKind::Local, message)
+    }
+
+    pub(crate) fn with_body(self, body: &str) -> Self {
+        Self {
+            body: Some(body.chars().take(MAX_ERROR_BODY_CHARS).collect()),
+            ..self
+        }
+    }
+
+    pub(crate) fn from_status(status: reqwest::StatusCode, retry_after: Option<Duration>, message: String) -> Self {
+        let kind = match status.as_u16() {
+            401 => UsageErrorKind::Unauthorized,
+            403 => UsageErrorKind::Forbidden,
+            429 => UsageErrorKind::RateLimited,
+            500..=599 => UsageErrorKind::ServerError,
+            _ => UsageErrorKind::HttpError,
+        };
+        Self {
+            http_status: Some(status.as_u16()),
+            retry_after_secs: retry_after.map(|delay| delay.as_secs()),
+            ..Self::new(kind, message)
+        }
+    }
+
+    /// Lower is more actionable; decides which endpoint's error is reported.
+    fn priority(&self) -> u8 {
+        match self.kind {
+            UsageErrorKind::RateLimited => 0,
+            UsageErrorKind::Forbidden => 1,
+            UsageErrorKind::Unauthorized => 2,
+            UsageErrorKind::Parse => 3,
+            UsageErrorKind::ServerError => 4,
+            UsageErrorKind::HttpError => 5,
+            UsageErrorKind::Network => 6,
+            UsageErrorKind::Local => 7,
+        }
+    }
+}
+
+impl From<AppError> for UsageError {
+    fn from(error: AppError) -> Self {
+        Self {
+            code: error.code,
+            ..Self::local(error.message)
+        }
+    }
+}
+
+impl std::fmt::Display for UsageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Combines the per-endpoint errors into one: the most actionable kind, the longest
+/// `Retry-After` and every endpoint's message.
+fn summarize_usage_errors(errors: Vec<UsageError>) -> UsageError {
+    let messages: Vec<&str> = errors.iter().map(|error| error.message.as_str()).collect();
+    let message = if messages.is_empty() {
+        "All API requests failed".to_string()
+    } else {
+        format!("All API requests failed: {}", messages.join(" | "))
+    };
+    let retry_after_secs = errors.iter().filter_map(|error| error.retry_after_secs).max();
+    let Some(primary) = errors.iter().min_by_key(|error| error.priority()) else {
+        return UsageError::new(UsageErrorKind::Network, message);
+    };
+    UsageError {
+        message,
+        retry_after_secs,
+        ..primary.clone()
+    }
+}
+
+/// Why no usage endpoint answered; `unauthorized` when every endpoint rejected the access token.
+struct UsageFailure {
+    error: UsageError,
+    unauthorized: bool,
+}
+
+pub(crate) fn read_auth_file(auth_path: &Path) -> Result<CodexAuthFile, String> {
     let content = fs::read_to_string(auth_path)
         .map_err(|e| format!("Failed to read authentication file: {}", e))?;
 
-    let auth: CodexAuthFile = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse authentication file: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse authentication file: {}", e))
+}
 
-    let access_token = &auth.tokens.access_token;
-    let account_id = &auth.tokens.account_id;
-
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(12))
-        .build()
-        .map_err(|e| format!("Failed to build usage client: {}", e))?;
-    let urls = vec![
-        "https://chatgpt.com/backend-api/wham/usage",
-        "https://api.openai.com/backend-api/wham/usage",
-        "https://api.openai.com/api/codex/usage",
-        "https://chat.openai.com/backend-api/wham/usage",
-    ];
-
-    let mut attempt_errors: Vec<String> = Vec::new();
-
-    for url in urls {
-        let mut request = client
-            .get(url)
-            .header("Authorization", format!("Bearer {}", access_token))
-            .header("Accept", "application/json")
-            .header("Cache-Control", "no-cache")
-            .header("Pragma", "no-cache")
-            .header(
-                "User-Agent",
-                "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
-            )
-            .header("Origin", "https://chatgpt.com");
+/// Asks one endpoint for usage, classifying any failure.
+async fn request_usage_from(
+    client: &reqwest::Client,
+    url: &str,
+    access_token: &str,
+    account_id: &str,
+    include_raw: bool,
+) -> Result<UsageInfo, UsageError> {
+    let response = usage_request(client, url, access_token, account_id)
+        .send()
+        .await
+        .map_err(|e| UsageError::new(UsageErrorKind::Network, format!("{} -> {}", url, e)))?;
+    let status = response.status();
+    let retry_after = retry_after_header(response.headers());
+    let body = response
+        .text()
+        .await
+        .map_err(|e| UsageError::new(UsageErrorKind::Network, format!("{} -> {}", url, e)))?;
+    if !status.is_success() {
+        return Err(UsageError::from_status(status, retry_after, format!("{} -> HTTP {}", url, status)).with_body(&body));
+    }
 
-        if !account_id.is_empty() {
-            request = request.header("ChatGPT-Account-Id", account_id);
-        }
+    let (usage, raw) = parse_usage_body(&body)
+        .map_err(|error| UsageError::new(UsageErrorKind::Parse, format!("{} -> {}", url, error)).with_body(&body))?;
+    Ok(UsageInfo {
+        endpoint_used: include_raw.then(|| url.to_string()),
+        raw: include_raw.then_some(raw),
+        ..usage
+    })
+}
 
-        match request.send().await {
-            Ok(resp) => {
-                let status = resp.status();
-                if !status.is_success() {
-                    attempt_errors.push(format!("{} -> HTTP {}", url, status));
-                    continue;
-                }
+async fn request_usage(auth: &CodexAuthFile, include_raw: bool) -> Result<UsageInfo, UsageFailure> {
+    let failure = |message: String| UsageFailure {
+        error: UsageError::local(message),
+        unauthorized: false,
+    };
+    let tokens = auth.require_tokens("Usage fetch").map_err(failure)?;
+    let client = http_client(Duration::from_secs(12))
+        .map_err(|e| failure(format!("Failed to build usage client: {}", e.message)))?;
+    let endpoints = ordered_usage_endpoints().map_err(failure)?;
 
-                match resp.json::<ApiUsageResponse>().await {
-                    Ok(api_response) => match usage_from_api_response(api_response) {
-                        Ok(usage) => return Ok(usage),
-                        Err(error) => attempt_errors.push(format!("{} -> {}", url, error)),
-                    },
-                    Err(error) => {
-                        attempt_errors.push(format!("{} -> Failed to parse API response: {}", url, error));
-                    }
-                }
+    let mut errors = Vec::new();
+    for url in &endpoints {
+        match request_usage_from(&client, url, &tokens.access_token, &tokens.account_id, include_raw).await {
+            Ok(usage) => {
+                remember_usage_endpoint(url);
+                return Ok(UsageInfo {
+                    fetched_at: Some(chrono::Utc::now().timestamp_millis()),
+                    ..usage
+                });
             }
-            Err(e) => {
-                attempt_errors.push(format!("{} -> {}", url, e));
+            Err(error) => {
+                let rate_limited = error.kind == UsageErrorKind::RateLimited;
+                errors.push(error);
+                // Other endpoints share the same limit; asking them only extends it.
+                if rate_limited {
+                    break;
+                }
             }
         }
     }
 
-    if attempt_errors.is_empty() {
-        Err("All API requests failed".to_string())
-    } else {
-        Err(format!("All API requests failed: {}", attempt_errors.join(" | ")))
+    let unauthorized = !errors.is_empty() && errors.iter().all(|error| error.kind == UsageErrorKind::Unauthorized);
+    Err(UsageFailure {
+        error: summarize_usage_errors(errors),
+        unauthorized,
+    })
+}
+
+/// When the account can be used again: the latest reset among exhausted windows, if any.
+fn cooldown_until(usage: &UsageInfo) -> Option<i64> {
+    [&usage.primary_window, &usage.secondary_window]
+        .into_iter()
+        .flatten()
+        .filter(|window| window.used_percent >= 100.0)
+        .filter_map(|window| window.resets_at)
+        .max()
+}
+
+/// Persists the cooldown on the profile behind `auth_path`; a live auth.json without a matching
+/// profile is skipped, as is everything in read-only mode.
+fn record_cooldown(auth_path: &Path, auth: &CodexAuthFile, until: Option<i64>) {
+    if is_read_only() {
+        return;
+    }
+    if paths_match(&profile_for_auth_path(auth_path, auth), &get_codex_auth_file()) {
+        return;
+    }
+    let profile_id = usage_profile_id(auth_path, auth);
+    if let Err(error) = set_cooldown(&get_accounts_dir(), &profile_id, until) {
+        trace::emit("usage", "cooldown_write_failed", json!({ "error": error }));
+    }
+}
+
+/// Profile file behind `auth_path`. The live auth.json maps to the active profile when it is the
+/// same user, so a refresh saves the rotated refresh token in both places.
+pub(crate) fn profile_for_auth_path(auth_path: &Path, auth: &CodexAuthFile) -> PathBuf {
+    let live_auth_path = get_codex_auth_file();
+    if !paths_match(&auth_path.to_path_buf(), &live_auth_path) {
+        return auth_path.to_path_buf();
     }
+    let profile_id = extract_profile_id_from_auth(auth, None);
+    load_config()
+        .active_account_file
+        .and_then(|value| resolve_managed_account_path(&value, &get_accounts_dir()).ok())
+        .filter(|profile| {
+            !profile_id.trim().is_empty()
+                && read_auth_file(profile)
+                    .is_ok_and(|profile_auth| extract_profile_id_from_auth(&profile_auth, None) == profile_id)
+        })
+        .unwrap_or_else(|| auth_path.to_path_buf())
+}
+
+/// Fetches usage for the auth file at `auth_path`. When every endpoint answers 401 the tokens are
+/// refreshed once and the request is retried; a failed refresh is reported instead of the 401.
+/// Successful fetches are appended to the usage history. In read-only mode the 401 is returned as
+/// is and nothing is written.
+pub(crate) async fn fetch_usage_from_auth_path(
+    app: &tauri::AppHandle,
+    auth_path: &Path,
+) -> Result<UsageInfo, UsageError> {
+    fetch_usage_with_raw(app, auth_path, false).await
 }
 
+/// `fetch_usage_from_auth_path`, optionally keeping the raw response and the endpoint used.
+/// While an account is backing off after a 429 it is answered without a request.
+async fn fetch_usage_with_raw(
+    app: &tauri::AppHandle,
+    auth_path: &Path,
+    include_raw: bool,
+) -> Result<UsageInfo, UsageError> {
+    let auth = read_auth_file(auth_path).map_err(UsageError::local)?;
+    let cache = app.state::<UsageCache>();
+    let profile_id = usage_profile_id(auth_path, &auth);
+    if let Some(remaining) = cache.backoff_remaining(&profile_id, chrono::Utc::now().timestamp_millis()) {
+        return Err(UsageError {
+            retry_after_secs: Some(remaining),
+            ..UsageError::new(
+                UsageErrorKind::RateLimited,
+                format!("Rate limited, retry in {}s", remaining),
+            )
+        });
+    }
+
+    let result = match request_usage(&auth, include_raw).await {
+        Err(failure) if failure.unauthorized && !is_read_only() => {
+            refresh_profile_file(&app.state::<RefreshLocks>(), &profile_for_auth_path(auth_path, &auth))
+                .await
+                .map_err(|error| {
+                    let message = format!("Access token was rejected and refresh failed: {}", error.message);
+                    UsageError {
+                        code: AppError::from(error).code,
+                        ..UsageError::new(UsageErrorKind::Unauthorized, message)
+                    }
+                })?;
+            let refreshed = read_auth_file(auth_path).map_err(UsageError::local)?;
+            request_usage(&refreshed, include_raw).await
+        }
+        result => result,
+    };
+    let now_secs = chrono::Utc::now().timestamp();
+    if let Err(failure) = &result {
+        if failure.error.kind == UsageErrorKind::RateLimited {
+            let delay = failure.error.retry_after_secs.unwrap_or(DEFAULT_RATE_LIMIT_BACKOFF_SECS);
+            cache.back_off(&profile_id, now_secs.saturating_add(delay as i64).saturating_mul(1000));
+        }
+    }
+    let cooldown = match &result {
+        Ok(usage) => Some(cooldown_until(usage)),
+        Err(failure) => failure
+            .error
+            .retry_after_secs
+            .filter(|_| failure.error.kind == UsageErrorKind::RateLimited)
+            .map(|delay| Some(now_secs.saturating_add(delay as i64))),
+    };
+    if let Some(until) = cooldown {
+        record_cooldown(auth_path, &auth, until);
+    }
+    let usage = result.map_err(|failure| failure.error)?;
+
+    if !is_read_only() {
+        if let Err(error) = record_usage_sample(&profile_id, &usage) {
+            trace::emit("usage", "history_write_failed", json!({ "error": error }));
+        }
+    }
+    Ok(usage)
+}
+
+/// Returns the cached usage when it is at most `max_age_secs` old (default 300) unless `force`.
+/// `include_raw` always fetches and attaches the raw response and the endpoint used.
 #[tauri::command]
-pub async fn fetch_usage(file_path: String) -> Result<UsageInfo, String> {
-    let validated_path = resolve_managed_account_path(&file_path, &get_accounts_dir())?;
-    fetch_usage_from_auth_path(&validated_path).await
+pub async fn fetch_usage(
+    app: tauri::AppHandle,
+    cache: tauri::State<'_, UsageCache>,
+    file_path: String,
+    max_age_secs: Option<u64>,
+    force: Option<bool>,
+    include_raw: Option<bool>,
+) -> Result<UsageInfo, UsageError> {
+    let validated_path = validate_readable_account_path(&file_path, &get_accounts_dir(), &get_codex_auth_file())?;
+    let cache_key = usage_cache_key(&validated_path);
+    let include_raw = include_raw.unwrap_or(false);
+    if !force.unwrap_or(false) && !include_raw {
+        let max_age_secs = max_age_secs.unwrap_or(DEFAULT_USAGE_MAX_AGE_SECS);
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        if let Some(cached) = cache_key
+            .as_deref()
+            .and_then(|profile_id| cache.get_fresh(profile_id, max_age_secs, now_ms))
+        {
+            return Ok(cached);
+        }
+    }
+
+    let usage = fetch_usage_with_raw(&app, &validated_path, include_raw).await?;
+    check_usage_thresholds(&app, &validated_path, &usage);
+    if let Some(profile_id) = cache_key {
+        cache.store(&profile_id, &usage);
+    }
+    Ok(usage)
 }
 
 #[tauri::command]
-pub async fn fetch_active_usage() -> Result<UsageInfo, String> {
+pub async fn fetch_active_usage(
+    app: tauri::AppHandle,
+    include_raw: Option<bool>,
+) -> Result<UsageInfo, UsageError> {
     let active_auth_path = get_codex_auth_file();
     if !active_auth_path.exists() {
-        return Err("Active authentication file does not exist".to_string());
+        return Err(UsageError::local("Active authentication file does not exist"));
     }
 
-    fetch_usage_from_auth_path(&active_auth_path).await
+    let usage = fetch_usage_with_raw(&app, &active_auth_path, include_raw.unwrap_or(false)).await?;
+    check_usage_thresholds(&app, &active_auth_path, &usage);
+    Ok(usage)
+}
+
+/// Usage (or the reason it is missing) for one account in `fetch_all_usage`.
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountUsageResult {
+    pub usage: Option<UsageInfo>,
+    pub error: Option<String>,
+    #[serde(rename = "errorKind")]
+    pub error_kind: Option<UsageErrorKind>,
+    /// The access token has expired; refresh the account instead of retrying.
+    #[serde(rename = "needsRefresh")]
+    pub needs_refresh: bool,
+}
+
+impl AccountUsageResult {
+    fn from_fetch(result: Result<UsageInfo, UsageError>) -> Self {
+        match result {
+            Ok(usage) => Self {
+                usage: Some(usage),
+                error: None,
+                error_kind: None,
+                needs_refresh: false,
+            },
+            Err(error) => Self {
+                usage: None,
+                error: Some(error.message),
+                error_kind: Some(error.kind),
+                needs_refresh: false,
+            },
+        }
+    }
+
+    fn needs_refresh() -> Self {
+        Self {
+            usage: None,
+            error: Some("Access token expired; refresh the account to load usage".to_string()),
+            error_kind: Some(UsageErrorKind::Unauthorized),
+            needs_refresh: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageProgress {
+    #[serde(rename = "profileId")]
+    pub profile_id: String,
+    #[serde(rename = "filePath")]
+    pub file_path: String,
+    pub done: usize,
+    pub total: usize,
+    pub result: AccountUsageResult,
+}
+
+/// Fetches usage for every token-based profile, `concurrency` at a time (default 4), keyed by
+/// profile id. Expired tokens are reported as `needsRefresh` without a request, so a bulk fetch
+/// never rotates refresh tokens. Emits `usage-progress` as each account finishes.
+#[tauri::command]
+pub async fn fetch_all_usage(
+    app: tauri::AppHandle,
+    concurrency: Option<u32>,
+) -> Result<HashMap<String, AccountUsageResult>, String> {
+    let now_secs = chrono::Utc::now().timestamp();
+    let mut results = HashMap::new();
+    let mut pending = Vec::new();
+    for file in collect_account_files(&get_accounts_dir(), None)? {
+        if file.auth.tokens.is_none() {
+            continue;
+        }
+        let profile_id = extract_profile_id_from_auth(&file.auth, Some(&file.path));
+        let expired = extract_info_from_auth(&file.auth, 0)
+            .expires_at
+            .is_some_and(|expires_at| expires_at <= now_secs);
+        if expired {
+            results.insert(profile_id, AccountUsageResult::needs_refresh());
+        } else {
+            pending.push((profile_id, file.path));
+        }
+    }
+
+    let total = pending.len();
+    let limit = concurrency.unwrap_or(DEFAULT_USAGE_CONCURRENCY).clamp(1, MAX_USAGE_CONCURRENCY) as usize;
+    let permits = Arc::new(tokio::sync::Semaphore::new(limit));
+    let mut tasks = tokio::task::JoinSet::new();
+    for (profile_id, path) in pending {
+        let permits = permits.clone();
+        let app = app.clone();
+        tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            let result = fetch_usage_from_auth_path(&app, &path).await;
+            if let Ok(usage) = &result {
+                if let Some(cache_key) = usage_cache_key(&path) {
+                    app.state::<UsageCache>().store(&cache_key, usage);
+                }
+                check_usage_thresholds(&app, &path, usage);
+            }
+            (profile_id, path, AccountUsageResult::from_fetch(result))
+        });
+    }
+    let mut done = 0;
+    while let Some(joined) = tasks.join_next().await {
+        let (profile_id, path, result) = joined.map_err(|e| format!("Usage task failed: {}", e))?;
+        done += 1;
+        let _ = app.emit(
+            USAGE_PROGRESS_EVENT,
+            UsageProgress {
+                profile_id: profile_id.clone(),
+                file_path: path.to_string_lossy().to_string(),
+                done,
+                total,
+                result: result.clone(),
+            },
+        );
+        results.insert(profile_id, result);
+    }
+    Ok(results)
+}
+
+fn health_status_for_http(status: reqwest::StatusCode) -> HealthStatus {
+    match status.as_u16() {
+        200..=299 => HealthStatus::Ok,
+        401 | 403 => HealthStatus::Unauthorized,
+        429 => HealthStatus::RateLimited,
+        _ => HealthStatus::NetworkError,
+    }
+}
+
+/// Makes one authenticated usage request to check that the token is still accepted. Never writes the file.
+#[tauri::command]
+pub async fn check_account_health(file_path: String) -> Result<AccountHealth, String> {
+    let validated_path = resolve_managed_account_path(&file_path, &get_accounts_dir())?;
+    let content = fs::read_to_string(&validated_path)
+        .map_err(|e| format!("Failed to read authentication file: {}", e))?;
+    let auth: CodexAuthFile = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse authentication file: {}", e))?;
+    let tokens = auth.require_tokens("Health check")?;
+
+    let client = http_client(HEALTH_CHECK_TIMEOUT)
+        .map_err(|e| format!("Failed to build health check client: {}", e.message))?;
+
+    let endpoints = ordered_usage_endpoints()?;
+    let started = std::time::Instant::now();
+    let response = usage_request(&client, &endpoints[0], &tokens.access_token, &tokens.account_id)
+        .send()
+        .await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    Ok(match response {
+        Ok(response) => {
+            let status = response.status();
+            AccountHealth {
+                status: health_status_for_http(status),
+                http_status: Some(status.as_u16()),
+                latency_ms,
+                message: (!status.is_success()).then(|| format!("HTTP {}", status)),
+            }
+        }
+        Err(error) => AccountHealth {
+            status: HealthStatus::NetworkError,
+            http_status: None,
+            latency_ms,
+            message: Some(if error.is_timeout() {
+                "Health check timed out".to_string()
+            } else {
+                error.to_string()
+            }),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        cooldown_until, health_status_for_http, parse_usage_body, prefer_endpoint, summarize_usage_errors, usage_cache_key,
+        HealthStatus, RateLimitWindow, UsageCache, UsageError, UsageErrorKind, UsageInfo,
+    };
+    use base64::Engine;
+    use std::time::Duration;
+    use reqwest::StatusCode;
+
+    #[test]
+    fn serves_cached_usage_only_while_fresh() {
+        let cache = UsageCache::default();
+        let usage = UsageInfo {
+            primary_window: None,
+            secondary_window: None,
+            plan_type: Some("plus".to_string()),
+            fetched_at: Some(1_000_000),
+            endpoint_used: None,
+            raw: None,
+        };
+        cache.store("acc-1", &usage);
+        cache.store("", &usage);
+
+        assert!(cache.get_fresh("acc-1", 300, 1_000_000 + 300_000).is_some());
+        assert!(cache.get_fresh("acc-1", 300, 1_000_000 + 300_001).is_none());
+        assert!(cache.get_fresh("acc-1", 0, 1_000_000).is_some());
+        assert!(cache.get_fresh("acc-2", 300, 1_000_000).is_none());
+        assert!(cache.get_fresh("", 300, 1_000_000).is_none());
+    }
+
+    #[test]
+    fn keys_workspace_teammates_separately() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let write_member = |name: &str, subject: &str| {
+            let encode = |value: &[u8]| base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(value);
+            let claims = serde_json::json!({ "sub": subject });
+            let id_token = format!("{}.{}.sig", encode(br#"{"alg":"none"}"#), encode(claims.to_string().as_bytes()));
+            let auth = serde_json::json!({
+                "tokens": { "access_token": "a", "account_id": "workspace", "id_token": id_token, "refresh_token": "r" },
+            });
+            let path = temp.path().join(name);
+            std::fs::write(&path, auth.to_string()).expect("write");
+            path
+        };
+
+        let alice = usage_cache_key(&write_member("alice.json", "user-a"));
+        let bob = usage_cache_key(&write_member("bob.json", "user-b"));
+        assert_eq!(alice.as_deref(), Some("user-a"));
+        assert_eq!(bob.as_deref(), Some("user-b"));
+        assert_eq!(usage_cache_key(&write_member("alice-copy.json", "user-a")), alice);
+    }
+
+    #[test]
+    fn cools_down_until_the_latest_exhausted_window_resets() {
+        let window = |used_percent: f64, resets_at: i64| RateLimitWindow {
+            used_percent,
+            window_minutes: None,
+            resets_at: Some(resets_at),
+        };
+        let usage = |primary: RateLimitWindow, secondary: RateLimitWindow| UsageInfo {
+            primary_window: Some(primary),
+            secondary_window: Some(secondary),
+            plan_type: None,
+            fetched_at: None,
+            endpoint_used: None,
+            raw: None,
+        };
+
+        assert_eq!(cooldown_until(&usage(window(99.0, 100), window(50.0, 900))), None);
+        assert_eq!(cooldown_until(&usage(window(100.0, 100), window(50.0, 900))), Some(100));
+        assert_eq!(cooldown_until(&usage(window(100.0, 100), window(100.0, 900))), Some(900));
+    }
+
+    #[test]
+    fn tries_the_last_successful_endpoint_first() {
+        let mut endpoints: Vec<String> = ["a", "b", "c"].iter().map(|url| url.to_string()).collect();
+        prefer_endpoint(&mut endpoints, Some("c"));
+        assert_eq!(endpoints, vec!["c", "a", "b"]);
+        prefer_endpoint(&mut endpoints, Some("gone"));
+        prefer_endpoint(&mut endpoints, None);
+        assert_eq!(endpoints, vec!["c", "a", "b"]);
+    }
+
+    #[test]
+    fn reports_the_most_actionable_endpoint_error() {
+        let not_found = UsageError::from_status(StatusCode::NOT_FOUND, None, "a -> HTTP 404".to_string());
+        let limited = UsageError::from_status(
+            StatusCode::TOO_MANY_REQUESTS,
+            Some(Duration::from_secs(90)),
+            "b -> HTTP 429".to_string(),
+        )
+        .with_body("slow down");
+        let offline = UsageError::new(UsageErrorKind::Network, "c -> connection refused");
+
+        let error = summarize_usage_errors(vec![not_found, limited, offline]);
+        assert_eq!(error.kind, UsageErrorKind::RateLimited);
+        assert_eq!(error.http_status, Some(429));
+        assert_eq!(error.retry_after_secs, Some(90));
+        assert_eq!(error.body.as_deref(), Some("slow down"));
+        assert!(error.message.contains("a -> HTTP 404 | b -> HTTP 429"));
+
+        let forbidden = UsageError::from_status(StatusCode::FORBIDDEN, None, "a -> HTTP 403".to_string());
+        assert_eq!(forbidden.code, crate::error::AppErrorCode::Forbidden);
+        assert_eq!(summarize_usage_errors(Vec::new()).kind, UsageErrorKind::Network);
+    }
+
+    #[test]
+    fn falls_back_to_loose_parsing_when_the_schema_shifts() {
+        let (usage, _) = parse_usage_body(
+            r#"{"plan_type":"plus","rate_limit":{"primary_window":{"used_percent":42.0,"limit_window_seconds":18000,"reset_at":1700000000}}}"#,
+        )
+        .expect("typed");
+        assert_eq!(usage.primary_window.expect("primary").window_minutes, Some(300));
+
+        let (usage, raw) = parse_usage_body(
+            r#"{"planType":"pro","rateLimits":{"primary":{"usedPercent":"n/a"},"secondaryWindow":{"usedPercent":12.5,"resetsAt":1700000600}}}"#,
+        )
+        .expect("fallback");
+        assert!(usage.primary_window.is_none());
+        let secondary = usage.secondary_window.expect("secondary");
+        assert_eq!(secondary.used_percent, 12.5);
+        assert_eq!(secondary.resets_at, Some(1_700_000_600));
+        assert_eq!(usage.plan_type.as_deref(), Some("pro"));
+        assert_eq!(raw["planType"], "pro");
+
+        assert!(parse_usage_body(r#"{"limits":{}}"#).is_err());
+        assert!(parse_usage_body("<html>").is_err());
+    }
+
+    #[test]
+    fn backs_off_rate_limited_accounts_until_the_deadline() {
+        let cache = UsageCache::default();
+        cache.back_off("acc-1", 10_000);
+        cache.back_off("", 10_000);
+
+        assert_eq!(cache.backoff_remaining("acc-1", 8_500), Some(2));
+        assert_eq!(cache.backoff_remaining("acc-2", 8_500), None);
+        assert_eq!(cache.backoff_remaining("", 8_500), None);
+        assert_eq!(cache.backoff_remaining("acc-1", 10_000), None);
+        assert!(cache.rate_limited_until.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn classifies_health_check_responses() {
+        assert_eq!(health_status_for_http(StatusCode::OK), HealthStatus::Ok);
+        assert_eq!(health_status_for_http(StatusCode::UNAUTHORIZED), HealthStatus::Unauthorized);
+        assert_eq!(health_status_for_http(StatusCode::FORBIDDEN), HealthStatus::Unauthorized);
+        assert_eq!(health_status_for_http(StatusCode::TOO_MANY_REQUESTS), HealthStatus::RateLimited);
+        assert_eq!(health_status_for_http(StatusCode::BAD_GATEWAY), HealthStatus::NetworkError);
+    }
 }
@@ -0,0 +1,95 @@
+use crate::account_meta::is_account_meta_file;
+use crate::get_accounts_dir;
+use notify_debouncer_mini::notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+
+pub const ACCOUNTS_CHANGED_EVENT: &str = "accounts-changed";
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(500);
+
+struct ActiveWatch {
+    dir: PathBuf,
+    _debouncer: Debouncer<RecommendedWatcher>,
+}
+
+/// Watches the accounts dir and emits `accounts-changed`; the frontend rescans on each event.
+#[derive(Default)]
+pub struct AccountsWatcher {
+    active: Mutex<Option<ActiveWatch>>,
+}
+
+/// Profile JSON changes are relevant; `meta.json` is skipped since the app rewrites it on every switch.
+fn is_relevant_change(path: &Path) -> bool {
+    path.extension().and_then(|s| s.to_str()) == Some("json") && !is_account_meta_file(path)
+}
+
+fn start_watch<R: tauri::Runtime>(app: &tauri::AppHandle<R>, dir: &Path) -> Result<ActiveWatch, String> {
+    let handle = app.clone();
+    let mut debouncer = new_debouncer(WATCH_DEBOUNCE, move |result: DebounceEventResult| {
+        if let Ok(events) = result {
+            // Debouncing collapses bursts of writes, so one emit per batch is enough.
+            if events.iter().any(|event| is_relevant_change(&event.path)) {
+                let _ = handle.emit(ACCOUNTS_CHANGED_EVENT, ());
+            }
+        }
+    })
+    .map_err(|e| format!("Failed to create accounts watcher: {}", e))?;
+    debouncer
+        .watcher()
+        .watch(dir, RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch accounts directory: {}", e))?;
+
+    Ok(ActiveWatch {
+        dir: dir.to_path_buf(),
+        _debouncer: debouncer,
+    })
+}
+
+/// Points the watcher at the configured accounts dir, creating the dir when it is missing so there
+/// is always something to watch, and replacing any previous watch.
+pub fn watch_accounts_dir<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Result<(), String> {
+    point_watch(app, false)
+}
+
+/// `watch_accounts_dir` that also replaces a watch on the same path, for when the dir was deleted
+/// and created again (a watch on the old dir no longer sees anything).
+pub fn restart_accounts_watch<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Result<(), String> {
+    point_watch(app, true)
+}
+
+fn point_watch<R: tauri::Runtime>(app: &tauri::AppHandle<R>, restart: bool) -> Result<(), String> {
+    let dir = get_accounts_dir();
+    let watcher = app.state::<AccountsWatcher>();
+    let mut active = watcher
+        .active
+        .lock()
+        .map_err(|_| "Accounts watcher lock poisoned".to_string())?;
+
+    if !restart && active.as_ref().is_some_and(|watch| watch.dir == dir) {
+        return Ok(());
+    }
+    *active = None;
+    if !dir.is_dir() {
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create accounts directory: {}", e))?;
+    }
+    *active = Some(start_watch(app, &dir)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_relevant_change;
+    use std::path::Path;
+
+    #[test]
+    fn only_profile_json_changes_are_relevant() {
+        assert!(is_relevant_change(Path::new("/accounts/work/a.json")));
+        assert!(!is_relevant_change(Path::new("/accounts/meta.json")));
+        assert!(!is_relevant_change(Path::new("/accounts/a.json.tmp")));
+        assert!(!is_relevant_change(Path::new("/accounts/work")));
+    }
+}
@@ -1,26 +1,84 @@
 use crate::account_files::{
+    account_group,
+    archived_accounts_dir,
     collect_account_files,
+    collect_all_account_files,
+    duplicate_key,
+    exposed_permission_mode,
     files_have_same_content,
+    find_duplicate_profile,
+    find_profile_by_name,
+    is_archived_account_path,
+    link_file_atomic,
+    match_active_profile,
     paths_match,
     ParsedAccountFile,
     resolve_available_account_target,
     resolve_managed_account_path,
+    restrict_permissions,
+    scan_account_files,
+    validate_account_path,
+    validate_readable_account_path,
+    write_private_file_atomic,
 };
-use crate::config::{load_config, save_config};
+use crate::account_activity::{load_account_activity, record_account_used, update_account_activity};
+use crate::account_cache::AccountScanCache;
+use crate::account_groups::{list_account_groups, resolve_group_dir};
+use crate::account_meta::{
+    account_meta_path,
+    load_account_meta,
+    profile_id_for_file,
+    set_refresh_dead,
+    update_account_meta,
+};
+use crate::account_order::{
+    assign_sort_indices,
+    prune_account_order,
+    rotation_after_active,
+    sort_accounts,
+    AccountSortKey,
+};
+use crate::account_status::{is_stale, parse_last_refresh, subscription_days_left};
+use crate::account_watcher::{restart_accounts_watch, watch_accounts_dir};
+use crate::account_tokens::{
+    refresh_before_switch,
+    revoke_token,
+    sync_active_tokens_to_profile,
+    RefreshLocks,
+    RevocationOutcome,
+    TokenRevocation,
+};
+use crate::auth_backups::backup_active_auth;
+use crate::command_guard::is_read_only;
+use crate::config::{load_config, save_config, SwitchMode, DEFAULT_TOKEN_REFRESH_THRESHOLD_MINUTES};
+use crate::config_overrides::apply_account_config_overrides;
 use crate::error::{AppError, AppResult};
+use crate::desktop_shell;
+use crate::profile_name::sanitize_profile_name;
+use crate::switch_history::{record_switch, SwitchSource};
+use crate::switch_hook::spawn_post_switch_hook;
+use crate::trace;
+use crate::trash_bin::remove_path;
+use crate::webdav_settings::{load_webdav_password, store_webdav_password};
+use crate::webdav_tombstones::record_account_deletion;
 use crate::{
     extract_profile_id_from_auth,
     extract_info_from_auth,
     get_accounts_dir,
     get_codex_auth_file,
     gateway_platform_key_entry,
-    webdav_password_entry,
+    sync_passphrase_entry,
     AccountInfo,
     CodexAuthFile,
     ScanResult,
 };
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use tauri::{Emitter, Manager};
+
+const MISSING_CREDENTIALS_ERROR: &str = "Account JSON has neither a tokens block nor an OPENAI_API_KEY";
 
 fn persist_active_account_file(path: Option<&PathBuf>) -> Result<(), String> {
     let mut config = load_config();
@@ -28,13 +86,13 @@ fn persist_active_account_file(path: Option<&PathBuf>) -> Result<(), String> {
     save_config(&config).map_err(|error| error.message.clone())
 }
 
-fn loaded_active_account_path(accounts_dir: &PathBuf) -> Option<PathBuf> {
+fn loaded_active_account_path(accounts_dir: &Path) -> Option<PathBuf> {
     load_config()
         .active_account_file
         .and_then(|value| resolve_managed_account_path(&value, accounts_dir).ok())
 }
 
-fn configured_active_account_path(accounts_dir: &PathBuf, codex_auth: &PathBuf) -> Option<PathBuf> {
+fn configured_active_account_path(accounts_dir: &Path, codex_auth: &Path) -> Option<PathBuf> {
     let configured_path = loaded_active_account_path(accounts_dir)?;
 
     if !configured_path.exists() || !codex_auth.exists() {
@@ -46,28 +104,77 @@ fn configured_active_account_path(accounts_dir: &PathBuf, codex_auth: &PathBuf)
 
 fn resolve_active_account_path(
     account_files: &[ParsedAccountFile],
-    accounts_dir: &PathBuf,
+    accounts_dir: &Path,
     codex_auth: &PathBuf,
 ) -> Option<PathBuf> {
     if !codex_auth.exists() {
         return None;
     }
 
-    let exact_match = account_files
+    // In symlink mode the runtime file points straight at the active profile.
+    if fs::symlink_metadata(codex_auth).is_ok_and(|metadata| metadata.file_type().is_symlink()) {
+        if let Some(linked) = account_files.iter().find(|file| paths_match(&file.path, codex_auth)) {
+            return Some(linked.path.clone());
+        }
+    }
+
+    // Copies of a profile have the same content; prefer the one that was switched to.
+    let configured_path = configured_active_account_path(accounts_dir, codex_auth);
+    let exact_matches: Vec<&PathBuf> = account_files
         .iter()
-        .find(|file| files_have_same_content(&file.path, codex_auth))
-        .map(|file| file.path.clone());
+        .filter(|file| files_have_same_content(&file.path, codex_auth))
+        .map(|file| &file.path)
+        .collect();
+    let exact_match = configured_path
+        .as_ref()
+        .and_then(|configured| exact_matches.iter().find(|path| paths_match(path, configured)).copied())
+        .or_else(|| exact_matches.first().copied());
 
     if let Some(exact_match_path) = exact_match {
-        return Some(exact_match_path);
+        return Some(exact_match_path.clone());
     }
 
-    configured_active_account_path(accounts_dir, codex_auth)
+    let active_auth = fs::read_to_string(codex_auth)
+        .ok()
+        .and_then(|content| serde_json::from_str::<CodexAuthFile>(&content).ok());
+    if let Some(active_auth) = active_auth.filter(|auth| auth.account_id().trim().is_empty()) {
+        if let Some(matched_path) = match_active_profile(account_files, &active_auth, configured_path.as_ref()) {
+            return Some(matched_path);
+        }
+    }
+
+    configured_path
 }
 
-/// Scan accounts directory and return all available accounts
+/// Scan accounts directory and return all available accounts, ordered by `sort_by` (name by default).
+/// Archived profiles are only listed when `include_archived` is set.
 #[tauri::command]
-pub fn scan_accounts() -> Result<ScanResult, String> {
+pub fn scan_accounts(
+    app: tauri::AppHandle,
+    cache: tauri::State<'_, AccountScanCache>,
+    locks: tauri::State<'_, RefreshLocks>,
+    sort_by: Option<AccountSortKey>,
+    descending: Option<bool>,
+    include_archived: Option<bool>,
+) -> Result<ScanResult, String> {
+    let dir_existed = get_accounts_dir().is_dir();
+    let mut result = scan_accounts_with_cache(&cache, sort_by, descending, include_archived)?;
+    // The scan created the dir, so any earlier watch (or none) cannot see it yet.
+    if !dir_existed {
+        if let Err(error) = restart_accounts_watch(&app) {
+            trace::emit("accounts", "watch_failed", serde_json::json!({ "error": error }));
+        }
+    }
+    locks.annotate(&mut result.accounts);
+    Ok(result)
+}
+
+pub(crate) fn scan_accounts_with_cache(
+    cache: &AccountScanCache,
+    sort_by: Option<AccountSortKey>,
+    descending: Option<bool>,
+    include_archived: Option<bool>,
+) -> Result<ScanResult, String> {
     let accounts_dir = get_accounts_dir();
     let codex_auth = get_codex_auth_file();
 
@@ -76,67 +183,425 @@ pub fn scan_accounts() -> Result<ScanResult, String> {
             .map_err(|e| format!("Failed to create accounts directory: {}", e))?;
     }
 
-    let mut account_files = collect_account_files(&accounts_dir, Some(&codex_auth))?;
+    let mut config = load_config();
+    let refresh_threshold_minutes = config.token_refresh_threshold_minutes;
+    let stale_after_days = config.stale_after_days;
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let now_secs = now_ms / 1000;
+    let include_archived = include_archived.unwrap_or(false);
+    let (mut account_files, mut errors) = scan_account_files(&accounts_dir, Some(&codex_auth), Some(cache))?;
     account_files.sort_by(|a, b| a.path.to_string_lossy().cmp(&b.path.to_string_lossy()));
     let active_account_path = resolve_active_account_path(&account_files, &accounts_dir, &codex_auth);
+    // The active profile counts as the original, so copies of it are the ones flagged.
+    let mut first_seen: HashMap<String, String> = account_files
+        .iter()
+        .filter(|file| active_account_path.as_ref().is_some_and(|path| paths_match(&file.path, path)))
+        .filter_map(|file| {
+            let (info, _) = cache.auth_info(file, refresh_threshold_minutes);
+            let key = duplicate_key(file.auth.account_id(), &info.email)?;
+            Some((key, file.path.to_string_lossy().to_string()))
+        })
+        .collect();
+    let meta_store = load_account_meta(&accounts_dir);
+    let activity_store = load_account_activity();
 
-    let accounts = account_files
+    let mut listed_files: Vec<(ParsedAccountFile, bool)> =
+        account_files.into_iter().map(|file| (file, false)).collect();
+    if include_archived {
+        let (mut archived_files, archived_errors) =
+            scan_account_files(&archived_accounts_dir(&accounts_dir), None, Some(cache))?;
+        archived_files.sort_by(|a, b| a.path.to_string_lossy().cmp(&b.path.to_string_lossy()));
+        listed_files.extend(archived_files.into_iter().map(|file| (file, true)));
+        errors.extend(archived_errors);
+    }
+
+    cache.retain_paths(&listed_files.iter().map(|(file, _)| file.path.clone()).collect());
+
+    let mut accounts: Vec<AccountInfo> = listed_files
         .into_iter()
-        .map(|file| {
-            let (email, plan_type, subscription_end, expires_at) = extract_info_from_auth(&file.auth);
+        .map(|(file, is_archived)| {
+            let (info, id) = cache.auth_info(&file, refresh_threshold_minutes);
             let name = file
                 .path
                 .file_stem()
                 .and_then(|s| s.to_str())
                 .unwrap_or("Untitled")
                 .to_string();
-            let is_active = active_account_path
-                .as_ref()
-                .map(|path| paths_match(&file.path, path))
-                .unwrap_or(false);
+            let is_active = !is_archived
+                && active_account_path
+                    .as_ref()
+                    .is_some_and(|path| paths_match(&file.path, path));
+            let file_path = file.path.to_string_lossy().to_string();
+            let duplicate_of = duplicate_key(file.auth.account_id(), &info.email).and_then(|key| {
+                match first_seen.get(&key).filter(|original| **original != file_path) {
+                    Some(original) => Some(original.clone()),
+                    None if first_seen.contains_key(&key) => None,
+                    None => {
+                        first_seen.insert(key, file_path.clone());
+                        None
+                    }
+                }
+            });
+
+            let meta = meta_store.get(&id);
+            let cooling_down_until = meta.cooling_down_until.filter(|until| *until > now_secs);
+            let activity = activity_store.get(&id);
+            let last_refresh_at = parse_last_refresh(&file.auth.last_refresh);
+            let group = if is_archived {
+                None
+            } else {
+                account_group(&file.path, &accounts_dir)
+            };
 
             AccountInfo {
-                id: extract_profile_id_from_auth(&file.auth, Some(&file.path)),
-                upstream_account_id: file.auth.tokens.account_id,
+                id,
+                upstream_account_id: file.auth.account_id().to_string(),
+                kind: file.auth.kind(),
+                api_key_hint: file.auth.api_key_hint(),
+                last_used_at: activity.last_used_at,
+                switch_count: meta.switch_count,
                 name,
-                email,
-                plan_type,
-                subscription_end,
+                needs_refresh: info.needs_refresh(),
+                email: info.email,
+                plan_type: info.plan_type,
+                plan: info.plan,
+                subscription_end: info.subscription_end,
+                subscription_ends_at: info.subscription_ends_at,
+                subscription_days_left: subscription_days_left(info.subscription_ends_at, now_secs),
+                subscription_expired: info.subscription_ends_at.is_some_and(|ends_at| ends_at <= now_secs),
                 is_active,
-                file_path: file.path.to_string_lossy().to_string(),
+                file_path,
                 auth_updated_at: file.modified_at,
-                expires_at,
+                expires_at: info.expires_at,
                 last_refresh: file.auth.last_refresh,
+                last_refresh_at,
+                is_stale: is_stale(last_refresh_at, now_ms, stale_after_days),
+                token_status: info.token_status,
+                duplicate_of,
+                notes: meta.notes,
+                tags: meta.tags,
+                config_override: meta.config_override,
+                refresh_dead: meta.refresh_dead,
+                is_archived,
+                sort_index: 0,
+                group,
+                file_size: file.file_size,
+                content_hash: file.content_hash,
+                refresh_state: None,
+                last_refresh_error: None,
+                cooling_down_until,
+                is_cooling_down: cooling_down_until.is_some(),
             }
         })
         .collect();
 
+    if !config.account_order.is_empty() {
+        let mut known_ids: HashSet<String> = accounts.iter().map(|account| account.id.clone()).collect();
+        if !include_archived {
+            known_ids.extend(
+                collect_account_files(&archived_accounts_dir(&accounts_dir), None)?
+                    .iter()
+                    .map(|file| extract_profile_id_from_auth(&file.auth, Some(&file.path))),
+            );
+        }
+        // Read-only mode prunes the order for this scan only.
+        let pruned = prune_account_order(&config.account_order, &known_ids);
+        if pruned != config.account_order {
+            config.account_order = pruned;
+            if !is_read_only() {
+                let _ = save_config(&config);
+            }
+        }
+    }
+
+    assign_sort_indices(&mut accounts, &config.account_order);
+    sort_accounts(&mut accounts, sort_by.unwrap_or_default(), descending.unwrap_or(false));
+    errors.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+
+    let groups = list_account_groups(&accounts_dir, &accounts);
     Ok(ScanResult {
         accounts,
         accounts_dir: accounts_dir.to_string_lossy().to_string(),
+        errors,
+        groups,
     })
 }
 
-#[tauri::command]
-pub fn switch_account(file_path: String) -> Result<(), String> {
-    let source = resolve_managed_account_path(&file_path, &get_accounts_dir())?;
-    let target = get_codex_auth_file();
+/// Resolves `file_path` to a profile that may be activated.
+fn switchable_account_path(file_path: &str, accounts_dir: &Path) -> AppResult<PathBuf> {
+    let source_path = validate_account_path(file_path, accounts_dir)?;
+    if !source_path.exists() {
+        return Err(AppError::not_found("Authentication file does not exist"));
+    }
+    if is_archived_account_path(&source_path, accounts_dir) {
+        return Err(AppError::forbidden("Archived accounts cannot be activated; unarchive it first"));
+    }
+    Ok(source_path)
+}
 
-    if !source.exists() {
-        return Err("Authentication file does not exist".to_string());
+/// `profile_content` with the API key from the live auth file carried over, or `None` when
+/// there is nothing to carry (no live key, or the profile brings its own).
+fn content_with_live_api_key(profile_content: &[u8], live_path: &Path) -> Result<Option<Vec<u8>>, String> {
+    let Some(live_auth) = fs::read(live_path)
+        .ok()
+        .and_then(|content| serde_json::from_slice::<CodexAuthFile>(&content).ok())
+    else {
+        return Ok(None);
+    };
+    let mut profile: CodexAuthFile = serde_json::from_slice(profile_content)
+        .map_err(|e| format!("Failed to parse authentication file: {}", e))?;
+    if !profile.inherit_api_key(&live_auth) {
+        return Ok(None);
     }
+    serde_json::to_vec_pretty(&profile)
+        .map(Some)
+        .map_err(|e| format!("Failed to serialize: {}", e))
+}
 
+/// Copies `source_path` over `~/.codex/auth.json` and records the switch.
+fn activate_account_file(source_path: &PathBuf, accounts_dir: &Path, source: SwitchSource) -> Result<(), String> {
+    let target = get_codex_auth_file();
     if let Some(parent) = target.parent() {
         if !parent.exists() {
             fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
         }
     }
 
-    fs::copy(&source, &target).map_err(|e| format!("Failed to copy authentication file: {}", e))?;
-    persist_active_account_file(Some(&source))?;
+    let config = load_config();
+    backup_active_auth()?;
+    let linked = config.switch_mode == SwitchMode::Symlink
+        && match link_file_atomic(&target, source_path) {
+            Ok(()) => {
+                // The link exposes whatever permissions the profile has.
+                let _ = restrict_permissions(source_path);
+                true
+            }
+            Err(error) => {
+                // Windows only allows symlinks with developer mode or elevation.
+                trace::emit(
+                    "accounts",
+                    "symlink_fallback",
+                    serde_json::json!({ "error": error.to_string() }),
+                );
+                false
+            }
+        };
+    if !linked {
+        let mut content =
+            fs::read(source_path).map_err(|e| format!("Failed to read authentication file: {}", e))?;
+        if config.preserve_api_key_on_switch {
+            if let Some(merged) = content_with_live_api_key(&content, &target)? {
+                content = merged;
+            }
+        }
+        write_private_file_atomic(&target, &content)
+            .map_err(|e| format!("Failed to write authentication file: {}", e))?;
+    }
+    // auth.json already holds the new account, so the bookkeeping below must not fail the switch.
+    log_switch_step("active_file", persist_active_account_file(Some(source_path)));
+    if let Ok(profile_id) = profile_id_for_file(source_path) {
+        let now = chrono::Utc::now().timestamp_millis();
+        log_switch_step("last_used", record_account_used(&profile_id, now));
+        log_switch_step(
+            "meta",
+            update_account_meta(accounts_dir, |store| store.record_switch(&profile_id, now)),
+        );
+        let name = source_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("Untitled");
+        log_switch_step("history", record_switch(&profile_id, name, source));
+        log_switch_step(
+            "config_overrides",
+            apply_account_config_overrides(accounts_dir, &profile_id),
+        );
+    }
     Ok(())
 }
 
+fn log_switch_step(step: &str, result: Result<(), String>) {
+    if let Err(error) = result {
+        trace::emit(
+            "accounts",
+            "switch_bookkeeping_failed",
+            serde_json::json!({ "step": step, "error": error }),
+        );
+    }
+}
+
+/// Emitted with the newly active `AccountInfo` after every successful switch.
+pub const ACCOUNT_SWITCHED_EVENT: &str = "account-switched";
+
+/// Confirms `target` now holds the same upstream account as `source`; something else rewriting
+/// auth.json right after the copy (sync clients, another switcher) shows up here.
+fn verify_switched_auth(source: &Path, target: &Path) -> AppResult<()> {
+    let read_account_id = |path: &Path| {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<CodexAuthFile>(&content).ok())
+            .map(|auth| auth.account_id().trim().to_string())
+    };
+    let expected = read_account_id(source)
+        .ok_or_else(|| AppError::io("Failed to read the switched profile back"))?;
+    match read_account_id(target) {
+        Some(active) if active == expected => Ok(()),
+        Some(active) => Err(AppError::switch_mismatch(format!(
+            "auth.json was written but holds account '{}' instead of '{}'",
+            active, expected
+        ))),
+        None => Err(AppError::switch_mismatch(
+            "auth.json was written but could not be read back as an auth file",
+        )),
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SwitchResult {
+    /// The now-active account.
+    #[serde(flatten)]
+    pub account: AccountInfo,
+    /// A `postSwitchCommand` was started; its `HookResult` follows as `POST_SWITCH_HOOK_EVENT`.
+    #[serde(rename = "postSwitchHookStarted")]
+    pub post_switch_hook_started: bool,
+}
+
+/// Fails with `SWITCH_MISMATCH` when the copy succeeded but auth.json does not hold the profile
+/// afterwards, and with `REFRESH_DEAD` for profiles whose refresh token was rejected unless `force`
+/// is set; other failures keep their own codes.
+pub(crate) async fn switch_to_path(
+    app: &tauri::AppHandle,
+    source_path: PathBuf,
+    source: Option<SwitchSource>,
+    skip_refresh: bool,
+    force: bool,
+) -> AppResult<SwitchResult> {
+    if !force {
+        let accounts_dir = get_accounts_dir();
+        let dead = profile_id_for_file(&source_path)
+            .is_ok_and(|profile_id| load_account_meta(&accounts_dir).get(&profile_id).refresh_dead);
+        if dead {
+            return Err(AppError::refresh_dead(
+                "This account's refresh token was rejected; log in again or switch with force",
+            ));
+        }
+    }
+    // Best effort: keep tokens Codex rotated in the live file before it gets replaced.
+    let _ = sync_active_tokens_to_profile();
+    let config = load_config();
+    if !skip_refresh {
+        refresh_before_switch(&app.state::<RefreshLocks>(), &source_path, config.switch_refresh_window_minutes)
+            .await
+            .map_err(AppError::auth)?;
+    }
+    activate_account_file(&source_path, &get_accounts_dir(), source.unwrap_or_default()).map_err(AppError::io)?;
+    verify_switched_auth(&source_path, &get_codex_auth_file())?;
+
+    let account = account_info_for_path(&app.state::<AccountScanCache>(), &source_path).map_err(AppError::io)?;
+    desktop_shell::show_current_account(app, &account.email);
+    let _ = app.emit(ACCOUNT_SWITCHED_EVENT, &account);
+    let post_switch_hook_started = spawn_post_switch_hook(app, config.post_switch_command.as_deref(), &account);
+    Ok(SwitchResult {
+        account,
+        post_switch_hook_started,
+    })
+}
+
+/// `source` records where the switch was triggered from in the switch history (defaults to the UI).
+/// Tokens that are expired or about to expire are refreshed first unless `skip_refresh` is set.
+/// `force` allows switching to a profile marked `refreshDead`.
+#[tauri::command]
+pub async fn switch_account(
+    app: tauri::AppHandle,
+    file_path: String,
+    source: Option<SwitchSource>,
+    skip_refresh: Option<bool>,
+    force: Option<bool>,
+) -> AppResult<SwitchResult> {
+    let source_path = switchable_account_path(&file_path, &get_accounts_dir())?;
+    switch_to_path(&app, source_path, source, skip_refresh.unwrap_or(false), force.unwrap_or(false)).await
+}
+
+/// Switches to the profile whose file stem or upstream account id is `name_or_id`.
+#[tauri::command]
+pub async fn switch_account_by_name(
+    app: tauri::AppHandle,
+    name_or_id: String,
+    source: Option<SwitchSource>,
+    skip_refresh: Option<bool>,
+    force: Option<bool>,
+) -> AppResult<SwitchResult> {
+    let accounts_dir = get_accounts_dir();
+    let files = collect_account_files(&accounts_dir, None).map_err(AppError::io)?;
+    let path = find_profile_by_name(&files, &accounts_dir, &name_or_id).map_err(AppError::not_found)?;
+    let source_path = switchable_account_path(&path.to_string_lossy(), &accounts_dir)?;
+    switch_to_path(&app, source_path, source, skip_refresh.unwrap_or(false), force.unwrap_or(false)).await
+}
+
+/// Switches to the account after the active one in the saved custom order (name order for
+/// unordered accounts), wrapping around. With `skip_expired`, profiles whose token is expired and
+/// cannot be refreshed, or whose refresh token is known dead, are passed over.
+#[tauri::command]
+pub async fn switch_next_account(
+    app: tauri::AppHandle,
+    skip_expired: Option<bool>,
+    source: Option<SwitchSource>,
+) -> AppResult<SwitchResult> {
+    let scan = scan_accounts_with_cache(&app.state::<AccountScanCache>(), Some(AccountSortKey::Custom), None, None)
+        .map_err(AppError::io)?;
+    let skip_expired = skip_expired.unwrap_or(false);
+    let candidates: Vec<PathBuf> = rotation_after_active(&scan.accounts)
+        .into_iter()
+        .filter(|account| !(skip_expired && account.refresh_dead))
+        .map(|account| PathBuf::from(&account.file_path))
+        .collect();
+    if !skip_expired {
+        let next = candidates
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::not_found("No other account to switch to"))?;
+        return switch_to_path(&app, next, source, false, false).await;
+    }
+
+    let window_minutes = load_config().switch_refresh_window_minutes;
+    for candidate in candidates {
+        if refresh_before_switch(&app.state::<RefreshLocks>(), &candidate, window_minutes).await.is_ok() {
+            return switch_to_path(&app, candidate, source, true, false).await;
+        }
+    }
+    Err(AppError::not_found("No other account with a usable token to switch to"))
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PermissionIssue {
+    #[serde(rename = "filePath")]
+    pub file_path: String,
+    /// Octal permission bits, e.g. `644`.
+    pub mode: String,
+}
+
+/// Profiles (archived included) and `~/.codex/auth.json` readable by group or others. Always
+/// empty on Windows.
+#[tauri::command]
+pub fn audit_permissions() -> Result<Vec<PermissionIssue>, String> {
+    let accounts_dir = get_accounts_dir();
+    let mut paths: Vec<PathBuf> = collect_all_account_files(&accounts_dir)?
+        .into_iter()
+        .map(|file| file.path)
+        .collect();
+    paths.push(get_codex_auth_file());
+
+    Ok(paths
+        .into_iter()
+        .filter_map(|path| {
+            let mode = exposed_permission_mode(&path).ok().flatten()?;
+            Some(PermissionIssue {
+                file_path: path.to_string_lossy().to_string(),
+                mode: format!("{:o}", mode),
+            })
+        })
+        .collect())
+}
+
 #[tauri::command]
 pub fn open_accounts_dir() -> Result<String, String> {
     let dir = get_accounts_dir();
@@ -180,27 +645,43 @@ pub fn get_accounts_dir_path() -> String {
 
 #[tauri::command]
 pub fn get_webdav_password() -> AppResult<Option<String>> {
-    let entry = webdav_password_entry()?;
-    match entry.get_password() {
-        Ok(password) => Ok(Some(password)),
+    Ok(load_webdav_password()?.map(|(password, _)| password))
+}
+
+#[tauri::command]
+pub fn set_webdav_password(password: String) -> AppResult<()> {
+    store_webdav_password(&password).map(|_| ())
+}
+
+/// The passphrase account files are encrypted with on the WebDAV server, if one is saved.
+pub(crate) fn stored_sync_passphrase() -> AppResult<Option<String>> {
+    match sync_passphrase_entry()?.get_password() {
+        Ok(passphrase) => Ok(Some(passphrase)),
         Err(keyring::Error::NoEntry) => Ok(None),
-        Err(e) => Err(AppError::secure_storage(format!("Failed to load WebDAV password: {}", e))),
+        Err(e) => Err(AppError::secure_storage(format!("Failed to load sync passphrase: {}", e))),
     }
 }
 
+/// Whether account files are encrypted before upload. The passphrase itself is never returned.
 #[tauri::command]
-pub fn set_webdav_password(password: String) -> AppResult<()> {
-    let entry = webdav_password_entry()?;
-    if password.trim().is_empty() {
+pub fn has_sync_passphrase() -> AppResult<bool> {
+    Ok(stored_sync_passphrase()?.is_some())
+}
+
+/// Saves the sync passphrase; an empty one turns encryption off for later uploads.
+#[tauri::command]
+pub fn set_sync_passphrase(passphrase: String) -> AppResult<()> {
+    let entry = sync_passphrase_entry()?;
+    if passphrase.is_empty() {
         return match entry.delete_credential() {
             Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
-            Err(e) => Err(AppError::secure_storage(format!("Failed to clear WebDAV password: {}", e))),
+            Err(e) => Err(AppError::secure_storage(format!("Failed to clear sync passphrase: {}", e))),
         };
     }
 
     entry
-        .set_password(&password)
-        .map_err(|e| AppError::secure_storage(format!("Failed to save WebDAV password: {}", e)))
+        .set_password(&passphrase)
+        .map_err(|e| AppError::secure_storage(format!("Failed to save sync passphrase: {}", e)))
 }
 
 #[tauri::command]
@@ -229,7 +710,7 @@ pub fn set_gateway_platform_key(platform_key: String) -> AppResult<()> {
 }
 
 #[tauri::command]
-pub fn set_accounts_dir(path: String) -> Result<(), String> {
+pub fn set_accounts_dir(app: tauri::AppHandle, path: String) -> Result<(), String> {
     let old_dir = get_accounts_dir();
     let new_dir = PathBuf::from(&path);
 
@@ -251,9 +732,14 @@ pub fn set_accounts_dir(path: String) -> Result<(), String> {
         source_files.sort_by(|a, b| a.path.to_string_lossy().cmp(&b.path.to_string_lossy()));
 
         for file in source_files {
+            let target_dir = match account_group(&file.path, &old_dir) {
+                Some(group) => new_dir.join(group),
+                None => new_dir.clone(),
+            };
+            fs::create_dir_all(&target_dir).map_err(|e| format!("Failed to create new directory: {}", e))?;
             let preferred_target = match file.path.file_name() {
-                Some(file_name) => new_dir.join(file_name),
-                None => resolve_available_account_target(&new_dir, "account"),
+                Some(file_name) => target_dir.join(file_name),
+                None => resolve_available_account_target(&target_dir, "account"),
             };
 
             if preferred_target.exists() {
@@ -266,7 +752,7 @@ pub fn set_accounts_dir(path: String) -> Result<(), String> {
                     .file_stem()
                     .and_then(|s| s.to_str())
                     .unwrap_or("account");
-                let target_path = resolve_available_account_target(&new_dir, file_stem);
+                let target_path = resolve_available_account_target(&target_dir, file_stem);
                 fs::copy(&file.path, &target_path).map_err(|e| {
                     format!("Failed to copy '{}' to new directory: {}", file.path.to_string_lossy(), e)
                 })?;
@@ -277,21 +763,60 @@ pub fn set_accounts_dir(path: String) -> Result<(), String> {
                 format!("Failed to copy '{}' to new directory: {}", file.path.to_string_lossy(), e)
             })?;
         }
+
+        let old_meta = account_meta_path(&old_dir);
+        let new_meta = account_meta_path(&new_dir);
+        if old_meta.exists() && !new_meta.exists() {
+            fs::copy(&old_meta, &new_meta).map_err(|e| format!("Failed to copy account metadata: {}", e))?;
+        }
     }
 
-    Ok(())
+    watch_accounts_dir(&app)
+}
+
+/// Whether `path` holds the account that is live in `~/.codex/auth.json`, by configured active
+/// file or by matching profile id.
+pub(crate) fn is_live_account(path: &PathBuf, accounts_dir: &Path) -> bool {
+    if loaded_active_account_path(accounts_dir).is_some_and(|active_path| paths_match(&active_path, path)) {
+        return true;
+    }
+    let live_auth = fs::read_to_string(get_codex_auth_file())
+        .ok()
+        .and_then(|content| serde_json::from_str::<CodexAuthFile>(&content).ok());
+    match (live_auth, profile_id_for_file(path)) {
+        (Some(live_auth), Ok(profile_id)) => extract_profile_id_from_auth(&live_auth, None) == profile_id,
+        _ => false,
+    }
+}
+
+/// Keeps meta.json and activity attached when a rename changes a path-derived profile id.
+fn carry_profile_id(accounts_dir: &Path, from: &str, to: &str) -> Result<(), String> {
+    if from == to {
+        return Ok(());
+    }
+    update_account_meta(accounts_dir, |store| store.rename_id(from, to))?;
+    update_account_activity(|store| store.rename_id(from, to))
 }
 
 #[tauri::command]
-pub fn rename_account(old_path: String, new_name: String) -> Result<(), String> {
+pub fn rename_account(old_path: String, new_name: String) -> AppResult<()> {
     let accounts_dir = get_accounts_dir();
-    let source = resolve_managed_account_path(&old_path, &accounts_dir)?;
+    let source = validate_account_path(&old_path, &accounts_dir)?;
     if !source.exists() {
-        return Err("Source file does not exist".to_string());
+        return Err(AppError::not_found("Source file does not exist"));
     }
 
-    let was_active = loaded_active_account_path(&accounts_dir)
-        .is_some_and(|active_path| paths_match(&active_path, &source));
+    let new_name = sanitize_profile_name(&new_name).map_err(|error| AppError::parse(error.to_string()))?;
+    rename_profile(&accounts_dir, &source, &new_name).map_err(AppError::io)
+}
+
+fn rename_profile(accounts_dir: &Path, source: &PathBuf, new_name: &str) -> Result<(), String> {
+    let was_active = loaded_active_account_path(accounts_dir)
+        .is_some_and(|active_path| paths_match(&active_path, source));
+    let codex_auth = get_codex_auth_file();
+    let was_linked = fs::symlink_metadata(&codex_auth).is_ok_and(|metadata| metadata.file_type().is_symlink())
+        && paths_match(&codex_auth, source);
+    let old_profile_id = profile_id_for_file(source).ok();
     let parent = source.parent().ok_or("Invalid path")?;
     let target = parent.join(format!("{}.json", new_name));
 
@@ -299,108 +824,685 @@ pub fn rename_account(old_path: String, new_name: String) -> Result<(), String>
         return Err("Target name already exists".to_string());
     }
 
-    fs::rename(&source, &target).map_err(|e| format!("Failed to rename: {}", e))?;
+    fs::rename(source, &target).map_err(|e| format!("Failed to rename: {}", e))?;
     if was_active {
         persist_active_account_file(Some(&target))?;
     }
+    if was_linked {
+        link_file_atomic(&codex_auth, &target).map_err(|e| format!("Renamed, but failed to relink auth.json: {}", e))?;
+    }
+    if let (Some(old_profile_id), Ok(new_profile_id)) = (old_profile_id, profile_id_for_file(&target)) {
+        carry_profile_id(accounts_dir, &old_profile_id, &new_profile_id)?;
+    }
     Ok(())
 }
 
+/// Copies a profile to `<new_name>.json` next to it. Notes and tags follow the copy even when its
+/// profile id is path-derived; the copy is never the active profile.
 #[tauri::command]
-pub fn read_account_content(file_path: String) -> Result<String, String> {
-    let path = resolve_managed_account_path(&file_path, &get_accounts_dir())?;
+pub fn duplicate_account(
+    cache: tauri::State<'_, AccountScanCache>,
+    file_path: String,
+    new_name: String,
+) -> AppResult<AccountInfo> {
+    let accounts_dir = get_accounts_dir();
+    let source = validate_account_path(&file_path, &accounts_dir)?;
+    if !source.is_file() {
+        return Err(AppError::not_found("Source file does not exist"));
+    }
+
+    let new_name = sanitize_profile_name(&new_name).map_err(|error| AppError::parse(error.to_string()))?;
+    let parent = source.parent().ok_or_else(|| AppError::io("Invalid path"))?;
+    let target = parent.join(format!("{}.json", new_name));
+    if target.exists() {
+        return Err(AppError::duplicate_account("Target name already exists"));
+    }
+
+    let content = fs::read(&source).map_err(|e| AppError::io(format!("Failed to read file: {}", e)))?;
+    write_private_file_atomic(&target, &content).map_err(|e| AppError::io(format!("Failed to write file: {}", e)))?;
+
+    if let (Ok(from), Ok(to)) = (profile_id_for_file(&source), profile_id_for_file(&target)) {
+        if from != to {
+            update_account_meta(&accounts_dir, |store| {
+                let original = store.get(&from);
+                let mut meta = store.get(&to);
+                meta.notes = original.notes;
+                meta.tags = original.tags;
+                store.set(&to, meta);
+            })
+            .map_err(AppError::io)?;
+        }
+    }
+    account_info_for_path(&cache, &target).map_err(AppError::io)
+}
+
+/// Keys whose values are secrets, matched at any depth so unknown nested structures are covered.
+const SECRET_KEYS: [&str; 4] = ["access_token", "id_token", "refresh_token", "OPENAI_API_KEY"];
+
+/// First 8 and last 4 characters of `secret`, or just `…` when that would reveal most of it.
+fn redact_secret(secret: &str) -> String {
+    let chars: Vec<char> = secret.chars().collect();
+    if chars.len() <= 16 {
+        return "…".to_string();
+    }
+    let head: String = chars[..8].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}…{}", head, tail)
+}
+
+fn redact_secrets(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(object) => {
+            for (key, field) in object.iter_mut() {
+                match field {
+                    serde_json::Value::String(secret) if SECRET_KEYS.contains(&key.as_str()) => {
+                        *secret = redact_secret(secret);
+                    }
+                    _ => redact_secrets(field),
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact_secrets),
+        _ => {}
+    }
+}
+
+/// Pretty-printed profile JSON; with `redacted`, token values are shortened for screen sharing
+/// and the result must not be saved back.
+#[tauri::command]
+pub fn read_account_content(file_path: String, redacted: Option<bool>) -> AppResult<String> {
+    let path = validate_readable_account_path(&file_path, &get_accounts_dir(), &get_codex_auth_file())?;
     if !path.exists() {
-        return Err("File does not exist".to_string());
+        return Err(AppError::not_found("File does not exist"));
     }
 
-    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read file: {}", e))?;
-    let parsed: serde_json::Value =
-        serde_json::from_str(&content).map_err(|e| format!("Failed to parse JSON: {}", e))?;
+    let content = fs::read_to_string(&path).map_err(|e| AppError::io(format!("Failed to read file: {}", e)))?;
+    let mut parsed: serde_json::Value =
+        serde_json::from_str(&content).map_err(|e| AppError::parse(format!("Failed to parse JSON: {}", e)))?;
+    if redacted.unwrap_or(false) {
+        redact_secrets(&mut parsed);
+    }
 
-    serde_json::to_string_pretty(&parsed).map_err(|e| format!("Failed to format JSON: {}", e))
+    serde_json::to_string_pretty(&parsed).map_err(|e| AppError::parse(format!("Failed to format JSON: {}", e)))
 }
 
+/// Checks the fields Codex reads without touching anything else, so unknown keys and key order
+/// survive an edit.
+pub(crate) fn validate_auth_value(value: &serde_json::Value) -> Result<(), String> {
+    let object = value.as_object().ok_or("Account JSON must be an object")?;
+    for key in ["OPENAI_API_KEY", "last_refresh"] {
+        if object.get(key).is_some_and(|field| !field.is_string() && !field.is_null()) {
+            return Err(format!("{} must be a string or null", key));
+        }
+    }
+
+    let tokens = match object.get("tokens") {
+        None | Some(serde_json::Value::Null) => None,
+        Some(tokens) => Some(tokens.as_object().ok_or("tokens must be an object")?),
+    };
+    if let Some(tokens) = tokens {
+        for key in ["access_token", "account_id", "id_token", "refresh_token"] {
+            match tokens.get(key) {
+                None => return Err(format!("tokens.{} is missing", key)),
+                Some(field) if !field.is_string() => return Err(format!("tokens.{} must be a string", key)),
+                Some(_) => {}
+            }
+        }
+    }
+
+    let has_api_key = object
+        .get("OPENAI_API_KEY")
+        .and_then(|key| key.as_str())
+        .is_some_and(|key| !key.trim().is_empty());
+    if tokens.is_none() && !has_api_key {
+        return Err(MISSING_CREDENTIALS_ERROR.to_string());
+    }
+    Ok(())
+}
+
+/// Writes the edited JSON as given (pretty-printed), keeping fields this app does not model.
 #[tauri::command]
-pub fn update_account_content(file_path: String, content: String) -> Result<(), String> {
-    let path = resolve_managed_account_path(&file_path, &get_accounts_dir())?;
+pub fn update_account_content(file_path: String, content: String) -> AppResult<()> {
+    let path = validate_account_path(&file_path, &get_accounts_dir())?;
     if !path.exists() {
-        return Err("File does not exist".to_string());
+        return Err(AppError::not_found("File does not exist"));
     }
 
-    let auth: CodexAuthFile =
-        serde_json::from_str(&content).map_err(|e| format!("Invalid JSON format: {}", e))?;
+    let value: serde_json::Value =
+        serde_json::from_str(&content).map_err(|e| AppError::parse(format!("Invalid JSON format: {}", e)))?;
+    validate_auth_value(&value).map_err(AppError::parse)?;
     let pretty_content =
-        serde_json::to_string_pretty(&auth).map_err(|e| format!("Failed to serialize: {}", e))?;
+        serde_json::to_string_pretty(&value).map_err(|e| AppError::parse(format!("Failed to serialize: {}", e)))?;
 
-    fs::write(&path, pretty_content).map_err(|e| format!("Failed to write file: {}", e))?;
+    write_private_file_atomic(&path, pretty_content.as_bytes())
+        .map_err(|e| AppError::io(format!("Failed to write file: {}", e)))?;
     Ok(())
 }
 
+/// `name` when given, otherwise the profile email, otherwise a timestamped placeholder.
+fn profile_file_name(name: &str, auth: &CodexAuthFile) -> Result<String, String> {
+    if !name.trim().is_empty() {
+        return sanitize_profile_name(name).map_err(|error| error.to_string());
+    }
+
+    let email = extract_info_from_auth(auth, DEFAULT_TOKEN_REFRESH_THRESHOLD_MINUTES).email;
+    if let Some(name) = Some(email)
+        .filter(|email| email != "Unknown")
+        .and_then(|email| sanitize_profile_name(&email).ok())
+    {
+        return Ok(name);
+    }
+    Ok(format!(
+        "account_{}",
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    ))
+}
+
+/// Stored profile (active or archived) whose profile id is `profile_id`.
+fn find_profile_file(accounts_dir: &PathBuf, profile_id: &str) -> Result<Option<PathBuf>, String> {
+    Ok(collect_all_account_files(accounts_dir)?
+        .into_iter()
+        .find(|file| extract_profile_id_from_auth(&file.auth, Some(&file.path)) == profile_id)
+        .map(|file| file.path))
+}
+
+fn profile_exists_error(existing_path: &Path) -> String {
+    let existing_name = existing_path
+        .file_name()
+        .and_then(|value| value.to_str())
+        .unwrap_or("existing profile");
+    format!("Profile already exists as '{}'", existing_name)
+}
+
+/// Scans and returns the entry for `path`, including archived profiles.
+pub(crate) fn account_info_for_path(cache: &AccountScanCache, path: &PathBuf) -> Result<AccountInfo, String> {
+    scan_accounts_with_cache(cache, None, None, Some(true))?
+        .accounts
+        .into_iter()
+        .find(|account| paths_match(&PathBuf::from(&account.file_path), path))
+        .ok_or_else(|| "Saved profile could not be read back".to_string())
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AddAccountOutcome {
+    Created,
+    Updated,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AddAccountResult {
+    pub outcome: AddAccountOutcome,
+    #[serde(rename = "filePath")]
+    pub file_path: String,
+}
+
+/// Fails with `DUPLICATE_ACCOUNT` when a stored profile (archived included) already holds the same
+/// upstream account, or the name is taken, unless `allow_duplicate` is set. With `overwrite` the
+/// existing profile for the account (or with the name) is replaced in place and keeps its metadata.
 #[tauri::command]
-pub fn add_account(name: String, content: String) -> Result<(), String> {
+pub fn add_account(
+    name: String,
+    content: String,
+    allow_duplicate: Option<bool>,
+    overwrite: Option<bool>,
+) -> AppResult<AddAccountResult> {
     let auth: CodexAuthFile =
-        serde_json::from_str(&content).map_err(|e| format!("Invalid JSON format: {}", e))?;
-    let profile_id = extract_profile_id_from_auth(&auth, None);
+        serde_json::from_str(&content).map_err(|e| AppError::parse(format!("Invalid JSON format: {}", e)))?;
+    store_profile(&auth, &name, allow_duplicate.unwrap_or(false), overwrite.unwrap_or(false))
+}
 
-    let file_name = if !name.trim().is_empty() {
-        name.trim().to_string()
-    } else {
-        let (email, _, _, _) = extract_info_from_auth(&auth);
-        if email != "Unknown" {
-            email
-        } else {
-            format!(
-                "account_{}",
-                std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs()
-            )
+/// Saves `auth` as a profile named `name` (or after its email); see `add_account` for the
+/// duplicate and overwrite rules.
+pub(crate) fn store_profile(
+    auth: &CodexAuthFile,
+    name: &str,
+    allow_duplicate: bool,
+    overwrite: bool,
+) -> AppResult<AddAccountResult> {
+    if !auth.has_credentials() {
+        return Err(AppError::parse(MISSING_CREDENTIALS_ERROR));
+    }
+
+    let file_name = profile_file_name(name, auth).map_err(AppError::parse)?;
+
+    let accounts_dir = get_accounts_dir();
+    if !accounts_dir.exists() {
+        fs::create_dir_all(&accounts_dir)
+            .map_err(|e| AppError::io(format!("Failed to create accounts directory: {}", e)))?;
+    }
+
+    let files = collect_all_account_files(&accounts_dir).map_err(AppError::io)?;
+    let duplicate = find_duplicate_profile(&files, auth).map(|file| file.path.clone());
+    let named_path = accounts_dir.join(format!("{}.json", file_name));
+    let target_path = match duplicate {
+        Some(existing) if overwrite => existing,
+        Some(existing) if !allow_duplicate => {
+            return Err(AppError::duplicate_account(profile_exists_error(&existing)));
+        }
+        _ if named_path.exists() && !overwrite => {
+            return Err(AppError::duplicate_account(format!("Account '{}' already exists", file_name)));
         }
+        _ => named_path,
     };
 
+    let previous_profile_id = profile_id_for_file(&target_path).ok();
+    let pretty_content = serde_json::to_string_pretty(auth)
+        .map_err(|e| AppError::parse(format!("Failed to serialize: {}", e)))?;
+    write_private_file_atomic(&target_path, pretty_content.as_bytes())
+        .map_err(|e| AppError::io(format!("Failed to write file: {}", e)))?;
+
+    let outcome = match previous_profile_id {
+        Some(previous_profile_id) => {
+            let profile_id = extract_profile_id_from_auth(auth, Some(&target_path));
+            carry_profile_id(&accounts_dir, &previous_profile_id, &profile_id).map_err(AppError::io)?;
+            AddAccountOutcome::Updated
+        }
+        None => AddAccountOutcome::Created,
+    };
+    Ok(AddAccountResult {
+        outcome,
+        file_path: target_path.to_string_lossy().to_string(),
+    })
+}
+
+/// Saves the live `~/.codex/auth.json` as a profile. An existing profile for the same account
+/// is only replaced when `overwrite` is set.
+#[tauri::command]
+pub fn capture_current_account(
+    cache: tauri::State<'_, AccountScanCache>,
+    name: Option<String>,
+    overwrite: Option<bool>,
+) -> AppResult<AccountInfo> {
+    let codex_auth = get_codex_auth_file();
+    if !codex_auth.exists() {
+        return Err(AppError::not_found("No active Codex login found (auth.json does not exist)"));
+    }
+    let content = fs::read_to_string(&codex_auth)
+        .map_err(|e| AppError::io(format!("Failed to read auth.json: {}", e)))?;
+    let auth: CodexAuthFile = serde_json::from_str(&content)
+        .map_err(|e| AppError::parse(format!("Failed to parse auth.json: {}", e)))?;
+    if !auth.has_credentials() {
+        return Err(AppError::parse(MISSING_CREDENTIALS_ERROR));
+    }
+
     let accounts_dir = get_accounts_dir();
     if !accounts_dir.exists() {
         fs::create_dir_all(&accounts_dir)
-            .map_err(|e| format!("Failed to create accounts directory: {}", e))?;
+            .map_err(|e| AppError::io(format!("Failed to create accounts directory: {}", e)))?;
     }
 
-    let existing_files = collect_account_files(&accounts_dir, None)?;
-    if let Some(existing_file) = existing_files.into_iter().find(|file| {
-        extract_profile_id_from_auth(&file.auth, Some(&file.path)) == profile_id
-    }) {
-        let existing_name = existing_file
-            .path
-            .file_name()
-            .and_then(|value| value.to_str())
-            .unwrap_or("existing profile");
-        return Err(format!("Profile already exists as '{}'", existing_name));
+    let profile_id = extract_profile_id_from_auth(&auth, None);
+    let target_path = match find_profile_file(&accounts_dir, &profile_id).map_err(AppError::io)? {
+        Some(existing_path) if overwrite.unwrap_or(false) => existing_path,
+        Some(existing_path) => return Err(AppError::duplicate_account(profile_exists_error(&existing_path))),
+        None => {
+            let file_name =
+                profile_file_name(name.as_deref().unwrap_or_default(), &auth).map_err(AppError::parse)?;
+            let target_path = accounts_dir.join(format!("{}.json", file_name));
+            if target_path.exists() {
+                return Err(AppError::duplicate_account(format!("Account '{}' already exists", file_name)));
+            }
+            target_path
+        }
+    };
+
+    let pretty_content = serde_json::to_string_pretty(&auth)
+        .map_err(|e| AppError::parse(format!("Failed to serialize: {}", e)))?;
+    write_private_file_atomic(&target_path, pretty_content.as_bytes())
+        .map_err(|e| AppError::io(format!("Failed to write file: {}", e)))?;
+    account_info_for_path(&cache, &target_path).map_err(AppError::io)
+}
+
+/// Moves the profile to the system trash; `permanently` deletes it outright. The live account is
+/// refused with `ACTIVE_ACCOUNT` unless `also_clear_active` is set, which backs up and removes
+/// `~/.codex/auth.json` as well.
+#[tauri::command]
+pub fn delete_account(
+    file_path: String,
+    permanently: Option<bool>,
+    also_clear_active: Option<bool>,
+) -> AppResult<()> {
+    let accounts_dir = get_accounts_dir();
+    let path = validate_account_path(&file_path, &accounts_dir)?;
+    if !path.exists() {
+        return Err(AppError::not_found("Account file not found"));
     }
 
-    let target_path = accounts_dir.join(format!("{}.json", file_name));
-    if target_path.exists() {
-        return Err(format!("Account '{}' already exists", file_name));
+    let is_live = is_live_account(&path, &accounts_dir);
+    if is_live && !also_clear_active.unwrap_or(false) {
+        let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or("This account");
+        return Err(AppError::active_account(format!(
+            "'{}' is the active account; deleting it also clears ~/.codex/auth.json",
+            name
+        )));
     }
 
-    let pretty_content =
-        serde_json::to_string_pretty(&auth).map_err(|e| format!("Failed to serialize: {}", e))?;
-    fs::write(target_path, pretty_content).map_err(|e| format!("Failed to write file: {}", e))?;
+    let was_active = loaded_active_account_path(&accounts_dir)
+        .is_some_and(|active_path| paths_match(&active_path, &path));
+    let profile_id = profile_id_for_file(&path).ok();
+    if is_live {
+        backup_active_auth().map_err(AppError::io)?;
+        let codex_auth = get_codex_auth_file();
+        if fs::symlink_metadata(&codex_auth).is_ok() {
+            fs::remove_file(&codex_auth)
+                .map_err(|e| AppError::io(format!("Failed to remove auth.json: {}", e)))?;
+        }
+    }
+    remove_path(&path, permanently.unwrap_or(false)).map_err(AppError::io)?;
+    if let Err(error) = record_account_deletion(&accounts_dir, &path) {
+        trace::emit("webdav", "tombstone_failed", serde_json::json!({ "error": error }));
+    }
+    if was_active || is_live {
+        persist_active_account_file(None).map_err(AppError::io)?;
+    }
+
+    if let Some(profile_id) = profile_id {
+        let still_referenced = collect_all_account_files(&accounts_dir)
+            .map_err(AppError::io)?
+            .iter()
+            .any(|file| extract_profile_id_from_auth(&file.auth, Some(&file.path)) == profile_id);
+        if !still_referenced {
+            update_account_meta(&accounts_dir, |store| {
+                store.accounts.remove(&profile_id);
+            })
+            .map_err(AppError::io)?;
+        }
+    }
     Ok(())
 }
 
+fn move_account_file(source: &PathBuf, target_dir: &Path) -> Result<PathBuf, String> {
+    if !target_dir.exists() {
+        fs::create_dir_all(target_dir).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+
+    let file_stem = source
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("account");
+    let target = resolve_available_account_target(target_dir, file_stem);
+    fs::rename(source, &target).map_err(|e| format!("Failed to move account file: {}", e))?;
+    Ok(target)
+}
+
+/// Moves a profile into the group folder `target_group` (the accounts dir itself when empty),
+/// creating it if needed, and returns the new path. Refuses to replace an existing file.
 #[tauri::command]
-pub fn delete_account(file_path: String) -> Result<(), String> {
+pub fn move_account(file_path: String, target_group: String) -> Result<String, String> {
     let accounts_dir = get_accounts_dir();
-    let path = resolve_managed_account_path(&file_path, &accounts_dir)?;
-    if !path.exists() {
+    let source = resolve_managed_account_path(&file_path, &accounts_dir)?;
+    if !source.exists() {
         return Err("Account file not found".to_string());
     }
+    if is_archived_account_path(&source, &accounts_dir) {
+        return Err("Archived accounts cannot be moved; unarchive it first".to_string());
+    }
+
+    let target_dir = if target_group.trim().is_empty() {
+        accounts_dir.clone()
+    } else {
+        resolve_group_dir(&accounts_dir, &target_group)?
+    };
+    fs::create_dir_all(&target_dir).map_err(|e| format!("Failed to create group: {}", e))?;
+    let resolved_accounts_dir =
+        fs::canonicalize(&accounts_dir).map_err(|e| format!("Failed to resolve accounts directory: {}", e))?;
+    let target_dir = fs::canonicalize(&target_dir).map_err(|e| format!("Failed to resolve group: {}", e))?;
+    if !target_dir.starts_with(&resolved_accounts_dir) {
+        return Err("Group is outside the managed accounts directory".to_string());
+    }
+
+    let target = target_dir.join(source.file_name().ok_or("Invalid path")?);
+    if paths_match(&source, &target) {
+        return Ok(target.to_string_lossy().to_string());
+    }
+    if target.exists() {
+        return Err("A file with this name already exists in the target group".to_string());
+    }
 
     let was_active = loaded_active_account_path(&accounts_dir)
-        .is_some_and(|active_path| paths_match(&active_path, &path));
-    fs::remove_file(&path).map_err(|e| format!("Failed to delete account: {}", e))?;
+        .is_some_and(|active_path| paths_match(&active_path, &source));
+    let codex_auth = get_codex_auth_file();
+    let was_linked = fs::symlink_metadata(&codex_auth).is_ok_and(|metadata| metadata.file_type().is_symlink())
+        && paths_match(&codex_auth, &source);
+    let old_profile_id = profile_id_for_file(&source).ok();
+
+    fs::rename(&source, &target).map_err(|e| format!("Failed to move account file: {}", e))?;
+    if was_active {
+        persist_active_account_file(Some(&target))?;
+    }
+    if was_linked {
+        link_file_atomic(&codex_auth, &target).map_err(|e| format!("Moved, but failed to relink auth.json: {}", e))?;
+    }
+    if let (Some(old_profile_id), Ok(new_profile_id)) = (old_profile_id, profile_id_for_file(&target)) {
+        carry_profile_id(&accounts_dir, &old_profile_id, &new_profile_id)?;
+    }
+    Ok(target.to_string_lossy().to_string())
+}
+
+/// Moves a profile into the `archived/` subfolder and returns its new path.
+#[tauri::command]
+pub fn archive_account(file_path: String) -> Result<String, String> {
+    let accounts_dir = get_accounts_dir();
+    let source = resolve_managed_account_path(&file_path, &accounts_dir)?;
+    if !source.exists() {
+        return Err("Account file not found".to_string());
+    }
+    if is_archived_account_path(&source, &accounts_dir) {
+        return Err("Account is already archived".to_string());
+    }
+
+    let was_active = loaded_active_account_path(&accounts_dir)
+        .is_some_and(|active_path| paths_match(&active_path, &source));
+    let target = move_account_file(&source, &archived_accounts_dir(&accounts_dir))?;
     if was_active {
         persist_active_account_file(None)?;
     }
-    Ok(())
+    Ok(target.to_string_lossy().to_string())
+}
+
+/// What `revoke_account` does with the profile once its refresh token is revoked.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RevokeFollowUp {
+    #[default]
+    Keep,
+    Archive,
+    Delete,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RevokeResult {
+    #[serde(rename = "refreshToken")]
+    pub refresh_token: TokenRevocation,
+    #[serde(rename = "accessToken")]
+    pub access_token: TokenRevocation,
+    /// The profile is the live Codex session; `activeCleared` says whether auth.json was removed.
+    #[serde(rename = "wasActive")]
+    pub was_active: bool,
+    #[serde(rename = "activeCleared")]
+    pub active_cleared: bool,
+    /// The follow-up actually applied; `keep` when revocation failed or the profile is still live.
+    #[serde(rename = "followUp")]
+    pub follow_up: RevokeFollowUp,
+    /// Where the profile ended up after archiving.
+    #[serde(rename = "filePath")]
+    pub file_path: Option<String>,
+}
+
+/// Revokes a profile's tokens upstream, then archives or deletes it when asked. The live auth.json
+/// is only cleared with `clear_active`; a live profile is never deleted while auth.json still uses it.
+#[tauri::command]
+pub async fn revoke_account(
+    file_path: String,
+    follow_up: Option<RevokeFollowUp>,
+    clear_active: Option<bool>,
+) -> AppResult<RevokeResult> {
+    let accounts_dir = get_accounts_dir();
+    let path = validate_account_path(&file_path, &accounts_dir)?;
+    let content = fs::read_to_string(&path)
+        .map_err(|e| AppError::not_found(format!("Failed to read authentication file: {}", e)))?;
+    let auth: CodexAuthFile = serde_json::from_str(&content)
+        .map_err(|e| AppError::parse(format!("Failed to parse authentication file: {}", e)))?;
+    let tokens = auth.require_tokens("Revocation").map_err(AppError::auth)?;
+    let profile_id = extract_profile_id_from_auth(&auth, Some(&path));
+    let was_active = is_live_account(&path, &accounts_dir);
+
+    let refresh_token = revoke_token(&tokens.refresh_token, "refresh_token").await;
+    let access_token = revoke_token(&tokens.access_token, "access_token").await;
+    let mut result = RevokeResult {
+        refresh_token,
+        access_token,
+        was_active,
+        active_cleared: false,
+        follow_up: RevokeFollowUp::Keep,
+        file_path: None,
+    };
+    if result.refresh_token.outcome != RevocationOutcome::Revoked {
+        return Ok(result);
+    }
+    set_refresh_dead(&accounts_dir, &profile_id, true).map_err(AppError::io)?;
+
+    if was_active && clear_active.unwrap_or(false) {
+        backup_active_auth().map_err(AppError::io)?;
+        let codex_auth = get_codex_auth_file();
+        if fs::symlink_metadata(&codex_auth).is_ok() {
+            fs::remove_file(&codex_auth)
+                .map_err(|e| AppError::io(format!("Failed to remove auth.json: {}", e)))?;
+        }
+        persist_active_account_file(None).map_err(AppError::io)?;
+        result.active_cleared = true;
+    }
+
+    match follow_up.unwrap_or_default() {
+        RevokeFollowUp::Keep => {}
+        RevokeFollowUp::Archive => {
+            result.file_path = Some(archive_account(file_path).map_err(AppError::io)?);
+            result.follow_up = RevokeFollowUp::Archive;
+        }
+        RevokeFollowUp::Delete if !was_active || result.active_cleared => {
+            delete_account(file_path, None, None)?;
+            result.follow_up = RevokeFollowUp::Delete;
+        }
+        RevokeFollowUp::Delete => {}
+    }
+    Ok(result)
+}
+
+/// Moves an archived profile back into the accounts dir and returns its new path.
+#[tauri::command]
+pub fn unarchive_account(file_path: String) -> Result<String, String> {
+    let accounts_dir = get_accounts_dir();
+    let source = resolve_managed_account_path(&file_path, &accounts_dir)?;
+    if !source.exists() {
+        return Err("Account file not found".to_string());
+    }
+    if !is_archived_account_path(&source, &accounts_dir) {
+        return Err("Account is not archived".to_string());
+    }
+
+    let target = move_account_file(&source, &accounts_dir)?;
+    Ok(target.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{redact_secrets, validate_auth_value, verify_switched_auth};
+    use crate::error::AppErrorCode;
+    use std::fs;
+
+    fn auth(account_id: &str) -> String {
+        format!(
+            r#"{{"OPENAI_API_KEY":null,"tokens":{{"access_token":"a","account_id":"{}","id_token":"i","refresh_token":"r"}}}}"#,
+            account_id
+        )
+    }
+
+    #[test]
+    fn distinguishes_mismatched_auth_after_switch() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let source = temp.path().join("work.json");
+        let target = temp.path().join("auth.json");
+        fs::write(&source, auth("acc-1")).expect("source");
+
+        fs::write(&target, auth("acc-1")).expect("target");
+        assert!(verify_switched_auth(&source, &target).is_ok());
+
+        fs::write(&target, auth("acc-2")).expect("target");
+        let error = verify_switched_auth(&source, &target).expect_err("mismatch");
+        assert_eq!(error.code, AppErrorCode::SwitchMismatch);
+
+        fs::write(&target, "{").expect("target");
+        let error = verify_switched_auth(&source, &target).expect_err("unreadable");
+        assert_eq!(error.code, AppErrorCode::SwitchMismatch);
+    }
+
+    #[test]
+    fn validates_edited_auth_with_precise_errors() {
+        let check = |content: &str| validate_auth_value(&serde_json::from_str(content).expect("json"));
+
+        assert!(check(&auth("acc-1")).is_ok());
+        assert!(check(r#"{"OPENAI_API_KEY":"sk-test"}"#).is_ok());
+        assert_eq!(check("[]").unwrap_err(), "Account JSON must be an object");
+        assert_eq!(
+            check(r#"{"tokens":{"access_token":"a","account_id":"x","id_token":"i","refresh_token":1}}"#).unwrap_err(),
+            "tokens.refresh_token must be a string"
+        );
+        assert_eq!(
+            check(r#"{"tokens":{"access_token":"a","account_id":"x","id_token":"i"}}"#).unwrap_err(),
+            "tokens.refresh_token is missing"
+        );
+        assert_eq!(check(r#"{"OPENAI_API_KEY":5}"#).unwrap_err(), "OPENAI_API_KEY must be a string or null");
+        assert!(check(r#"{"OPENAI_API_KEY":" "}"#).is_err());
+    }
+
+    #[test]
+    fn keeps_unknown_fields_and_order_through_an_edit() {
+        let edited = r#"{
+  "tokens": {
+    "refresh_token": "r",
+    "id_token": "i",
+    "account_id": "acc-1",
+    "access_token": "a",
+    "scope": "openid"
+  },
+  "zz_custom": {"nested": [1, 2]},
+  "OPENAI_API_KEY": null,
+  "auth_mode": "chatgpt"
+}"#;
+        let value: serde_json::Value = serde_json::from_str(edited).expect("json");
+        validate_auth_value(&value).expect("valid");
+        let written: serde_json::Value =
+            serde_json::from_str(&serde_json::to_string_pretty(&value).expect("pretty")).expect("reparse");
+
+        assert_eq!(written, value);
+        let keys: Vec<&str> = written.as_object().expect("object").keys().map(String::as_str).collect();
+        assert_eq!(keys, vec!["tokens", "zz_custom", "OPENAI_API_KEY", "auth_mode"]);
+        assert_eq!(written["tokens"]["scope"], "openid");
+    }
+
+    #[test]
+    fn redacts_secrets_at_any_depth() {
+        let mut value: serde_json::Value = serde_json::json!({
+            "OPENAI_API_KEY": "sk-proj-0123456789abcdefWXYZ",
+            "tokens": {
+                "access_token": "eyJhbGciOiJSUzI1NiJ9.payload.signature",
+                "account_id": "acc-1",
+                "id_token": "short",
+                "refresh_token": null
+            },
+            "extra": [{ "nested": { "refresh_token": "rt_abcdefghijklmnop_1234" } }]
+        });
+        redact_secrets(&mut value);
+
+        assert_eq!(value["OPENAI_API_KEY"], "sk-proj-…WXYZ");
+        assert_eq!(value["tokens"]["access_token"], "eyJhbGci…ture");
+        assert_eq!(value["tokens"]["account_id"], "acc-1");
+        assert_eq!(value["tokens"]["id_token"], "…");
+        assert!(value["tokens"]["refresh_token"].is_null());
+        assert_eq!(value["extra"][0]["nested"]["refresh_token"], "rt_abcde…1234");
+    }
 }
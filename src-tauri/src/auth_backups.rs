@@ -0,0 +1,229 @@
+use crate::account_files::{restrict_permissions, write_private_file_atomic};
+use crate::config::{get_config_file, load_config};
+use crate::{extract_info_from_auth, get_codex_auth_file, CodexAuthFile};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const AUTH_BACKUPS_DIR: &str = "backups";
+const AUTH_BACKUP_PREFIX: &str = "auth-";
+const CONFIG_BACKUP_PREFIX: &str = "config-";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AuthBackup {
+    /// File name inside the backups dir; pass back to `restore_auth_backup`.
+    pub file: String,
+    /// Unix ms.
+    #[serde(rename = "createdAt")]
+    pub created_at: i64,
+    #[serde(rename = "accountId")]
+    pub account_id: Option<String>,
+    pub email: Option<String>,
+}
+
+pub fn get_auth_backups_dir() -> PathBuf {
+    get_config_file().with_file_name(AUTH_BACKUPS_DIR)
+}
+
+fn is_backup_name(name: &str, prefix: &str, extension: &str) -> bool {
+    name.starts_with(prefix) && name.ends_with(&format!(".{}", extension)) && !name.contains(['/', '\\'])
+}
+
+fn is_auth_backup_name(name: &str) -> bool {
+    is_backup_name(name, AUTH_BACKUP_PREFIX, "json")
+}
+
+/// Backup file paths with `prefix`, oldest first; the timestamped names sort chronologically.
+fn backup_paths(dir: &Path, prefix: &str, extension: &str) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| is_backup_name(name, prefix, extension))
+        })
+        .collect();
+    paths.sort();
+    paths
+}
+
+fn describe_backup(path: &Path) -> AuthBackup {
+    let auth = fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<CodexAuthFile>(&content).ok());
+    let email = auth
+        .as_ref()
+        .map(|auth| extract_info_from_auth(auth, 0).email)
+        .filter(|email| email != "Unknown");
+    let account_id = auth
+        .as_ref()
+        .map(|auth| auth.account_id().trim().to_string())
+        .filter(|account_id| !account_id.is_empty());
+    let created_at = fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or(0);
+
+    AuthBackup {
+        file: path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        created_at,
+        account_id,
+        email,
+    }
+}
+
+/// Copies `source` into `dir` as `<prefix><timestamp>.<extension>` and drops the oldest backups
+/// beyond `limit`. Nothing is written when `source` is missing or identical to the newest backup.
+fn backup_file(
+    source: &Path,
+    dir: &Path,
+    prefix: &str,
+    extension: &str,
+    limit: usize,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<Option<PathBuf>, String> {
+    let Ok(content) = fs::read(source) else {
+        return Ok(None);
+    };
+    if backup_paths(dir, prefix, extension)
+        .last()
+        .is_some_and(|latest| fs::read(latest).is_ok_and(|latest| latest == content))
+    {
+        return Ok(None);
+    }
+
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create backups directory: {}", e))?;
+    let stamp = now.format("%Y%m%dT%H%M%S%3fZ");
+    let mut target = dir.join(format!("{}{}.{}", prefix, stamp, extension));
+    let mut counter = 1;
+    while target.exists() {
+        target = dir.join(format!("{}{}_{}.{}", prefix, stamp, counter, extension));
+        counter += 1;
+    }
+    let file_name = source.file_name().unwrap_or_default().to_string_lossy();
+    fs::write(&target, &content).map_err(|e| format!("Failed to back up {}: {}", file_name, e))?;
+    let _ = restrict_permissions(&target);
+
+    let paths = backup_paths(dir, prefix, extension);
+    let excess = paths.len().saturating_sub(limit.max(1));
+    for path in paths.into_iter().take(excess) {
+        let _ = fs::remove_file(path);
+    }
+    Ok(Some(target))
+}
+
+/// Saves the current `~/.codex/auth.json` before it is overwritten.
+pub fn backup_active_auth() -> Result<(), String> {
+    backup_file(
+        &get_codex_auth_file(),
+        &get_auth_backups_dir(),
+        AUTH_BACKUP_PREFIX,
+        "json",
+        load_config().auth_backup_limit,
+        chrono::Utc::now(),
+    )
+    .map(|_| ())
+}
+
+/// Saves `~/.codex/config.toml` before per-account overrides rewrite it.
+pub fn backup_codex_config(config_path: &Path) -> Result<(), String> {
+    backup_file(
+        config_path,
+        &get_auth_backups_dir(),
+        CONFIG_BACKUP_PREFIX,
+        "toml",
+        load_config().auth_backup_limit,
+        chrono::Utc::now(),
+    )
+    .map(|_| ())
+}
+
+/// Newest first.
+#[tauri::command]
+pub fn list_auth_backups() -> Vec<AuthBackup> {
+    let mut backups: Vec<AuthBackup> = backup_paths(&get_auth_backups_dir(), AUTH_BACKUP_PREFIX, "json")
+        .iter()
+        .map(|path| describe_backup(path))
+        .collect();
+    backups.reverse();
+    backups
+}
+
+/// Restores a backup over `~/.codex/auth.json`, backing up the current file first, and reports
+/// which account the restored file belongs to.
+#[tauri::command]
+pub fn restore_auth_backup(file: String) -> Result<AuthBackup, String> {
+    if !is_auth_backup_name(&file) {
+        return Err("Invalid backup file name".to_string());
+    }
+    let path = get_auth_backups_dir().join(&file);
+    let content = fs::read(&path).map_err(|e| format!("Failed to read backup: {}", e))?;
+    serde_json::from_slice::<CodexAuthFile>(&content).map_err(|e| format!("Backup is not a valid auth file: {}", e))?;
+    let backup = describe_backup(&path);
+
+    backup_active_auth()?;
+    let target = get_codex_auth_file();
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    write_private_file_atomic(&target, &content).map_err(|e| format!("Failed to restore auth.json: {}", e))?;
+    Ok(backup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{backup_file, backup_paths, describe_backup, is_auth_backup_name, AUTH_BACKUP_PREFIX};
+    use chrono::TimeZone;
+    use std::fs;
+
+    #[test]
+    fn rotates_and_skips_unchanged_backups() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let auth = temp.path().join("auth.json");
+        let dir = temp.path().join("backups");
+        let at = |second| chrono::Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, second).unwrap();
+
+        let backup = |second| backup_file(&auth, &dir, AUTH_BACKUP_PREFIX, "json", 2, at(second));
+
+        assert_eq!(backup(0).expect("missing"), None);
+        for (second, content) in [(1, "one"), (2, "two"), (3, "two"), (4, "three")] {
+            fs::write(&auth, content).expect("auth");
+            backup(second).expect("backup");
+        }
+
+        let kept: Vec<String> = backup_paths(&dir, AUTH_BACKUP_PREFIX, "json")
+            .iter()
+            .map(|path| fs::read_to_string(path).expect("read"))
+            .collect();
+        assert_eq!(kept, vec!["two".to_string(), "three".to_string()]);
+    }
+
+    #[test]
+    fn describes_backup_accounts() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let path = temp.path().join("auth-20250101T000000000Z.json");
+        fs::write(
+            &path,
+            r#"{"OPENAI_API_KEY":null,"tokens":{"access_token":"a","account_id":"acc-1","id_token":"i","refresh_token":"r"}}"#,
+        )
+        .expect("backup");
+
+        let backup = describe_backup(&path);
+        assert_eq!(backup.file, "auth-20250101T000000000Z.json");
+        assert_eq!(backup.account_id.as_deref(), Some("acc-1"));
+        assert_eq!(backup.email, None);
+        assert!(is_auth_backup_name(&backup.file));
+        assert!(!is_auth_backup_name("../auth-x.json"));
+        assert!(!is_auth_backup_name("config.json"));
+    }
+}
@@ -0,0 +1,194 @@
+use crate::account_cache::AccountScanCache;
+use crate::account_usage::{fetch_usage_from_auth_path, UsageInfo};
+use crate::accounts::{scan_accounts_with_cache, switch_to_path};
+use crate::command_guard::is_read_only;
+use crate::config::{load_config, AutoSwitchConfig};
+use crate::switch_history::SwitchSource;
+use crate::usage_alerts::check_usage_thresholds;
+use crate::{get_codex_auth_file, trace, AccountInfo};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tauri::{Emitter, Manager};
+
+pub const AUTO_SWITCHED_EVENT: &str = "auto-switched";
+
+/// How often the loop re-reads the config while auto-switch is disabled or the app is read-only.
+const DISABLED_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AutoSwitchEvent {
+    pub reason: String,
+    /// `usedPercent` of the previous account's primary window that triggered the switch.
+    #[serde(rename = "usedPercent")]
+    pub used_percent: f64,
+    pub from: Option<String>,
+    pub to: AccountInfo,
+}
+
+/// The higher of both windows, so a candidate with a spent weekly limit is not picked either.
+fn peak_used_percent(usage: &UsageInfo) -> Option<f64> {
+    [&usage.primary_window, &usage.secondary_window]
+        .into_iter()
+        .flatten()
+        .map(|window| window.used_percent)
+        .reduce(f64::max)
+}
+
+fn should_fail_over(
+    used_percent: f64,
+    settings: &AutoSwitchConfig,
+    last_switch: Option<Instant>,
+    now: Instant,
+) -> bool {
+    let cooled_down = last_switch.is_none_or(|at| {
+        now.duration_since(at) >= Duration::from_secs(settings.cooldown_minutes.saturating_mul(60))
+    });
+    used_percent >= settings.threshold_percent && cooled_down
+}
+
+/// Whether a polling round runs; read-only mode must never switch accounts.
+fn should_poll(settings: &AutoSwitchConfig, read_only: bool) -> bool {
+    settings.enabled && !read_only
+}
+
+/// Index of the least-used candidate, ignoring any at or above `threshold_percent`.
+fn pick_least_used(candidates: &[Option<f64>], threshold_percent: f64) -> Option<usize> {
+    candidates
+        .iter()
+        .enumerate()
+        .filter_map(|(index, used)| used.map(|used| (index, used)))
+        .filter(|(_, used)| *used < threshold_percent)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(index, _)| index)
+}
+
+/// One polling round; returns whether a switch happened.
+async fn check_active_usage(
+    app: &tauri::AppHandle,
+    settings: &AutoSwitchConfig,
+    last_switch: Option<Instant>,
+) -> Result<bool, String> {
+    let active_auth_path = get_codex_auth_file();
+    let usage = fetch_usage_from_auth_path(app, &active_auth_path)
+        .await
+        .map_err(|error| error.message)?;
+    check_usage_thresholds(app, &active_auth_path, &usage);
+    let Some(used_percent) = usage.primary_window.as_ref().map(|window| window.used_percent) else {
+        return Ok(false);
+    };
+    if !should_fail_over(used_percent, settings, last_switch, Instant::now()) {
+        return Ok(false);
+    }
+
+    let scan = scan_accounts_with_cache(&app.state::<AccountScanCache>(), None, None, None)?;
+    let from = scan
+        .accounts
+        .iter()
+        .find(|account| account.is_active)
+        .map(|account| account.name.clone());
+    let candidates: Vec<&AccountInfo> = scan
+        .accounts
+        .iter()
+        .filter(|account| {
+            !account.is_active
+                && !account.is_archived
+                && !account.refresh_dead
+                && !account.is_cooling_down
+                && account.duplicate_of.is_none()
+        })
+        .collect();
+    let mut usages = Vec::with_capacity(candidates.len());
+    for candidate in &candidates {
+        let usage = fetch_usage_from_auth_path(app, &PathBuf::from(&candidate.file_path)).await;
+        usages.push(usage.ok().as_ref().and_then(peak_used_percent));
+    }
+    let Some(index) = pick_least_used(&usages, settings.threshold_percent) else {
+        trace::emit(
+            "auto_switch",
+            "no_candidate",
+            serde_json::json!({ "usedPercent": used_percent }),
+        );
+        return Ok(false);
+    };
+
+    let target = PathBuf::from(&candidates[index].file_path);
+    let result = switch_to_path(app, target, Some(SwitchSource::Auto), false, false)
+        .await
+        .map_err(|error| error.message)?;
+    let _ = app.emit(
+        AUTO_SWITCHED_EVENT,
+        AutoSwitchEvent {
+            reason: format!(
+                "Primary rate limit window at {:.0}% (threshold {:.0}%)",
+                used_percent, settings.threshold_percent
+            ),
+            used_percent,
+            from,
+            to: result.account,
+        },
+    );
+    Ok(true)
+}
+
+/// Background loop behind `autoSwitch`: polls the active account's usage and fails over to the
+/// least-used other account once the primary window crosses the threshold.
+pub async fn run_auto_switch(app: tauri::AppHandle) {
+    let mut last_switch: Option<Instant> = None;
+    loop {
+        let settings = load_config().auto_switch;
+        if !should_poll(&settings, is_read_only()) {
+            tokio::time::sleep(DISABLED_POLL_INTERVAL).await;
+            continue;
+        }
+
+        match check_active_usage(&app, &settings, last_switch).await {
+            Ok(true) => last_switch = Some(Instant::now()),
+            Ok(false) => {}
+            Err(error) => trace::emit("auto_switch", "check_failed", serde_json::json!({ "error": error })),
+        }
+        tokio::time::sleep(Duration::from_secs(settings.poll_interval_minutes.max(1).saturating_mul(60))).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pick_least_used, should_fail_over, should_poll};
+    use crate::config::AutoSwitchConfig;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn fails_over_past_threshold_outside_cooldown() {
+        let settings = AutoSwitchConfig {
+            enabled: true,
+            threshold_percent: 95.0,
+            poll_interval_minutes: 5,
+            cooldown_minutes: 30,
+        };
+        let now = Instant::now() + Duration::from_secs(3600);
+
+        assert!(!should_fail_over(94.9, &settings, None, now));
+        assert!(should_fail_over(95.0, &settings, None, now));
+        assert!(!should_fail_over(100.0, &settings, Some(now - Duration::from_secs(29 * 60)), now));
+        assert!(should_fail_over(100.0, &settings, Some(now - Duration::from_secs(30 * 60)), now));
+    }
+
+    #[test]
+    fn picks_least_used_candidate_below_threshold() {
+        assert_eq!(pick_least_used(&[Some(40.0), None, Some(12.5), Some(80.0)], 95.0), Some(2));
+        assert_eq!(pick_least_used(&[Some(96.0), None], 95.0), None);
+        assert_eq!(pick_least_used(&[], 95.0), None);
+    }
+
+    #[test]
+    fn read_only_mode_skips_polling() {
+        let mut settings = AutoSwitchConfig {
+            enabled: true,
+            ..AutoSwitchConfig::default()
+        };
+        assert!(should_poll(&settings, false));
+        assert!(!should_poll(&settings, true));
+        settings.enabled = false;
+        assert!(!should_poll(&settings, false));
+    }
+}
@@ -1,3 +1,5 @@
+use crate::profile_name::sanitize_profile_name;
+use crate::trash_bin::remove_path;
 use crate::{get_codex_dir, get_prompts_dir, get_skills_dir, PromptInfo, SkillInfo};
 use std::fs;
 use std::path::PathBuf;
@@ -48,14 +50,7 @@ fn resolve_path_within(base_dir: &PathBuf, path: &str) -> Result<PathBuf, String
 }
 
 fn sanitize_leaf_name(name: &str) -> Result<String, String> {
-    let trimmed = name.trim();
-    if trimmed.is_empty() {
-        return Err("Name cannot be empty".to_string());
-    }
-    if trimmed.contains('/') || trimmed.contains('\\') || trimmed.contains("..") {
-        return Err("Name contains unsupported path characters".to_string());
-    }
-    Ok(trimmed.to_string())
+    sanitize_profile_name(name).map_err(|error| error.to_string())
 }
 
 fn scan_prompts_recursive(dir: &PathBuf, prompts: &mut Vec<PromptInfo>) {
@@ -208,9 +203,9 @@ pub fn create_prompt(name: String, description: String, content: String) -> Resu
 }
 
 #[tauri::command]
-pub fn delete_prompt(file_path: String) -> Result<(), String> {
+pub fn delete_prompt(file_path: String, permanently: Option<bool>) -> Result<(), String> {
     let path = resolve_path_within(&get_prompts_dir(), &file_path)?;
-    fs::remove_file(&path).map_err(|e| format!("Failed to delete file: {}", e))
+    remove_path(&path, permanently.unwrap_or(false))
 }
 
 #[tauri::command]
@@ -249,9 +244,9 @@ pub fn create_skill(name: String, description: String) -> Result<String, String>
 }
 
 #[tauri::command]
-pub fn delete_skill(dir_path: String) -> Result<(), String> {
+pub fn delete_skill(dir_path: String, permanently: Option<bool>) -> Result<(), String> {
     let skill_dir = resolve_path_within(&get_skills_dir(), &dir_path)?;
-    fs::remove_dir_all(&skill_dir).map_err(|e| format!("Failed to delete directory: {}", e))
+    remove_path(&skill_dir, permanently.unwrap_or(false))
 }
 
 #[tauri::command]
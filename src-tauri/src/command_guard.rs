@@ -0,0 +1,191 @@
+use crate::desktop_shell;
+use crate::error::{AppError, AppResult};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+pub const READ_ONLY_FLAG: &str = "--read-only";
+
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandAccess {
+    Read,
+    Write,
+}
+
+/// Every registered command must be listed here; unknown commands are treated as writes.
+pub fn command_access(command: &str) -> Option<CommandAccess> {
+    match command {
+        "scan_accounts"
+        | "search_accounts"
+        | "get_accounts_dir_path"
+        | "open_accounts_dir"
+        | "fetch_usage"
+        | "fetch_active_usage"
+        | "fetch_all_usage"
+        | "fetch_credits"
+        | "recommend_account"
+        | "check_account_health"
+        | "get_app_config"
+        | "get_webdav_password"
+        | "get_webdav_config"
+        | "has_sync_passphrase"
+        | "get_sync_status"
+        | "get_device_name"
+        | "get_gateway_platform_key"
+        | "read_account_content"
+        | "webdav_sync_preview"
+        | "webdav_list_snapshots"
+        | "scan_prompts"
+        | "scan_skills"
+        | "read_prompt_content"
+        | "read_skill_content"
+        | "read_agents_md"
+        | "read_config_toml"
+        | "open_codex_dir"
+        | "get_switch_history"
+        | "get_usage_history"
+        | "list_auth_backups"
+        | "audit_permissions"
+        | "get_account_stats"
+        | "get_read_only"
+        | "set_read_only" => Some(CommandAccess::Read),
+        "switch_account"
+        | "switch_account_by_name"
+        | "switch_next_account"
+        | "import_account_file"
+        | "import_accounts_from_dir"
+        | "import_accounts_archive"
+        | "export_account"
+        | "export_accounts_archive"
+        | "rename_account"
+        | "set_debug_logging"
+        | "set_token_refresh_threshold"
+        | "set_account_order"
+        | "set_switch_history_limit"
+        | "set_usage_history_retention"
+        | "set_stale_after_days"
+        | "set_auth_backup_limit"
+        | "set_switch_refresh_window"
+        | "set_preserve_api_key_on_switch"
+        | "set_switch_mode"
+        | "set_post_switch_command"
+        | "set_auto_switch"
+        | "set_background_refresh"
+        | "set_usage_alerts"
+        | "set_tray_usage"
+        | "set_proxy"
+        | "set_device_name"
+        | "set_usage_endpoints"
+        | "set_oauth_endpoint"
+        | "set_webdav_password"
+        | "set_webdav_config"
+        | "set_sync_passphrase"
+        | "set_gateway_platform_key"
+        | "set_accounts_dir"
+        | "add_account"
+        | "start_login"
+        | "cancel_login"
+        | "cancel_sync"
+        | "capture_current_account"
+        | "restore_auth_backup"
+        | "delete_account"
+        | "duplicate_account"
+        | "set_account_meta"
+        | "set_account_config_override"
+        | "reset_account_stats"
+        | "archive_account"
+        | "unarchive_account"
+        | "move_account"
+        | "create_account_group"
+        | "delete_account_group"
+        | "update_account_content"
+        | "webdav_sync_upload"
+        | "webdav_sync_download"
+        | "webdav_sync_bidirectional"
+        | "webdav_test_connection"
+        | "save_prompt_content"
+        | "create_prompt"
+        | "delete_prompt"
+        | "save_skill_content"
+        | "create_skill"
+        | "delete_skill"
+        | "save_agents_md"
+        | "save_config_toml"
+        | "webdav_sync_codex_upload"
+        | "webdav_sync_codex_download"
+        | "webdav_create_snapshot"
+        | "webdav_restore_snapshot"
+        | "refresh_account_token"
+        | "refresh_all_tokens"
+        | "restore_account_backup"
+        | "revoke_account"
+        | "queue_refresh"
+        | "sync_active_to_profile"
+        | "import_default_account" => Some(CommandAccess::Write),
+        _ => None,
+    }
+}
+
+pub fn is_read_only() -> bool {
+    READ_ONLY.load(Ordering::SeqCst)
+}
+
+pub fn set_read_only_mode(enabled: bool) {
+    READ_ONLY.store(enabled, Ordering::SeqCst);
+}
+
+fn guard_command(read_only: bool, command: &str) -> AppResult<()> {
+    if !read_only || command_access(command) == Some(CommandAccess::Read) {
+        return Ok(());
+    }
+
+    Err(AppError::read_only_mode(format!(
+        "'{}' is unavailable while read-only mode is active",
+        command
+    )))
+}
+
+pub fn check_command(command: &str) -> AppResult<()> {
+    guard_command(is_read_only(), command)
+}
+
+#[tauri::command]
+pub fn get_read_only() -> bool {
+    is_read_only()
+}
+
+#[tauri::command]
+pub fn set_read_only(app: tauri::AppHandle, enabled: bool) -> bool {
+    set_read_only_mode(enabled);
+    desktop_shell::apply_read_only_indicator(&app, enabled);
+    enabled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{command_access, guard_command, CommandAccess};
+    use crate::REGISTERED_COMMANDS;
+
+    #[test]
+    fn every_registered_command_has_explicit_access() {
+        for command in REGISTERED_COMMANDS {
+            assert!(
+                command_access(command).is_some(),
+                "command '{}' has no read-only classification",
+                command
+            );
+        }
+    }
+
+    #[test]
+    fn read_only_mode_blocks_writes_but_allows_reads() {
+        assert!(guard_command(true, "scan_accounts").is_ok());
+        assert!(guard_command(true, "set_read_only").is_ok());
+        assert!(guard_command(false, "switch_account").is_ok());
+        assert_eq!(command_access("switch_account"), Some(CommandAccess::Write));
+
+        let error = guard_command(true, "switch_account").expect_err("blocked");
+        assert_eq!(error.code, crate::error::AppErrorCode::ReadOnlyMode);
+        assert!(guard_command(true, "unregistered_command").is_err());
+    }
+}
@@ -0,0 +1,237 @@
+use crate::account_files::{resolve_managed_account_path, write_file_atomic};
+use crate::account_meta::{load_account_meta, profile_id_for_file, update_account_meta};
+use crate::auth_backups::backup_codex_config;
+use crate::config::get_config_file;
+use crate::{get_accounts_dir, get_codex_dir};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, Item, Table, TableLike, Value};
+
+/// Remembers which `config.toml` keys the last switch overrode and what they held before.
+const APPLIED_OVERRIDES_FILE: &str = "config-overrides.json";
+
+type KeyPath = Vec<String>;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AppliedOverride {
+    pub key: KeyPath,
+    /// The value as TOML before the override; `None` when the key did not exist.
+    pub original: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct AppliedOverrides {
+    #[serde(default)]
+    entries: Vec<AppliedOverride>,
+}
+
+fn applied_overrides_path() -> PathBuf {
+    get_config_file().with_file_name(APPLIED_OVERRIDES_FILE)
+}
+
+fn collect_entries(table: &Table, prefix: &[String], entries: &mut Vec<(KeyPath, Value)>) -> Result<(), String> {
+    for (key, item) in table.iter() {
+        let mut path = prefix.to_vec();
+        path.push(key.to_string());
+        match item {
+            Item::Table(table) => collect_entries(table, &path, entries)?,
+            Item::Value(value) => entries.push((path, value.clone())),
+            Item::ArrayOfTables(_) => {
+                return Err(format!("Arrays of tables are not supported in overrides ('{}')", path.join(".")));
+            }
+            Item::None => {}
+        }
+    }
+    Ok(())
+}
+
+/// Leaf keys of an override snippet; nested tables become dotted key paths.
+fn override_entries(snippet: &str) -> Result<Vec<(KeyPath, Value)>, String> {
+    let document = snippet
+        .parse::<DocumentMut>()
+        .map_err(|e| format!("Invalid config override TOML: {}", e))?;
+    let mut entries = Vec::new();
+    collect_entries(document.as_table(), &[], &mut entries)?;
+    Ok(entries)
+}
+
+fn undecorated(value: &Value) -> Value {
+    let mut value = value.clone();
+    value.decor_mut().clear();
+    value
+}
+
+fn get_value(document: &DocumentMut, key: &[String]) -> Option<Value> {
+    let mut item = document.as_item();
+    for segment in key {
+        item = item.get(segment.as_str())?;
+    }
+    item.as_value().map(undecorated)
+}
+
+fn set_value(document: &mut DocumentMut, key: &[String], value: Value) {
+    let Some((last, parents)) = key.split_last() else {
+        return;
+    };
+    let mut table: &mut dyn TableLike = document.as_table_mut();
+    for segment in parents {
+        let item = table.entry(segment).or_insert(toml_edit::table());
+        if !item.is_table_like() {
+            *item = toml_edit::table();
+        }
+        table = item.as_table_like_mut().expect("table-like item");
+    }
+    // Replacing in place keeps the key's comments and the value's spacing.
+    if let Some(existing) = table.get_mut(last).and_then(|item| item.as_value_mut()) {
+        let decor = existing.decor().clone();
+        *existing = value;
+        *existing.decor_mut() = decor;
+        return;
+    }
+    table.insert(last, Item::Value(undecorated(&value)));
+}
+
+/// Removes `key`, then any parent tables the removal left empty.
+fn remove_value(document: &mut DocumentMut, key: &[String]) {
+    fn remove_in(table: &mut dyn TableLike, key: &[String]) {
+        match key {
+            [] => {}
+            [last] => {
+                table.remove(last);
+            }
+            [first, rest @ ..] => {
+                let Some(child) = table.get_mut(first).and_then(|item| item.as_table_like_mut()) else {
+                    return;
+                };
+                remove_in(child, rest);
+                if child.is_empty() {
+                    table.remove(first);
+                }
+            }
+        }
+    }
+    remove_in(document.as_table_mut(), key);
+}
+
+/// Undoes `previous` and then applies `overrides` to `config`, returning the new text and the
+/// record needed to undo it on the next switch. Keys outside both sets are left untouched.
+fn apply_overrides(
+    config: &str,
+    previous: &[AppliedOverride],
+    overrides: &[(KeyPath, Value)],
+) -> Result<(String, Vec<AppliedOverride>), String> {
+    let mut document = config
+        .parse::<DocumentMut>()
+        .map_err(|e| format!("Failed to parse config.toml: {}", e))?;
+
+    for applied in previous.iter().rev() {
+        match applied.original.as_deref().map(str::parse::<Value>) {
+            Some(Ok(value)) => set_value(&mut document, &applied.key, value),
+            Some(Err(_)) | None => remove_value(&mut document, &applied.key),
+        }
+    }
+
+    let mut applied = Vec::new();
+    for (key, value) in overrides {
+        applied.push(AppliedOverride {
+            key: key.clone(),
+            original: get_value(&document, key).map(|value| value.to_string()),
+        });
+        set_value(&mut document, key, value.clone());
+    }
+    Ok((document.to_string(), applied))
+}
+
+/// Rewrites `~/.codex/config.toml` with the overrides stored for `profile_id`, restoring keys a
+/// previous account overrode. Does nothing when neither side has overrides.
+pub fn apply_account_config_overrides(accounts_dir: &Path, profile_id: &str) -> Result<(), String> {
+    let state_path = applied_overrides_path();
+    let previous: AppliedOverrides = fs::read_to_string(&state_path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    let overrides = match load_account_meta(accounts_dir).get(profile_id).config_override {
+        Some(snippet) => override_entries(&snippet)?,
+        None => Vec::new(),
+    };
+    if previous.entries.is_empty() && overrides.is_empty() {
+        return Ok(());
+    }
+
+    let config_path = get_codex_dir().join("config.toml");
+    let current = fs::read_to_string(&config_path).unwrap_or_default();
+    let (updated, entries) = apply_overrides(&current, &previous.entries, &overrides)?;
+    if updated != current {
+        backup_codex_config(&config_path)?;
+        write_file_atomic(&config_path, updated.as_bytes())
+            .map_err(|e| format!("Failed to write config.toml: {}", e))?;
+    }
+
+    if let Some(parent) = state_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+    let state = serde_json::to_string_pretty(&AppliedOverrides { entries })
+        .map_err(|e| format!("Failed to serialize applied overrides: {}", e))?;
+    fs::write(&state_path, state).map_err(|e| format!("Failed to record applied overrides: {}", e))
+}
+
+/// Stores a TOML snippet merged into `config.toml` whenever this account is switched to.
+/// An empty or missing snippet removes the override.
+#[tauri::command]
+pub fn set_account_config_override(file_path: String, config_override: Option<String>) -> Result<(), String> {
+    let accounts_dir = get_accounts_dir();
+    let path = resolve_managed_account_path(&file_path, &accounts_dir)?;
+    let profile_id = profile_id_for_file(&path)?;
+    let snippet = config_override
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    if let Some(snippet) = snippet.as_deref() {
+        override_entries(snippet)?;
+    }
+
+    update_account_meta(&accounts_dir, |store| {
+        let mut meta = store.get(&profile_id);
+        meta.config_override = snippet;
+        store.set(&profile_id, meta);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_overrides, override_entries};
+
+    const CONFIG: &str = "# user settings\nmodel = \"gpt-5\"\napproval_policy = \"on-request\"\n\n[model_providers.team]\nname = \"Team\"\n";
+
+    #[test]
+    fn applies_overrides_without_touching_other_keys() {
+        let overrides = override_entries("model = \"o3\"\nmodel_provider = \"team\"\n").expect("overrides");
+        let (updated, applied) = apply_overrides(CONFIG, &[], &overrides).expect("apply");
+
+        assert!(updated.starts_with("# user settings\nmodel = \"o3\"\napproval_policy = \"on-request\"\n"));
+        assert!(updated.contains("model_provider = \"team\""));
+        assert!(updated.contains("[model_providers.team]\nname = \"Team\"\n"));
+        assert_eq!(applied[0].original.as_deref(), Some("\"gpt-5\""));
+        assert_eq!(applied[1].original, None);
+    }
+
+    #[test]
+    fn restores_previous_values_when_next_account_has_no_override() {
+        let overrides = override_entries("model = \"o3\"\n[model_providers.team]\nbase_url = \"https://x\"\n")
+            .expect("overrides");
+        let (overridden, applied) = apply_overrides(CONFIG, &[], &overrides).expect("apply");
+        assert!(overridden.contains("base_url = \"https://x\""));
+
+        let (restored, applied) = apply_overrides(&overridden, &applied, &[]).expect("restore");
+        assert!(applied.is_empty());
+        assert!(restored.contains("model = \"gpt-5\""));
+        assert!(!restored.contains("base_url"));
+        assert!(restored.contains("name = \"Team\""));
+    }
+
+    #[test]
+    fn rejects_invalid_snippets() {
+        assert!(override_entries("model = ").is_err());
+        assert!(override_entries("[[profiles]]\nname = \"a\"\n").is_err());
+    }
+}
@@ -1,7 +1,132 @@
-use crate::{extract_info_from_auth, get_codex_auth_file, CodexAuthFile};
+use crate::account_usage::{fetch_usage_from_auth_path, UsageInfo};
+use crate::command_guard::is_read_only;
+use crate::config::{load_config, DEFAULT_TOKEN_REFRESH_THRESHOLD_MINUTES};
+use crate::{extract_info_from_auth, get_codex_auth_file, trace, CodexAuthFile};
 use std::fs;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
 use tauri::{Emitter, Manager};
 
+const TRAY_ID: &str = "main";
+const WINDOW_TITLE: &str = "Code Revolver - Codex Account Cylinder";
+const TRAY_TOOLTIP: &str = "Code Revolver";
+/// How often the tray poll re-reads the config while it is disabled or no account is active.
+const TRAY_IDLE_INTERVAL: Duration = Duration::from_secs(60);
+const TRAY_USAGE_TIMEOUT: Duration = Duration::from_secs(60);
+/// Wall-clock time running ahead of monotonic time by more than this means the machine slept.
+const SLEEP_DETECTION_SLACK: Duration = Duration::from_secs(30);
+
+fn with_read_only_suffix(base: &str, read_only: bool) -> String {
+    if read_only {
+        format!("{} (Read-only)", base)
+    } else {
+        base.to_string()
+    }
+}
+
+/// Usage summary shown as the tray tooltip instead of the app name while the tray poll is on.
+#[derive(Default)]
+struct TrayUsageLabel(Mutex<Option<String>>);
+
+fn tray_tooltip<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> String {
+    app.try_state::<TrayUsageLabel>()
+        .and_then(|label| label.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone())
+        .unwrap_or_else(|| TRAY_TOOLTIP.to_string())
+}
+
+pub fn apply_read_only_indicator<R: tauri::Runtime>(app: &tauri::AppHandle<R>, read_only: bool) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.set_title(&with_read_only_suffix(WINDOW_TITLE, read_only));
+    }
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_tooltip(Some(with_read_only_suffix(&tray_tooltip(app), read_only)));
+    }
+}
+
+/// Handle to the tray's "Current: …" item so it can be relabelled after a switch.
+struct TrayAccountItem<R: tauri::Runtime>(tauri::menu::MenuItem<R>);
+
+fn current_account_label(email: &str) -> String {
+    format!("Current: {}", email)
+}
+
+/// Relabels the tray's current-account item; a no-op before the tray is built.
+pub fn show_current_account<R: tauri::Runtime>(app: &tauri::AppHandle<R>, email: &str) {
+    if let Some(item) = app.try_state::<TrayAccountItem<R>>() {
+        let _ = item.0.set_text(current_account_label(email));
+    }
+}
+
+/// "email · 62% (resets 16:40)", or just the email without a primary window.
+fn usage_label(email: &str, usage: Option<&UsageInfo>) -> String {
+    let Some(window) = usage.and_then(|usage| usage.primary_window.as_ref()) else {
+        return email.to_string();
+    };
+    let resets = window
+        .resets_at
+        .and_then(|resets_at| chrono::DateTime::from_timestamp(resets_at, 0))
+        .map(|resets_at| format!(" (resets {})", resets_at.with_timezone(&chrono::Local).format("%H:%M")))
+        .unwrap_or_default();
+    format!("{} · {:.0}%{}", email, window.used_percent, resets)
+}
+
+/// Sets the tooltip and the "Current: …" item; `None` restores the plain tooltip.
+fn show_tray_usage<R: tauri::Runtime>(app: &tauri::AppHandle<R>, label: Option<String>) {
+    if let Some(label) = &label {
+        show_current_account(app, label);
+    }
+    if let Some(state) = app.try_state::<TrayUsageLabel>() {
+        *state.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = label;
+    }
+    apply_read_only_indicator(app, is_read_only());
+}
+
+/// Background loop behind `trayUsage`: polls the active account's usage and shows it in the tray.
+/// Failures fall back to the email; a result that straddles a sleep is dropped as stale.
+pub async fn run_tray_usage(app: tauri::AppHandle) {
+    let mut showing = false;
+    loop {
+        let settings = load_config().tray_usage;
+        let auth = fs::read_to_string(get_codex_auth_file())
+            .ok()
+            .and_then(|content| serde_json::from_str::<CodexAuthFile>(&content).ok())
+            .filter(|auth| auth.tokens.is_some());
+        let Some(auth) = auth.filter(|_| settings.enabled) else {
+            if showing {
+                show_tray_usage(&app, None);
+                showing = false;
+            }
+            tokio::time::sleep(TRAY_IDLE_INTERVAL).await;
+            continue;
+        };
+
+        let email = extract_info_from_auth(&auth, DEFAULT_TOKEN_REFRESH_THRESHOLD_MINUTES).email;
+        let started_at = Instant::now();
+        let started_wall = SystemTime::now();
+        let result = tokio::time::timeout(
+            TRAY_USAGE_TIMEOUT,
+            fetch_usage_from_auth_path(&app, &get_codex_auth_file()),
+        )
+        .await;
+        let wall_elapsed = started_wall.elapsed().unwrap_or_default();
+        if wall_elapsed > started_at.elapsed() + SLEEP_DETECTION_SLACK {
+            trace::emit("tray_usage", "slept_mid_request", serde_json::json!({}));
+        } else {
+            let usage = match result {
+                Ok(Ok(usage)) => Some(usage),
+                Ok(Err(error)) => {
+                    trace::emit("tray_usage", "fetch_failed", serde_json::json!({ "error": error.message }));
+                    None
+                }
+                Err(_) => None,
+            };
+            show_tray_usage(&app, Some(usage_label(&email, usage.as_ref())));
+            showing = true;
+        }
+        tokio::time::sleep(Duration::from_secs(settings.interval_minutes.max(1).saturating_mul(60))).await;
+    }
+}
+
 pub fn setup_tray<R: tauri::Runtime>(app: &tauri::App<R>) -> tauri::Result<()> {
     use tauri::menu::{Menu, MenuItem, PredefinedMenuItem};
     use tauri::tray::TrayIconBuilder;
@@ -10,8 +135,8 @@ pub fn setup_tray<R: tauri::Runtime>(app: &tauri::App<R>) -> tauri::Result<()> {
     let account_info = if codex_auth.exists() {
         if let Ok(content) = fs::read_to_string(&codex_auth) {
             if let Ok(auth) = serde_json::from_str::<CodexAuthFile>(&content) {
-                let (email, _, _, _) = extract_info_from_auth(&auth);
-                format!("Current: {}", email)
+                let info = extract_info_from_auth(&auth, DEFAULT_TOKEN_REFRESH_THRESHOLD_MINUTES);
+                current_account_label(&info.email)
             } else {
                 "Current: Unknown".to_string()
             }
@@ -23,6 +148,8 @@ pub fn setup_tray<R: tauri::Runtime>(app: &tauri::App<R>) -> tauri::Result<()> {
     };
 
     let account_item = MenuItem::with_id(app, "account", &account_info, false, None::<&str>)?;
+    app.manage(TrayAccountItem(account_item.clone()));
+    app.manage(TrayUsageLabel::default());
     let separator = PredefinedMenuItem::separator(app)?;
     let show = MenuItem::with_id(app, "show", "Show Window", true, None::<&str>)?;
     let refresh = MenuItem::with_id(app, "refresh", "Refresh", true, None::<&str>)?;
@@ -30,8 +157,9 @@ pub fn setup_tray<R: tauri::Runtime>(app: &tauri::App<R>) -> tauri::Result<()> {
     let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
     let menu = Menu::with_items(app, &[&account_item, &separator, &show, &refresh, &separator2, &quit])?;
 
-    TrayIconBuilder::new()
+    TrayIconBuilder::with_id(TRAY_ID)
         .icon(app.default_window_icon().unwrap().clone())
+        .tooltip(with_read_only_suffix(TRAY_TOOLTIP, is_read_only()))
         .menu(&menu)
         .show_menu_on_left_click(false)
         .on_menu_event(|app, event| match event.id.as_ref() {
@@ -63,5 +191,31 @@ pub fn setup_tray<R: tauri::Runtime>(app: &tauri::App<R>) -> tauri::Result<()> {
         })
         .build(app)?;
 
+    apply_read_only_indicator(app.handle(), is_read_only());
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::usage_label;
+    use crate::account_usage::{RateLimitWindow, UsageInfo};
+
+    #[test]
+    fn labels_usage_or_falls_back_to_email() {
+        let usage = UsageInfo {
+            primary_window: Some(RateLimitWindow {
+                used_percent: 61.6,
+                window_minutes: Some(300),
+                resets_at: None,
+            }),
+            secondary_window: None,
+            plan_type: None,
+            fetched_at: None,
+            endpoint_used: None,
+            raw: None,
+        };
+
+        assert_eq!(usage_label("me@example.com", Some(&usage)), "me@example.com · 62%");
+        assert_eq!(usage_label("me@example.com", None), "me@example.com");
+    }
+}
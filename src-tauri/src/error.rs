@@ -11,6 +11,13 @@ pub enum AppErrorCode {
     Parse,
     SecureStorage,
     External,
+    ReadOnlyMode,
+    SwitchMismatch,
+    RefreshDead,
+    ActiveAccount,
+    DuplicateAccount,
+    PathOutsideAccounts,
+    SyncInProgress,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -58,6 +65,34 @@ impl AppError {
     pub fn external(message: impl Into<String>) -> Self {
         Self::new(AppErrorCode::External, message)
     }
+
+    pub fn read_only_mode(message: impl Into<String>) -> Self {
+        Self::new(AppErrorCode::ReadOnlyMode, message)
+    }
+
+    pub fn switch_mismatch(message: impl Into<String>) -> Self {
+        Self::new(AppErrorCode::SwitchMismatch, message)
+    }
+
+    pub fn refresh_dead(message: impl Into<String>) -> Self {
+        Self::new(AppErrorCode::RefreshDead, message)
+    }
+
+    pub fn active_account(message: impl Into<String>) -> Self {
+        Self::new(AppErrorCode::ActiveAccount, message)
+    }
+
+    pub fn duplicate_account(message: impl Into<String>) -> Self {
+        Self::new(AppErrorCode::DuplicateAccount, message)
+    }
+
+    pub fn path_outside_accounts(message: impl Into<String>) -> Self {
+        Self::new(AppErrorCode::PathOutsideAccounts, message)
+    }
+
+    pub fn sync_in_progress(message: impl Into<String>) -> Self {
+        Self::new(AppErrorCode::SyncInProgress, message)
+    }
 }
 
 impl std::fmt::Display for AppError {
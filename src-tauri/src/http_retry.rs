@@ -0,0 +1,80 @@
+use std::time::{Duration, SystemTime};
+
+/// Upper bound for a single backoff step, whatever the server asks for.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Network hiccups, rate limits and server errors may succeed on a later attempt; other 4xx
+/// responses will not.
+pub fn is_retryable_status(status: reqwest::StatusCode) -> bool {
+    status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Parses a `Retry-After` value given either as seconds or as an HTTP date.
+pub fn parse_retry_after(value: &str, now: chrono::DateTime<chrono::Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some((at.with_timezone(&chrono::Utc) - now).to_std().unwrap_or(Duration::ZERO))
+}
+
+pub fn retry_after_header(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    headers
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| parse_retry_after(value, chrono::Utc::now()))
+}
+
+/// Delay before retry number `attempt` (1-based): `Retry-After` when given, otherwise exponential
+/// from `base` plus up to 50% jitter.
+pub fn backoff_delay(attempt: u32, base: Duration, retry_after: Option<Duration>, jitter_seed: u32) -> Duration {
+    if let Some(retry_after) = retry_after {
+        return retry_after.min(MAX_RETRY_DELAY);
+    }
+    let exponential = base.saturating_mul(1 << attempt.saturating_sub(1).min(16));
+    let jitter = exponential.mul_f64(f64::from(jitter_seed % 1000) / 2000.0);
+    (exponential + jitter).min(MAX_RETRY_DELAY)
+}
+
+/// Cheap jitter source; retries only need to avoid lockstep, not unpredictability.
+pub fn jitter_seed() -> u32 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|elapsed| elapsed.subsec_nanos())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{backoff_delay, is_retryable_status, parse_retry_after};
+    use chrono::TimeZone;
+    use std::time::Duration;
+
+    #[test]
+    fn parses_retry_after_seconds_and_dates() {
+        let now = chrono::Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(parse_retry_after(" 7 ", now), Some(Duration::from_secs(7)));
+        assert_eq!(
+            parse_retry_after("Wed, 01 Jan 2025 00:00:30 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(parse_retry_after("Tue, 31 Dec 2024 23:59:00 GMT", now), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn backs_off_exponentially_with_bounded_jitter() {
+        let base = Duration::from_secs(1);
+        assert_eq!(backoff_delay(1, base, None, 0), Duration::from_secs(1));
+        assert_eq!(backoff_delay(2, base, None, 0), Duration::from_secs(2));
+        assert_eq!(backoff_delay(2, base, None, 999), Duration::from_millis(2999));
+        assert_eq!(backoff_delay(1, base, Some(Duration::from_secs(5)), 999), Duration::from_secs(5));
+        assert_eq!(backoff_delay(20, base, None, 0), Duration::from_secs(30));
+
+        assert!(is_retryable_status(reqwest::StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable_status(reqwest::StatusCode::BAD_GATEWAY));
+        assert!(!is_retryable_status(reqwest::StatusCode::BAD_REQUEST));
+        assert!(!is_retryable_status(reqwest::StatusCode::UNAUTHORIZED));
+    }
+}
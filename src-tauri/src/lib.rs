@@ -1,43 +1,241 @@
+mod account_activity;
+mod account_cache;
+mod account_credits;
+mod account_export;
 mod account_files;
+mod account_groups;
+mod account_import;
+mod account_plan;
+mod account_recommend;
+mod account_search;
+mod account_meta;
+mod account_order;
+mod account_status;
 mod account_tokens;
 mod account_usage;
+mod account_watcher;
 mod accounts;
+mod auth_backups;
+mod auto_switch;
 mod codex_content;
+mod command_guard;
 mod config;
+mod config_overrides;
 mod desktop_shell;
 mod error;
+mod http_client;
+mod http_retry;
+mod oauth_login;
+mod profile_name;
+mod refresh_scheduler;
+mod switch_history;
+mod switch_hook;
+mod sync_ignore;
 mod trace;
+mod trash_bin;
+mod usage_alerts;
+mod usage_history;
+mod webdav_auth;
+mod webdav_crypto;
 mod webdav_plan;
+mod webdav_progress;
 mod webdav_propfind;
+mod webdav_settings;
+mod webdav_snapshots;
 mod webdav_sync;
+mod webdav_tombstones;
 
+use account_cache::AccountScanCache;
+use account_credits::fetch_credits;
+use account_export::{export_account, export_accounts_archive};
+use account_groups::{create_account_group, delete_account_group, GroupInfo};
+use account_import::{import_account_file, import_accounts_archive, import_accounts_from_dir};
+use account_meta::{get_account_stats, reset_account_stats, set_account_meta};
+use account_plan::{plan_info, PlanInfo};
+use account_recommend::recommend_account;
+use account_search::search_accounts;
+use account_status::{evaluate_token_status, parse_subscription_end, TokenStatus};
 use account_tokens::*;
 use account_usage::*;
+use account_watcher::{watch_accounts_dir, AccountsWatcher};
 use accounts::*;
+use auth_backups::{list_auth_backups, restore_auth_backup};
+use oauth_login::{cancel_login, start_login, LoginSessions};
+use refresh_scheduler::queue_refresh;
+use command_guard::*;
 use config::*;
+use config_overrides::set_account_config_override;
 use codex_content::*;
 use error::{AppError, AppResult};
 use serde::{Deserialize, Serialize};
+use tauri::Manager;
 use std::path::PathBuf;
+use switch_history::get_switch_history;
+use usage_alerts::UsageAlerts;
+use usage_history::get_usage_history;
+use webdav_progress::{cancel_sync, get_sync_status, SyncSessions};
+use webdav_settings::{get_webdav_config, set_webdav_config};
 use webdav_sync::*;
 
+/// Key order of the JSON object a value was read from, so rewriting it keeps the user's layout
+/// instead of the struct's field order.
+#[derive(Debug, Clone, Default)]
+pub struct KeyOrder(Vec<String>);
+
+impl KeyOrder {
+    fn of(value: &serde_json::Value) -> Self {
+        Self(value.as_object().map(|map| map.keys().cloned().collect()).unwrap_or_default())
+    }
+
+    /// `value` with its keys in the recorded order; keys that were not there come last.
+    fn apply(&self, value: serde_json::Value) -> serde_json::Value {
+        let serde_json::Value::Object(map) = value else {
+            return value;
+        };
+        let mut ordered = serde_json::Map::new();
+        for key in &self.0 {
+            if let Some(field) = map.get(key) {
+                ordered.insert(key.clone(), field.clone());
+            }
+        }
+        for (key, field) in map {
+            if !ordered.contains_key(&key) {
+                ordered.insert(key, field);
+            }
+        }
+        serde_json::Value::Object(ordered)
+    }
+}
+
+/// Implements `Serialize`/`Deserialize` for a `#[serde(remote = "Self")]` struct with a `key_order`
+/// field, going through `serde_json::Value` to record and restore the original key order.
+macro_rules! impl_ordered_serde {
+    ($type:ident) => {
+        impl Serialize for $type {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let value = $type::serialize(self, serde_json::value::Serializer).map_err(serde::ser::Error::custom)?;
+                self.key_order.apply(value).serialize(serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $type {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = serde_json::Value::deserialize(deserializer)?;
+                let key_order = KeyOrder::of(&value);
+                let mut parsed = $type::deserialize(value).map_err(serde::de::Error::custom)?;
+                parsed.key_order = key_order;
+                Ok(parsed)
+            }
+        }
+    };
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(remote = "Self")]
 pub struct CodexTokens {
     pub access_token: String,
     pub account_id: String,
     pub id_token: String,
     pub refresh_token: String,
+    /// Fields written by the Codex CLI that this app does not model; kept so rewrites don't drop them.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+    #[serde(skip)]
+    pub key_order: KeyOrder,
 }
 
+impl_ordered_serde!(CodexTokens);
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(remote = "Self")]
 pub struct CodexAuthFile {
     #[serde(rename = "OPENAI_API_KEY")]
     pub openai_api_key: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_null_default")]
     pub last_refresh: String,
-    pub tokens: CodexTokens,
+    /// Missing for profiles that only carry `OPENAI_API_KEY`.
+    #[serde(default)]
+    pub tokens: Option<CodexTokens>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+    #[serde(skip)]
+    pub key_order: KeyOrder,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl_ordered_serde!(CodexAuthFile);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum AccountKind {
+    #[default]
+    #[serde(rename = "chatgpt")]
+    ChatGpt,
+    #[serde(rename = "apiKey")]
+    ApiKey,
+}
+
+fn deserialize_null_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+impl CodexAuthFile {
+    pub fn kind(&self) -> AccountKind {
+        if self.tokens.is_some() {
+            AccountKind::ChatGpt
+        } else {
+            AccountKind::ApiKey
+        }
+    }
+
+    pub(crate) fn api_key(&self) -> Option<&str> {
+        self.openai_api_key
+            .as_deref()
+            .map(str::trim)
+            .filter(|key| !key.is_empty())
+    }
+
+    pub fn has_credentials(&self) -> bool {
+        self.tokens.is_some() || self.api_key().is_some()
+    }
+
+    /// ChatGPT tokens, or an error naming `action` for API-key-only profiles.
+    pub fn require_tokens(&self, action: &str) -> Result<&CodexTokens, String> {
+        self.tokens
+            .as_ref()
+            .ok_or_else(|| format!("{} is not supported for API-key profiles", action))
+    }
+
+    pub fn account_id(&self) -> &str {
+        self.tokens.as_ref().map(|tokens| tokens.account_id.as_str()).unwrap_or("")
+    }
+
+    /// Last four characters of the API key, for display without revealing the key.
+    pub fn api_key_hint(&self) -> Option<String> {
+        let key = self.api_key()?;
+        let start = key.char_indices().rev().nth(3).map(|(index, _)| index).unwrap_or(0);
+        Some(format!("…{}", &key[start..]))
+    }
+
+    /// Takes `live`'s API key when this profile has none; a key on the profile always wins.
+    /// Returns whether the key was carried over.
+    pub fn inherit_api_key(&mut self, live: &CodexAuthFile) -> bool {
+        if self.api_key().is_some() {
+            return false;
+        }
+        match live.api_key() {
+            Some(key) => {
+                self.openai_api_key = Some(key.to_string());
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct AccountInfo {
     pub id: String,
     #[serde(rename = "upstreamAccountId")]
@@ -46,8 +244,16 @@ pub struct AccountInfo {
     pub email: String,
     #[serde(rename = "planType")]
     pub plan_type: String,
+    pub plan: PlanInfo,
     #[serde(rename = "subscriptionEnd")]
     pub subscription_end: Option<String>,
+    /// Unix seconds; `None` when the claim is missing or unparseable.
+    #[serde(rename = "subscriptionEndsAt")]
+    pub subscription_ends_at: Option<i64>,
+    #[serde(rename = "subscriptionDaysLeft")]
+    pub subscription_days_left: Option<i64>,
+    #[serde(rename = "subscriptionExpired")]
+    pub subscription_expired: bool,
     #[serde(rename = "isActive")]
     pub is_active: bool,
     #[serde(rename = "filePath")]
@@ -58,6 +264,59 @@ pub struct AccountInfo {
     pub expires_at: Option<i64>,
     #[serde(rename = "lastRefresh")]
     pub last_refresh: String,
+    /// `last_refresh` in Unix ms, `None` when missing or malformed.
+    #[serde(rename = "lastRefreshAt")]
+    pub last_refresh_at: Option<i64>,
+    #[serde(rename = "isStale")]
+    pub is_stale: bool,
+    #[serde(rename = "tokenStatus")]
+    pub token_status: TokenStatus,
+    #[serde(rename = "needsRefresh")]
+    pub needs_refresh: bool,
+    #[serde(rename = "duplicateOf")]
+    pub duplicate_of: Option<String>,
+    pub notes: Option<String>,
+    pub tags: Vec<String>,
+    #[serde(rename = "configOverride")]
+    pub config_override: Option<String>,
+    /// The refresh token was rejected upstream; switching requires `force`.
+    #[serde(rename = "refreshDead")]
+    pub refresh_dead: bool,
+    #[serde(rename = "isArchived")]
+    pub is_archived: bool,
+    #[serde(rename = "sortIndex")]
+    pub sort_index: usize,
+    pub kind: AccountKind,
+    #[serde(rename = "apiKeyHint")]
+    pub api_key_hint: Option<String>,
+    #[serde(rename = "lastUsedAt")]
+    pub last_used_at: Option<i64>,
+    #[serde(rename = "switchCount")]
+    pub switch_count: u64,
+    /// Folder below the accounts dir, `/`-separated; `None` for top-level profiles.
+    pub group: Option<String>,
+    #[serde(rename = "fileSize")]
+    pub file_size: u64,
+    /// SHA-256 hex of the profile file as parsed; `None` when it is unknown.
+    #[serde(rename = "contentHash")]
+    pub content_hash: Option<String>,
+    /// Token refresh activity seen by this process; `None` until a refresh is queued or run.
+    #[serde(rename = "refreshState")]
+    pub refresh_state: Option<RefreshState>,
+    #[serde(rename = "lastRefreshError")]
+    pub last_refresh_error: Option<String>,
+    /// Unix seconds when the exhausted rate limit resets, while that is still in the future.
+    #[serde(rename = "coolingDownUntil")]
+    pub cooling_down_until: Option<i64>,
+    #[serde(rename = "isCoolingDown")]
+    pub is_cooling_down: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanError {
+    #[serde(rename = "filePath")]
+    pub file_path: String,
+    pub reason: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -65,6 +324,9 @@ pub struct ScanResult {
     pub accounts: Vec<AccountInfo>,
     #[serde(rename = "accountsDir")]
     pub accounts_dir: String,
+    pub errors: Vec<ScanError>,
+    /// Group folders under the accounts dir, including empty ones; `accounts` stays flat.
+    pub groups: Vec<GroupInfo>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -125,6 +387,7 @@ pub(crate) fn get_skills_dir() -> PathBuf {
 
 const WEBDAV_SECRET_SERVICE: &str = "code-revolver";
 const WEBDAV_SECRET_ACCOUNT: &str = "webdav";
+const SYNC_PASSPHRASE_SECRET_ACCOUNT: &str = "webdav-sync-passphrase";
 const GATEWAY_SECRET_SERVICE: &str = "code-revolver";
 const GATEWAY_SECRET_ACCOUNT: &str = "gateway-platform-key";
 
@@ -133,12 +396,17 @@ pub(crate) fn webdav_password_entry() -> AppResult<keyring::Entry> {
         .map_err(|e| AppError::secure_storage(format!("Failed to initialize secure password storage: {}", e)))
 }
 
+pub(crate) fn sync_passphrase_entry() -> AppResult<keyring::Entry> {
+    keyring::Entry::new(WEBDAV_SECRET_SERVICE, SYNC_PASSPHRASE_SECRET_ACCOUNT)
+        .map_err(|e| AppError::secure_storage(format!("Failed to initialize secure passphrase storage: {}", e)))
+}
+
 pub(crate) fn gateway_platform_key_entry() -> AppResult<keyring::Entry> {
     keyring::Entry::new(GATEWAY_SECRET_SERVICE, GATEWAY_SECRET_ACCOUNT)
         .map_err(|e| AppError::secure_storage(format!("Failed to initialize secure gateway key storage: {}", e)))
 }
 
-fn decode_jwt_payload(token: &str) -> Option<serde_json::Value> {
+pub(crate) fn decode_jwt_payload(token: &str) -> Option<serde_json::Value> {
     let parts: Vec<&str> = token.split('.').collect();
     if parts.len() != 3 {
         return None;
@@ -164,35 +432,108 @@ fn decode_jwt_payload(token: &str) -> Option<serde_json::Value> {
     serde_json::from_slice(&decoded).ok()
 }
 
-pub(crate) fn extract_info_from_auth(auth: &CodexAuthFile) -> (String, String, Option<String>, Option<i64>) {
-    if let Some(payload) = decode_jwt_payload(&auth.tokens.id_token) {
-        let email = payload
-            .get("email")
-            .and_then(|v| v.as_str())
-            .unwrap_or("Unknown")
-            .to_string();
-        let expires_at = payload.get("exp").and_then(|v| v.as_i64());
-        let auth_data = payload.get("https://api.openai.com/auth");
-
-        let plan_type = auth_data
-            .and_then(|a| a.get("chatgpt_plan_type"))
-            .and_then(|v| v.as_str())
-            .unwrap_or("unknown")
-            .to_string();
-
-        let subscription_end = auth_data
-            .and_then(|a| a.get("chatgpt_subscription_active_until"))
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string());
+#[derive(Debug, Clone)]
+pub(crate) struct AuthInfo {
+    pub email: String,
+    pub plan_type: String,
+    pub plan: PlanInfo,
+    pub subscription_end: Option<String>,
+    /// `subscription_end` parsed to Unix seconds.
+    pub subscription_ends_at: Option<i64>,
+    pub expires_at: Option<i64>,
+    pub token_status: TokenStatus,
+}
+
+impl AuthInfo {
+    pub fn needs_refresh(&self) -> bool {
+        self.token_status.needs_refresh()
+    }
+}
+
+#[derive(Debug, Default)]
+struct TokenClaims {
+    email: Option<String>,
+    plan_type: Option<String>,
+    subscription_end: Option<String>,
+    expires_at: Option<i64>,
+}
 
-        return (email, plan_type, subscription_end, expires_at);
+impl TokenClaims {
+    fn or(self, fallback: TokenClaims) -> TokenClaims {
+        TokenClaims {
+            email: self.email.or(fallback.email),
+            plan_type: self.plan_type.or(fallback.plan_type),
+            subscription_end: self.subscription_end.or(fallback.subscription_end),
+            expires_at: self.expires_at.or(fallback.expires_at),
+        }
     }
+}
+
+fn decode_token_claims(token: &str) -> Option<TokenClaims> {
+    let payload = decode_jwt_payload(token)?;
+    let auth_data = payload.get("https://api.openai.com/auth");
+    // Access tokens carry the email under the profile claim rather than at the top level.
+    let profile_data = payload.get("https://api.openai.com/profile");
+    let string_claim = |value: Option<&serde_json::Value>| value.and_then(|v| v.as_str()).map(|s| s.to_string());
 
-    ("Unknown".to_string(), "unknown".to_string(), None, None)
+    Some(TokenClaims {
+        email: string_claim(payload.get("email").or_else(|| profile_data.and_then(|p| p.get("email")))),
+        plan_type: string_claim(auth_data.and_then(|a| a.get("chatgpt_plan_type"))),
+        // Older tokens carried the subscription end as a bare epoch number.
+        subscription_end: auth_data
+            .and_then(|a| a.get("chatgpt_subscription_active_until"))
+            .and_then(|v| v.as_str().map(|s| s.to_string()).or_else(|| v.as_number().map(|n| n.to_string()))),
+        expires_at: payload.get("exp").and_then(|v| v.as_i64()),
+    })
+}
+
+/// Reads claims from the id_token, falling back to the access_token when the id_token is opaque or has no email.
+pub(crate) fn extract_info_from_auth(auth: &CodexAuthFile, refresh_threshold_minutes: i64) -> AuthInfo {
+    let now = chrono::Utc::now().timestamp();
+
+    let id_claims = auth
+        .tokens
+        .as_ref()
+        .and_then(|tokens| decode_token_claims(&tokens.id_token));
+    let access_claims = if id_claims.as_ref().is_none_or(|claims| claims.email.is_none()) {
+        auth.tokens
+            .as_ref()
+            .and_then(|tokens| decode_token_claims(&tokens.access_token))
+    } else {
+        None
+    };
+    let claims = match (id_claims, access_claims) {
+        (Some(id_claims), Some(access_claims)) => id_claims.or(access_claims),
+        (Some(claims), None) | (None, Some(claims)) => claims,
+        (None, None) => TokenClaims::default(),
+    };
+
+    let plan_type = claims.plan_type.unwrap_or_else(|| "unknown".to_string());
+    AuthInfo {
+        email: claims.email.unwrap_or_else(|| "Unknown".to_string()),
+        plan: plan_info(&plan_type),
+        plan_type,
+        subscription_ends_at: claims.subscription_end.as_deref().and_then(parse_subscription_end),
+        subscription_end: claims.subscription_end,
+        expires_at: claims.expires_at,
+        token_status: evaluate_token_status(claims.expires_at, now, refresh_threshold_minutes),
+    }
 }
 
 pub(crate) fn extract_profile_id_from_auth(auth: &CodexAuthFile, fallback_path: Option<&PathBuf>) -> String {
-    if let Some(payload) = decode_jwt_payload(&auth.tokens.id_token) {
+    if auth.tokens.is_none() {
+        if let Some(key) = auth.api_key() {
+            use sha2::{Digest, Sha256};
+            let digest = format!("{:x}", Sha256::digest(key.as_bytes()));
+            return format!("api-key:{}", &digest[..16]);
+        }
+    }
+
+    if let Some(payload) = auth
+        .tokens
+        .as_ref()
+        .and_then(|tokens| decode_jwt_payload(&tokens.id_token))
+    {
         if let Some(subject) = payload.get("sub").and_then(|value| value.as_str()) {
             let normalized = subject.trim();
             if !normalized.is_empty() {
@@ -212,67 +553,338 @@ pub(crate) fn extract_profile_id_from_auth(auth: &CodexAuthFile, fallback_path:
         return format!("path:{}", path.to_string_lossy().to_ascii_lowercase());
     }
 
-    auth.tokens.account_id.clone()
+    auth.account_id().to_string()
+}
+
+macro_rules! app_commands {
+    ($($command:ident),* $(,)?) => {
+        #[cfg(test)]
+        pub(crate) const REGISTERED_COMMANDS: &[&str] = &[$(stringify!($command)),*];
+
+        fn app_invoke_handler() -> impl Fn(tauri::ipc::Invoke) -> bool + Send + Sync + 'static {
+            tauri::generate_handler![$($command),*]
+        }
+    };
 }
 
+app_commands![
+    scan_accounts,
+    search_accounts,
+    switch_account,
+    switch_account_by_name,
+    switch_next_account,
+    audit_permissions,
+    import_account_file,
+    import_accounts_from_dir,
+    import_accounts_archive,
+    export_account,
+    export_accounts_archive,
+    get_accounts_dir_path,
+    open_accounts_dir,
+    fetch_usage,
+    fetch_active_usage,
+    fetch_all_usage,
+    fetch_credits,
+    recommend_account,
+    check_account_health,
+    rename_account,
+    get_app_config,
+    set_debug_logging,
+    set_token_refresh_threshold,
+    set_account_order,
+    set_switch_history_limit,
+    set_usage_history_retention,
+    set_stale_after_days,
+    set_auth_backup_limit,
+    set_switch_refresh_window,
+    set_preserve_api_key_on_switch,
+    set_switch_mode,
+    set_post_switch_command,
+    set_auto_switch,
+    set_background_refresh,
+    set_usage_alerts,
+    set_tray_usage,
+    set_proxy,
+    get_device_name,
+    set_device_name,
+    set_usage_endpoints,
+    set_oauth_endpoint,
+    get_switch_history,
+    get_usage_history,
+    get_webdav_password,
+    set_webdav_password,
+    get_webdav_config,
+    set_webdav_config,
+    has_sync_passphrase,
+    set_sync_passphrase,
+    get_gateway_platform_key,
+    set_gateway_platform_key,
+    set_accounts_dir,
+    add_account,
+    start_login,
+    cancel_login,
+    cancel_sync,
+    webdav_create_snapshot,
+    webdav_list_snapshots,
+    webdav_restore_snapshot,
+    get_sync_status,
+    capture_current_account,
+    list_auth_backups,
+    restore_auth_backup,
+    delete_account,
+    duplicate_account,
+    move_account,
+    set_account_meta,
+    set_account_config_override,
+    get_account_stats,
+    reset_account_stats,
+    archive_account,
+    unarchive_account,
+    create_account_group,
+    delete_account_group,
+    read_account_content,
+    update_account_content,
+    webdav_sync_upload,
+    webdav_sync_download,
+    webdav_sync_bidirectional,
+    webdav_sync_preview,
+    webdav_test_connection,
+    scan_prompts,
+    scan_skills,
+    read_prompt_content,
+    save_prompt_content,
+    create_prompt,
+    delete_prompt,
+    read_skill_content,
+    save_skill_content,
+    create_skill,
+    delete_skill,
+    read_agents_md,
+    save_agents_md,
+    read_config_toml,
+    save_config_toml,
+    open_codex_dir,
+    webdav_sync_codex_upload,
+    webdav_sync_codex_download,
+    refresh_account_token,
+    refresh_all_tokens,
+    restore_account_backup,
+    revoke_account,
+    queue_refresh,
+    sync_active_to_profile,
+    import_default_account,
+    get_read_only,
+    set_read_only,
+];
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    if std::env::args().any(|arg| arg == READ_ONLY_FLAG) {
+        set_read_only_mode(true);
+    }
+
+    let handler = app_invoke_handler();
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
+        .manage(AccountScanCache::default())
+        .manage(RefreshLocks::default())
+        .manage(LoginSessions::default())
+        .manage(SyncSessions::default())
+        .manage(UsageCache::default())
+        .manage(UsageAlerts::default())
+        .manage(AccountsWatcher::default())
         .on_window_event(|window, event| {
-            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
-                api.prevent_close();
-                let _ = window.hide();
+            match event {
+                tauri::WindowEvent::CloseRequested { api, .. } => {
+                    api.prevent_close();
+                    let _ = window.hide();
+                }
+                // Codex may have rotated tokens while the app was in the background.
+                tauri::WindowEvent::Focused(true) if !is_read_only() => {
+                    if let Err(error) = sync_active_tokens_to_profile() {
+                        trace::emit("accounts", "active_token_sync_failed", serde_json::json!({ "error": error }));
+                    }
+                }
+                _ => {}
             }
         })
         .setup(|app| {
             desktop_shell::setup_tray(app)?;
+            if let Err(error) = watch_accounts_dir(app.handle()) {
+                trace::emit("accounts", "watch_failed", serde_json::json!({ "error": error }));
+            }
+            tauri::async_runtime::spawn(auto_switch::run_auto_switch(app.handle().clone()));
+            tauri::async_runtime::spawn(refresh_scheduler::run_background_refresh(app.handle().clone()));
+            tauri::async_runtime::spawn(usage_alerts::run_usage_alerts(app.handle().clone()));
+            tauri::async_runtime::spawn(desktop_shell::run_tray_usage(app.handle().clone()));
+            let (refresh_queue, refresh_receiver) = refresh_scheduler::RefreshQueue::new();
+            app.manage(refresh_queue);
+            tauri::async_runtime::spawn(refresh_scheduler::run_refresh_queue(app.handle().clone(), refresh_receiver));
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![
-            scan_accounts,
-            switch_account,
-            get_accounts_dir_path,
-            open_accounts_dir,
-            fetch_usage,
-            fetch_active_usage,
-            rename_account,
-            get_app_config,
-            set_debug_logging,
-            get_webdav_password,
-            set_webdav_password,
-            get_gateway_platform_key,
-            set_gateway_platform_key,
-            set_accounts_dir,
-            add_account,
-            delete_account,
-            read_account_content,
-            update_account_content,
-            webdav_sync_upload,
-            webdav_sync_download,
-            webdav_sync_preview,
-            webdav_test_connection,
-            scan_prompts,
-            scan_skills,
-            read_prompt_content,
-            save_prompt_content,
-            create_prompt,
-            delete_prompt,
-            read_skill_content,
-            save_skill_content,
-            create_skill,
-            delete_skill,
-            read_agents_md,
-            save_agents_md,
-            read_config_toml,
-            save_config_toml,
-            open_codex_dir,
-            webdav_sync_codex_upload,
-            webdav_sync_codex_download,
-            refresh_account_token,
-            import_default_account
-        ])
+        .invoke_handler(move |invoke| {
+            if let Err(error) = check_command(invoke.message.command()) {
+                invoke.resolver.reject(error);
+                return true;
+            }
+            handler(invoke)
+        })
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{extract_info_from_auth, extract_profile_id_from_auth, AccountKind, CodexAuthFile, CodexTokens, TokenStatus};
+    use base64::Engine;
+
+    fn jwt(payload: serde_json::Value) -> String {
+        let encode = |value: &[u8]| base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(value);
+        format!("{}.{}.sig", encode(br#"{"alg":"none"}"#), encode(payload.to_string().as_bytes()))
+    }
+
+    fn auth(id_token: String, access_token: String) -> CodexAuthFile {
+        CodexAuthFile {
+            openai_api_key: None,
+            last_refresh: String::new(),
+            tokens: Some(CodexTokens {
+                access_token,
+                account_id: "acc".to_string(),
+                id_token,
+                refresh_token: "refresh".to_string(),
+                extra: Default::default(),
+                key_order: Default::default(),
+            }),
+            extra: Default::default(),
+            key_order: Default::default(),
+        }
+    }
+
+    #[test]
+    fn reads_claims_from_id_token() {
+        let id_token = jwt(serde_json::json!({
+            "email": "id@example.com",
+            "exp": 4_000_000_000_i64,
+            "https://api.openai.com/auth": { "chatgpt_plan_type": "plus" },
+        }));
+        let info = extract_info_from_auth(&auth(id_token, "opaque".to_string()), 30);
+        assert_eq!(info.email, "id@example.com");
+        assert_eq!(info.plan_type, "plus");
+        assert_eq!(info.token_status, TokenStatus::Valid);
+    }
+
+    #[test]
+    fn falls_back_to_access_token_claims_when_id_token_is_opaque() {
+        let access_token = jwt(serde_json::json!({
+            "exp": 4_000_000_000_i64,
+            "https://api.openai.com/profile": { "email": "access@example.com" },
+            "https://api.openai.com/auth": {
+                "chatgpt_plan_type": "team",
+                "chatgpt_subscription_active_until": "2030-01-01T00:00:00Z",
+            },
+        }));
+        let info = extract_info_from_auth(&auth("opaque-id-token".to_string(), access_token), 30);
+        assert_eq!(info.email, "access@example.com");
+        assert_eq!(info.plan_type, "team");
+        assert!(info.plan.is_team_or_enterprise);
+        assert_eq!(info.subscription_end.as_deref(), Some("2030-01-01T00:00:00Z"));
+        assert_eq!(info.subscription_ends_at, Some(1_893_456_000));
+        assert_eq!(info.expires_at, Some(4_000_000_000));
+    }
+
+    #[test]
+    fn reads_epoch_subscription_end_claims() {
+        let id_token = jwt(serde_json::json!({
+            "email": "id@example.com",
+            "https://api.openai.com/auth": { "chatgpt_subscription_active_until": 1_893_456_000_i64 },
+        }));
+        let info = extract_info_from_auth(&auth(id_token, "opaque".to_string()), 30);
+        assert_eq!(info.subscription_end.as_deref(), Some("1893456000"));
+        assert_eq!(info.subscription_ends_at, Some(1_893_456_000));
+    }
+
+    #[test]
+    fn reports_unknown_when_no_token_decodes() {
+        let info = extract_info_from_auth(&auth("opaque".to_string(), "also-opaque".to_string()), 30);
+        assert_eq!(info.email, "Unknown");
+        assert_eq!(info.plan_type, "unknown");
+        assert_eq!(info.expires_at, None);
+        assert_eq!(info.subscription_ends_at, None);
+        assert_eq!(info.token_status, TokenStatus::Unknown);
+    }
+
+    #[test]
+    fn inherits_live_api_key_only_when_profile_has_none() {
+        let with_key = |key: Option<&str>| CodexAuthFile {
+            openai_api_key: key.map(str::to_string),
+            ..auth("id".to_string(), "access".to_string())
+        };
+
+        let mut profile = with_key(None);
+        assert!(profile.inherit_api_key(&with_key(Some("sk-live"))));
+        assert_eq!(profile.openai_api_key.as_deref(), Some("sk-live"));
+
+        let mut profile = with_key(Some("sk-profile"));
+        assert!(!profile.inherit_api_key(&with_key(Some("sk-live"))));
+        assert_eq!(profile.openai_api_key.as_deref(), Some("sk-profile"));
+
+        let mut profile = with_key(Some("sk-profile"));
+        assert!(!profile.inherit_api_key(&with_key(None)));
+        assert_eq!(profile.openai_api_key.as_deref(), Some("sk-profile"));
+
+        let mut profile = with_key(None);
+        assert!(!profile.inherit_api_key(&with_key(None)));
+        assert!(!profile.inherit_api_key(&with_key(Some("  "))));
+        assert_eq!(profile.openai_api_key, None);
+    }
+
+    #[test]
+    fn parses_api_key_only_profiles() {
+        let auth: CodexAuthFile =
+            serde_json::from_str(r#"{"OPENAI_API_KEY":"sk-test-abcd1234","tokens":null,"last_refresh":null}"#)
+                .expect("api key profile");
+        assert_eq!(auth.kind(), AccountKind::ApiKey);
+        assert!(auth.has_credentials());
+        assert_eq!(auth.api_key_hint().as_deref(), Some("…1234"));
+        assert!(auth.require_tokens("Usage fetch").is_err());
+        assert!(extract_profile_id_from_auth(&auth, None).starts_with("api-key:"));
+        assert_eq!(extract_info_from_auth(&auth, 30).token_status, TokenStatus::Unknown);
+
+        let empty: CodexAuthFile = serde_json::from_str("{}").expect("empty profile");
+        assert!(!empty.has_credentials());
+    }
+
+    #[test]
+    fn rewriting_an_auth_file_keeps_unknown_fields() {
+        let original = serde_json::json!({
+            "OPENAI_API_KEY": null,
+            "last_refresh": "2025-01-01T00:00:00Z",
+            "tokens": {
+                "access_token": "a",
+                "account_id": "acc",
+                "id_token": "i",
+                "refresh_token": "r",
+                "workspace_id": "ws-1",
+            },
+            "auth_mode": "chatgpt",
+        });
+
+        let auth: CodexAuthFile = serde_json::from_value(original.clone()).expect("parse");
+        let rewritten = serde_json::to_value(&auth).expect("serialize");
+        assert_eq!(rewritten, original);
+    }
+
+    #[test]
+    fn rewriting_an_auth_file_keeps_the_key_order() {
+        let original = r#"{"auth_mode":"chatgpt","tokens":{"refresh_token":"r","workspace_id":"ws-1","id_token":"i","account_id":"acc","access_token":"a"},"last_refresh":"2025-01-01T00:00:00Z","OPENAI_API_KEY":null}"#;
+
+        let mut auth: CodexAuthFile = serde_json::from_str(original).expect("parse");
+        assert_eq!(serde_json::to_string(&auth).expect("serialize"), original);
+
+        auth.tokens.as_mut().expect("tokens").refresh_token = "rotated".to_string();
+        auth.extra.insert("added".to_string(), serde_json::json!(true));
+        let rewritten = serde_json::to_string(&auth).expect("serialize");
+        assert_eq!(rewritten, original.replace(r#""r""#, r#""rotated""#).replace("null}", "null,\"added\":true}"));
+    }
+}
@@ -0,0 +1,471 @@
+use crate::account_meta::set_refresh_dead;
+use crate::accounts::{store_profile, AddAccountResult};
+use crate::config::{load_config, validate_auth_url};
+use crate::error::{AppError, AppResult};
+use crate::http_client::http_client;
+use crate::{decode_jwt_payload, extract_info_from_auth, extract_profile_id_from_auth, get_accounts_dir, trace, CodexAuthFile};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+
+pub const LOGIN_WAITING_EVENT: &str = "login-waiting";
+pub const LOGIN_COMPLETE_EVENT: &str = "login-complete";
+pub const LOGIN_FAILED_EVENT: &str = "login-failed";
+
+/// Port of the redirect URI registered for the Codex client id.
+const LOGIN_CALLBACK_PORT: u16 = 1455;
+const LOGIN_CALLBACK_PATH: &str = "/auth/callback";
+const LOGIN_SCOPE: &str = "openid profile email offline_access";
+/// How long the browser has to come back before the listener gives up.
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+const CALLBACK_READ_TIMEOUT: Duration = Duration::from_secs(5);
+const TOKEN_EXCHANGE_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_REQUEST_HEAD: usize = 8 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LoginStart {
+    /// Page to open in the browser.
+    #[serde(rename = "authUrl")]
+    pub auth_url: String,
+    #[serde(rename = "redirectUri")]
+    pub redirect_uri: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum LoginFailureKind {
+    Timeout,
+    Cancelled,
+    /// The authorization server redirected back with an error, e.g. the user declined.
+    Denied,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LoginFailedEvent {
+    pub kind: LoginFailureKind,
+    pub message: String,
+}
+
+impl LoginFailedEvent {
+    fn new(kind: LoginFailureKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LoginCompleteEvent {
+    #[serde(flatten)]
+    pub result: AddAccountResult,
+    pub email: String,
+}
+
+struct ActiveLogin {
+    id: u64,
+    cancel: oneshot::Sender<()>,
+    task: tauri::async_runtime::JoinHandle<()>,
+}
+
+/// The login currently waiting for its browser redirect; at most one, since they share a port.
+#[derive(Default)]
+pub struct LoginSessions {
+    current: Mutex<Option<ActiveLogin>>,
+    next_id: AtomicU64,
+}
+
+impl LoginSessions {
+    fn take(&self) -> Option<ActiveLogin> {
+        self.current.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take()
+    }
+
+    fn finish(&self, id: u64) {
+        let mut current = self.current.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if current.as_ref().is_some_and(|login| login.id == id) {
+            *current = None;
+        }
+    }
+}
+
+fn random_token(bytes: usize) -> AppResult<String> {
+    let mut buffer = vec![0u8; bytes];
+    getrandom::fill(&mut buffer)
+        .map_err(|e| AppError::external(format!("Failed to generate login secret: {}", e)))?;
+    Ok(URL_SAFE_NO_PAD.encode(buffer))
+}
+
+/// S256 code challenge for `verifier` (RFC 7636).
+fn pkce_challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+/// The authorize endpoint next to the configured token endpoint, with the PKCE parameters.
+fn authorize_url(token_url: &str, client_id: &str, redirect_uri: &str, challenge: &str, state: &str) -> Result<String, String> {
+    let mut url = reqwest::Url::parse(token_url)
+        .and_then(|url| url.join("authorize"))
+        .map_err(|e| format!("Invalid auth URL: {}", e))?;
+    url.query_pairs_mut()
+        .append_pair("response_type", "code")
+        .append_pair("client_id", client_id)
+        .append_pair("redirect_uri", redirect_uri)
+        .append_pair("scope", LOGIN_SCOPE)
+        .append_pair("code_challenge", challenge)
+        .append_pair("code_challenge_method", "S256")
+        .append_pair("id_token_add_organizations", "true")
+        .append_pair("codex_cli_simplified_flow", "true")
+        .append_pair("state", state);
+    Ok(url.to_string())
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Callback {
+    Code(String),
+    Denied(String),
+    /// Not our redirect (other path, missing or foreign `state`); keep waiting.
+    Unrelated,
+}
+
+/// Interprets the request line of a request to the callback listener.
+fn parse_callback(request_head: &str, expected_state: &str) -> Callback {
+    let Some(target) = request_head
+        .lines()
+        .next()
+        .and_then(|line| line.strip_prefix("GET "))
+        .and_then(|rest| rest.split_whitespace().next())
+    else {
+        return Callback::Unrelated;
+    };
+    let Ok(url) = reqwest::Url::parse(&format!("http://localhost{}", target)) else {
+        return Callback::Unrelated;
+    };
+    if url.path() != LOGIN_CALLBACK_PATH {
+        return Callback::Unrelated;
+    }
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+    };
+    if param("state").as_deref() != Some(expected_state) {
+        return Callback::Unrelated;
+    }
+    if let Some(error) = param("error") {
+        let description = param("error_description").unwrap_or_default();
+        return Callback::Denied(if description.is_empty() {
+            error
+        } else {
+            format!("{}: {}", error, description)
+        });
+    }
+    match param("code").filter(|code| !code.is_empty()) {
+        Some(code) => Callback::Code(code),
+        None => Callback::Unrelated,
+    }
+}
+
+async fn read_request_head(stream: &mut TcpStream) -> String {
+    let mut head = Vec::new();
+    let mut chunk = [0u8; 1024];
+    let read = async {
+        while head.len() < MAX_REQUEST_HEAD && !head.windows(4).any(|window| window == b"\r\n\r\n") {
+            match stream.read(&mut chunk).await {
+                Ok(0) | Err(_) => break,
+                Ok(read) => head.extend_from_slice(&chunk[..read]),
+            }
+        }
+    };
+    let _ = tokio::time::timeout(CALLBACK_READ_TIMEOUT, read).await;
+    String::from_utf8_lossy(&head).into_owned()
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, message: &str) {
+    let body = format!(
+        "<!doctype html><html><head><meta charset=\"utf-8\"><title>Code Revolver</title></head>\
+         <body style=\"font-family:sans-serif;text-align:center;padding-top:4em\"><p>{}</p></body></html>",
+        message
+    );
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+/// Serves the callback listener until the browser brings back a code or an error.
+async fn wait_for_code(listener: TcpListener, state: &str) -> Result<String, LoginFailedEvent> {
+    loop {
+        let (mut stream, _) = listener
+            .accept()
+            .await
+            .map_err(|e| LoginFailedEvent::new(LoginFailureKind::Failed, format!("Login listener failed: {}", e)))?;
+        match parse_callback(&read_request_head(&mut stream).await, state) {
+            Callback::Code(code) => {
+                respond(&mut stream, "200 OK", "Login received. You can close this window and return to Code Revolver.").await;
+                return Ok(code);
+            }
+            Callback::Denied(message) => {
+                respond(&mut stream, "200 OK", "Login was not completed. You can close this window.").await;
+                return Err(LoginFailedEvent::new(LoginFailureKind::Denied, message));
+            }
+            Callback::Unrelated => respond(&mut stream, "404 Not Found", "Not found").await,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CodeExchangeResponse {
+    id_token: String,
+    access_token: String,
+    refresh_token: String,
+}
+
+/// Builds an auth.json in the layout the Codex CLI writes after login.
+fn auth_from_tokens(tokens: CodeExchangeResponse, refreshed_at: String) -> Result<CodexAuthFile, String> {
+    let account_id = decode_jwt_payload(&tokens.id_token)
+        .and_then(|claims| {
+            claims
+                .pointer("/https:~1~1api.openai.com~1auth/chatgpt_account_id")
+                .and_then(|value| value.as_str())
+                .map(str::to_string)
+        })
+        .ok_or_else(|| "Login response did not include a ChatGPT account id".to_string())?;
+    serde_json::from_value(serde_json::json!({
+        "OPENAI_API_KEY": null,
+        "tokens": {
+            "id_token": tokens.id_token,
+            "access_token": tokens.access_token,
+            "refresh_token": tokens.refresh_token,
+            "account_id": account_id,
+        },
+        "last_refresh": refreshed_at,
+    }))
+    .map_err(|e| format!("Failed to build authentication file: {}", e))
+}
+
+async fn exchange_code(
+    token_url: &str,
+    client_id: &str,
+    redirect_uri: &str,
+    code: &str,
+    verifier: &str,
+) -> Result<CodexAuthFile, String> {
+    let response = http_client(TOKEN_EXCHANGE_TIMEOUT)
+        .map_err(|e| e.message)?
+        .post(token_url)
+        .form(&[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", redirect_uri),
+            ("client_id", client_id),
+            ("code_verifier", verifier),
+        ])
+        .send()
+        .await
+        .map_err(|e| format!("Token exchange failed: {}", e))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("Token exchange failed: HTTP {} - {}", status, body));
+    }
+    let tokens: CodeExchangeResponse = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse token response: {}", e))?;
+    auth_from_tokens(tokens, chrono::Utc::now().to_rfc3339())
+}
+
+struct PendingLogin {
+    token_url: String,
+    client_id: String,
+    redirect_uri: String,
+    verifier: String,
+    state: String,
+    overwrite: bool,
+}
+
+async fn complete_login(
+    listener: TcpListener,
+    login: PendingLogin,
+    cancel: oneshot::Receiver<()>,
+) -> Result<LoginCompleteEvent, LoginFailedEvent> {
+    let code = tokio::select! {
+        result = wait_for_code(listener, &login.state) => result?,
+        _ = tokio::time::sleep(LOGIN_TIMEOUT) => {
+            return Err(LoginFailedEvent::new(LoginFailureKind::Timeout, "Login timed out waiting for the browser"));
+        }
+        _ = cancel => return Err(LoginFailedEvent::new(LoginFailureKind::Cancelled, "Login cancelled")),
+    };
+
+    let failed = |message: String| LoginFailedEvent::new(LoginFailureKind::Failed, message);
+    let auth = exchange_code(&login.token_url, &login.client_id, &login.redirect_uri, &code, &login.verifier)
+        .await
+        .map_err(failed)?;
+    let result = store_profile(&auth, "", false, login.overwrite).map_err(|error| failed(error.message))?;
+    let profile_id = extract_profile_id_from_auth(&auth, Some(&PathBuf::from(&result.file_path)));
+    let _ = set_refresh_dead(&get_accounts_dir(), &profile_id, false);
+    Ok(LoginCompleteEvent {
+        result,
+        email: extract_info_from_auth(&auth, 0).email,
+    })
+}
+
+/// Starts the PKCE browser login and returns the URL to open. The result arrives as
+/// `login-complete` or `login-failed`; the new profile is named after the account email and, with
+/// `overwrite`, replaces an existing profile of the same account. A previous pending login is
+/// cancelled first.
+#[tauri::command]
+pub async fn start_login(
+    app: tauri::AppHandle,
+    sessions: tauri::State<'_, LoginSessions>,
+    overwrite: Option<bool>,
+) -> AppResult<LoginStart> {
+    if let Some(previous) = sessions.take() {
+        let _ = previous.cancel.send(());
+        let _ = previous.task.await;
+    }
+
+    let config = load_config();
+    let token_url = validate_auth_url(&config.token_refresh_url, config.allow_insecure_auth_url)?;
+    let listener = TcpListener::bind(("127.0.0.1", LOGIN_CALLBACK_PORT))
+        .await
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::AddrInUse => AppError::external(format!(
+                "Port {} is already in use; close any running `codex login` and try again",
+                LOGIN_CALLBACK_PORT
+            )),
+            _ => AppError::io(format!("Failed to start login listener: {}", e)),
+        })?;
+
+    let redirect_uri = format!("http://localhost:{}{}", LOGIN_CALLBACK_PORT, LOGIN_CALLBACK_PATH);
+    let verifier = random_token(64)?;
+    let state = random_token(32)?;
+    let auth_url = authorize_url(&token_url, &config.oauth_client_id, &redirect_uri, &pkce_challenge(&verifier), &state)
+        .map_err(AppError::parse)?;
+    let login = PendingLogin {
+        token_url,
+        client_id: config.oauth_client_id,
+        redirect_uri: redirect_uri.clone(),
+        verifier,
+        state,
+        overwrite: overwrite.unwrap_or(false),
+    };
+
+    let id = sessions.next_id.fetch_add(1, Ordering::SeqCst);
+    let (cancel, cancelled) = oneshot::channel();
+    let handle = app.clone();
+    let task = tauri::async_runtime::spawn(async move {
+        match complete_login(listener, login, cancelled).await {
+            Ok(event) => {
+                trace::emit("login", "complete", serde_json::json!({ "filePath": event.result.file_path }));
+                let _ = handle.emit(LOGIN_COMPLETE_EVENT, event);
+            }
+            Err(event) => {
+                trace::emit("login", "failed", serde_json::json!({ "kind": event.kind, "error": event.message }));
+                let _ = handle.emit(LOGIN_FAILED_EVENT, event);
+            }
+        }
+        handle.state::<LoginSessions>().finish(id);
+    });
+    *sessions.current.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(ActiveLogin { id, cancel, task });
+
+    let start = LoginStart { auth_url, redirect_uri };
+    let _ = app.emit(LOGIN_WAITING_EVENT, start.clone());
+    Ok(start)
+}
+
+/// Stops a pending login and frees the callback port. Returns whether one was running.
+#[tauri::command]
+pub async fn cancel_login(sessions: tauri::State<'_, LoginSessions>) -> AppResult<bool> {
+    let Some(login) = sessions.take() else {
+        return Ok(false);
+    };
+    let _ = login.cancel.send(());
+    let _ = login.task.await;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{auth_from_tokens, authorize_url, parse_callback, pkce_challenge, Callback, CodeExchangeResponse};
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use base64::Engine;
+
+    #[test]
+    fn builds_pkce_authorize_url() {
+        // RFC 7636 appendix B.
+        assert_eq!(
+            pkce_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+
+        let url = authorize_url(
+            "https://auth.openai.com/oauth/token",
+            "client",
+            "http://localhost:1455/auth/callback",
+            "challenge",
+            "state-1",
+        )
+        .expect("url");
+        let parsed = reqwest::Url::parse(&url).expect("parse");
+        assert_eq!(parsed.path(), "/oauth/authorize");
+        let query: std::collections::HashMap<_, _> = parsed.query_pairs().into_owned().collect();
+        assert_eq!(query["redirect_uri"], "http://localhost:1455/auth/callback");
+        assert_eq!(query["code_challenge_method"], "S256");
+        assert_eq!(query["state"], "state-1");
+    }
+
+    #[test]
+    fn accepts_only_matching_callbacks() {
+        let head = |target: &str| format!("GET {} HTTP/1.1\r\nHost: localhost:1455\r\n\r\n", target);
+        assert_eq!(
+            parse_callback(&head("/auth/callback?code=abc&state=s1"), "s1"),
+            Callback::Code("abc".to_string())
+        );
+        assert_eq!(parse_callback(&head("/auth/callback?code=abc&state=other"), "s1"), Callback::Unrelated);
+        assert_eq!(parse_callback(&head("/favicon.ico"), "s1"), Callback::Unrelated);
+        assert_eq!(parse_callback("", "s1"), Callback::Unrelated);
+        assert_eq!(
+            parse_callback(&head("/auth/callback?error=access_denied&error_description=User%20cancelled&state=s1"), "s1"),
+            Callback::Denied("access_denied: User cancelled".to_string())
+        );
+    }
+
+    #[test]
+    fn builds_auth_file_from_exchanged_tokens() {
+        let claims = serde_json::json!({ "https://api.openai.com/auth": { "chatgpt_account_id": "acc-1" } });
+        let id_token = format!("h.{}.s", URL_SAFE_NO_PAD.encode(claims.to_string()));
+        let auth = auth_from_tokens(
+            CodeExchangeResponse {
+                id_token,
+                access_token: "access".to_string(),
+                refresh_token: "refresh".to_string(),
+            },
+            "2025-01-01T00:00:00Z".to_string(),
+        )
+        .expect("auth");
+        assert_eq!(auth.account_id(), "acc-1");
+        assert_eq!(auth.last_refresh, "2025-01-01T00:00:00Z");
+        assert!(auth.openai_api_key.is_none());
+
+        let opaque = CodeExchangeResponse {
+            id_token: "opaque".to_string(),
+            access_token: "access".to_string(),
+            refresh_token: "refresh".to_string(),
+        };
+        assert!(auth_from_tokens(opaque, String::new()).is_err());
+    }
+}
@@ -0,0 +1,121 @@
+use serde::Serialize;
+
+/// Longest name accepted, in characters, leaving room for the extension and collision suffixes.
+pub const MAX_PROFILE_NAME_LENGTH: usize = 100;
+
+/// Characters Windows rejects in file names; they are dropped rather than reported.
+const WINDOWS_INVALID_CHARS: [char; 7] = ['<', '>', ':', '"', '|', '?', '*'];
+
+const WINDOWS_RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9", "LPT1",
+    "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum InvalidName {
+    Empty,
+    PathSeparator { character: char },
+    ParentReference,
+    Reserved { name: String },
+    TooLong { max: usize },
+}
+
+impl std::fmt::Display for InvalidName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InvalidName::Empty => write!(f, "Name cannot be empty"),
+            InvalidName::PathSeparator { character } => write!(f, "Name cannot contain '{}'", character),
+            InvalidName::ParentReference => write!(f, "Name cannot be '.' or '..'"),
+            InvalidName::Reserved { name } => write!(f, "'{}' is a reserved name on Windows", name),
+            InvalidName::TooLong { max } => write!(f, "Name cannot be longer than {} characters", max),
+        }
+    }
+}
+
+impl std::error::Error for InvalidName {}
+
+/// A name usable as a single file or folder name on every platform. Path separators and `.`/`..`
+/// are rejected; characters Windows forbids, control characters and trailing dots or spaces are
+/// removed.
+pub fn sanitize_profile_name(name: &str) -> Result<String, InvalidName> {
+    let trimmed = name.trim();
+    if let Some(character) = trimmed.chars().find(|c| matches!(c, '/' | '\\')) {
+        return Err(InvalidName::PathSeparator { character });
+    }
+    if trimmed == "." || trimmed == ".." {
+        return Err(InvalidName::ParentReference);
+    }
+
+    let cleaned: String = trimmed
+        .chars()
+        .filter(|c| !c.is_control() && !WINDOWS_INVALID_CHARS.contains(c))
+        .collect();
+    let cleaned = cleaned.trim().trim_end_matches(['.', ' ']).to_string();
+    if cleaned.is_empty() {
+        return Err(InvalidName::Empty);
+    }
+    if cleaned.chars().count() > MAX_PROFILE_NAME_LENGTH {
+        return Err(InvalidName::TooLong {
+            max: MAX_PROFILE_NAME_LENGTH,
+        });
+    }
+    let stem = cleaned.split('.').next().unwrap_or_default();
+    if WINDOWS_RESERVED_NAMES.iter().any(|reserved| reserved.eq_ignore_ascii_case(stem)) {
+        return Err(InvalidName::Reserved { name: cleaned });
+    }
+    Ok(cleaned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sanitize_profile_name, InvalidName, MAX_PROFILE_NAME_LENGTH};
+
+    #[test]
+    fn rejects_paths_and_parent_references() {
+        assert_eq!(
+            sanitize_profile_name("work/pro"),
+            Err(InvalidName::PathSeparator { character: '/' })
+        );
+        assert_eq!(
+            sanitize_profile_name("..\\escape"),
+            Err(InvalidName::PathSeparator { character: '\\' })
+        );
+        assert_eq!(sanitize_profile_name(".."), Err(InvalidName::ParentReference));
+        assert_eq!(sanitize_profile_name(" . "), Err(InvalidName::ParentReference));
+        assert_eq!(sanitize_profile_name("   "), Err(InvalidName::Empty));
+        assert_eq!(sanitize_profile_name("?*..."), Err(InvalidName::Empty));
+    }
+
+    #[test]
+    fn strips_windows_invalid_characters_and_trailing_dots() {
+        assert_eq!(sanitize_profile_name("a<b>c:d\"e|f?g*h").unwrap(), "abcdefgh");
+        assert_eq!(sanitize_profile_name("work. . ").unwrap(), "work");
+        assert_eq!(sanitize_profile_name("tab\there").unwrap(), "tabhere");
+        assert_eq!(sanitize_profile_name("user@example.com").unwrap(), "user@example.com");
+        assert_eq!(sanitize_profile_name("a..b").unwrap(), "a..b");
+    }
+
+    #[test]
+    fn rejects_reserved_and_overlong_names() {
+        assert!(matches!(sanitize_profile_name("con"), Err(InvalidName::Reserved { .. })));
+        assert!(matches!(sanitize_profile_name("LPT1.backup"), Err(InvalidName::Reserved { .. })));
+        assert_eq!(sanitize_profile_name("console").unwrap(), "console");
+
+        let long = "ä".repeat(MAX_PROFILE_NAME_LENGTH + 1);
+        assert_eq!(
+            sanitize_profile_name(&long),
+            Err(InvalidName::TooLong {
+                max: MAX_PROFILE_NAME_LENGTH
+            })
+        );
+        assert!(sanitize_profile_name(&"ä".repeat(MAX_PROFILE_NAME_LENGTH)).is_ok());
+    }
+
+    #[test]
+    fn keeps_unicode_names() {
+        assert_eq!(sanitize_profile_name(" 工作账号 ").unwrap(), "工作账号");
+        assert_eq!(sanitize_profile_name("Zoë – Team 🚀").unwrap(), "Zoë – Team 🚀");
+        assert_eq!(sanitize_profile_name("Работа").unwrap(), "Работа");
+    }
+}
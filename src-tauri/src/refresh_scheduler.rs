@@ -0,0 +1,194 @@
+use crate::account_files::{collect_account_files, validate_account_path};
+use crate::account_meta::load_account_meta;
+use crate::account_tokens::{refresh_profile_file, RefreshError, RefreshErrorKind, RefreshLocks, RefreshState};
+use crate::command_guard::is_read_only;
+use crate::config::{load_config, BackgroundRefreshConfig};
+use crate::error::{AppError, AppResult};
+use crate::{extract_info_from_auth, extract_profile_id_from_auth, get_accounts_dir, trace};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+use tokio::sync::mpsc;
+
+pub const TOKEN_REFRESH_FAILED_EVENT: &str = "token-refresh-failed";
+pub const REFRESH_STATE_EVENT: &str = "refresh-state-changed";
+
+/// How often the loop re-reads the config while background refresh is disabled or the app is
+/// read-only.
+const DISABLED_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenRefreshFailedEvent {
+    pub name: String,
+    #[serde(rename = "filePath")]
+    pub file_path: String,
+    pub error: String,
+    #[serde(rename = "errorKind")]
+    pub error_kind: RefreshErrorKind,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RefreshStateEvent {
+    #[serde(rename = "filePath")]
+    pub file_path: String,
+    pub state: RefreshState,
+    pub error: Option<String>,
+}
+
+/// Profiles waiting for the queue worker, fed by `queue_refresh`.
+pub struct RefreshQueue {
+    sender: mpsc::UnboundedSender<PathBuf>,
+}
+
+impl RefreshQueue {
+    pub fn new() -> (Self, mpsc::UnboundedReceiver<PathBuf>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (Self { sender }, receiver)
+    }
+}
+
+fn profile_name(path: &Path) -> String {
+    path.file_stem().unwrap_or_default().to_string_lossy().to_string()
+}
+
+fn report_refresh_failure(app: &tauri::AppHandle, path: &Path, error: RefreshError) {
+    let name = profile_name(path);
+    trace::emit(
+        "background_refresh",
+        "refresh_failed",
+        serde_json::json!({ "name": name, "error": error.message }),
+    );
+    let _ = app.emit(
+        TOKEN_REFRESH_FAILED_EVENT,
+        TokenRefreshFailedEvent {
+            name,
+            file_path: path.to_string_lossy().to_string(),
+            error: error.message,
+            error_kind: error.kind,
+        },
+    );
+}
+
+/// Whether a token with `exp` of `expires_at` (Unix seconds) is within the lead window.
+fn is_due(expires_at: Option<i64>, now_secs: i64, lead_minutes: i64) -> bool {
+    expires_at.is_some_and(|expires_at| expires_at - now_secs <= lead_minutes.saturating_mul(60))
+}
+
+/// One pass over all profiles; dead refresh tokens are skipped since retrying cannot help.
+async fn refresh_due_profiles(app: &tauri::AppHandle, settings: &BackgroundRefreshConfig) -> Result<(), String> {
+    let accounts_dir = get_accounts_dir();
+    let meta = load_account_meta(&accounts_dir);
+    let now_secs = chrono::Utc::now().timestamp();
+    for file in collect_account_files(&accounts_dir, None)? {
+        if file.auth.tokens.is_none() {
+            continue;
+        }
+        let profile_id = extract_profile_id_from_auth(&file.auth, Some(&file.path));
+        if meta.get(&profile_id).refresh_dead {
+            continue;
+        }
+        let expires_at = extract_info_from_auth(&file.auth, 0).expires_at;
+        if !is_due(expires_at, now_secs, settings.lead_minutes) {
+            continue;
+        }
+
+        match refresh_profile_file(&app.state::<RefreshLocks>(), &file.path).await {
+            Ok(_) => trace::emit(
+                "background_refresh",
+                "refreshed",
+                serde_json::json!({ "name": profile_name(&file.path) }),
+            ),
+            Err(error) => report_refresh_failure(app, &file.path, error),
+        }
+    }
+    Ok(())
+}
+
+/// Background loop behind `backgroundRefresh`: refreshes every profile whose access token
+/// expires within the lead window, using the same path as `refresh_account_token`.
+pub async fn run_background_refresh(app: tauri::AppHandle) {
+    loop {
+        let settings = load_config().background_refresh;
+        if !settings.enabled || is_read_only() {
+            tokio::time::sleep(DISABLED_POLL_INTERVAL).await;
+            continue;
+        }
+
+        if let Err(error) = refresh_due_profiles(&app, &settings).await {
+            trace::emit("background_refresh", "pass_failed", serde_json::json!({ "error": error }));
+        }
+        tokio::time::sleep(Duration::from_secs(settings.interval_minutes.max(1).saturating_mul(60))).await;
+    }
+}
+
+/// Drains `queue_refresh` requests one at a time, emitting `refresh-state-changed` when each ends.
+/// Requests still queued when read-only mode is turned on fail without refreshing.
+pub async fn run_refresh_queue(app: tauri::AppHandle, mut receiver: mpsc::UnboundedReceiver<PathBuf>) {
+    while let Some(path) = receiver.recv().await {
+        let locks = app.state::<RefreshLocks>();
+        if is_read_only() {
+            let error = "Read-only mode is on; the token was not refreshed".to_string();
+            locks.set_state(&path, RefreshState::Failed, Some(error.clone()));
+            let _ = app.emit(
+                REFRESH_STATE_EVENT,
+                RefreshStateEvent {
+                    file_path: path.to_string_lossy().to_string(),
+                    state: RefreshState::Failed,
+                    error: Some(error),
+                },
+            );
+            continue;
+        }
+        let (state, error) = match refresh_profile_file(&locks, &path).await {
+            Ok(_) => (RefreshState::Succeeded, None),
+            Err(error) => {
+                let message = error.message.clone();
+                report_refresh_failure(&app, &path, error);
+                (RefreshState::Failed, Some(message))
+            }
+        };
+        let _ = app.emit(
+            REFRESH_STATE_EVENT,
+            RefreshStateEvent {
+                file_path: path.to_string_lossy().to_string(),
+                state,
+                error,
+            },
+        );
+    }
+}
+
+/// Hands a profile to the refresh worker and returns at once. `false` when it is already queued
+/// or being refreshed.
+#[tauri::command]
+pub fn queue_refresh(
+    locks: tauri::State<'_, RefreshLocks>,
+    queue: tauri::State<'_, RefreshQueue>,
+    file_path: String,
+) -> AppResult<bool> {
+    let validated_path = validate_account_path(&file_path, &get_accounts_dir())?;
+    if !locks.try_queue(&validated_path) {
+        return Ok(false);
+    }
+    queue
+        .sender
+        .send(validated_path)
+        .map_err(|_| AppError::external("Refresh worker is not running"))?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_due;
+
+    #[test]
+    fn refreshes_only_within_lead_window() {
+        let now = 1_700_000_000;
+        assert!(is_due(Some(now + 59 * 60), now, 60));
+        assert!(is_due(Some(now + 60 * 60), now, 60));
+        assert!(is_due(Some(now - 10), now, 60));
+        assert!(!is_due(Some(now + 61 * 60), now, 60));
+        assert!(!is_due(None, now, 60));
+    }
+}
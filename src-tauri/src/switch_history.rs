@@ -0,0 +1,138 @@
+use crate::config::{get_config_file, load_config};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const SWITCH_HISTORY_FILE: &str = "history.jsonl";
+const ROTATED_SWITCH_HISTORY_FILE: &str = "history.1.jsonl";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SwitchSource {
+    #[default]
+    Ui,
+    Tray,
+    Cli,
+    /// Automatic failover, see `auto_switch`.
+    Auto,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SwitchHistoryEntry {
+    /// Unix ms.
+    pub timestamp: i64,
+    #[serde(rename = "profileId", alias = "accountId")]
+    pub profile_id: String,
+    pub name: String,
+    pub source: SwitchSource,
+}
+
+pub fn get_switch_history_file() -> PathBuf {
+    get_config_file().with_file_name(SWITCH_HISTORY_FILE)
+}
+
+fn rotated_history_file(path: &Path) -> PathBuf {
+    path.with_file_name(ROTATED_SWITCH_HISTORY_FILE)
+}
+
+fn read_history_lines(path: &Path) -> Vec<SwitchHistoryEntry> {
+    let Ok(content) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+
+    content
+        .lines()
+        .filter_map(|line| serde_json::from_str::<SwitchHistoryEntry>(line.trim()).ok())
+        .collect()
+}
+
+/// Appends `entry`; once the file holds `max_entries` lines it is rotated to `history.1.jsonl`.
+fn append_history_entry(path: &Path, entry: &SwitchHistoryEntry, max_entries: usize) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create history directory: {}", e))?;
+    }
+
+    let line_count = fs::read_to_string(path)
+        .map(|content| content.lines().count())
+        .unwrap_or(0);
+    if line_count >= max_entries.max(1) {
+        fs::rename(path, rotated_history_file(path))
+            .map_err(|e| format!("Failed to rotate switch history: {}", e))?;
+    }
+
+    let line = serde_json::to_string(entry).map_err(|e| format!("Failed to serialize history entry: {}", e))?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open switch history: {}", e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to write switch history: {}", e))
+}
+
+/// Newest entries first, spanning the current and rotated log.
+fn read_switch_history(path: &Path, limit: usize) -> Vec<SwitchHistoryEntry> {
+    let mut entries = read_history_lines(&rotated_history_file(path));
+    entries.extend(read_history_lines(path));
+    entries.reverse();
+    entries.truncate(limit);
+    entries
+}
+
+pub fn record_switch(profile_id: &str, name: &str, source: SwitchSource) -> Result<(), String> {
+    let entry = SwitchHistoryEntry {
+        timestamp: chrono::Utc::now().timestamp_millis(),
+        profile_id: profile_id.to_string(),
+        name: name.to_string(),
+        source,
+    };
+    append_history_entry(&get_switch_history_file(), &entry, load_config().switch_history_limit)
+}
+
+#[tauri::command]
+pub fn get_switch_history(limit: Option<usize>) -> Vec<SwitchHistoryEntry> {
+    let limit = limit.unwrap_or_else(|| load_config().switch_history_limit);
+    read_switch_history(&get_switch_history_file(), limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{append_history_entry, read_switch_history, SwitchHistoryEntry, SwitchSource};
+    use std::fs::OpenOptions;
+    use std::io::Write;
+
+    fn entry(timestamp: i64) -> SwitchHistoryEntry {
+        SwitchHistoryEntry {
+            timestamp,
+            profile_id: format!("user-{}", timestamp),
+            name: format!("account-{}", timestamp),
+            source: SwitchSource::Tray,
+        }
+    }
+
+    #[test]
+    fn rotates_and_reads_newest_first_skipping_corrupt_lines() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let path = temp.path().join("history.jsonl");
+
+        for timestamp in 1..=3 {
+            append_history_entry(&path, &entry(timestamp), 2).expect("append");
+        }
+        let mut file = OpenOptions::new().append(true).open(&path).expect("open");
+        writeln!(file, "{{not json").expect("corrupt line");
+        append_history_entry(&path, &entry(4), 10).expect("append");
+
+        let timestamps: Vec<i64> = read_switch_history(&path, 10).iter().map(|entry| entry.timestamp).collect();
+        assert_eq!(timestamps, vec![4, 3, 2, 1]);
+        assert_eq!(read_switch_history(&path, 2).len(), 2);
+        assert!(temp.path().join("history.1.jsonl").exists());
+    }
+
+    #[test]
+    fn reads_entries_written_under_the_old_field_name() {
+        let line = r#"{"timestamp":1,"accountId":"user-1","name":"work","source":"ui"}"#;
+        let entry: SwitchHistoryEntry = serde_json::from_str(line).expect("legacy entry");
+        assert_eq!(entry.profile_id, "user-1");
+        assert!(serde_json::to_string(&entry).expect("serialize").contains(r#""profileId":"user-1""#));
+    }
+}
@@ -0,0 +1,136 @@
+use crate::AccountInfo;
+use serde::{Deserialize, Serialize};
+use std::process::Stdio;
+use std::time::Duration;
+use tauri::Emitter;
+
+const POST_SWITCH_HOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Emitted with the `HookResult` once a `postSwitchCommand` started by a switch has finished.
+pub const POST_SWITCH_HOOK_EVENT: &str = "post-switch-hook";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HookResult {
+    pub command: String,
+    /// `None` when the hook could not be started, was killed by a signal, or timed out.
+    #[serde(rename = "exitCode")]
+    pub exit_code: Option<i32>,
+    pub stderr: String,
+    /// The hook outlived the timeout and was killed.
+    #[serde(rename = "timedOut")]
+    pub timed_out: bool,
+    /// Set when the hook could not be started at all.
+    pub error: Option<String>,
+}
+
+fn shell_command(command: &str) -> tokio::process::Command {
+    #[cfg(target_os = "windows")]
+    {
+        let mut shell = tokio::process::Command::new("cmd");
+        shell.arg("/C").arg(command);
+        shell
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let mut shell = tokio::process::Command::new("sh");
+        shell.arg("-c").arg(command);
+        shell
+    }
+}
+
+fn hook_env(account: &AccountInfo) -> Vec<(&'static str, String)> {
+    vec![
+        ("CR_ACCOUNT_NAME", account.name.clone()),
+        ("CR_ACCOUNT_EMAIL", account.email.clone()),
+        ("CR_ACCOUNT_ID", account.upstream_account_id.clone()),
+        ("CR_PROFILE_ID", account.id.clone()),
+        ("CR_ACCOUNT_FILE", account.file_path.clone()),
+    ]
+}
+
+async fn run_hook(command: &str, env: Vec<(&'static str, String)>, timeout: Duration) -> HookResult {
+    let mut result = HookResult {
+        command: command.to_string(),
+        exit_code: None,
+        stderr: String::new(),
+        timed_out: false,
+        error: None,
+    };
+
+    let child = shell_command(command)
+        .envs(env)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        // Dropping the wait on timeout kills the hook instead of leaving it running unobserved.
+        .kill_on_drop(true)
+        .spawn();
+    let child = match child {
+        Ok(child) => child,
+        Err(error) => {
+            result.error = Some(format!("Failed to start post-switch command: {}", error));
+            return result;
+        }
+    };
+
+    match tokio::time::timeout(timeout, child.wait_with_output()).await {
+        Ok(Ok(output)) => {
+            result.exit_code = output.status.code();
+            result.stderr = String::from_utf8_lossy(&output.stderr).trim_end().to_string();
+        }
+        Ok(Err(error)) => result.error = Some(format!("Failed to wait for post-switch command: {}", error)),
+        Err(_) => result.timed_out = true,
+    }
+    result
+}
+
+/// Starts the configured `postSwitchCommand` in the background with the new account in `CR_*`
+/// variables, so a slow hook never holds up the switch. Its outcome, failures included, arrives as
+/// `POST_SWITCH_HOOK_EVENT`. Returns whether a hook was started.
+pub fn spawn_post_switch_hook(app: &tauri::AppHandle, command: Option<&str>, account: &AccountInfo) -> bool {
+    let Some(command) = command.map(str::trim).filter(|command| !command.is_empty()) else {
+        return false;
+    };
+    let command = command.to_string();
+    let env = hook_env(account);
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = run_hook(&command, env, POST_SWITCH_HOOK_TIMEOUT).await;
+        let _ = app.emit(POST_SWITCH_HOOK_EVENT, result);
+    });
+    true
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::{hook_env, run_hook};
+    use crate::AccountInfo;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn captures_stderr_and_exit_code_with_account_env() {
+        let account = AccountInfo {
+            name: "work-pro".to_string(),
+            ..Default::default()
+        };
+        let result = run_hook("echo \"$CR_ACCOUNT_NAME\" >&2; exit 3", hook_env(&account), Duration::from_secs(5)).await;
+        assert_eq!(result.exit_code, Some(3));
+        assert_eq!(result.stderr, "work-pro");
+        assert!(!result.timed_out);
+    }
+
+    #[tokio::test]
+    async fn kills_hooks_that_time_out() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let marker = temp.path().join("finished");
+        let command = format!("sleep 1; touch '{}'", marker.display());
+        let result = run_hook(&command, Vec::new(), Duration::from_millis(50)).await;
+        assert!(result.timed_out);
+        assert_eq!(result.exit_code, None);
+
+        // The timed-out hook was killed, so it never gets to finish.
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert!(!marker.exists());
+    }
+}
@@ -0,0 +1,188 @@
+use glob::{MatchOptions, Pattern};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Gitignore-style patterns read from `~/.codex` and any folder below it that is uploaded.
+pub const SYNC_IGNORE_FILE: &str = ".syncignore";
+/// Files larger than this are never uploaded.
+pub const MAX_SYNC_FILE_SIZE: u64 = 10 * 1024 * 1024;
+/// Applied before every `.syncignore`, so a `!dist/` line there can bring a folder back.
+const DEFAULT_PATTERNS: &str = "node_modules/\nvenv/\ndist/\n*.pyc\n";
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+struct Rule {
+    pattern: Pattern,
+    negate: bool,
+    dir_only: bool,
+    /// Matched against the path below the `.syncignore` folder, not just the entry name.
+    anchored: bool,
+}
+
+impl Rule {
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negate, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+        let anchored = line.contains('/');
+        let pattern = Pattern::new(line.trim_start_matches('/')).ok()?;
+        Some(Self {
+            pattern,
+            negate,
+            dir_only,
+            anchored,
+        })
+    }
+
+    fn matches(&self, path: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        let target = if self.anchored {
+            path
+        } else {
+            path.rsplit('/').next().unwrap_or(path)
+        };
+        self.pattern.matches_with(target, MATCH_OPTIONS)
+    }
+}
+
+/// Patterns of one `.syncignore`, relative to its folder.
+struct Layer {
+    base: String,
+    rules: Vec<Rule>,
+}
+
+fn parse_rules(content: &str) -> Vec<Rule> {
+    content.lines().filter_map(Rule::parse).collect()
+}
+
+/// Decides which entries of a local folder tree are uploaded. Each value describes one folder;
+/// `descend` gives the one for a subfolder, picking up its `.syncignore`.
+#[derive(Clone)]
+pub struct SyncIgnore {
+    layers: Vec<Arc<Layer>>,
+    /// Path of the current folder below the root, with a trailing slash.
+    dir: String,
+}
+
+impl SyncIgnore {
+    /// The built-in patterns plus `root/.syncignore`.
+    pub fn load(root: &Path) -> Self {
+        let defaults = Self {
+            layers: vec![Arc::new(Layer {
+                base: String::new(),
+                rules: parse_rules(DEFAULT_PATTERNS),
+            })],
+            dir: String::new(),
+        };
+        defaults.with_file(root)
+    }
+
+    /// The subfolder `name` of the current folder, found at `path`.
+    pub fn descend(&self, path: &Path, name: &str) -> Self {
+        Self {
+            layers: self.layers.clone(),
+            dir: format!("{}{}/", self.dir, name),
+        }
+        .with_file(path)
+    }
+
+    /// Adds the patterns of `dir/.syncignore`, if there is one, where `dir` is the current folder.
+    fn with_file(mut self, dir: &Path) -> Self {
+        if let Ok(content) = fs::read_to_string(dir.join(SYNC_IGNORE_FILE)) {
+            self.layers.push(Arc::new(Layer {
+                base: self.dir.clone(),
+                rules: parse_rules(&content),
+            }));
+        }
+        self
+    }
+
+    /// Path of the entry `name` of the current folder, below the root.
+    pub fn path_of(&self, name: &str) -> String {
+        format!("{}{}", self.dir, name)
+    }
+
+    /// Whether the entry `name` at `path` is left out by a pattern or, for files, by size. The
+    /// last matching pattern wins, deeper `.syncignore` files after shallower ones.
+    pub fn is_ignored(&self, path: &Path, name: &str, is_dir: bool) -> bool {
+        if !is_dir && fs::metadata(path).is_ok_and(|metadata| metadata.len() > MAX_SYNC_FILE_SIZE) {
+            return true;
+        }
+        let full = self.path_of(name);
+        let mut ignored = false;
+        for layer in self.layers.iter() {
+            let Some(relative) = full.strip_prefix(&layer.base) else {
+                continue;
+            };
+            for rule in &layer.rules {
+                if rule.matches(relative, is_dir) {
+                    ignored = !rule.negate;
+                }
+            }
+        }
+        ignored
+    }
+}
+
+/// Whether `name` is never synced: dotfiles (`.syncignore` included) and `__`-prefixed entries
+/// such as `__pycache__`.
+pub fn is_hidden(name: &str) -> bool {
+    name.starts_with("__") || name.starts_with('.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_hidden, SyncIgnore, MAX_SYNC_FILE_SIZE, SYNC_IGNORE_FILE};
+    use std::fs;
+
+    #[test]
+    fn defaults_root_and_nested_files_apply_in_order() {
+        let root = tempfile::tempdir().expect("root");
+        fs::write(root.path().join(SYNC_IGNORE_FILE), "*.log\n# comment\n/skills/big/\n!keep.log\n").unwrap();
+        let skill = root.path().join("skills/pdf");
+        fs::create_dir_all(&skill).unwrap();
+        fs::write(skill.join(SYNC_IGNORE_FILE), "out/\n!dist/\nassets/*.psd\n").unwrap();
+        let large = skill.join("video.bin");
+        fs::File::create(&large).unwrap().set_len(MAX_SYNC_FILE_SIZE + 1).unwrap();
+
+        let ignore = SyncIgnore::load(root.path());
+        let skills = ignore.descend(&root.path().join("skills"), "skills");
+        let none = root.path().join("missing");
+        assert!(ignore.is_ignored(&none, "node_modules", true));
+        assert!(!ignore.is_ignored(&none, "node_modules", false));
+        assert!(ignore.is_ignored(&none, "debug.log", false));
+        assert!(!ignore.is_ignored(&none, "keep.log", false));
+        assert!(skills.is_ignored(&none, "big", true));
+        assert!(!skills.is_ignored(&none, "pdf", true));
+        assert!(skills.is_ignored(&none, "cache.pyc", false));
+
+        let pdf = skills.descend(&skill, "pdf");
+        assert_eq!(pdf.path_of("out"), "skills/pdf/out");
+        assert!(pdf.is_ignored(&none, "out", true));
+        assert!(!pdf.is_ignored(&none, "dist", true));
+        assert!(pdf.is_ignored(&none, "trace.log", false));
+        assert!(pdf.is_ignored(&large, "video.bin", false));
+        let assets = pdf.descend(&skill.join("assets"), "assets");
+        assert!(assets.is_ignored(&none, "cover.psd", false));
+        assert!(!assets.is_ignored(&none, "cover.png", false));
+        assert!(!skills.is_ignored(&none, "out", true));
+
+        assert!(is_hidden(".syncignore") && is_hidden("__pycache__") && !is_hidden("SKILL.md"));
+    }
+}
@@ -0,0 +1,101 @@
+use crate::config::get_config_file;
+use crate::trace;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Used when the platform has no trash, e.g. headless Linux without a trash implementation.
+const LOCAL_TRASH_DIR: &str = ".trash";
+
+fn local_trash_dir() -> PathBuf {
+    get_config_file().with_file_name(LOCAL_TRASH_DIR)
+}
+
+fn copy_dir(source: &Path, target: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(target)?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let destination = target.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &destination)?;
+        } else {
+            fs::copy(entry.path(), &destination)?;
+        }
+    }
+    Ok(())
+}
+
+/// Moves `path` into `trash_dir` as `<timestamp>-<name>`, copying when a rename is not possible
+/// (e.g. across filesystems).
+fn move_to_local_trash(path: &Path, trash_dir: &Path, now: chrono::DateTime<chrono::Utc>) -> Result<PathBuf, String> {
+    fs::create_dir_all(trash_dir).map_err(|e| format!("Failed to create trash directory: {}", e))?;
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let stamp = now.format("%Y%m%dT%H%M%S%3fZ");
+    let mut target = trash_dir.join(format!("{}-{}", stamp, name));
+    let mut counter = 1;
+    while fs::symlink_metadata(&target).is_ok() {
+        target = trash_dir.join(format!("{}_{}-{}", stamp, counter, name));
+        counter += 1;
+    }
+
+    if fs::rename(path, &target).is_ok() {
+        return Ok(target);
+    }
+    if path.is_dir() {
+        copy_dir(path, &target).and_then(|()| fs::remove_dir_all(path))
+    } else {
+        fs::copy(path, &target).and_then(|_| fs::remove_file(path))
+    }
+    .map_err(|e| format!("Failed to move to trash: {}", e))?;
+    Ok(target)
+}
+
+/// Deletes a file or directory: into the OS trash by default, falling back to `~/.myswitch/.trash`,
+/// or for good when `permanently` is set.
+pub fn remove_path(path: &Path, permanently: bool) -> Result<(), String> {
+    if permanently {
+        return if path.is_dir() {
+            fs::remove_dir_all(path).map_err(|e| format!("Failed to delete directory: {}", e))
+        } else {
+            fs::remove_file(path).map_err(|e| format!("Failed to delete file: {}", e))
+        };
+    }
+
+    match trash::delete(path) {
+        Ok(()) => Ok(()),
+        Err(error) => {
+            trace::emit("trash", "system_trash_failed", serde_json::json!({ "error": error.to_string() }));
+            move_to_local_trash(path, &local_trash_dir(), chrono::Utc::now()).map(|_| ())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::move_to_local_trash;
+    use chrono::TimeZone;
+    use std::fs;
+
+    #[test]
+    fn moves_files_and_directories_into_local_trash() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let trash_dir = temp.path().join(".trash");
+        let now = chrono::Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+
+        let file = temp.path().join("work.json");
+        fs::write(&file, "{}").expect("file");
+        let trashed = move_to_local_trash(&file, &trash_dir, now).expect("trash file");
+        assert!(!file.exists());
+        assert_eq!(trashed.file_name().unwrap(), "20250101T000000000Z-work.json");
+
+        fs::write(&file, "{}").expect("file again");
+        let second = move_to_local_trash(&file, &trash_dir, now).expect("trash again");
+        assert_ne!(second, trashed);
+
+        let skill = temp.path().join("skill");
+        fs::create_dir_all(skill.join("assets")).expect("skill dir");
+        fs::write(skill.join("SKILL.md"), "# skill").expect("skill md");
+        let trashed = move_to_local_trash(&skill, &trash_dir, now).expect("trash dir");
+        assert!(!skill.exists());
+        assert!(trashed.join("SKILL.md").is_file());
+    }
+}
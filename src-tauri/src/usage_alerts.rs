@@ -0,0 +1,188 @@
+use crate::account_usage::{fetch_usage_from_auth_path, profile_for_auth_path, read_auth_file, usage_profile_id, RateLimitWindow, UsageInfo};
+use crate::config::{load_config, UsageAlertConfig};
+use crate::{get_codex_auth_file, trace};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+
+pub const USAGE_THRESHOLD_EVENT: &str = "usage-threshold";
+
+/// How often the loop re-reads the config while alerts are disabled or auto-switch polls instead.
+const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(60);
+/// `resets_at` drifts by a few seconds between polls; a later value beyond this is a new window.
+const RESET_TOLERANCE_SECS: i64 = 60;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum UsageWindowKind {
+    Primary,
+    Secondary,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageThresholdEvent {
+    #[serde(rename = "profileId")]
+    pub profile_id: String,
+    pub name: String,
+    pub window: UsageWindowKind,
+    #[serde(rename = "usedPercent")]
+    pub used_percent: f64,
+    #[serde(rename = "thresholdPercent")]
+    pub threshold_percent: f64,
+    #[serde(rename = "resetsAt")]
+    pub resets_at: Option<i64>,
+}
+
+/// `resets_at` of the window each alert last fired for, so an alert fires once per window.
+#[derive(Default)]
+pub struct UsageAlerts {
+    fired: Mutex<HashMap<(String, UsageWindowKind), Option<i64>>>,
+}
+
+fn is_new_window(fired_for: Option<i64>, resets_at: Option<i64>) -> bool {
+    match (fired_for, resets_at) {
+        (Some(fired_for), Some(resets_at)) => resets_at - fired_for > RESET_TOLERANCE_SECS,
+        (None, Some(_)) => true,
+        (_, None) => false,
+    }
+}
+
+impl UsageAlerts {
+    /// Windows of `usage` at or above their threshold that have not alerted since their last reset.
+    fn crossed(
+        &self,
+        profile_id: &str,
+        usage: &UsageInfo,
+        settings: &UsageAlertConfig,
+    ) -> Vec<(UsageWindowKind, f64, RateLimitWindow)> {
+        let windows = [
+            (UsageWindowKind::Primary, settings.primary_percent, &usage.primary_window),
+            (UsageWindowKind::Secondary, settings.secondary_percent, &usage.secondary_window),
+        ];
+        let mut fired = self.fired.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut crossed = Vec::new();
+        for (kind, threshold, window) in windows {
+            let (Some(threshold), Some(window)) = (threshold, window) else {
+                continue;
+            };
+            if window.used_percent < threshold {
+                continue;
+            }
+            let key = (profile_id.to_string(), kind);
+            if fired.get(&key).is_some_and(|fired_for| !is_new_window(*fired_for, window.resets_at)) {
+                continue;
+            }
+            fired.insert(key, window.resets_at);
+            crossed.push((kind, threshold, window.clone()));
+        }
+        crossed
+    }
+}
+
+/// Emits `usage-threshold` for each window of a freshly fetched `usage` that crossed its threshold.
+pub fn check_usage_thresholds(app: &tauri::AppHandle, auth_path: &Path, usage: &UsageInfo) {
+    let settings = load_config().usage_alerts;
+    if !settings.enabled {
+        return;
+    }
+    let Ok(auth) = read_auth_file(auth_path) else {
+        return;
+    };
+    let profile_id = usage_profile_id(auth_path, &auth);
+    if profile_id.trim().is_empty() {
+        return;
+    }
+
+    let name = profile_for_auth_path(auth_path, &auth)
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    for (window, threshold_percent, crossed) in app.state::<UsageAlerts>().crossed(&profile_id, usage, &settings) {
+        trace::emit(
+            "usage_alerts",
+            "threshold_crossed",
+            serde_json::json!({ "name": name, "window": window, "usedPercent": crossed.used_percent }),
+        );
+        let _ = app.emit(
+            USAGE_THRESHOLD_EVENT,
+            UsageThresholdEvent {
+                profile_id: profile_id.clone(),
+                name: name.clone(),
+                window,
+                used_percent: crossed.used_percent,
+                threshold_percent,
+                resets_at: crossed.resets_at,
+            },
+        );
+    }
+}
+
+/// Background loop behind `usageAlerts`: polls the active account's usage so thresholds are noticed
+/// without the window open. Auto-switch already polls the active account, so this idles while it runs.
+pub async fn run_usage_alerts(app: tauri::AppHandle) {
+    loop {
+        let config = load_config();
+        if !config.usage_alerts.enabled || config.auto_switch.enabled {
+            tokio::time::sleep(IDLE_POLL_INTERVAL).await;
+            continue;
+        }
+
+        let auth_path = get_codex_auth_file();
+        if auth_path.exists() {
+            match fetch_usage_from_auth_path(&app, &auth_path).await {
+                Ok(usage) => check_usage_thresholds(&app, &auth_path, &usage),
+                Err(error) => trace::emit("usage_alerts", "poll_failed", serde_json::json!({ "error": error.message })),
+            }
+        }
+        let interval_minutes = config.usage_alerts.poll_interval_minutes.max(1);
+        tokio::time::sleep(Duration::from_secs(interval_minutes.saturating_mul(60))).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{UsageAlerts, UsageWindowKind};
+    use crate::account_usage::{RateLimitWindow, UsageInfo};
+    use crate::config::UsageAlertConfig;
+
+    fn usage(used_percent: f64, resets_at: i64) -> UsageInfo {
+        UsageInfo {
+            primary_window: Some(RateLimitWindow {
+                used_percent,
+                window_minutes: Some(300),
+                resets_at: Some(resets_at),
+            }),
+            secondary_window: None,
+            plan_type: None,
+            fetched_at: None,
+            endpoint_used: None,
+            raw: None,
+        }
+    }
+
+    #[test]
+    fn fires_once_per_window_reset() {
+        let alerts = UsageAlerts::default();
+        let settings = UsageAlertConfig {
+            enabled: true,
+            ..UsageAlertConfig::default()
+        };
+        let kinds = |usage: &UsageInfo| -> Vec<UsageWindowKind> {
+            alerts
+                .crossed("acct", usage, &settings)
+                .into_iter()
+                .map(|(kind, _, _)| kind)
+                .collect()
+        };
+
+        assert!(kinds(&usage(79.0, 10_000)).is_empty());
+        assert_eq!(kinds(&usage(81.0, 10_000)), vec![UsageWindowKind::Primary]);
+        assert!(kinds(&usage(95.0, 10_005)).is_empty());
+        assert!(kinds(&usage(10.0, 28_000)).is_empty());
+        assert_eq!(kinds(&usage(85.0, 28_000)), vec![UsageWindowKind::Primary]);
+    }
+}
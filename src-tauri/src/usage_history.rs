@@ -0,0 +1,164 @@
+use crate::account_files::write_private_file_atomic;
+use crate::account_usage::UsageInfo;
+use crate::config::{get_config_file, load_config};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const USAGE_HISTORY_FILE: &str = "usage_history.jsonl";
+/// A new sample replaces the account's previous one when they are closer together than this.
+const SAMPLE_COLLAPSE_WINDOW_MS: i64 = 5 * 60 * 1000;
+
+/// Serializes writers; usage for several accounts can arrive at once.
+static HISTORY_WRITE: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UsageSample {
+    /// Unix ms.
+    pub timestamp: i64,
+    /// Profile id, stable across renames and moves. Older samples keyed by the shared upstream
+    /// account id no longer parse and are dropped on the next rewrite.
+    #[serde(rename = "profileId")]
+    pub profile_id: String,
+    #[serde(rename = "primaryUsedPercent")]
+    pub primary_used_percent: Option<f64>,
+    #[serde(rename = "primaryResetsAt")]
+    pub primary_resets_at: Option<i64>,
+    #[serde(rename = "secondaryUsedPercent")]
+    pub secondary_used_percent: Option<f64>,
+    #[serde(rename = "secondaryResetsAt")]
+    pub secondary_resets_at: Option<i64>,
+}
+
+impl UsageSample {
+    fn from_usage(profile_id: &str, usage: &UsageInfo, timestamp: i64) -> Self {
+        Self {
+            timestamp,
+            profile_id: profile_id.to_string(),
+            primary_used_percent: usage.primary_window.as_ref().map(|window| window.used_percent),
+            primary_resets_at: usage.primary_window.as_ref().and_then(|window| window.resets_at),
+            secondary_used_percent: usage.secondary_window.as_ref().map(|window| window.used_percent),
+            secondary_resets_at: usage.secondary_window.as_ref().and_then(|window| window.resets_at),
+        }
+    }
+}
+
+pub fn get_usage_history_file() -> PathBuf {
+    get_config_file().with_file_name(USAGE_HISTORY_FILE)
+}
+
+fn read_samples(path: &Path) -> Vec<UsageSample> {
+    let Ok(content) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+
+    content
+        .lines()
+        .filter_map(|line| serde_json::from_str::<UsageSample>(line.trim()).ok())
+        .collect()
+}
+
+/// Appends `sample`. Samples older than `retention_days` are dropped and a sample close to the
+/// account's previous one replaces it; either case rewrites the file, otherwise it is appended to.
+fn append_sample(path: &Path, sample: &UsageSample, retention_days: i64) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create history directory: {}", e))?;
+    }
+
+    let cutoff = sample.timestamp - retention_days.max(1).saturating_mul(24 * 60 * 60 * 1000);
+    let mut samples = read_samples(path);
+    let expired = samples.first().is_some_and(|first| first.timestamp < cutoff);
+    let previous = samples.iter().rposition(|existing| existing.profile_id == sample.profile_id);
+    let collapse = previous.is_some_and(|index| sample.timestamp - samples[index].timestamp < SAMPLE_COLLAPSE_WINDOW_MS);
+
+    let line = serde_json::to_string(sample).map_err(|e| format!("Failed to serialize usage sample: {}", e))?;
+    if !expired && !collapse {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| format!("Failed to open usage history: {}", e))?;
+        return writeln!(file, "{}", line).map_err(|e| format!("Failed to write usage history: {}", e));
+    }
+
+    if let (true, Some(index)) = (collapse, previous) {
+        samples.remove(index);
+    }
+    samples.retain(|existing| existing.timestamp >= cutoff);
+    samples.push(sample.clone());
+    let mut content = String::new();
+    for sample in &samples {
+        content.push_str(&serde_json::to_string(sample).map_err(|e| format!("Failed to serialize usage sample: {}", e))?);
+        content.push('\n');
+    }
+    write_private_file_atomic(path, content.as_bytes()).map_err(|e| format!("Failed to write usage history: {}", e))
+}
+
+/// Records a successful usage fetch for `profile_id`; fetches without rate-limit windows are skipped.
+pub fn record_usage_sample(profile_id: &str, usage: &UsageInfo) -> Result<(), String> {
+    let profile_id = profile_id.trim();
+    if profile_id.is_empty() || (usage.primary_window.is_none() && usage.secondary_window.is_none()) {
+        return Ok(());
+    }
+    let timestamp = usage.fetched_at.unwrap_or_else(|| chrono::Utc::now().timestamp_millis());
+    let sample = UsageSample::from_usage(profile_id, usage, timestamp);
+    let _guard = HISTORY_WRITE.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    append_sample(&get_usage_history_file(), &sample, load_config().usage_history_retention_days)
+}
+
+/// Samples for `profile_id` (`AccountInfo::id`), oldest first, optionally only those at or after
+/// `since` (Unix ms).
+#[tauri::command]
+pub fn get_usage_history(profile_id: String, since: Option<i64>) -> Vec<UsageSample> {
+    let since = since.unwrap_or(i64::MIN);
+    read_samples(&get_usage_history_file())
+        .into_iter()
+        .filter(|sample| sample.profile_id == profile_id && sample.timestamp >= since)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{append_sample, read_samples, UsageSample};
+
+    const MINUTE: i64 = 60 * 1000;
+    const DAY: i64 = 24 * 60 * MINUTE;
+
+    fn sample(profile_id: &str, timestamp: i64, used: f64) -> UsageSample {
+        UsageSample {
+            timestamp,
+            profile_id: profile_id.to_string(),
+            primary_used_percent: Some(used),
+            primary_resets_at: None,
+            secondary_used_percent: None,
+            secondary_resets_at: None,
+        }
+    }
+
+    #[test]
+    fn collapses_close_samples_and_prunes_expired_ones() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let path = temp.path().join("usage_history.jsonl");
+        let start = 100 * DAY;
+
+        append_sample(&path, &sample("a", start, 10.0), 30).expect("first");
+        append_sample(&path, &sample("b", start + MINUTE, 50.0), 30).expect("other account");
+        append_sample(&path, &sample("a", start + 2 * MINUTE, 12.0), 30).expect("collapsed");
+        append_sample(&path, &sample("a", start + 10 * MINUTE, 15.0), 30).expect("appended");
+        let points: Vec<(String, f64)> = read_samples(&path)
+            .into_iter()
+            .map(|sample| (sample.profile_id, sample.primary_used_percent.unwrap()))
+            .collect();
+        assert_eq!(
+            points,
+            vec![("b".to_string(), 50.0), ("a".to_string(), 12.0), ("a".to_string(), 15.0)]
+        );
+
+        append_sample(&path, &sample("a", start + 31 * DAY, 1.0), 30).expect("prune");
+        let remaining = read_samples(&path);
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].timestamp, start + 31 * DAY);
+    }
+}
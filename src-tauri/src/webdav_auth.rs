@@ -0,0 +1,367 @@
+use crate::webdav_sync::WebDavConfig;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, WWW_AUTHENTICATE};
+use md5::Md5;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigestAlgorithm {
+    Md5,
+    Md5Sess,
+    Sha256,
+    Sha256Sess,
+}
+
+impl DigestAlgorithm {
+    fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_uppercase().as_str() {
+            "MD5" => Some(Self::Md5),
+            "MD5-SESS" => Some(Self::Md5Sess),
+            "SHA-256" => Some(Self::Sha256),
+            "SHA-256-SESS" => Some(Self::Sha256Sess),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Md5 => "MD5",
+            Self::Md5Sess => "MD5-sess",
+            Self::Sha256 => "SHA-256",
+            Self::Sha256Sess => "SHA-256-sess",
+        }
+    }
+
+    fn hash(self, input: &str) -> String {
+        let digest = match self {
+            Self::Md5 | Self::Md5Sess => Md5::digest(input.as_bytes()).to_vec(),
+            Self::Sha256 | Self::Sha256Sess => Sha256::digest(input.as_bytes()).to_vec(),
+        };
+        digest.iter().fold(String::new(), |mut hex, byte| {
+            let _ = write!(hex, "{:02x}", byte);
+            hex
+        })
+    }
+}
+
+/// A `WWW-Authenticate: Digest` challenge (RFC 7616) this client can answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DigestChallenge {
+    pub realm: String,
+    pub nonce: String,
+    pub opaque: Option<String>,
+    pub algorithm: DigestAlgorithm,
+    /// Whether the server asked for `qop=auth`; RFC 2069 servers send no qop.
+    pub qop_auth: bool,
+    /// The previous nonce expired but the credentials were right.
+    pub stale: bool,
+}
+
+/// `key=value` and `key="quoted, value"` pairs of a challenge, keys lowercased.
+fn challenge_params(params: &str) -> HashMap<String, String> {
+    let mut values = HashMap::new();
+    let mut rest = params.trim();
+    while !rest.is_empty() {
+        let Some((key, after)) = rest.split_once('=') else {
+            break;
+        };
+        let key = key.trim().trim_start_matches(',').trim().to_ascii_lowercase();
+        let after = after.trim_start();
+        let (value, remaining) = if let Some(quoted) = after.strip_prefix('"') {
+            let mut value = String::new();
+            let mut chars = quoted.char_indices();
+            let mut end = quoted.len();
+            while let Some((index, ch)) = chars.next() {
+                match ch {
+                    '\\' => value.extend(chars.next().map(|(_, escaped)| escaped)),
+                    '"' => {
+                        end = index + 1;
+                        break;
+                    }
+                    _ => value.push(ch),
+                }
+            }
+            (value, &quoted[end..])
+        } else {
+            let end = after.find(',').unwrap_or(after.len());
+            (after[..end].trim().to_string(), &after[end..])
+        };
+        values.insert(key, value);
+        rest = remaining.trim_start().trim_start_matches(',').trim_start();
+    }
+    values
+}
+
+impl DigestChallenge {
+    /// Parses one challenge value, `Digest realm="…", nonce="…", …`.
+    pub fn parse(value: &str) -> Option<Self> {
+        let (scheme, params) = value.trim().split_once(' ')?;
+        if !scheme.eq_ignore_ascii_case("digest") {
+            return None;
+        }
+        let params = challenge_params(params);
+        let algorithm = match params.get("algorithm") {
+            Some(algorithm) => DigestAlgorithm::parse(algorithm)?,
+            None => DigestAlgorithm::Md5,
+        };
+        let qop_auth = match params.get("qop") {
+            Some(qop) => {
+                if !qop.split(',').any(|option| option.trim().eq_ignore_ascii_case("auth")) {
+                    return None;
+                }
+                true
+            }
+            None => false,
+        };
+        Some(Self {
+            realm: params.get("realm").cloned().unwrap_or_default(),
+            nonce: params.get("nonce")?.clone(),
+            opaque: params.get("opaque").cloned(),
+            algorithm,
+            qop_auth,
+            stale: params.get("stale").is_some_and(|stale| stale.eq_ignore_ascii_case("true")),
+        })
+    }
+
+    /// `Authorization` value for `method` on `uri` (path and query), as request number `nc` with
+    /// client nonce `cnonce`.
+    pub fn authorization(&self, username: &str, password: &str, method: &str, uri: &str, nc: u32, cnonce: &str) -> String {
+        let algorithm = self.algorithm;
+        let mut ha1 = algorithm.hash(&format!("{}:{}:{}", username, self.realm, password));
+        if matches!(algorithm, DigestAlgorithm::Md5Sess | DigestAlgorithm::Sha256Sess) {
+            ha1 = algorithm.hash(&format!("{}:{}:{}", ha1, self.nonce, cnonce));
+        }
+        let ha2 = algorithm.hash(&format!("{}:{}", method, uri));
+        let nc = format!("{:08x}", nc);
+        let response = if self.qop_auth {
+            algorithm.hash(&format!("{}:{}:{}:{}:auth:{}", ha1, self.nonce, nc, cnonce, ha2))
+        } else {
+            algorithm.hash(&format!("{}:{}:{}", ha1, self.nonce, ha2))
+        };
+
+        let quote = |value: &str| value.replace('\\', "\\\\").replace('"', "\\\"");
+        let mut header = format!(
+            r#"Digest username="{}", realm="{}", nonce="{}", uri="{}", algorithm={}, response="{}""#,
+            quote(username),
+            quote(&self.realm),
+            quote(&self.nonce),
+            quote(uri),
+            algorithm.name(),
+            response
+        );
+        if self.qop_auth {
+            let _ = write!(header, r#", qop=auth, nc={}, cnonce="{}""#, nc, cnonce);
+        }
+        if let Some(opaque) = &self.opaque {
+            let _ = write!(header, r#", opaque="{}""#, quote(opaque));
+        }
+        header
+    }
+}
+
+fn digest_challenge(headers: &HeaderMap) -> Option<DigestChallenge> {
+    headers
+        .get_all(WWW_AUTHENTICATE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .find_map(DigestChallenge::parse)
+}
+
+fn offers_basic(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(WWW_AUTHENTICATE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .any(|value| value.trim_start().get(..6).is_some_and(|scheme| scheme.eq_ignore_ascii_case("basic ")))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebDavAuthScheme {
+    Basic,
+    Digest,
+}
+
+impl WebDavAuthScheme {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Basic => "Basic",
+            Self::Digest => "Digest",
+        }
+    }
+}
+
+struct DigestSession {
+    challenge: DigestChallenge,
+    /// Requests sent with the current nonce.
+    count: u32,
+}
+
+/// How a host is authenticated, learned from its first 401.
+enum HostAuth {
+    Basic,
+    Digest(DigestSession),
+}
+
+/// Scheme to retry a 401 with, given what the host was sent so far: Basic when the first
+/// challenge offers it, else Digest, and a fresh Digest nonce when the last one went stale.
+/// `None` returns the 401, since the credentials were refused.
+fn next_auth(sent: Option<&HostAuth>, offers_basic: bool, challenge: Option<DigestChallenge>) -> Option<HostAuth> {
+    let session = |challenge| HostAuth::Digest(DigestSession { challenge, count: 0 });
+    match (sent, challenge) {
+        (None, _) if offers_basic => Some(HostAuth::Basic),
+        (None, Some(challenge)) => Some(session(challenge)),
+        (Some(HostAuth::Digest(_)), Some(challenge)) if challenge.stale => Some(session(challenge)),
+        _ => None,
+    }
+}
+
+/// HTTP client for one sync. The first request to a host carries no credentials; its 401 decides
+/// between Basic, when offered, and Digest, so the password never goes out in Basic to a server
+/// that only takes Digest. The scheme and Digest nonce are kept per host for later requests.
+pub struct WebDavClient {
+    http: reqwest::Client,
+    hosts: Mutex<HashMap<String, HostAuth>>,
+}
+
+fn host_key(url: &reqwest::Url) -> String {
+    format!("{}:{}", url.host_str().unwrap_or_default(), url.port_or_known_default().unwrap_or_default())
+}
+
+fn random_cnonce() -> String {
+    let mut bytes = [0u8; 8];
+    let _ = getrandom::fill(&mut bytes);
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+impl WebDavClient {
+    pub fn new(http: reqwest::Client) -> Self {
+        Self {
+            http,
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn request(&self, method: reqwest::Method, url: String) -> reqwest::RequestBuilder {
+        self.http.request(method, url)
+    }
+
+    /// Scheme used for `url`'s host so far; `None` until the host asked for credentials.
+    pub fn auth_scheme(&self, url: &str) -> Option<WebDavAuthScheme> {
+        let hosts = self.hosts.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        match hosts.get(&host_key(&reqwest::Url::parse(url).ok()?))? {
+            HostAuth::Basic => Some(WebDavAuthScheme::Basic),
+            HostAuth::Digest(_) => Some(WebDavAuthScheme::Digest),
+        }
+    }
+
+    fn authorize(&self, config: &WebDavConfig, mut request: reqwest::Request) -> reqwest::Request {
+        let host = host_key(request.url());
+        let mut hosts = self.hosts.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let header = match hosts.get_mut(&host) {
+            Some(HostAuth::Digest(session)) => {
+                session.count += 1;
+                let uri = match request.url().query() {
+                    Some(query) => format!("{}?{}", request.url().path(), query),
+                    None => request.url().path().to_string(),
+                };
+                let method = request.method().as_str();
+                let cnonce = random_cnonce();
+                session
+                    .challenge
+                    .authorization(&config.username, &config.password, method, &uri, session.count, &cnonce)
+            }
+            Some(HostAuth::Basic) => format!("Basic {}", STANDARD.encode(format!("{}:{}", config.username, config.password))),
+            None => return request,
+        };
+        if let Ok(mut value) = HeaderValue::from_str(&header) {
+            value.set_sensitive(true);
+            request.headers_mut().insert(AUTHORIZATION, value);
+        }
+        request
+    }
+
+    /// Sends `request` with `config`'s credentials, answering a 401 once as `next_auth` decides.
+    pub async fn send(&self, config: &WebDavConfig, request: reqwest::RequestBuilder) -> reqwest::Result<reqwest::Response> {
+        let request = request.build()?;
+        let host = host_key(request.url());
+        let retry = request.try_clone();
+        let response = self.http.execute(self.authorize(config, request)).await?;
+        if response.status() != reqwest::StatusCode::UNAUTHORIZED {
+            return Ok(response);
+        }
+        let Some(retry) = retry else {
+            return Ok(response);
+        };
+
+        {
+            let mut hosts = self.hosts.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            let headers = response.headers();
+            let Some(auth) = next_auth(hosts.get(&host), offers_basic(headers), digest_challenge(headers)) else {
+                return Ok(response);
+            };
+            hosts.insert(host, auth);
+        }
+        self.http.execute(self.authorize(config, retry)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{next_auth, DigestAlgorithm, DigestChallenge, DigestSession, HostAuth};
+
+    #[test]
+    fn md5_matches_reference_digests() {
+        assert_eq!(DigestAlgorithm::Md5.hash(""), "d41d8cd98f00b204e9800998ecf8427e");
+        assert_eq!(
+            DigestAlgorithm::Md5.hash("The quick brown fox jumps over the lazy dog"),
+            "9e107d9d372bb6826bd81d3542a419d6"
+        );
+        assert_eq!(DigestAlgorithm::Md5.hash(&"a".repeat(100)), "36a92cc94a9e0fa21f625f8bfb007adf");
+    }
+
+    #[test]
+    fn answers_rfc_7616_challenges() {
+        let challenge = DigestChallenge::parse(
+            r#"Digest realm="http-auth@example.org", qop="auth, auth-int", algorithm=SHA-256, nonce="7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v", opaque="FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS""#,
+        )
+        .expect("challenge");
+        assert_eq!(challenge.algorithm, DigestAlgorithm::Sha256);
+        assert!(challenge.qop_auth && !challenge.stale);
+
+        let cnonce = "f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ";
+        let header = challenge.authorization("Mufasa", "Circle of Life", "GET", "/dir/index.html", 1, cnonce);
+        assert!(header.contains(r#"response="753927fa0e85d155564e2e272a28d1802ca10daf4496794697cf8db5856cb6c1""#));
+        assert!(header.contains("nc=00000001"));
+
+        let md5 = DigestChallenge {
+            algorithm: DigestAlgorithm::Md5,
+            ..challenge
+        };
+        let header = md5.authorization("Mufasa", "Circle of Life", "GET", "/dir/index.html", 1, cnonce);
+        assert!(header.contains(r#"response="8ca523f5e9506fed4657c9700eebdbec""#));
+
+        assert_eq!(DigestChallenge::parse(r#"Basic realm="dav""#), None);
+        assert_eq!(DigestChallenge::parse(r#"Digest realm="dav", nonce="n", qop="auth-int""#), None);
+    }
+
+    #[test]
+    fn picks_basic_only_when_offered_and_never_after_digest() {
+        let challenge = DigestChallenge::parse(r#"Digest realm="dav", nonce="n1", qop="auth""#).expect("challenge");
+        let stale = DigestChallenge::parse(r#"Digest realm="dav", nonce="n2", qop="auth", stale=true"#).expect("stale");
+        let digest = HostAuth::Digest(DigestSession { challenge: challenge.clone(), count: 3 });
+
+        assert!(matches!(next_auth(None, true, Some(challenge.clone())), Some(HostAuth::Basic)));
+        assert!(matches!(next_auth(None, false, Some(challenge.clone())), Some(HostAuth::Digest(_))));
+        assert!(next_auth(None, false, None).is_none());
+        assert!(next_auth(Some(&HostAuth::Basic), true, Some(challenge.clone())).is_none());
+        assert!(next_auth(Some(&digest), true, Some(challenge)).is_none());
+        match next_auth(Some(&digest), true, Some(stale)) {
+            Some(HostAuth::Digest(session)) => assert_eq!((session.challenge.nonce.as_str(), session.count), ("n2", 0)),
+            _ => panic!("expected a fresh Digest session"),
+        }
+    }
+}
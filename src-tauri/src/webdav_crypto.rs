@@ -0,0 +1,191 @@
+use crate::error::{AppError, AppResult};
+use aes_gcm::aead::{Aead, Payload};
+use aes_gcm::{Aes256Gcm, Key, KeyInit, Nonce};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Suffix of encrypted account files on the server, after their `.json`.
+pub const ENCRYPTED_SUFFIX: &str = ".enc";
+
+const MAGIC: &[u8; 5] = b"CRENC";
+const FORMAT_VERSION: u8 = 1;
+const PBKDF2_ITERATIONS: u32 = 600_000;
+/// Highest iteration count accepted from a file header, so a crafted file cannot stall a sync.
+const MAX_ITERATIONS: u32 = 4 * PBKDF2_ITERATIONS;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const CHECK_LEN: usize = 8;
+const TAG_LEN: usize = 16;
+/// Magic, version, iterations, salt, nonce and key check; authenticated along with the content.
+const HEADER_LEN: usize = MAGIC.len() + 1 + 4 + SALT_LEN + NONCE_LEN + CHECK_LEN;
+
+/// Derived keys by iteration count and salt.
+type DerivedKeys = HashMap<(u32, [u8; SALT_LEN]), [u8; 32]>;
+
+/// Whether `data` starts with the encrypted file header.
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Encrypts account files with AES-256-GCM under a key derived from the sync passphrase with
+/// PBKDF2-HMAC-SHA256. Each file gets a random nonce; its header records the salt and iteration
+/// count, so files from other machines decrypt with the same passphrase.
+pub struct SyncCipher {
+    passphrase: String,
+    iterations: u32,
+    /// Salt for files this cipher encrypts, so one sync derives its key once.
+    salt: [u8; SALT_LEN],
+    keys: Mutex<DerivedKeys>,
+}
+
+fn random_bytes<const N: usize>() -> AppResult<[u8; N]> {
+    let mut bytes = [0u8; N];
+    getrandom::fill(&mut bytes).map_err(|e| AppError::external(format!("Failed to generate random bytes: {}", e)))?;
+    Ok(bytes)
+}
+
+fn key_check(key: &[u8; 32]) -> [u8; CHECK_LEN] {
+    let digest = Sha256::new()
+        .chain_update(key)
+        .chain_update(b"code-revolver sync key check")
+        .finalize();
+    let mut check = [0u8; CHECK_LEN];
+    check.copy_from_slice(&digest[..CHECK_LEN]);
+    check
+}
+
+impl SyncCipher {
+    pub fn new(passphrase: &str) -> AppResult<Self> {
+        Self::build(passphrase, PBKDF2_ITERATIONS)
+    }
+
+    /// A cipher with a cheap key derivation, for tests.
+    #[cfg(test)]
+    pub fn with_iterations(passphrase: &str, iterations: u32) -> AppResult<Self> {
+        Self::build(passphrase, iterations)
+    }
+
+    fn build(passphrase: &str, iterations: u32) -> AppResult<Self> {
+        Ok(Self {
+            passphrase: passphrase.to_string(),
+            iterations,
+            salt: random_bytes()?,
+            keys: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn key(&self, iterations: u32, salt: [u8; SALT_LEN]) -> [u8; 32] {
+        let mut keys = self.keys.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        *keys.entry((iterations, salt)).or_insert_with(|| {
+            let mut key = [0u8; 32];
+            pbkdf2::pbkdf2_hmac::<Sha256>(self.passphrase.as_bytes(), &salt, iterations, &mut key);
+            key
+        })
+    }
+
+    pub fn encrypt(&self, plaintext: &[u8]) -> AppResult<Vec<u8>> {
+        let key = self.key(self.iterations, self.salt);
+        let nonce: [u8; NONCE_LEN] = random_bytes()?;
+        let mut data = Vec::with_capacity(HEADER_LEN + plaintext.len() + TAG_LEN);
+        data.extend_from_slice(MAGIC);
+        data.push(FORMAT_VERSION);
+        data.extend_from_slice(&self.iterations.to_be_bytes());
+        data.extend_from_slice(&self.salt);
+        data.extend_from_slice(&nonce);
+        data.extend_from_slice(&key_check(&key));
+        let payload = Payload { msg: plaintext, aad: &data };
+        let ciphertext = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key))
+            .encrypt(Nonce::from_slice(&nonce), payload)
+            .map_err(|_| AppError::external("Failed to encrypt the file"))?;
+        data.extend_from_slice(&ciphertext);
+        Ok(data)
+    }
+
+    /// Decrypts a file written by `encrypt`, telling a wrong passphrase apart from damaged or
+    /// altered content.
+    pub fn decrypt(&self, data: &[u8]) -> AppResult<Vec<u8>> {
+        if !is_encrypted(data) {
+            return Err(AppError::parse("Not an encrypted sync file"));
+        }
+        if data.len() < HEADER_LEN + TAG_LEN {
+            return Err(AppError::parse("Encrypted file is truncated"));
+        }
+        let (header, body) = data.split_at(HEADER_LEN);
+        if header[MAGIC.len()] != FORMAT_VERSION {
+            return Err(AppError::parse(format!(
+                "Encrypted file uses unsupported format version {}",
+                header[MAGIC.len()]
+            )));
+        }
+        let (iterations, rest) = header[MAGIC.len() + 1..].split_at(4);
+        let (salt, rest) = rest.split_at(SALT_LEN);
+        let (nonce, check) = rest.split_at(NONCE_LEN);
+        let iterations = u32::from_be_bytes(iterations.try_into().expect("iteration count"));
+        let salt: [u8; SALT_LEN] = salt.try_into().expect("salt");
+        if iterations == 0 {
+            return Err(AppError::parse("Encrypted file header is damaged"));
+        }
+        if iterations > MAX_ITERATIONS {
+            return Err(AppError::auth(format!(
+                "Encrypted file asks for {} key derivation iterations; at most {} are accepted",
+                iterations, MAX_ITERATIONS
+            )));
+        }
+
+        let key = self.key(iterations, salt);
+        if key_check(&key)[..] != *check {
+            return Err(AppError::auth("Wrong sync passphrase"));
+        }
+        Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key))
+            .decrypt(Nonce::from_slice(nonce), Payload { msg: body, aad: header })
+            .map_err(|_| AppError::parse("Encrypted file was modified or damaged"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_encrypted, SyncCipher, MAGIC, MAX_ITERATIONS};
+    use crate::error::AppErrorCode;
+
+    #[test]
+    fn round_trips_and_rejects_wrong_passphrases_and_tampering() {
+        let cipher = SyncCipher::with_iterations("correct horse", 1_000).unwrap();
+        let plaintext = br#"{"tokens":{"refresh_token":"secret"}}"#;
+        let encrypted = cipher.encrypt(plaintext).unwrap();
+        assert!(is_encrypted(&encrypted));
+        assert!(!encrypted.windows(6).any(|window| window == b"secret"));
+
+        let other_machine = SyncCipher::with_iterations("correct horse", 5).unwrap();
+        assert_eq!(other_machine.decrypt(&encrypted).unwrap(), plaintext);
+
+        let wrong = SyncCipher::with_iterations("battery staple", 1_000).unwrap();
+        assert_eq!(wrong.decrypt(&encrypted).unwrap_err().code, AppErrorCode::Auth);
+
+        let mut tampered = encrypted.clone();
+        let last = tampered.len() - 20;
+        tampered[last] ^= 1;
+        let error = cipher.decrypt(&tampered).unwrap_err();
+        assert_eq!(error.code, AppErrorCode::Parse);
+        assert!(error.message.contains("modified"), "{}", error.message);
+
+        assert!(cipher.decrypt(&encrypted[..30]).is_err());
+        assert!(cipher.decrypt(plaintext).is_err());
+    }
+
+    #[test]
+    fn rejects_excessive_iteration_counts_before_deriving_a_key() {
+        let cipher = SyncCipher::with_iterations("correct horse", 1_000).unwrap();
+        let mut encrypted = cipher.encrypt(b"{}").unwrap();
+        let at = MAGIC.len() + 1;
+        encrypted[at..at + 4].copy_from_slice(&u32::MAX.to_be_bytes());
+        let started = std::time::Instant::now();
+        let error = cipher.decrypt(&encrypted).unwrap_err();
+        assert_eq!(error.code, AppErrorCode::Auth);
+        assert!(error.message.contains("iterations"), "{}", error.message);
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+
+        encrypted[at..at + 4].copy_from_slice(&(MAX_ITERATIONS + 1).to_be_bytes());
+        assert_eq!(cipher.decrypt(&encrypted).unwrap_err().code, AppErrorCode::Auth);
+    }
+}
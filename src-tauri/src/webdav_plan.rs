@@ -1,5 +1,6 @@
+use crate::webdav_tombstones::Tombstones;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
@@ -20,6 +21,82 @@ pub enum SyncItemAction {
     Unchanged,
 }
 
+/// Which side wins when a file differs between the local and remote copy.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncStrategy {
+    /// Transfer only when the source copy was modified later than the target copy.
+    #[default]
+    NewerWins,
+    LocalWins,
+    RemoteWins,
+    /// Transfer every file, even ones the manifest marks as unchanged.
+    Force,
+}
+
+/// Side picked by the user for a file reported as a conflict.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncSide {
+    Local,
+    Remote,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncDirection {
+    Upload,
+    Download,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncPlanAction {
+    Upload,
+    Download,
+    /// Changed, but the strategy keeps the target copy.
+    Skip,
+    Delete,
+    Conflict,
+}
+
+/// One planned change to an account file; unchanged files are left out of a plan.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SyncPlanItem {
+    /// Path below `accounts/`.
+    pub name: String,
+    pub action: SyncPlanAction,
+    /// Copy removed by a `delete`.
+    pub side: Option<SyncSide>,
+    #[serde(rename = "localSize")]
+    pub local_size: Option<u64>,
+    #[serde(rename = "remoteSize")]
+    pub remote_size: Option<u64>,
+    #[serde(rename = "localModifiedAt")]
+    pub local_modified_at: Option<i64>,
+    #[serde(rename = "remoteModifiedAt")]
+    pub remote_modified_at: Option<i64>,
+}
+
+impl SyncPlanItem {
+    fn new(
+        name: &str,
+        action: SyncPlanAction,
+        side: Option<SyncSide>,
+        local: Option<&SyncPreviewEntry>,
+        remote: Option<&SyncPreviewEntry>,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            action,
+            side,
+            local_size: local.and_then(|entry| entry.size),
+            remote_size: remote.and_then(|entry| entry.size),
+            local_modified_at: local.and_then(|entry| entry.modified_at),
+            remote_modified_at: remote.and_then(|entry| entry.modified_at),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncPreviewItem {
     pub name: String,
@@ -48,11 +125,187 @@ pub struct SyncPreviewEntry {
     pub name: String,
     pub item_type: SyncItemType,
     pub modified_at: Option<i64>,
+    pub size: Option<u64>,
     pub hash: Option<String>,
 }
 
+/// A file as of its last bidirectional sync, kept locally to tell which side changed since.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SyncStateEntry {
+    pub hash: String,
+    #[serde(rename = "remoteModifiedAt")]
+    pub remote_modified_at: Option<i64>,
+}
+
 const UNCHANGED_WINDOW_MS: i64 = 2_000;
 
+/// Whether a changed file is copied over its target. `target_modified` is `None` when the target
+/// does not exist and `Some(None)` when its modification time is unknown. Under `NewerWins` the
+/// source must be newer by more than `UNCHANGED_WINDOW_MS`; unknown times transfer as before.
+pub fn should_transfer(
+    strategy: SyncStrategy,
+    direction: SyncDirection,
+    source_modified: Option<i64>,
+    target_modified: Option<Option<i64>>,
+) -> bool {
+    let Some(target_modified) = target_modified else {
+        return true;
+    };
+    match (strategy, direction) {
+        (SyncStrategy::Force, _)
+        | (SyncStrategy::LocalWins, SyncDirection::Upload)
+        | (SyncStrategy::RemoteWins, SyncDirection::Download) => true,
+        (SyncStrategy::LocalWins, SyncDirection::Download) | (SyncStrategy::RemoteWins, SyncDirection::Upload) => false,
+        (SyncStrategy::NewerWins, _) => source_modified
+            .zip(target_modified)
+            .is_none_or(|(source, target)| source - target > UNCHANGED_WINDOW_MS),
+    }
+}
+
+/// Direction of one file in a bidirectional sync; `remote.hash` is the hash the remote manifest
+/// recorded on upload. With a sync state, a side changed when its hash (or, without a manifest
+/// hash, the remote modification time) differs from it, and changes on both sides conflict unless
+/// `strategy` or `resolution` picks a side. Without one the newer copy wins.
+pub fn plan_bidirectional(
+    strategy: SyncStrategy,
+    local: Option<&SyncPreviewEntry>,
+    remote: Option<&SyncPreviewEntry>,
+    state: Option<&SyncStateEntry>,
+    resolution: Option<SyncSide>,
+) -> SyncItemAction {
+    let (local, remote) = match (local, remote) {
+        (Some(local), Some(remote)) => (local, remote),
+        (Some(_), None) => return SyncItemAction::Upload,
+        (None, Some(_)) => return SyncItemAction::Download,
+        (None, None) => return SyncItemAction::Unchanged,
+    };
+    if local.hash.is_some() && local.hash == remote.hash {
+        return SyncItemAction::Unchanged;
+    }
+
+    let newer = match (local.modified_at, remote.modified_at) {
+        (Some(left), Some(right)) if left - right > UNCHANGED_WINDOW_MS => SyncItemAction::Upload,
+        (Some(left), Some(right)) if right - left > UNCHANGED_WINDOW_MS => SyncItemAction::Download,
+        _ => SyncItemAction::Conflict,
+    };
+    let (local_changed, remote_changed) = match state {
+        Some(state) => (
+            local.hash.as_deref() != Some(state.hash.as_str()),
+            match remote.hash.as_deref() {
+                Some(hash) => hash != state.hash,
+                None => remote.modified_at != state.remote_modified_at,
+            },
+        ),
+        None if newer != SyncItemAction::Conflict => return newer,
+        None => (true, true),
+    };
+
+    match (local_changed, remote_changed, resolution, strategy) {
+        (false, false, _, _) => SyncItemAction::Unchanged,
+        (true, false, _, _) => SyncItemAction::Upload,
+        (false, true, _, _) => SyncItemAction::Download,
+        (true, true, Some(SyncSide::Local), _) | (true, true, None, SyncStrategy::LocalWins) => SyncItemAction::Upload,
+        (true, true, Some(SyncSide::Remote), _) | (true, true, None, SyncStrategy::RemoteWins) => SyncItemAction::Download,
+        (true, true, None, SyncStrategy::Force) if newer == SyncItemAction::Conflict => SyncItemAction::Upload,
+        (true, true, None, SyncStrategy::Force) => newer,
+        (true, true, None, SyncStrategy::NewerWins) => SyncItemAction::Conflict,
+    }
+}
+
+/// Deletions for the copies on `side` that `tombstones` bury, returning the deleted names.
+fn plan_deletions(
+    side: SyncSide,
+    local: &HashMap<String, SyncPreviewEntry>,
+    remote: &HashMap<String, SyncPreviewEntry>,
+    tombstones: &Tombstones,
+    plan: &mut Vec<SyncPlanItem>,
+) -> HashSet<String> {
+    let copies = if side == SyncSide::Local { local } else { remote };
+    let mut deleted = HashSet::new();
+    for (name, copy) in copies {
+        if tombstones.buries(name, copy.modified_at) {
+            plan.push(SyncPlanItem::new(name, SyncPlanAction::Delete, Some(side), local.get(name), remote.get(name)));
+            deleted.insert(name.clone());
+        }
+    }
+    deleted
+}
+
+fn sort_plan(mut plan: Vec<SyncPlanItem>) -> Vec<SyncPlanItem> {
+    // Stable, so a deletion stays ahead of a transfer replacing the same file.
+    plan.sort_by(|a, b| a.name.cmp(&b.name));
+    plan
+}
+
+/// Plan for an upload or download of the account files, keyed by path below `accounts/`. Target
+/// copies buried by `tombstones` are deleted and buried source copies are left alone; a changed
+/// file is transferred when `should_transfer` allows it and skipped otherwise.
+pub fn plan_one_way(
+    direction: SyncDirection,
+    strategy: SyncStrategy,
+    local: &HashMap<String, SyncPreviewEntry>,
+    remote: &HashMap<String, SyncPreviewEntry>,
+    tombstones: &Tombstones,
+) -> Vec<SyncPlanItem> {
+    let mut plan = Vec::new();
+    let (sources, targets, target_side, transfer) = match direction {
+        SyncDirection::Upload => (local, remote, SyncSide::Remote, SyncPlanAction::Upload),
+        SyncDirection::Download => (remote, local, SyncSide::Local, SyncPlanAction::Download),
+    };
+    let deleted = plan_deletions(target_side, local, remote, tombstones, &mut plan);
+
+    for (name, source) in sources {
+        if tombstones.buries(name, source.modified_at) {
+            continue;
+        }
+        let target = targets.get(name).filter(|_| !deleted.contains(name));
+        let unchanged = target.is_some_and(|target| target.hash.is_some() && target.hash == source.hash);
+        if unchanged && strategy != SyncStrategy::Force {
+            continue;
+        }
+        let action = if should_transfer(strategy, direction, source.modified_at, target.map(|target| target.modified_at)) {
+            transfer
+        } else {
+            SyncPlanAction::Skip
+        };
+        let (local, remote) = match direction {
+            SyncDirection::Upload => (Some(source), target),
+            SyncDirection::Download => (target, Some(source)),
+        };
+        plan.push(SyncPlanItem::new(name, action, None, local, remote));
+    }
+    sort_plan(plan)
+}
+
+/// Plan for a bidirectional sync: buried copies on either side are deleted, then each file follows
+/// `plan_bidirectional` against its `states` entry (keyed like `local` and `remote`).
+pub fn plan_two_way(
+    strategy: SyncStrategy,
+    local: &HashMap<String, SyncPreviewEntry>,
+    remote: &HashMap<String, SyncPreviewEntry>,
+    states: &HashMap<String, SyncStateEntry>,
+    resolutions: &HashMap<String, SyncSide>,
+    tombstones: &Tombstones,
+) -> Vec<SyncPlanItem> {
+    let mut plan = Vec::new();
+    let deleted_local = plan_deletions(SyncSide::Local, local, remote, tombstones, &mut plan);
+    let deleted_remote = plan_deletions(SyncSide::Remote, local, remote, tombstones, &mut plan);
+
+    let names: BTreeSet<&String> = local.keys().chain(remote.keys()).collect();
+    for name in names {
+        let local = local.get(name).filter(|_| !deleted_local.contains(name));
+        let remote = remote.get(name).filter(|_| !deleted_remote.contains(name));
+        let action = match plan_bidirectional(strategy, local, remote, states.get(name), resolutions.get(name).copied()) {
+            SyncItemAction::Upload => SyncPlanAction::Upload,
+            SyncItemAction::Download => SyncPlanAction::Download,
+            SyncItemAction::Conflict => SyncPlanAction::Conflict,
+            SyncItemAction::Unchanged => continue,
+        };
+        plan.push(SyncPlanItem::new(name, action, None, local, remote));
+    }
+    sort_plan(plan)
+}
+
 pub fn build_sync_preview(
     local_entries: Vec<SyncPreviewEntry>,
     remote_entries: Vec<SyncPreviewEntry>,
@@ -122,7 +375,12 @@ pub fn build_sync_preview(
 
 #[cfg(test)]
 mod tests {
-    use super::{build_sync_preview, SyncItemAction, SyncItemType, SyncPreviewEntry};
+    use super::{
+        build_sync_preview, plan_bidirectional, plan_one_way, should_transfer, SyncDirection, SyncItemAction,
+        SyncItemType, SyncPlanAction, SyncPreviewEntry, SyncSide, SyncStateEntry, SyncStrategy,
+    };
+    use crate::webdav_tombstones::Tombstones;
+    use std::collections::HashMap;
 
     #[test]
     fn marks_changed_files_as_conflicts_instead_of_auto_overwrite() {
@@ -131,12 +389,14 @@ mod tests {
                 name: "accounts/demo.json".to_string(),
                 item_type: SyncItemType::Account,
                 modified_at: Some(1000),
+                size: None,
                 hash: Some("left".to_string()),
             }],
             vec![SyncPreviewEntry {
                 name: "accounts/demo.json".to_string(),
                 item_type: SyncItemType::Account,
                 modified_at: Some(6000),
+                size: None,
                 hash: Some("right".to_string()),
             }],
         );
@@ -152,12 +412,14 @@ mod tests {
                 name: "prompts/demo.md".to_string(),
                 item_type: SyncItemType::Prompt,
                 modified_at: Some(1000),
+                size: None,
                 hash: Some("same-hash".to_string()),
             }],
             vec![SyncPreviewEntry {
                 name: "prompts/demo.md".to_string(),
                 item_type: SyncItemType::Prompt,
                 modified_at: Some(9000),
+                size: None,
                 hash: Some("same-hash".to_string()),
             }],
         );
@@ -165,4 +427,88 @@ mod tests {
         assert_eq!(preview.conflict_count, 0);
         assert_eq!(preview.items[0].action, SyncItemAction::Unchanged);
     }
+
+    #[test]
+    fn newer_wins_transfers_only_strictly_newer_sources() {
+        let upload = |strategy, source, target| should_transfer(strategy, SyncDirection::Upload, source, target);
+
+        assert!(upload(SyncStrategy::NewerWins, Some(10_000), None));
+        assert!(upload(SyncStrategy::NewerWins, Some(10_000), Some(Some(5_000))));
+        assert!(!upload(SyncStrategy::NewerWins, Some(10_000), Some(Some(9_000))));
+        assert!(!upload(SyncStrategy::NewerWins, Some(5_000), Some(Some(10_000))));
+        assert!(upload(SyncStrategy::NewerWins, None, Some(Some(10_000))));
+        assert!(!upload(SyncStrategy::RemoteWins, Some(10_000), Some(Some(5_000))));
+        assert!(upload(SyncStrategy::RemoteWins, Some(10_000), None));
+        assert!(!should_transfer(SyncStrategy::LocalWins, SyncDirection::Download, Some(10_000), Some(Some(5_000))));
+        assert!(upload(SyncStrategy::Force, Some(5_000), Some(Some(10_000))));
+    }
+
+    #[test]
+    fn bidirectional_plan_follows_changes_since_the_last_sync() {
+        let entry = |hash: &str, modified_at: i64| SyncPreviewEntry {
+            name: "demo.json".to_string(),
+            item_type: SyncItemType::Account,
+            modified_at: Some(modified_at),
+            size: None,
+            hash: Some(hash.to_string()),
+        };
+        let state = SyncStateEntry {
+            hash: "base".to_string(),
+            remote_modified_at: Some(1_000),
+        };
+        let plan = |local: &SyncPreviewEntry, remote: &SyncPreviewEntry, state, resolution| {
+            plan_bidirectional(SyncStrategy::NewerWins, Some(local), Some(remote), state, resolution)
+        };
+
+        assert_eq!(plan(&entry("edited", 50_000), &entry("base", 1_000), Some(&state), None), SyncItemAction::Upload);
+        // Only the remote copy changed, so the older remote edit still wins over the untouched local file.
+        assert_eq!(plan(&entry("base", 90_000), &entry("theirs", 5_000), Some(&state), None), SyncItemAction::Download);
+        assert_eq!(plan(&entry("mine", 9_000), &entry("theirs", 5_000), Some(&state), None), SyncItemAction::Conflict);
+        assert_eq!(
+            plan(&entry("mine", 9_000), &entry("theirs", 5_000), Some(&state), Some(SyncSide::Remote)),
+            SyncItemAction::Download
+        );
+        assert_eq!(plan(&entry("mine", 9_000), &entry("theirs", 5_000), None, None), SyncItemAction::Upload);
+        assert_eq!(plan(&entry("same", 9_000), &entry("same", 1_000), Some(&state), None), SyncItemAction::Unchanged);
+    }
+
+    #[test]
+    fn one_way_plan_deletes_buried_targets_and_skips_older_sources() {
+        let entry = |name: &str, hash: &str, modified_at: i64| {
+            let entry = SyncPreviewEntry {
+                name: name.to_string(),
+                item_type: SyncItemType::Account,
+                modified_at: Some(modified_at),
+                size: Some(10),
+                hash: Some(hash.to_string()),
+            };
+            (name.to_string(), entry)
+        };
+        let local = HashMap::from([
+            entry("new.json", "n", 9_000),
+            entry("same.json", "s", 9_000),
+            entry("stale.json", "mine", 1_000),
+        ]);
+        let remote = HashMap::from([
+            entry("same.json", "s", 1_000),
+            entry("stale.json", "theirs", 8_000),
+            entry("gone.json", "g", 2_000),
+        ]);
+        let tombstones = Tombstones {
+            deleted: [("gone.json".to_string(), 5_000)].into(),
+        };
+
+        let plan = plan_one_way(SyncDirection::Upload, SyncStrategy::NewerWins, &local, &remote, &tombstones);
+        let actions: Vec<(&str, SyncPlanAction, Option<SyncSide>)> =
+            plan.iter().map(|item| (item.name.as_str(), item.action, item.side)).collect();
+        assert_eq!(
+            actions,
+            vec![
+                ("gone.json", SyncPlanAction::Delete, Some(SyncSide::Remote)),
+                ("new.json", SyncPlanAction::Upload, None),
+                ("stale.json", SyncPlanAction::Skip, None),
+            ]
+        );
+        assert_eq!(plan[2].remote_modified_at, Some(8_000));
+    }
 }
@@ -0,0 +1,267 @@
+use crate::webdav_sync::SyncResult;
+use serde::Serialize;
+use crate::error::{AppError, AppResult};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{Emitter, Manager};
+use tokio::sync::OwnedMutexGuard;
+
+pub const SYNC_PROGRESS_EVENT: &str = "sync-progress";
+pub const SYNC_COMPLETE_EVENT: &str = "sync-complete";
+/// Sent instead of `sync-complete` when `cancel_sync` stopped the sync.
+pub const SYNC_CANCELLED_EVENT: &str = "sync-cancelled";
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncPhase {
+    Listing,
+    Uploading,
+    Downloading,
+    Deleting,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncProgressEvent {
+    #[serde(rename = "sessionId")]
+    pub session_id: u64,
+    pub phase: SyncPhase,
+    /// File being transferred, or the remote folder being listed.
+    pub name: String,
+    /// 1-based position of the file; 0 while listing.
+    pub index: usize,
+    /// Files known so far; grows while a download lists remote folders.
+    pub total: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncCompleteEvent {
+    #[serde(rename = "sessionId")]
+    pub session_id: u64,
+    pub result: SyncResult,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncState {
+    Idle,
+    Running,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncStatus {
+    pub state: SyncState,
+    /// Last progress event of the running sync; `None` when idle or before its first event.
+    pub progress: Option<SyncProgressEvent>,
+}
+
+#[derive(Default)]
+struct SessionCounters {
+    next_id: AtomicU64,
+    /// Sessions with an id up to this one were cancelled.
+    cancelled_through: AtomicU64,
+    running: AtomicUsize,
+    /// Held by the running sync for its whole duration, so syncs never interleave their writes.
+    lock: Arc<tokio::sync::Mutex<()>>,
+    snapshot: Mutex<Option<SyncProgressEvent>>,
+}
+
+impl SessionCounters {
+    fn snapshot(&self) -> std::sync::MutexGuard<'_, Option<SyncProgressEvent>> {
+        self.snapshot.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Hands out the id tagging each sync command's events and cancels running syncs.
+#[derive(Default)]
+pub struct SyncSessions {
+    counters: Arc<SessionCounters>,
+}
+
+enum SyncEvent {
+    Progress(SyncProgressEvent),
+    Complete(SyncCompleteEvent),
+    Cancelled(SyncCompleteEvent),
+}
+
+/// Emits the progress events of one sync command and holds the sync lock until dropped, so a
+/// sync that fails or panics releases it too.
+pub struct SyncProgress {
+    emit: Option<Box<dyn Fn(SyncEvent) + Send + Sync>>,
+    counters: Arc<SessionCounters>,
+    _lock: OwnedMutexGuard<()>,
+    session_id: u64,
+    index: usize,
+    total: usize,
+}
+
+impl SyncProgress {
+    /// Starts a sync, or fails with `SyncInProgress` while another one runs.
+    pub fn start(app: &tauri::AppHandle) -> AppResult<Self> {
+        let mut progress = Self::with_counters(app.state::<SyncSessions>().counters.clone())?;
+        let app = app.clone();
+        let emit = move |event| {
+            let _ = match event {
+                SyncEvent::Progress(event) => app.emit(SYNC_PROGRESS_EVENT, event),
+                SyncEvent::Complete(event) => app.emit(SYNC_COMPLETE_EVENT, event),
+                SyncEvent::Cancelled(event) => app.emit(SYNC_CANCELLED_EVENT, event),
+            };
+        };
+        progress.emit = Some(Box::new(emit));
+        Ok(progress)
+    }
+
+    fn with_counters(counters: Arc<SessionCounters>) -> AppResult<Self> {
+        let lock = counters
+            .lock
+            .clone()
+            .try_lock_owned()
+            .map_err(|_| AppError::sync_in_progress("A sync is already in progress"))?;
+        let session_id = counters.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        counters.running.fetch_add(1, Ordering::SeqCst);
+        Ok(Self {
+            emit: None,
+            counters,
+            _lock: lock,
+            session_id,
+            index: 0,
+            total: 0,
+        })
+    }
+
+    /// Progress that goes nowhere, for driving the sync helpers without an app.
+    #[cfg(test)]
+    pub fn detached() -> Self {
+        Self::with_counters(Arc::default()).expect("fresh sessions are unlocked")
+    }
+
+    #[cfg(test)]
+    pub fn cancel(&self) {
+        self.counters.cancelled_through.fetch_max(self.session_id, Ordering::SeqCst);
+    }
+
+    /// Whether `cancel_sync` was called since this sync started.
+    pub fn is_cancelled(&self) -> bool {
+        self.counters.cancelled_through.load(Ordering::SeqCst) >= self.session_id
+    }
+
+    /// Files reported so far and the running total.
+    #[cfg(test)]
+    pub fn counts(&self) -> (usize, usize) {
+        (self.index, self.total)
+    }
+
+    pub fn add_total(&mut self, count: usize) {
+        self.total += count;
+    }
+
+    pub fn listing(&self, name: &str) {
+        self.emit(SyncPhase::Listing, name, 0);
+    }
+
+    /// Reports the next file, `name`, as being worked on.
+    pub fn file(&mut self, phase: SyncPhase, name: &str) {
+        self.index += 1;
+        self.total = self.total.max(self.index);
+        self.emit(phase, name, self.index);
+    }
+
+    pub fn complete(&self, result: &SyncResult) {
+        if let Some(emit) = &self.emit {
+            let event = SyncCompleteEvent {
+                session_id: self.session_id,
+                result: result.clone(),
+            };
+            emit(if result.cancelled {
+                SyncEvent::Cancelled(event)
+            } else {
+                SyncEvent::Complete(event)
+            });
+        }
+    }
+
+    fn emit(&self, phase: SyncPhase, name: &str, index: usize) {
+        let event = SyncProgressEvent {
+            session_id: self.session_id,
+            phase,
+            name: name.to_string(),
+            index,
+            total: self.total,
+        };
+        *self.counters.snapshot() = Some(event.clone());
+        if let Some(emit) = &self.emit {
+            emit(SyncEvent::Progress(event));
+        }
+    }
+}
+
+impl Drop for SyncProgress {
+    fn drop(&mut self) {
+        *self.counters.snapshot() = None;
+        self.counters.running.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+fn sync_status(counters: &SessionCounters) -> SyncStatus {
+    let progress = counters.snapshot().clone();
+    let state = if counters.lock.try_lock().is_ok() {
+        SyncState::Idle
+    } else {
+        SyncState::Running
+    };
+    SyncStatus {
+        state,
+        progress: progress.filter(|_| state == SyncState::Running),
+    }
+}
+
+/// Whether a sync is running, with its latest progress.
+#[tauri::command]
+pub fn get_sync_status(sessions: tauri::State<'_, SyncSessions>) -> AppResult<SyncStatus> {
+    Ok(sync_status(&sessions.counters))
+}
+
+/// Stops every running sync after the file it is transferring; they return what they did so far
+/// marked `cancelled`. Returns whether any sync was running.
+#[tauri::command]
+pub fn cancel_sync(sessions: tauri::State<'_, SyncSessions>) -> AppResult<bool> {
+    let counters = &sessions.counters;
+    counters
+        .cancelled_through
+        .fetch_max(counters.next_id.load(Ordering::SeqCst), Ordering::SeqCst);
+    Ok(counters.running.load(Ordering::SeqCst) > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sync_status, SessionCounters, SyncPhase, SyncProgress, SyncState};
+    use crate::error::AppErrorCode;
+    use std::sync::Arc;
+
+    #[test]
+    fn one_sync_at_a_time_and_the_lock_is_released_on_drop() {
+        let counters = Arc::new(SessionCounters::default());
+        assert_eq!(sync_status(&counters).state, SyncState::Idle);
+
+        let mut first = SyncProgress::with_counters(counters.clone()).expect("first sync");
+        first.add_total(3);
+        first.file(SyncPhase::Uploading, "a.json");
+        let status = sync_status(&counters);
+        assert_eq!(status.state, SyncState::Running);
+        let progress = status.progress.expect("snapshot");
+        assert_eq!((progress.name.as_str(), progress.index, progress.total), ("a.json", 1, 3));
+
+        let error = SyncProgress::with_counters(counters.clone()).err().expect("second sync refused");
+        assert_eq!(error.code, AppErrorCode::SyncInProgress);
+
+        let crashed = std::thread::spawn(move || {
+            let _first = first;
+            panic!("sync crashed");
+        })
+        .join();
+        assert!(crashed.is_err());
+        let status = sync_status(&counters);
+        assert_eq!(status.state, SyncState::Idle);
+        assert!(status.progress.is_none());
+        assert!(SyncProgress::with_counters(counters).is_ok());
+    }
+}
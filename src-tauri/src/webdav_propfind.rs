@@ -1,6 +1,9 @@
 use crate::error::{AppError, AppResult};
 use quick_xml::events::Event;
-use quick_xml::Reader;
+use quick_xml::name::{Namespace, ResolveResult};
+use quick_xml::NsReader;
+
+const DAV_NAMESPACE: &[u8] = b"DAV:";
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct WebDavResource {
@@ -27,13 +30,26 @@ impl ResponseBuilder {
             content_length: self.content_length,
         })
     }
+
+    fn set_text(&mut self, target: &str, value: &str) {
+        let value = value.trim();
+        match target {
+            // Properties such as `owner` can nest their own `href`; the response's comes first.
+            "href" if self.href.is_none() => self.href = Some(value.to_string()),
+            "getlastmodified" => self.last_modified = parse_http_date(value),
+            "getcontentlength" => self.content_length = value.parse::<u64>().ok(),
+            _ => {}
+        }
+    }
 }
 
-fn local_name(raw: &[u8]) -> &str {
-    std::str::from_utf8(raw)
-        .ok()
-        .and_then(|value| value.rsplit(':').next())
-        .unwrap_or("")
+/// Elements in another namespace are ignored; unbound or undeclared prefixes are taken as `DAV:`
+/// since some servers omit the declaration.
+fn dav_local_name(namespace: &ResolveResult, local_name: &[u8]) -> Option<String> {
+    if matches!(namespace, ResolveResult::Bound(Namespace(uri)) if *uri != DAV_NAMESPACE) {
+        return None;
+    }
+    std::str::from_utf8(local_name).ok().map(str::to_string)
 }
 
 fn parse_http_date(value: &str) -> Option<i64> {
@@ -42,72 +58,95 @@ fn parse_http_date(value: &str) -> Option<i64> {
         .map(|dt| dt.timestamp_millis())
 }
 
+fn xml_error(error: impl std::fmt::Display) -> AppError {
+    AppError::parse(format!("Failed to parse PROPFIND XML: {}", error))
+}
+
+/// Decoded path of an `href`, which servers send either as a path or as an absolute URL, without
+/// repeated or trailing slashes.
+pub fn href_path(href: &str) -> String {
+    let path = match href.split_once("://") {
+        Some((_, rest)) => rest.find('/').map(|index| &rest[index..]).unwrap_or("/"),
+        None => href,
+    };
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+    let decoded = urlencoding::decode(path)
+        .map(|value| value.into_owned())
+        .unwrap_or_else(|_| path.to_string());
+    let segments: Vec<&str> = decoded.split('/').filter(|segment| !segment.is_empty()).collect();
+    format!("/{}", segments.join("/"))
+}
+
+/// Resources of a `multistatus` PROPFIND response, each with the properties from its own
+/// `response` element. Text is entity-decoded; `href` is returned as sent.
 pub fn parse_propfind_resources(body: &str) -> AppResult<Vec<WebDavResource>> {
-    let mut reader = Reader::from_str(body);
-    reader.config_mut().trim_text(true);
+    let mut reader = NsReader::from_str(body);
 
     let mut buf = Vec::new();
     let mut resources = Vec::new();
     let mut current_response: Option<ResponseBuilder> = None;
-    let mut text_target: Option<String> = None;
+    let mut in_resource_type = false;
+    let mut text_target: Option<(String, String)> = None;
 
     loop {
-        match reader.read_event_into(&mut buf) {
-            Ok(Event::Start(event)) => {
-                let name = local_name(event.name().as_ref()).to_string();
-                match name.as_str() {
-                    "response" => current_response = Some(ResponseBuilder::default()),
-                    "collection" => {
-                        if let Some(response) = current_response.as_mut() {
-                            response.is_collection = true;
-                        }
-                    }
-                    "href" | "getlastmodified" | "getcontentlength" => {
-                        text_target = Some(name);
-                    }
-                    _ => {}
-                }
-            }
-            Ok(Event::Empty(event)) => {
-                if local_name(event.name().as_ref()) == "collection" {
+        let (namespace, event) = reader.read_resolved_event_into(&mut buf).map_err(xml_error)?;
+        match event {
+            Event::Start(event) => match dav_local_name(&namespace, event.local_name().as_ref()).as_deref() {
+                Some("response") => current_response = Some(ResponseBuilder::default()),
+                Some("resourcetype") => in_resource_type = true,
+                Some("collection") if in_resource_type => {
                     if let Some(response) = current_response.as_mut() {
                         response.is_collection = true;
                     }
                 }
+                Some(name @ ("href" | "getlastmodified" | "getcontentlength")) => {
+                    text_target = Some((name.to_string(), String::new()));
+                }
+                _ => {}
+            },
+            Event::Empty(event) => {
+                let is_collection = dav_local_name(&namespace, event.local_name().as_ref()).as_deref() == Some("collection");
+                if let (true, Some(response)) = (is_collection && in_resource_type, current_response.as_mut()) {
+                    response.is_collection = true;
+                }
+            }
+            Event::Text(event) => {
+                if let Some((_, text)) = text_target.as_mut() {
+                    text.push_str(&event.xml_content().map_err(xml_error)?);
+                }
+            }
+            Event::CData(event) => {
+                if let Some((_, text)) = text_target.as_mut() {
+                    text.push_str(&event.decode().map_err(xml_error)?);
+                }
             }
-            Ok(Event::Text(event)) => {
-                if let (Some(response), Some(target)) = (current_response.as_mut(), text_target.as_deref()) {
-                    let value = event
-                        .xml_content()
-                        .map_err(|e| AppError::parse(format!("Failed to decode PROPFIND XML text: {}", e)))?
-                        .into_owned();
-                    match target {
-                        "href" => response.href = Some(value),
-                        "getlastmodified" => response.last_modified = parse_http_date(&value),
-                        "getcontentlength" => {
-                            response.content_length = value.parse::<u64>().ok();
-                        }
-                        _ => {}
+            Event::GeneralRef(reference) => {
+                if let Some((_, text)) = text_target.as_mut() {
+                    let name = reference.decode().map_err(xml_error)?;
+                    match reference.resolve_char_ref().map_err(xml_error)? {
+                        Some(character) => text.push(character),
+                        None => match quick_xml::escape::resolve_predefined_entity(&name) {
+                            Some(value) => text.push_str(value),
+                            None => return Err(xml_error(format!("unknown entity &{};", name))),
+                        },
                     }
                 }
             }
-            Ok(Event::End(event)) => {
-                match local_name(event.name().as_ref()) {
-                    "response" => {
-                        if let Some(response) = current_response.take().and_then(ResponseBuilder::into_resource) {
-                            resources.push(response);
-                        }
+            Event::End(event) => match dav_local_name(&namespace, event.local_name().as_ref()).as_deref() {
+                Some("response") => {
+                    if let Some(response) = current_response.take().and_then(ResponseBuilder::into_resource) {
+                        resources.push(response);
                     }
-                    "href" | "getlastmodified" | "getcontentlength" => {
-                        text_target = None;
+                }
+                Some("resourcetype") => in_resource_type = false,
+                Some("href" | "getlastmodified" | "getcontentlength") => {
+                    if let (Some((target, text)), Some(response)) = (text_target.take(), current_response.as_mut()) {
+                        response.set_text(&target, &text);
                     }
-                    _ => {}
                 }
-            }
-            Ok(Event::Eof) => break,
-            Err(error) => {
-                return Err(AppError::parse(format!("Failed to parse PROPFIND XML: {}", error)));
-            }
+                _ => {}
+            },
+            Event::Eof => break,
             _ => {}
         }
 
@@ -119,7 +158,7 @@ pub fn parse_propfind_resources(body: &str) -> AppResult<Vec<WebDavResource>> {
 
 #[cfg(test)]
 mod tests {
-    use super::parse_propfind_resources;
+    use super::{href_path, parse_propfind_resources};
 
     #[test]
     fn parses_files_and_directories_with_modification_time() {
@@ -152,4 +191,39 @@ mod tests {
         assert!(resources[1].last_modified.is_some());
         assert!(resources[2].is_collection);
     }
+
+    #[test]
+    fn parses_foreign_prefixes_absolute_hrefs_and_entities() {
+        let body = r#"<?xml version="1.0"?>
+        <multistatus xmlns="DAV:" xmlns:oc="http://owncloud.org/ns" xmlns:nc="http://nextcloud.org/ns">
+          <response>
+            <href>https://cloud.example.com/remote.php/dav/files/me/sync/</href>
+            <propstat><prop><resourcetype><collection/></resourcetype></prop><status>HTTP/1.1 200 OK</status></propstat>
+          </response>
+          <response>
+            <href>https://cloud.example.com/remote.php/dav/files/me/sync/Tom%20&amp;%20Jerry.json</href>
+            <propstat>
+              <prop>
+                <resourcetype/>
+                <oc:collection/>
+                <getlastmodified>Sat, 07 Mar 2026 12:34:56 GMT</getlastmodified>
+              </prop>
+            </propstat>
+            <propstat><prop><getcontentlength/></prop><status>HTTP/1.1 404 Not Found</status></propstat>
+          </response>
+        </multistatus>"#;
+
+        let resources = parse_propfind_resources(body).expect("resources");
+        assert_eq!(resources.len(), 2);
+        assert!(resources[0].is_collection);
+        assert_eq!(
+            resources[1].href,
+            "https://cloud.example.com/remote.php/dav/files/me/sync/Tom%20&%20Jerry.json"
+        );
+        assert!(!resources[1].is_collection);
+        assert!(resources[1].last_modified.is_some());
+        assert_eq!(resources[1].content_length, None);
+        assert_eq!(href_path(&resources[1].href), "/remote.php/dav/files/me/sync/Tom & Jerry.json");
+        assert_eq!(href_path(&resources[0].href), "/remote.php/dav/files/me/sync");
+    }
 }
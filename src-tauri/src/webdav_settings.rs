@@ -0,0 +1,230 @@
+use crate::account_files::write_private_file_atomic;
+use crate::config::{get_config_file, load_config, save_config, WebDavSettings};
+use crate::error::{AppError, AppResult};
+use crate::trace;
+use crate::webdav_password_entry;
+use crate::webdav_sync::WebDavConfig;
+use serde::Serialize;
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Holds the WebDAV password when no OS keyring is available, e.g. Linux without a secret service.
+const PASSWORD_FALLBACK_FILE: &str = "webdav_password";
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PasswordStorage {
+    Keyring,
+    /// Plaintext file readable only by the user, used because the keyring failed.
+    File,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StoredWebDavConfig {
+    #[serde(flatten)]
+    pub settings: WebDavSettings,
+    #[serde(rename = "hasPassword")]
+    pub has_password: bool,
+    #[serde(rename = "passwordStorage")]
+    pub password_storage: Option<PasswordStorage>,
+}
+
+fn password_file() -> PathBuf {
+    get_config_file().with_file_name(PASSWORD_FALLBACK_FILE)
+}
+
+fn read_password_file(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().filter(|password| !password.is_empty())
+}
+
+fn remove_password_file(path: &Path) -> AppResult<()> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(AppError::io(format!("Failed to remove the WebDAV password file: {}", e))),
+    }
+}
+
+/// The saved WebDAV password and where it came from. The keyring wins; the fallback file is read
+/// when the keyring has no entry or cannot be reached.
+pub fn load_webdav_password() -> AppResult<Option<(String, PasswordStorage)>> {
+    let keyring_error = match webdav_password_entry().map(|entry| entry.get_password()) {
+        Ok(Ok(password)) => return Ok(Some((password, PasswordStorage::Keyring))),
+        Ok(Err(keyring::Error::NoEntry)) => None,
+        Ok(Err(e)) => Some(AppError::secure_storage(format!("Failed to load WebDAV password: {}", e))),
+        Err(error) => Some(error),
+    };
+    match (read_password_file(&password_file()), keyring_error) {
+        (Some(password), _) => Ok(Some((password, PasswordStorage::File))),
+        (None, Some(error)) => Err(error),
+        (None, None) => Ok(None),
+    }
+}
+
+/// Saves the WebDAV password in the keyring, or in a private plaintext file when the keyring
+/// fails. An empty password clears both. Returns where the password went.
+pub fn store_webdav_password(password: &str) -> AppResult<Option<PasswordStorage>> {
+    let path = password_file();
+    let entry = webdav_password_entry();
+    if password.trim().is_empty() {
+        if let Ok(entry) = &entry {
+            match entry.delete_credential() {
+                Ok(()) | Err(keyring::Error::NoEntry) => {}
+                Err(e) => return Err(AppError::secure_storage(format!("Failed to clear WebDAV password: {}", e))),
+            }
+        }
+        remove_password_file(&path)?;
+        return Ok(None);
+    }
+
+    let keyring_result = entry.and_then(|entry| {
+        entry
+            .set_password(password)
+            .map_err(|e| AppError::secure_storage(format!("Failed to save WebDAV password: {}", e)))
+    });
+    match keyring_result {
+        Ok(()) => {
+            remove_password_file(&path)?;
+            Ok(Some(PasswordStorage::Keyring))
+        }
+        Err(error) => {
+            trace::emit("webdav", "password_file_fallback", json!({ "error": error.message }));
+            write_private_file_atomic(&path, password.as_bytes())
+                .map_err(|e| AppError::io(format!("{}; the fallback file failed too: {}", error.message, e)))?;
+            Ok(Some(PasswordStorage::File))
+        }
+    }
+}
+
+fn config_from_settings(settings: WebDavSettings, password: String) -> WebDavConfig {
+    WebDavConfig {
+        url: settings.url,
+        username: settings.username,
+        password,
+        remote_path: settings.remote_path,
+        concurrency: settings.concurrency,
+        allow_invalid_certs: settings.allow_invalid_certs,
+        custom_ca_path: settings.custom_ca_path,
+        sync_passphrase: None,
+        snapshot_retention: settings.snapshot_retention,
+    }
+}
+
+fn settings_from_config(config: &WebDavConfig) -> AppResult<WebDavSettings> {
+    let url = config.url.trim();
+    let parsed = reqwest::Url::parse(url).map_err(|e| AppError::parse(format!("Invalid WebDAV URL '{}': {}", url, e)))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(AppError::parse("WebDAV URL must use http or https"));
+    }
+    Ok(WebDavSettings {
+        url: url.to_string(),
+        username: config.username.trim().to_string(),
+        remote_path: config.remote_path.trim().to_string(),
+        concurrency: config.concurrency,
+        allow_invalid_certs: config.allow_invalid_certs,
+        custom_ca_path: config
+            .custom_ca_path
+            .as_deref()
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+            .map(str::to_string),
+        snapshot_retention: config.snapshot_retention,
+    })
+}
+
+/// The config a sync command runs with: `config` as given, its empty password filled from the
+/// saved one, or the saved config when omitted.
+pub fn resolve_webdav_config(config: Option<WebDavConfig>) -> AppResult<WebDavConfig> {
+    let mut config = match config {
+        Some(config) => config,
+        None => {
+            let settings = load_config()
+                .webdav
+                .ok_or_else(|| AppError::not_found("WebDAV is not configured"))?;
+            config_from_settings(settings, String::new())
+        }
+    };
+    if config.password.is_empty() {
+        if let Some((password, _)) = load_webdav_password()? {
+            config.password = password;
+        }
+    }
+    Ok(config)
+}
+
+/// The saved WebDAV settings, without the password.
+#[tauri::command]
+pub fn get_webdav_config() -> AppResult<Option<StoredWebDavConfig>> {
+    let Some(settings) = load_config().webdav else {
+        return Ok(None);
+    };
+    let password_storage = load_webdav_password()?.map(|(_, storage)| storage);
+    Ok(Some(StoredWebDavConfig {
+        settings,
+        has_password: password_storage.is_some(),
+        password_storage,
+    }))
+}
+
+/// Saves the WebDAV settings. A non-empty password replaces the saved one; an empty one keeps it.
+#[tauri::command]
+pub fn set_webdav_config(config: WebDavConfig) -> AppResult<StoredWebDavConfig> {
+    let settings = settings_from_config(&config)?;
+    let mut app_config = load_config();
+    app_config.webdav = Some(settings.clone());
+    save_config(&app_config)?;
+
+    let password_storage = if config.password.is_empty() {
+        load_webdav_password()?.map(|(_, storage)| storage)
+    } else {
+        store_webdav_password(&config.password)?
+    };
+    Ok(StoredWebDavConfig {
+        settings,
+        has_password: password_storage.is_some(),
+        password_storage,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{config_from_settings, read_password_file, settings_from_config};
+    use crate::config::WebDavSettings;
+    use std::fs;
+
+    #[test]
+    fn settings_round_trip_without_the_password() {
+        let settings = WebDavSettings {
+            url: "https://dav.example.com/dav/".to_string(),
+            username: "me@example.com".to_string(),
+            remote_path: "/code-revolver/".to_string(),
+            concurrency: Some(2),
+            allow_invalid_certs: true,
+            custom_ca_path: None,
+            snapshot_retention: Some(5),
+        };
+        let mut config = config_from_settings(settings.clone(), "secret".to_string());
+        assert_eq!(config.password, "secret");
+        config.url = format!("  {}  ", config.url);
+        config.custom_ca_path = Some(" ".to_string());
+        assert_eq!(settings_from_config(&config).expect("valid"), settings);
+
+        let stored = serde_json::to_string(&settings).unwrap();
+        assert!(stored.contains("remotePath") && !stored.contains("secret"));
+
+        config.url = "ftp://dav.example.com".to_string();
+        assert!(settings_from_config(&config).is_err());
+    }
+
+    #[test]
+    fn empty_password_file_counts_as_missing() {
+        let dir = tempfile::tempdir().expect("dir");
+        let path = dir.path().join("webdav_password");
+        assert_eq!(read_password_file(&path), None);
+        fs::write(&path, "").unwrap();
+        assert_eq!(read_password_file(&path), None);
+        fs::write(&path, "pw").unwrap();
+        assert_eq!(read_password_file(&path).as_deref(), Some("pw"));
+    }
+}
@@ -0,0 +1,74 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Remote folder beside `accounts/` holding one folder per snapshot.
+pub const SNAPSHOTS_REMOTE_DIR: &str = "snapshots";
+pub const DEFAULT_SNAPSHOT_RETENTION: u32 = 10;
+/// Basic ISO 8601 in UTC, e.g. `20261016T104931.123Z`: safe in URLs and sorts by time.
+const SNAPSHOT_NAME_FORMAT: &str = "%Y%m%dT%H%M%S%.3fZ";
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct SnapshotInfo {
+    pub name: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SnapshotTarget {
+    /// The local accounts folder.
+    Local,
+    /// The remote `accounts/` folder.
+    Remote,
+}
+
+pub fn new_snapshot(now: DateTime<Utc>) -> SnapshotInfo {
+    let name = now.format(SNAPSHOT_NAME_FORMAT).to_string();
+    parse_snapshot_name(&name).expect("snapshot names parse")
+}
+
+/// The snapshot named `name`, or `None` for names this app would not have made.
+pub fn parse_snapshot_name(name: &str) -> Option<SnapshotInfo> {
+    let created = NaiveDateTime::parse_from_str(name, SNAPSHOT_NAME_FORMAT).ok()?.and_utc();
+    (created.format(SNAPSHOT_NAME_FORMAT).to_string() == name).then(|| SnapshotInfo {
+        name: name.to_string(),
+        created_at: created.timestamp_millis(),
+    })
+}
+
+/// Snapshots among the folder `names`, newest first.
+pub fn sorted_snapshots(names: impl IntoIterator<Item = String>) -> Vec<SnapshotInfo> {
+    let mut snapshots: Vec<SnapshotInfo> = names.into_iter().filter_map(|name| parse_snapshot_name(&name)).collect();
+    snapshots.sort_by_key(|snapshot| std::cmp::Reverse(snapshot.created_at));
+    snapshots
+}
+
+/// Snapshots beyond the newest `keep` (at least one) of `snapshots`, sorted newest first.
+pub fn snapshots_to_prune(snapshots: &[SnapshotInfo], keep: u32) -> &[SnapshotInfo] {
+    snapshots.get(keep.max(1) as usize..).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{new_snapshot, parse_snapshot_name, snapshots_to_prune, sorted_snapshots};
+    use chrono::TimeZone;
+
+    #[test]
+    fn names_round_trip_and_prune_oldest_first() {
+        let snapshot = new_snapshot(chrono::Utc.with_ymd_and_hms(2026, 10, 16, 10, 49, 31).unwrap());
+        assert_eq!(snapshot.name, "20261016T104931.000Z");
+        assert_eq!(parse_snapshot_name(&snapshot.name), Some(snapshot));
+        for name in ["../accounts", "20261016T104931Z", "20261016T104931.000Z/x", "latest"] {
+            assert_eq!(parse_snapshot_name(name), None, "{}", name);
+        }
+
+        let names = ["20261015T000000.000Z", "notes", "20261016T000000.000Z", "20261014T000000.000Z"];
+        let snapshots = sorted_snapshots(names.map(str::to_string));
+        let order: Vec<&str> = snapshots.iter().map(|snapshot| snapshot.name.as_str()).collect();
+        assert_eq!(order, ["20261016T000000.000Z", "20261015T000000.000Z", "20261014T000000.000Z"]);
+        assert_eq!(snapshots_to_prune(&snapshots, 2), &snapshots[2..]);
+        assert_eq!(snapshots_to_prune(&snapshots, 0), &snapshots[1..]);
+        assert!(snapshots_to_prune(&snapshots, 5).is_empty());
+    }
+}
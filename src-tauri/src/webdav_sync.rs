@@ -1,18 +1,48 @@
+use crate::account_files::{write_private_file_atomic, MAX_ACCOUNT_DIR_DEPTH};
+use crate::accounts::{is_live_account, stored_sync_passphrase};
+use crate::config::{device_name, get_config_file, load_config};
+use crate::http_client::{client_builder, describe_route};
 use crate::error::{AppError, AppErrorCode, AppResult};
+use crate::http_retry::{backoff_delay, is_retryable_status, jitter_seed, retry_after_header};
+use crate::sync_ignore::{is_hidden, SyncIgnore};
 use crate::trace;
-use crate::webdav_plan::{build_sync_preview, SyncItemType, SyncPreview, SyncPreviewEntry};
-use crate::webdav_propfind::{parse_propfind_resources, WebDavResource};
+use crate::trash_bin::remove_path;
+use crate::webdav_auth::WebDavClient;
+use crate::webdav_crypto::{is_encrypted, SyncCipher, ENCRYPTED_SUFFIX};
+use crate::webdav_progress::{SyncPhase, SyncProgress};
+use crate::webdav_plan::{
+    build_sync_preview, plan_one_way, plan_two_way, SyncDirection, SyncItemType, SyncPlanAction, SyncPlanItem,
+    SyncPreview, SyncPreviewEntry, SyncSide, SyncStateEntry, SyncStrategy,
+};
+use crate::webdav_propfind::{href_path, parse_propfind_resources, WebDavResource};
+use crate::webdav_settings::resolve_webdav_config;
+use crate::webdav_snapshots::{
+    new_snapshot, parse_snapshot_name, snapshots_to_prune, sorted_snapshots, SnapshotInfo, SnapshotTarget,
+    DEFAULT_SNAPSHOT_RETENTION, SNAPSHOTS_REMOTE_DIR,
+};
+use crate::webdav_tombstones::{get_tombstones_file, load_tombstones, save_tombstones, Tombstones};
 use crate::{get_accounts_dir, get_codex_dir, get_prompts_dir, get_skills_dir};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use sha2::{Digest, Sha256};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use futures_util::{stream, StreamExt};
 use std::fs;
-use std::path::Path;
+use std::future::Future;
+use std::path::{Component, Path};
 use std::time::Duration;
 
 const SYNC_MANIFEST_FILE: &str = ".code-revolver-sync.json";
 const SYNC_MANIFEST_VERSION: u32 = 1;
+const SYNC_STATE_FILE: &str = "webdav_sync_state.json";
+const REMOTE_TOMBSTONES_FILE: &str = ".code-revolver-tombstones.json";
+/// Remote folder for AGENTS.MD, config.toml, prompts/ and skills/, beside `accounts/`. Earlier
+/// releases uploaded them to the remote root, which downloads still fall back to.
+const CODEX_REMOTE_DIR: &str = "codex";
+const DEFAULT_TRANSFER_CONCURRENCY: u32 = 4;
+const WEBDAV_MAX_ATTEMPTS: u32 = 3;
+const WEBDAV_RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+const MAX_TRANSFER_CONCURRENCY: u32 = 16;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WebDavConfig {
@@ -21,13 +51,75 @@ pub struct WebDavConfig {
     pub password: String,
     #[serde(rename = "remotePath")]
     pub remote_path: String,
+    /// Files transferred at once within a folder; defaults to 4.
+    #[serde(default)]
+    pub concurrency: Option<u32>,
+    /// Skips TLS certificate and hostname checks, for self-signed servers. Off by default.
+    #[serde(default, rename = "allowInvalidCerts")]
+    pub allow_invalid_certs: bool,
+    /// PEM (or DER) certificate trusted in addition to the system roots.
+    #[serde(default, rename = "customCaPath")]
+    pub custom_ca_path: Option<String>,
+    /// Encrypts account files before upload; defaults to the passphrase saved with
+    /// `set_sync_passphrase`.
+    #[serde(default, rename = "syncPassphrase")]
+    pub sync_passphrase: Option<String>,
+    /// Snapshots kept on the server; defaults to 10.
+    #[serde(default, rename = "snapshotRetention")]
+    pub snapshot_retention: Option<u32>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SyncResult {
     pub uploaded: Vec<String>,
     pub downloaded: Vec<String>,
     pub errors: Vec<String>,
+    /// Changed files the sync strategy kept on the other side.
+    #[serde(default)]
+    pub skipped: Vec<String>,
+    /// Files changed on both sides since the last bidirectional sync; neither copy was touched
+    /// unless `overwritten` is set.
+    #[serde(default)]
+    pub conflicts: Vec<SyncConflict>,
+    /// Files removed because they were deleted on another machine after their last change:
+    /// remote copies on upload, local copies (to the trash) on download, either in both directions.
+    #[serde(default)]
+    pub deleted: Vec<String>,
+    /// Account files the sync would change, or changed; a dry run fills only this.
+    #[serde(default)]
+    pub plan: Vec<SyncPlanItem>,
+    /// `cancel_sync` stopped the sync; the other fields list what was done before.
+    #[serde(default)]
+    pub cancelled: bool,
+    /// Prompt and skill paths left out of the upload by `.syncignore`, the built-in patterns or
+    /// the size limit; folders end with `/` and were not looked into.
+    #[serde(default)]
+    pub ignored: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncConflict {
+    /// Path below `accounts/`.
+    pub name: String,
+    #[serde(rename = "localModifiedAt")]
+    pub local_modified_at: Option<i64>,
+    #[serde(rename = "remoteModifiedAt")]
+    pub remote_modified_at: Option<i64>,
+    /// Device that last uploaded the remote copy, when the manifest records it.
+    #[serde(default, rename = "remoteDevice")]
+    pub remote_device: Option<String>,
+    /// The local change was downloaded over anyway: a warning that another device uploaded the
+    /// remote copy after it.
+    #[serde(default)]
+    pub overwritten: bool,
+}
+
+/// Per-remote record of each file's last bidirectional sync, keyed by `remote_url` and then by
+/// manifest key.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SyncState {
+    #[serde(default)]
+    remotes: HashMap<String, HashMap<String, SyncStateEntry>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,6 +141,9 @@ struct SyncManifest {
     generated_at: i64,
     #[serde(default)]
     entries: HashMap<String, SyncManifestEntry>,
+    /// This machine's device name, recorded with each entry it upserts.
+    #[serde(skip)]
+    device: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -56,6 +151,11 @@ struct SyncManifestEntry {
     hash: String,
     #[serde(rename = "modifiedAt")]
     modified_at: i64,
+    /// Device that uploaded the file; missing in entries written before devices were recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    device: Option<String>,
+    #[serde(default, rename = "uploadedAt", skip_serializing_if = "Option::is_none")]
+    uploaded_at: Option<i64>,
 }
 
 impl Default for CodexSyncConfig {
@@ -69,11 +169,25 @@ impl Default for CodexSyncConfig {
     }
 }
 
-fn webdav_client() -> AppResult<reqwest::Client> {
-    reqwest::Client::builder()
+fn webdav_client(config: &WebDavConfig) -> AppResult<WebDavClient> {
+    let mut builder = client_builder(&load_config().proxy)?
         .connect_timeout(Duration::from_secs(15))
         .timeout(Duration::from_secs(60))
+        .danger_accept_invalid_certs(config.allow_invalid_certs);
+    if let Some(path) = config.custom_ca_path.as_deref().map(str::trim).filter(|path| !path.is_empty()) {
+        let pem = fs::read(path).map_err(|e| AppError::io(format!("Failed to read CA certificate '{}': {}", path, e)))?;
+        let certificates = reqwest::Certificate::from_pem_bundle(&pem)
+            .ok()
+            .filter(|certificates| !certificates.is_empty())
+            .or_else(|| reqwest::Certificate::from_der(&pem).ok().map(|certificate| vec![certificate]))
+            .ok_or_else(|| AppError::parse(format!("'{}' is not a PEM or DER certificate", path)))?;
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+    builder
         .build()
+        .map(WebDavClient::new)
         .map_err(|e| AppError::network(format!("Failed to create WebDAV client: {}", e)))
 }
 
@@ -90,14 +204,12 @@ fn normalize_remote_path(path: &str) -> String {
 
 fn scoped_config(config: &WebDavConfig, relative_dir: &str) -> WebDavConfig {
     WebDavConfig {
-        url: config.url.clone(),
-        username: config.username.clone(),
-        password: config.password.clone(),
         remote_path: format!(
             "{}{}/",
             normalize_remote_path(&config.remote_path),
             relative_dir.trim_matches('/'),
         ),
+        ..config.clone()
     }
 }
 
@@ -119,6 +231,59 @@ fn http_status_error(context: &str, status: reqwest::StatusCode) -> AppError {
     }
 }
 
+/// Sends the request `build` makes, retrying network failures, 423 Locked, 429 and 5xx with
+/// backoff. Any other response, 401/403/404 included, is returned for the caller to judge.
+/// `context` names the operation in errors, which count the attempts when retries ran out.
+async fn send_with_retry(
+    client: &WebDavClient,
+    config: &WebDavConfig,
+    context: &str,
+    build: impl Fn() -> reqwest::RequestBuilder,
+) -> AppResult<reqwest::Response> {
+    let mut attempt = 1;
+    loop {
+        let (error, retry_after) = match client.send(config, build()).await {
+            Ok(response) if response.status() != reqwest::StatusCode::LOCKED && !is_retryable_status(response.status()) => {
+                return Ok(response);
+            }
+            Ok(response) => (http_status_error(context, response.status()), retry_after_header(response.headers())),
+            Err(e) => (AppError::network(format!("{}: {}", context, e)), None),
+        };
+
+        if attempt >= WEBDAV_MAX_ATTEMPTS {
+            trace::emit("webdav", "gave_up", json!({ "attempts": attempt, "error": error.message }));
+            return Err(AppError {
+                message: format!("{} (after {} attempts)", error.message, attempt),
+                ..error
+            });
+        }
+        let delay = backoff_delay(attempt, WEBDAV_RETRY_BASE_DELAY, retry_after, jitter_seed());
+        trace::emit(
+            "webdav",
+            "retry",
+            json!({ "attempt": attempt, "delayMs": delay.as_millis() as u64, "error": error.message }),
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// Whether `error` failed TLS certificate verification, e.g. a self-signed or expired certificate.
+fn is_certificate_error(error: &reqwest::Error) -> bool {
+    let mut source: Option<&dyn std::error::Error> = Some(error);
+    while let Some(error) = source {
+        let message = error.to_string().to_ascii_lowercase();
+        if ["certificate", "self signed", "self-signed", "unknownissuer"]
+            .iter()
+            .any(|needle| message.contains(needle))
+        {
+            return true;
+        }
+        source = error.source();
+    }
+    false
+}
+
 fn is_not_found(error: &AppError) -> bool {
     error.code == AppErrorCode::NotFound || error.message.contains("404")
 }
@@ -130,14 +295,94 @@ fn path_modified_at(path: &Path) -> Option<i64> {
     Some(elapsed.as_millis() as i64)
 }
 
-fn hash_content(content: &str) -> String {
+fn path_size(path: &Path) -> Option<u64> {
+    fs::metadata(path).ok().map(|metadata| metadata.len())
+}
+
+/// Whether `cancel_sync` stopped this sync, marking `result` cancelled.
+fn sync_cancelled(progress: &SyncProgress, result: &mut SyncResult) -> bool {
+    if progress.is_cancelled() {
+        result.cancelled = true;
+    }
+    result.cancelled
+}
+
+fn transfer_concurrency(config: &WebDavConfig) -> usize {
+    config
+        .concurrency
+        .unwrap_or(DEFAULT_TRANSFER_CONCURRENCY)
+        .clamp(1, MAX_TRANSFER_CONCURRENCY) as usize
+}
+
+/// Runs `transfer` for `items`, `concurrency` at a time, reporting each finished item to
+/// `progress` as `describe` names it. A failed transfer does not stop the others; a cancelled sync
+/// starts no more. Outcomes come back in the order of `items`, leaving out those never started.
+async fn run_transfers<'a, T, O, Fut>(
+    items: &'a [T],
+    concurrency: usize,
+    transfer: impl Fn(&'a T) -> Fut,
+    describe: impl Fn(&T) -> (SyncPhase, &str),
+    progress: &mut SyncProgress,
+    result: &mut SyncResult,
+) -> Vec<(&'a T, O)>
+where
+    Fut: Future<Output = O>,
+{
+    let mut outcomes = Vec::new();
+    if sync_cancelled(progress, result) {
+        return Vec::new();
+    }
+    // Built up front (futures do nothing until polled): a mapping closure here trips the `Send`
+    // check of the command futures.
+    let mut pending = Vec::with_capacity(items.len());
+    for (index, item) in items.iter().enumerate() {
+        let transfer = transfer(item);
+        pending.push(async move { (index, transfer.await) });
+    }
+    let mut transfers = stream::iter(pending).buffer_unordered(concurrency);
+    while let Some((index, outcome)) = transfers.next().await {
+        let (phase, name) = describe(&items[index]);
+        progress.file(phase, name);
+        outcomes.push((index, outcome));
+        if sync_cancelled(progress, result) {
+            break;
+        }
+    }
+    outcomes.sort_by_key(|(index, _)| *index);
+    outcomes.into_iter().map(|(index, outcome)| (&items[index], outcome)).collect()
+}
+
+/// Lowercase hex SHA-256 of `content`.
+pub(crate) fn hash_content(content: &[u8]) -> String {
     let mut hasher = Sha256::new();
-    hasher.update(content.as_bytes());
+    hasher.update(content);
     format!("{:x}", hasher.finalize())
 }
 
 fn hash_file(path: &Path) -> Option<String> {
-    fs::read_to_string(path).ok().map(|content| hash_content(&content))
+    fs::read(path).ok().map(|content| hash_content(&content))
+}
+
+/// Content-Type for an upload of `filename`; unknown files go as `application/octet-stream`.
+fn content_type(filename: &str) -> &'static str {
+    let extension = Path::new(filename)
+        .extension()
+        .and_then(|value| value.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match extension.as_str() {
+        "json" => "application/json; charset=utf-8",
+        "md" | "markdown" => "text/markdown; charset=utf-8",
+        "toml" => "application/toml; charset=utf-8",
+        "txt" | "py" | "sh" | "js" | "ts" | "yaml" | "yml" => "text/plain; charset=utf-8",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        _ => "application/octet-stream",
+    }
 }
 
 fn manifest_key(prefix: &str, name: &str) -> String {
@@ -146,6 +391,7 @@ fn manifest_key(prefix: &str, name: &str) -> String {
 
 fn collect_local_tree(
     dir: &Path,
+    ignore: &SyncIgnore,
     item_type: SyncItemType,
     prefix: &str,
     entries: &mut Vec<SyncPreviewEntry>,
@@ -159,7 +405,7 @@ fn collect_local_tree(
         let Some(name) = path.file_name().and_then(|value| value.to_str()) else {
             continue;
         };
-        if name.starts_with("__") || name.starts_with('.') {
+        if is_hidden(name) || ignore.is_ignored(&path, name, path.is_dir()) {
             continue;
         }
 
@@ -167,6 +413,7 @@ fn collect_local_tree(
         if path.is_dir() {
             collect_local_tree(
                 &path,
+                &ignore.descend(&path, name),
                 item_type.clone(),
                 &format!("{}/", next_name.trim_end_matches('/')),
                 entries,
@@ -176,35 +423,75 @@ fn collect_local_tree(
                 name: next_name,
                 item_type: item_type.clone(),
                 modified_at: path_modified_at(&path),
+                size: path_size(&path),
                 hash: hash_file(&path),
             });
         }
     }
 }
 
-fn collect_accounts_preview_entries() -> Vec<SyncPreviewEntry> {
-    let dir = get_accounts_dir();
-    let mut entries = Vec::new();
+/// Files below `dir` that `upload_dir_recursive` visits.
+fn count_local_files(dir: &Path, ignore: &SyncIgnore) -> usize {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return 0;
+    };
+    read_dir
+        .flatten()
+        .map(|entry| {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            let is_dir = path.is_dir();
+            if is_hidden(&name) || ignore.is_ignored(&path, &name, is_dir) {
+                0
+            } else if is_dir {
+                count_local_files(&path, &ignore.descend(&path, &name))
+            } else {
+                1
+            }
+        })
+        .sum()
+}
+
+/// Account folders (groups and `archived/`) are mirrored remotely, down to `MAX_ACCOUNT_DIR_DEPTH`.
+fn is_synced_account_dir(path: &Path, depth: usize) -> bool {
+    depth < MAX_ACCOUNT_DIR_DEPTH
+        && path.is_dir()
+        && path
+            .file_name()
+            .and_then(|value| value.to_str())
+            .is_some_and(|name| !name.starts_with('.'))
+}
+
+fn collect_account_json_entries(dir: &Path, prefix: &str, depth: usize, entries: &mut Vec<SyncPreviewEntry>) {
     let Ok(read_dir) = fs::read_dir(dir) else {
-        return entries;
+        return;
     };
 
     for entry in read_dir.flatten() {
         let path = entry.path();
-        if path.extension().and_then(|value| value.to_str()) != Some("json") {
-            continue;
-        }
         let Some(name) = path.file_name().and_then(|value| value.to_str()) else {
             continue;
         };
+        if is_synced_account_dir(&path, depth) {
+            collect_account_json_entries(&path, &format!("{}{}/", prefix, name), depth + 1, entries);
+            continue;
+        }
+        if path.extension().and_then(|value| value.to_str()) != Some("json") {
+            continue;
+        }
         entries.push(SyncPreviewEntry {
-            name: name.to_string(),
+            name: format!("{}{}", prefix, name),
             item_type: SyncItemType::Account,
             modified_at: path_modified_at(&path),
+            size: path_size(&path),
             hash: hash_file(&path),
         });
     }
+}
 
+fn collect_accounts_preview_entries() -> Vec<SyncPreviewEntry> {
+    let mut entries = Vec::new();
+    collect_account_json_entries(&get_accounts_dir(), "", 0, &mut entries);
     entries
 }
 
@@ -219,6 +506,7 @@ fn collect_codex_preview_entries(sync_config: &CodexSyncConfig) -> Vec<SyncPrevi
                 name: "AGENTS.MD".to_string(),
                 item_type: SyncItemType::Agents,
                 modified_at: path_modified_at(&path),
+                size: path_size(&path),
                 hash: hash_file(&path),
             });
         }
@@ -231,14 +519,18 @@ fn collect_codex_preview_entries(sync_config: &CodexSyncConfig) -> Vec<SyncPrevi
                 name: "config.toml".to_string(),
                 item_type: SyncItemType::Config,
                 modified_at: path_modified_at(&path),
+                size: path_size(&path),
                 hash: hash_file(&path),
             });
         }
     }
 
+    let ignore = SyncIgnore::load(&codex_dir);
     if sync_config.sync_prompts {
+        let prompts_dir = get_prompts_dir();
         collect_local_tree(
-            &get_prompts_dir(),
+            &prompts_dir,
+            &ignore.descend(&prompts_dir, "prompts"),
             SyncItemType::Prompt,
             "prompts/",
             &mut entries,
@@ -246,8 +538,10 @@ fn collect_codex_preview_entries(sync_config: &CodexSyncConfig) -> Vec<SyncPrevi
     }
 
     if sync_config.sync_skills {
+        let skills_dir = get_skills_dir();
         collect_local_tree(
-            &get_skills_dir(),
+            &skills_dir,
+            &ignore.descend(&skills_dir, "skills"),
             SyncItemType::Skill,
             "skills/",
             &mut entries,
@@ -257,45 +551,54 @@ fn collect_codex_preview_entries(sync_config: &CodexSyncConfig) -> Vec<SyncPrevi
     entries
 }
 
-fn relative_resource_name(resource: &WebDavResource, remote_path: &str) -> Option<String> {
-    let decoded = urlencoding::decode(&resource.href)
-        .map(|value| value.into_owned())
-        .unwrap_or_else(|_| resource.href.clone());
-    let remote_base = normalize_remote_path(remote_path).trim_end_matches('/').to_string();
-
-    if decoded.trim_end_matches('/').ends_with(&remote_base) {
-        return None;
-    }
+/// Whether `name` is one plain path component on every platform, so joining it to a local dir
+/// stays inside that dir: no `.`, `..`, backslash, NUL or drive prefix.
+fn is_plain_file_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    !name.contains(['\\', '\0', ':'])
+        && matches!(components.next(), Some(Component::Normal(_)))
+        && components.next().is_none()
+}
 
-    let trimmed = decoded.trim_end_matches('/');
-    let leaf = trimmed.rsplit('/').next()?.trim();
-    if leaf.is_empty() || leaf == SYNC_MANIFEST_FILE {
+/// Name of `resource` directly below the remote dir of `config`, or `None` for the dir itself, the
+/// sync manifest and names that are not a plain file name. Servers that rewrite the mount prefix
+/// are matched by the trailing path.
+fn relative_resource_name(resource: &WebDavResource, config: &WebDavConfig) -> Option<String> {
+    let path = href_path(&resource.href);
+    let remote_base = href_path(&remote_url(config, None));
+    let remote_dir = href_path(&config.remote_path);
+
+    let name = match path.strip_prefix(&remote_base).and_then(|rest| rest.strip_prefix('/')) {
+        Some(rest) => rest.to_string(),
+        None if path == remote_base || path.ends_with(&remote_dir) => return None,
+        None => path.rsplit('/').next()?.to_string(),
+    };
+    if !is_plain_file_name(&name) || name == SYNC_MANIFEST_FILE || name == REMOTE_TOMBSTONES_FILE {
         return None;
     }
 
-    Some(leaf.to_string())
+    Some(name)
 }
 
 async fn propfind(
-    client: &reqwest::Client,
+    client: &WebDavClient,
     config: &WebDavConfig,
     depth: u8,
 ) -> AppResult<Vec<WebDavResource>> {
-    let response = client
-        .request(
-            reqwest::Method::from_bytes(b"PROPFIND").expect("PROPFIND"),
-            remote_url(config, None),
-        )
-        .basic_auth(&config.username, Some(&config.password))
-        .header("Depth", depth.to_string())
-        .header("Content-Type", "application/xml; charset=utf-8")
-        .header("Accept", "*/*")
-        .body(
-            r#"<?xml version="1.0" encoding="utf-8"?><propfind xmlns="DAV:"><prop><displayname/><resourcetype/><getcontentlength/><getlastmodified/></prop></propfind>"#,
-        )
-        .send()
-        .await
-        .map_err(|e| AppError::network(format!("Failed to query WebDAV directory: {}", e)))?;
+    let response = send_with_retry(client, config, "Failed to query WebDAV directory", || {
+        client
+            .request(
+                reqwest::Method::from_bytes(b"PROPFIND").expect("PROPFIND"),
+                remote_url(config, None),
+            )
+            .header("Depth", depth.to_string())
+            .header("Content-Type", "application/xml; charset=utf-8")
+            .header("Accept", "*/*")
+            .body(
+                r#"<?xml version="1.0" encoding="utf-8"?><propfind xmlns="DAV:"><prop><displayname/><resourcetype/><getcontentlength/><getlastmodified/></prop></propfind>"#,
+            )
+    })
+    .await?;
 
     let status = response.status();
     if !status.is_success() && status.as_u16() != 207 {
@@ -310,11 +613,18 @@ async fn propfind(
     parse_propfind_resources(&body)
 }
 
-async fn webdav_ensure_dir(client: &reqwest::Client, config: &WebDavConfig) -> AppResult<()> {
+async fn remote_exists(client: &WebDavClient, config: &WebDavConfig) -> AppResult<bool> {
+    match propfind(client, config, 0).await {
+        Ok(_) => Ok(true),
+        Err(error) if is_not_found(&error) => Ok(false),
+        Err(error) => Err(error),
+    }
+}
+
+async fn webdav_ensure_dir(client: &WebDavClient, config: &WebDavConfig) -> AppResult<()> {
+    let request = client.request(reqwest::Method::from_bytes(b"MKCOL").expect("MKCOL"), remote_url(config, None));
     let response = client
-        .request(reqwest::Method::from_bytes(b"MKCOL").expect("MKCOL"), remote_url(config, None))
-        .basic_auth(&config.username, Some(&config.password))
-        .send()
+        .send(config, request)
         .await
         .map_err(|e| AppError::network(format!("Failed to create WebDAV directory: {}", e)))?;
 
@@ -327,19 +637,18 @@ async fn webdav_ensure_dir(client: &reqwest::Client, config: &WebDavConfig) -> A
 }
 
 async fn webdav_upload(
-    client: &reqwest::Client,
+    client: &WebDavClient,
     config: &WebDavConfig,
     filename: &str,
-    content: &str,
+    content: &[u8],
 ) -> AppResult<()> {
-    let response = client
-        .put(remote_url(config, Some(filename)))
-        .basic_auth(&config.username, Some(&config.password))
-        .header("Content-Type", "application/json; charset=utf-8")
-        .body(content.to_string())
-        .send()
-        .await
-        .map_err(|e| AppError::network(format!("Upload failed for '{}': {}", filename, e)))?;
+    let response = send_with_retry(client, config, &format!("Upload failed for '{}'", filename), || {
+        client
+            .request(reqwest::Method::PUT, remote_url(config, Some(filename)))
+            .header("Content-Type", content_type(filename))
+            .body(content.to_vec())
+    })
+    .await?;
 
     if response.status().is_success() || response.status().as_u16() == 201 {
         return Ok(());
@@ -352,22 +661,22 @@ async fn webdav_upload(
 }
 
 async fn webdav_download(
-    client: &reqwest::Client,
+    client: &WebDavClient,
     config: &WebDavConfig,
     filename: &str,
-) -> AppResult<String> {
-    let response = client
-        .get(remote_url(config, Some(filename)))
-        .basic_auth(&config.username, Some(&config.password))
-        .header("Accept", "*/*")
-        .send()
-        .await
-        .map_err(|e| AppError::network(format!("Download failed for '{}': {}", filename, e)))?;
+) -> AppResult<Vec<u8>> {
+    let response = send_with_retry(client, config, &format!("Download failed for '{}'", filename), || {
+        client
+            .request(reqwest::Method::GET, remote_url(config, Some(filename)))
+            .header("Accept", "*/*")
+    })
+    .await?;
 
     if response.status().is_success() {
         return response
-            .text()
+            .bytes()
             .await
+            .map(|bytes| bytes.to_vec())
             .map_err(|e| AppError::network(format!("Failed to read '{}' response: {}", filename, e)));
     }
 
@@ -377,8 +686,30 @@ async fn webdav_download(
     ))
 }
 
+/// `webdav_download` for the JSON and markdown files, which must be UTF-8.
+async fn webdav_download_text(client: &WebDavClient, config: &WebDavConfig, filename: &str) -> AppResult<String> {
+    let content = webdav_download(client, config, filename).await?;
+    String::from_utf8(content).map_err(|_| AppError::parse(format!("'{}' is not valid UTF-8", filename)))
+}
+
+async fn webdav_delete(client: &WebDavClient, config: &WebDavConfig, filename: &str) -> AppResult<()> {
+    let response = client
+        .send(config, client.request(reqwest::Method::DELETE, remote_url(config, Some(filename))))
+        .await
+        .map_err(|e| AppError::network(format!("Delete failed for '{}': {}", filename, e)))?;
+
+    if response.status().is_success() || response.status().as_u16() == 404 {
+        return Ok(());
+    }
+
+    Err(http_status_error(
+        &format!("Delete failed for '{}'", filename),
+        response.status(),
+    ))
+}
+
 async fn list_remote_entries(
-    client: &reqwest::Client,
+    client: &WebDavClient,
     root_config: &WebDavConfig,
     item_type: SyncItemType,
     prefix: &str,
@@ -396,7 +727,7 @@ async fn list_remote_entries(
         };
 
         for resource in resources {
-            let Some(name) = relative_resource_name(&resource, &config.remote_path) else {
+            let Some(name) = relative_resource_name(&resource, &config) else {
                 continue;
             };
 
@@ -413,6 +744,7 @@ async fn list_remote_entries(
                 name: format!("{}{}", current_prefix, name),
                 item_type: item_type.clone(),
                 modified_at: resource.last_modified,
+                size: resource.content_length,
                 hash: manifest
                     .entries
                     .get(&manifest_key(&current_manifest_prefix, &name))
@@ -424,106 +756,215 @@ async fn list_remote_entries(
     Ok(entries)
 }
 
-async fn list_remote_root_files(client: &reqwest::Client, config: &WebDavConfig) -> AppResult<Vec<WebDavResource>> {
+async fn list_remote_root_files(client: &WebDavClient, config: &WebDavConfig) -> AppResult<Vec<WebDavResource>> {
     propfind(client, config, 1).await
 }
 
-async fn load_sync_manifest(client: &reqwest::Client, config: &WebDavConfig) -> AppResult<SyncManifest> {
-    match webdav_download(client, config, SYNC_MANIFEST_FILE).await {
+fn empty_manifest() -> SyncManifest {
+    SyncManifest {
+        version: SYNC_MANIFEST_VERSION,
+        generated_at: chrono::Utc::now().timestamp_millis(),
+        entries: HashMap::new(),
+        device: device_name(&load_config()),
+    }
+}
+
+async fn load_sync_manifest(client: &WebDavClient, config: &WebDavConfig) -> AppResult<SyncManifest> {
+    match webdav_download_text(client, config, SYNC_MANIFEST_FILE).await {
         Ok(content) => serde_json::from_str::<SyncManifest>(&content)
+            .map(|manifest| SyncManifest {
+                device: device_name(&load_config()),
+                ..manifest
+            })
             .map_err(|e| AppError::parse(format!("Failed to parse sync manifest: {}", e))),
-        Err(error) if is_not_found(&error) => Ok(SyncManifest {
-            version: SYNC_MANIFEST_VERSION,
-            generated_at: chrono::Utc::now().timestamp_millis(),
-            entries: HashMap::new(),
-        }),
+        Err(error) if is_not_found(&error) => Ok(empty_manifest()),
         Err(error) => Err(error),
     }
 }
 
 async fn write_sync_manifest(
-    client: &reqwest::Client,
+    client: &WebDavClient,
     config: &WebDavConfig,
     manifest: &SyncManifest,
 ) -> AppResult<()> {
     let body = serde_json::to_string_pretty(manifest)
         .map_err(|e| AppError::parse(format!("Failed to serialize sync manifest: {}", e)))?;
-    webdav_upload(client, config, SYNC_MANIFEST_FILE, &body).await
+    webdav_upload(client, config, SYNC_MANIFEST_FILE, body.as_bytes()).await
 }
 
 fn upsert_manifest_entry(manifest: &mut SyncManifest, key: String, hash: String, modified_at: i64) {
     manifest.version = SYNC_MANIFEST_VERSION;
     manifest.generated_at = chrono::Utc::now().timestamp_millis();
-    manifest.entries.insert(key, SyncManifestEntry { hash, modified_at });
+    let entry = SyncManifestEntry {
+        hash,
+        modified_at,
+        device: Some(manifest.device.clone()),
+        uploaded_at: Some(manifest.generated_at),
+    };
+    manifest.entries.insert(key, entry);
+}
+
+/// Warnings for `plan` downloads that replace a local change with a copy another device uploaded
+/// after it. Files unchanged since their last bidirectional sync (`synced`, by name) are left out.
+fn overwrite_warnings(
+    manifest: &SyncManifest,
+    plan: &[SyncPlanItem],
+    local: &HashMap<String, SyncPreviewEntry>,
+    synced: &HashMap<String, SyncStateEntry>,
+) -> Vec<SyncConflict> {
+    let warning = |item: &SyncPlanItem| {
+        let local = local.get(&item.name)?;
+        let local_hash = local.hash.as_ref()?;
+        let entry = manifest.entries.get(&manifest_key("accounts/", &item.name))?;
+        let device = entry.device.as_ref().filter(|device| **device != manifest.device)?;
+        let changed = entry.hash != *local_hash && synced.get(&item.name).is_none_or(|state| state.hash != *local_hash);
+        (changed && entry.uploaded_at? > local.modified_at?).then(|| SyncConflict {
+            name: item.name.clone(),
+            local_modified_at: local.modified_at,
+            remote_modified_at: item.remote_modified_at,
+            remote_device: Some(device.clone()),
+            overwritten: true,
+        })
+    };
+    plan.iter()
+        .filter(|item| item.action == SyncPlanAction::Download)
+        .filter_map(warning)
+        .collect()
+}
+
+/// Account entries of one remote's sync state, keyed by path below `accounts/`.
+fn account_states(file_states: &HashMap<String, SyncStateEntry>) -> HashMap<String, SyncStateEntry> {
+    file_states
+        .iter()
+        .filter_map(|(key, entry)| key.strip_prefix("accounts/").map(|name| (name.to_string(), entry.clone())))
+        .collect()
+}
+
+/// A changed local file read for upload.
+struct PendingUpload {
+    name: String,
+    key: String,
+    content: Vec<u8>,
+    hash: String,
+    modified_at: i64,
 }
 
+/// Uploads the changed files below `dir`, `transfer_concurrency` at a time within each folder,
+/// leaving out what `ignore` matches. Each remote folder is created before anything inside it is
+/// uploaded.
+#[allow(clippy::too_many_arguments)]
 async fn upload_dir_recursive(
-    client: &reqwest::Client,
+    client: &WebDavClient,
     config: &WebDavConfig,
     dir: &Path,
+    ignore: &SyncIgnore,
     manifest_prefix: &str,
     manifest: &mut SyncManifest,
+    progress: &mut SyncProgress,
     result: &mut SyncResult,
 ) {
     let Ok(entries) = fs::read_dir(dir) else {
         return;
     };
+    let mut paths: Vec<_> = entries.flatten().map(|entry| entry.path()).collect();
+    paths.sort();
 
-    for entry in entries.flatten() {
-        let path = entry.path();
-        let Some(name) = path.file_name().and_then(|value| value.to_str()) else {
+    let mut uploads = Vec::new();
+    let mut dirs = Vec::new();
+    for path in paths {
+        let Some(name) = path.file_name().and_then(|value| value.to_str()).map(str::to_string) else {
             continue;
         };
-        if name.starts_with("__") || name.starts_with('.') {
+        let is_dir = path.is_dir();
+        if is_hidden(&name) {
             continue;
         }
-
-        if path.is_dir() {
-            let nested_config = scoped_config(config, name);
-            if let Err(error) = webdav_ensure_dir(client, &nested_config).await {
-                result.errors.push(format!("{}: {}", nested_config.remote_path, error));
-                continue;
-            }
-            let nested_prefix = format!("{}/", manifest_key(manifest_prefix, name).trim_end_matches('/'));
-            Box::pin(upload_dir_recursive(client, &nested_config, &path, &nested_prefix, manifest, result)).await;
+        if ignore.is_ignored(&path, &name, is_dir) {
+            let suffix = if is_dir { "/" } else { "" };
+            result.ignored.push(format!("{}{}", ignore.path_of(&name), suffix));
+            continue;
+        }
+        if is_dir {
+            dirs.push((name, path));
             continue;
         }
 
-        match fs::read_to_string(&path) {
+        let key = manifest_key(manifest_prefix, &name);
+        match fs::read(&path) {
             Ok(content) => {
-                let content_hash = hash_content(&content);
-                let key = manifest_key(manifest_prefix, name);
-                if manifest.entries.get(&key).is_some_and(|entry| entry.hash == content_hash) {
+                let hash = hash_content(&content);
+                if manifest.entries.get(&key).is_some_and(|entry| entry.hash == hash) {
+                    progress.file(SyncPhase::Uploading, &key);
                     continue;
                 }
-                match webdav_upload(client, config, name, &content).await {
-                    Ok(()) => {
-                        result.uploaded.push(format!("{}{}", config.remote_path, name));
-                        upsert_manifest_entry(
-                            manifest,
-                            key,
-                            content_hash,
-                            path_modified_at(&path).unwrap_or_else(|| chrono::Utc::now().timestamp_millis()),
-                        );
-                    }
-                    Err(error) => result.errors.push(format!("{}: {}", name, error)),
-                }
+                let modified_at = path_modified_at(&path).unwrap_or_else(|| chrono::Utc::now().timestamp_millis());
+                uploads.push(PendingUpload { name, key, content, hash, modified_at });
             }
-            Err(error) => result
-                .errors
-                .push(format!("{}: Failed to read file: {}", path.to_string_lossy(), error)),
+            Err(error) => {
+                progress.file(SyncPhase::Uploading, &key);
+                result
+                    .errors
+                    .push(format!("{}: Failed to read file: {}", path.to_string_lossy(), error));
+            }
+        }
+    }
+
+    let uploaded = run_transfers(
+        &uploads,
+        transfer_concurrency(config),
+        |upload| webdav_upload(client, config, &upload.name, &upload.content),
+        |upload| (SyncPhase::Uploading, upload.key.as_str()),
+        progress,
+        result,
+    )
+    .await;
+    for (upload, outcome) in uploaded {
+        match outcome {
+            Ok(()) => {
+                result.uploaded.push(format!("{}{}", config.remote_path, upload.name));
+                upsert_manifest_entry(manifest, upload.key.clone(), upload.hash.clone(), upload.modified_at);
+            }
+            Err(error) => result.errors.push(format!("{}: {}", upload.name, error)),
+        }
+    }
+
+    for (name, path) in dirs {
+        if sync_cancelled(progress, result) {
+            return;
+        }
+        let nested_config = scoped_config(config, &name);
+        if let Err(error) = webdav_ensure_dir(client, &nested_config).await {
+            result.errors.push(format!("{}: {}", nested_config.remote_path, error));
+            continue;
         }
+        let nested_prefix = format!("{}/", manifest_key(manifest_prefix, &name).trim_end_matches('/'));
+        let nested_ignore = ignore.descend(&path, &name);
+        let nested = upload_dir_recursive(
+            client,
+            &nested_config,
+            &path,
+            &nested_ignore,
+            &nested_prefix,
+            manifest,
+            progress,
+            result,
+        );
+        Box::pin(nested).await;
     }
 }
 
+/// Downloads the files below the remote `config` dir into `local_dir`, `transfer_concurrency` at a
+/// time within each folder.
 async fn download_dir_recursive(
-    client: &reqwest::Client,
+    client: &WebDavClient,
     config: &WebDavConfig,
     local_dir: &Path,
     manifest_prefix: &str,
     manifest: &SyncManifest,
+    progress: &mut SyncProgress,
     result: &mut SyncResult,
 ) {
+    progress.listing(manifest_prefix);
     let resources = match propfind(client, config, 1).await {
         Ok(value) => value,
         Err(error) if is_not_found(&error) => return,
@@ -533,31 +974,37 @@ async fn download_dir_recursive(
         }
     };
 
+    let mut files = Vec::new();
+    let mut dirs = Vec::new();
     for resource in resources {
-        let Some(name) = relative_resource_name(&resource, &config.remote_path) else {
+        let Some(name) = relative_resource_name(&resource, config) else {
             continue;
         };
-
         if resource.is_collection {
-            let nested_local = local_dir.join(&name);
-            if let Err(error) = fs::create_dir_all(&nested_local) {
-                result
-                    .errors
-                    .push(format!("{}: Failed to create local directory: {}", nested_local.to_string_lossy(), error));
-                continue;
-            }
-            let nested_config = scoped_config(config, &name);
-            let nested_prefix = format!("{}/", manifest_key(manifest_prefix, &name).trim_end_matches('/'));
-            Box::pin(download_dir_recursive(client, &nested_config, &nested_local, &nested_prefix, manifest, result)).await;
-            continue;
+            dirs.push(name);
+        } else {
+            files.push((manifest_key(manifest_prefix, &name), name));
         }
-
-        match webdav_download(client, config, &name).await {
+    }
+    files.sort();
+    dirs.sort();
+    progress.add_total(files.len());
+
+    let downloaded = run_transfers(
+        &files,
+        transfer_concurrency(config),
+        |(_, name)| webdav_download(client, config, name),
+        |(key, _)| (SyncPhase::Downloading, key.as_str()),
+        progress,
+        result,
+    )
+    .await;
+    for ((key, name), outcome) in downloaded {
+        match outcome {
             Ok(content) => {
-                let target = local_dir.join(&name);
-                let key = manifest_key(manifest_prefix, &name);
+                let target = local_dir.join(name);
                 let content_hash = hash_content(&content);
-                if manifest.entries.get(&key).is_some_and(|entry| entry.hash == content_hash)
+                if manifest.entries.get(key).is_some_and(|entry| entry.hash == content_hash)
                     && target.exists()
                     && hash_file(&target).as_deref() == Some(content_hash.as_str())
                 {
@@ -573,80 +1020,315 @@ async fn download_dir_recursive(
             Err(error) => result.errors.push(format!("{}: {}", name, error)),
         }
     }
+
+    for name in dirs {
+        if sync_cancelled(progress, result) {
+            return;
+        }
+        let nested_local = local_dir.join(&name);
+        if let Err(error) = fs::create_dir_all(&nested_local) {
+            result
+                .errors
+                .push(format!("{}: Failed to create local directory: {}", nested_local.to_string_lossy(), error));
+            continue;
+        }
+        let nested_config = scoped_config(config, &name);
+        let nested_prefix = format!("{}/", manifest_key(manifest_prefix, &name).trim_end_matches('/'));
+        let nested = download_dir_recursive(client, &nested_config, &nested_local, &nested_prefix, manifest, progress, result);
+        Box::pin(nested).await;
+    }
+}
+
+async fn load_remote_tombstones(client: &WebDavClient, config: &WebDavConfig) -> AppResult<Tombstones> {
+    match webdav_download_text(client, config, REMOTE_TOMBSTONES_FILE).await {
+        Ok(content) => serde_json::from_str::<Tombstones>(&content)
+            .map_err(|e| AppError::parse(format!("Failed to parse sync tombstones: {}", e))),
+        Err(error) if is_not_found(&error) => Ok(Tombstones::default()),
+        Err(error) => Err(error),
+    }
+}
+
+async fn write_remote_tombstones(client: &WebDavClient, config: &WebDavConfig, tombstones: &Tombstones) -> AppResult<()> {
+    let body = serde_json::to_string_pretty(tombstones)
+        .map_err(|e| AppError::parse(format!("Failed to serialize sync tombstones: {}", e)))?;
+    webdav_upload(client, config, REMOTE_TOMBSTONES_FILE, body.as_bytes()).await
+}
+
+/// Local and remote tombstones merged and pruned; empty when deletions are not propagated.
+async fn merged_tombstones(client: &WebDavClient, config: &WebDavConfig, propagate_deletes: bool) -> AppResult<Tombstones> {
+    if !propagate_deletes {
+        return Ok(Tombstones::default());
+    }
+    let mut tombstones = load_tombstones(&get_tombstones_file());
+    tombstones.merge(load_remote_tombstones(client, config).await?);
+    tombstones.prune(chrono::Utc::now().timestamp_millis());
+    Ok(tombstones)
+}
+
+/// Saves merged tombstones locally and, unless `local_only`, at the remote root.
+async fn store_tombstones(
+    client: &WebDavClient,
+    config: &WebDavConfig,
+    tombstones: &Tombstones,
+    local_only: bool,
+    result: &mut SyncResult,
+) {
+    if tombstones.is_empty() {
+        return;
+    }
+    if let Err(error) = save_tombstones(&get_tombstones_file(), tombstones) {
+        result.errors.push(format!("tombstones: {}", error));
+    }
+    if !local_only {
+        if let Err(error) = write_remote_tombstones(client, config, tombstones).await {
+            result.errors.push(format!("tombstones: {}", error));
+        }
+    }
+}
+
+/// Cipher for account files: the passphrase in `config`, else the saved one. `None` uploads
+/// plaintext.
+fn sync_cipher(config: &WebDavConfig) -> AppResult<Option<SyncCipher>> {
+    let passphrase = match config.sync_passphrase.as_deref().filter(|passphrase| !passphrase.is_empty()) {
+        Some(passphrase) => Some(passphrase.to_string()),
+        None => stored_sync_passphrase()?,
+    };
+    passphrase.as_deref().map(SyncCipher::new).transpose()
+}
+
+/// The remote `accounts/` folder of one sync. Account files are stored as `<name>.enc` when
+/// encrypted; both forms are listed under the plain name.
+struct RemoteAccounts<'a> {
+    client: &'a WebDavClient,
+    config: WebDavConfig,
+    cipher: Option<SyncCipher>,
+    /// Names stored encrypted, and in plaintext, as last listed.
+    encrypted: HashSet<String>,
+    plain: HashSet<String>,
+}
+
+impl<'a> RemoteAccounts<'a> {
+    fn new(client: &'a WebDavClient, config: WebDavConfig, cipher: Option<SyncCipher>) -> Self {
+        Self {
+            client,
+            config,
+            cipher,
+            encrypted: HashSet::new(),
+            plain: HashSet::new(),
+        }
+    }
+}
+
+fn local_account_entries() -> HashMap<String, SyncPreviewEntry> {
+    collect_accounts_preview_entries()
+        .into_iter()
+        .map(|entry| (entry.name.clone(), entry))
+        .collect()
+}
+
+/// Remote account files by plain name; an encrypted copy wins over a plaintext one.
+async fn remote_account_entries(
+    accounts: &mut RemoteAccounts<'_>,
+    manifest: &SyncManifest,
+) -> AppResult<HashMap<String, SyncPreviewEntry>> {
+    let listed = list_remote_entries(accounts.client, &accounts.config, SyncItemType::Account, "", "accounts/", manifest).await?;
+    let mut entries = HashMap::new();
+    for mut entry in listed {
+        let encrypted = match entry.name.strip_suffix(ENCRYPTED_SUFFIX) {
+            Some(name) => {
+                entry.name = name.to_string();
+                true
+            }
+            None => false,
+        };
+        if account_file_location(&accounts.config, &entry.name).is_err() {
+            continue;
+        }
+        entry.hash = manifest
+            .entries
+            .get(&manifest_key("accounts/", &entry.name))
+            .map(|manifest_entry| manifest_entry.hash.clone());
+        if encrypted {
+            accounts.encrypted.insert(entry.name.clone());
+        } else {
+            accounts.plain.insert(entry.name.clone());
+        }
+        if encrypted || !entries.contains_key(&entry.name) {
+            entries.insert(entry.name.clone(), entry);
+        }
+    }
+    Ok(entries)
+}
+
+/// Moves the local account file `name` to the trash. The live account is kept, as
+/// `delete_account` would refuse it too.
+fn delete_local_account_file(accounts_dir: &Path, name: &str) -> AppResult<()> {
+    let path = accounts_dir.join(name);
+    if is_live_account(&path, accounts_dir) {
+        return Err(AppError::forbidden("Deleted elsewhere but kept as the active account"));
+    }
+    remove_path(&path, false).map_err(AppError::io)
+}
+
+/// Adds the `warnings` for files that were downloaded to the conflicts of `result`.
+fn keep_downloaded_warnings(warnings: Vec<SyncConflict>, result: &mut SyncResult) {
+    let downloaded: HashSet<&String> = result.downloaded.iter().collect();
+    let kept: Vec<SyncConflict> = warnings.into_iter().filter(|warning| downloaded.contains(&warning.name)).collect();
+    result.conflicts.extend(kept);
+}
+
+/// Carries out an account sync `plan` in the local accounts dir, recording each outcome in
+/// `result`. `file_states` (keyed by manifest key) is kept up to date for the bidirectional sync.
+async fn apply_account_plan(
+    accounts: &RemoteAccounts<'_>,
+    plan: &[SyncPlanItem],
+    manifest: &mut SyncManifest,
+    mut file_states: Option<&mut HashMap<String, SyncStateEntry>>,
+    progress: &mut SyncProgress,
+    result: &mut SyncResult,
+) {
+    let accounts_dir = &get_accounts_dir();
+    let mut transfers = Vec::new();
+    for item in plan {
+        match item.action {
+            SyncPlanAction::Skip => result.skipped.push(item.name.clone()),
+            SyncPlanAction::Conflict => result.conflicts.push(SyncConflict {
+                name: item.name.clone(),
+                local_modified_at: item.local_modified_at,
+                remote_modified_at: item.remote_modified_at,
+                remote_device: manifest
+                    .entries
+                    .get(&manifest_key("accounts/", &item.name))
+                    .and_then(|entry| entry.device.clone()),
+                overwritten: false,
+            }),
+            SyncPlanAction::Upload | SyncPlanAction::Download | SyncPlanAction::Delete => transfers.push(item),
+        }
+    }
+    progress.add_total(transfers.len());
+
+    let outcomes = run_transfers(
+        &transfers,
+        transfer_concurrency(&accounts.config),
+        |item| async move {
+            let name = item.name.as_str();
+            match (item.action, item.side) {
+                (SyncPlanAction::Upload, _) => upload_account_file(accounts, accounts_dir, name).await.map(Some),
+                (SyncPlanAction::Download, _) => download_account_file(accounts, accounts_dir, name).await.map(Some),
+                (_, Some(SyncSide::Local)) => delete_local_account_file(accounts_dir, name).map(|()| None),
+                _ => delete_remote_account_file(accounts, name).await.map(|()| None),
+            }
+        },
+        |item| {
+            let phase = match item.action {
+                SyncPlanAction::Upload => SyncPhase::Uploading,
+                SyncPlanAction::Download => SyncPhase::Downloading,
+                _ => SyncPhase::Deleting,
+            };
+            (phase, item.name.as_str())
+        },
+        progress,
+        result,
+    )
+    .await;
+
+    for (item, outcome) in outcomes {
+        let name = &item.name;
+        let key = manifest_key("accounts/", name);
+        let content = match outcome {
+            Ok(content) => content,
+            Err(error) => {
+                result.errors.push(format!("{}: {}", name, error));
+                continue;
+            }
+        };
+        match (item.action, content) {
+            (SyncPlanAction::Upload, Some(content)) => {
+                let hash = hash_content(content.as_bytes());
+                let modified_at = item
+                    .local_modified_at
+                    .unwrap_or_else(|| chrono::Utc::now().timestamp_millis());
+                upsert_manifest_entry(manifest, key.clone(), hash.clone(), modified_at);
+                if let Some(file_states) = file_states.as_deref_mut() {
+                    file_states.insert(key, SyncStateEntry { hash, remote_modified_at: None });
+                }
+                result.uploaded.push(name.clone());
+            }
+            (SyncPlanAction::Download, Some(content)) => {
+                if let Some(file_states) = file_states.as_deref_mut() {
+                    let remote_modified_at = item.remote_modified_at;
+                    file_states.insert(key, SyncStateEntry { hash: hash_content(content.as_bytes()), remote_modified_at });
+                }
+                result.downloaded.push(name.clone());
+            }
+            _ => {
+                if item.side != Some(SyncSide::Local) {
+                    manifest.entries.remove(&key);
+                }
+                if let Some(file_states) = file_states.as_deref_mut() {
+                    file_states.remove(&key);
+                }
+                result.deleted.push(name.clone());
+            }
+        }
+    }
 }
 
 #[tauri::command]
 pub async fn webdav_sync_preview(
-    config: WebDavConfig,
+    config: Option<WebDavConfig>,
     sync_config: CodexSyncConfig,
     sync_accounts: bool,
 ) -> AppResult<SyncPreview> {
-    let client = webdav_client()?;
+    let config = resolve_webdav_config(config)?;
+    let client = webdav_client(&config)?;
     let mut local_entries = collect_codex_preview_entries(&sync_config);
     let mut remote_entries = Vec::new();
     let manifest = load_sync_manifest(&client, &config).await?;
 
     if sync_accounts {
         local_entries.extend(collect_accounts_preview_entries());
-        remote_entries.extend(
-            list_remote_entries(
-                &client,
-                &scoped_config(&config, "accounts"),
-                SyncItemType::Account,
-                "",
-                "accounts/",
-                &manifest,
-            ).await?,
-        );
-    }
-
-    if sync_config.sync_prompts {
-        remote_entries.extend(
-            list_remote_entries(
-                &client,
-                &scoped_config(&config, "prompts"),
-                SyncItemType::Prompt,
-                "prompts/",
-                "prompts/",
-                &manifest,
-            )
-                .await?,
-        );
+        let mut accounts = RemoteAccounts::new(&client, scoped_config(&config, "accounts"), None);
+        remote_entries.extend(remote_account_entries(&mut accounts, &manifest).await?.into_values());
     }
 
-    if sync_config.sync_skills {
+    for (name, item_type) in codex_dirs(&sync_config) {
+        let (dir_config, manifest_prefix) = codex_remote_dir(&client, &config, name).await?;
         remote_entries.extend(
-            list_remote_entries(
-                &client,
-                &scoped_config(&config, "skills"),
-                SyncItemType::Skill,
-                "skills/",
-                "skills/",
-                &manifest,
-            )
-                .await?,
+            list_remote_entries(&client, &dir_config, item_type, &format!("{}/", name), &manifest_prefix, &manifest).await?,
         );
     }
 
-    if sync_config.sync_agents_md || sync_config.sync_config_toml {
-        for resource in list_remote_root_files(&client, &config).await? {
-            let Some(name) = relative_resource_name(&resource, &config.remote_path) else {
-                continue;
+    let root_files = codex_root_files(&sync_config);
+    if !root_files.is_empty() {
+        let codex_config = scoped_config(&config, CODEX_REMOTE_DIR);
+        for (location, manifest_prefix) in [(&codex_config, "codex/"), (&config, "")] {
+            let resources = match list_remote_root_files(&client, location).await {
+                Ok(value) => value,
+                Err(error) if is_not_found(&error) => continue,
+                Err(error) => return Err(error),
             };
-
-            if sync_config.sync_agents_md && name == "AGENTS.MD" {
-                remote_entries.push(SyncPreviewEntry {
-                    name,
-                    item_type: SyncItemType::Agents,
-                    modified_at: resource.last_modified,
-                    hash: manifest.entries.get("AGENTS.MD").map(|entry| entry.hash.clone()),
-                });
-            } else if sync_config.sync_config_toml && name == "config.toml" {
+            for resource in resources {
+                let Some(name) = relative_resource_name(&resource, location) else {
+                    continue;
+                };
+                let item_type = match name.as_str() {
+                    "AGENTS.MD" => SyncItemType::Agents,
+                    "config.toml" => SyncItemType::Config,
+                    _ => continue,
+                };
+                if resource.is_collection
+                    || !root_files.contains(&name.as_str())
+                    || remote_entries.iter().any(|entry| entry.name == name)
+                {
+                    continue;
+                }
                 remote_entries.push(SyncPreviewEntry {
+                    hash: manifest.entries.get(&manifest_key(manifest_prefix, &name)).map(|entry| entry.hash.clone()),
                     name,
-                    item_type: SyncItemType::Config,
+                    item_type,
                     modified_at: resource.last_modified,
-                    hash: manifest.entries.get("config.toml").map(|entry| entry.hash.clone()),
+                    size: resource.content_length,
                 });
             }
         }
@@ -665,159 +1347,336 @@ pub async fn webdav_sync_preview(
     Ok(preview)
 }
 
+/// Uploads the account files. With `dry_run` nothing is changed and `plan` lists what would be.
 #[tauri::command]
-pub async fn webdav_sync_upload(config: WebDavConfig) -> AppResult<SyncResult> {
-    let client = webdav_client()?;
-    let accounts_dir = get_accounts_dir();
-    let accounts_config = scoped_config(&config, "accounts");
+pub async fn webdav_sync_upload(
+    app: tauri::AppHandle,
+    config: Option<WebDavConfig>,
+    strategy: Option<SyncStrategy>,
+    propagate_deletes: Option<bool>,
+    dry_run: Option<bool>,
+) -> AppResult<SyncResult> {
+    let config = resolve_webdav_config(config)?;
+    let client = webdav_client(&config)?;
+    let mut progress = SyncProgress::start(&app)?;
+    let strategy = strategy.unwrap_or_default();
+    let dry_run = dry_run.unwrap_or(false);
+    let mut accounts = RemoteAccounts::new(&client, scoped_config(&config, "accounts"), sync_cipher(&config)?);
     let mut manifest = load_sync_manifest(&client, &config).await?;
 
-    let mut result = SyncResult {
-        uploaded: Vec::new(),
-        downloaded: Vec::new(),
-        errors: Vec::new(),
-    };
-
-    if let Err(error) = webdav_ensure_dir(&client, &config).await {
-        result.errors.push(format!("root dir: {}", error));
-    }
-    if let Err(error) = webdav_ensure_dir(&client, &accounts_config).await {
-        result.errors.push(format!("accounts dir: {}", error));
-    }
+    let tombstones = merged_tombstones(&client, &config, propagate_deletes.unwrap_or(true)).await?;
+    let remote = remote_account_entries(&mut accounts, &manifest).await?;
+    let plan = plan_one_way(SyncDirection::Upload, strategy, &local_account_entries(), &remote, &tombstones);
+    let mut result = SyncResult { plan, ..SyncResult::default() };
 
-    if let Ok(entries) = fs::read_dir(&accounts_dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.extension().and_then(|value| value.to_str()) != Some("json") {
-                continue;
-            }
-
-            let Some(filename) = path.file_name().and_then(|value| value.to_str()) else {
-                continue;
-            };
-
-            match fs::read_to_string(&path) {
-                Ok(content) => {
-                    let content_hash = hash_content(&content);
-                    let key = manifest_key("accounts/", filename);
-                    if manifest.entries.get(&key).is_some_and(|entry| entry.hash == content_hash) {
-                        continue;
-                    }
-                    match webdav_upload(&client, &accounts_config, filename, &content).await {
-                        Ok(()) => {
-                            result.uploaded.push(filename.to_string());
-                            upsert_manifest_entry(
-                                &mut manifest,
-                                key,
-                                content_hash,
-                                path_modified_at(&path).unwrap_or_else(|| chrono::Utc::now().timestamp_millis()),
-                            );
-                        }
-                        Err(error) => result.errors.push(format!("{}: {}", filename, error)),
-                    }
-                }
-                Err(error) => result.errors.push(format!("{}: Failed to read file: {}", filename, error)),
-            }
+    if !dry_run {
+        if let Err(error) = webdav_ensure_dir(&client, &config).await {
+            result.errors.push(format!("root dir: {}", error));
+        }
+        if let Err(error) = webdav_ensure_dir(&client, &accounts.config).await {
+            result.errors.push(format!("accounts dir: {}", error));
         }
+        store_tombstones(&client, &config, &tombstones, false, &mut result).await;
+        let plan = std::mem::take(&mut result.plan);
+        apply_account_plan(&accounts, &plan, &mut manifest, None, &mut progress, &mut result).await;
+        result.plan = plan;
+        let _ = write_sync_manifest(&client, &config, &manifest).await;
     }
-
-    let _ = write_sync_manifest(&client, &config, &manifest).await;
     trace::emit(
         "webdav",
         "sync_accounts_upload",
         json!({
+            "strategy": strategy,
+            "dryRun": dry_run,
+            "planned": result.plan.len(),
             "uploaded": result.uploaded.len(),
+            "deleted": result.deleted.len(),
+            "skipped": result.skipped.len(),
+            "cancelled": result.cancelled,
             "errors": result.errors.len(),
         }),
     );
+    progress.complete(&result);
     Ok(result)
 }
 
+/// Downloads the account files. With `dry_run` nothing is changed and `plan` lists what would be.
 #[tauri::command]
-pub async fn webdav_sync_download(config: WebDavConfig) -> AppResult<SyncResult> {
-    let client = webdav_client()?;
+pub async fn webdav_sync_download(
+    app: tauri::AppHandle,
+    config: Option<WebDavConfig>,
+    strategy: Option<SyncStrategy>,
+    propagate_deletes: Option<bool>,
+    dry_run: Option<bool>,
+) -> AppResult<SyncResult> {
+    let config = resolve_webdav_config(config)?;
+    let client = webdav_client(&config)?;
+    let mut progress = SyncProgress::start(&app)?;
+    let strategy = strategy.unwrap_or_default();
+    let dry_run = dry_run.unwrap_or(false);
     let accounts_dir = get_accounts_dir();
-    let accounts_config = scoped_config(&config, "accounts");
-    let manifest = load_sync_manifest(&client, &config).await?;
+    let mut accounts = RemoteAccounts::new(&client, scoped_config(&config, "accounts"), sync_cipher(&config)?);
+    let mut manifest = load_sync_manifest(&client, &config).await?;
 
-    let mut result = SyncResult {
-        uploaded: Vec::new(),
-        downloaded: Vec::new(),
-        errors: Vec::new(),
-    };
+    let tombstones = merged_tombstones(&client, &config, propagate_deletes.unwrap_or(true)).await?;
+    let remote = remote_account_entries(&mut accounts, &manifest).await?;
+    let local = local_account_entries();
+    let plan = plan_one_way(SyncDirection::Download, strategy, &local, &remote, &tombstones);
+    let mut result = SyncResult { plan, ..SyncResult::default() };
 
-    if !accounts_dir.exists() {
-        fs::create_dir_all(&accounts_dir)
-            .map_err(|e| AppError::io(format!("Failed to create local accounts directory: {}", e)))?;
+    if !dry_run {
+        if !accounts_dir.exists() {
+            fs::create_dir_all(&accounts_dir)
+                .map_err(|e| AppError::io(format!("Failed to create local accounts directory: {}", e)))?;
+        }
+        store_tombstones(&client, &config, &tombstones, true, &mut result).await;
+        let state = load_sync_state(&get_sync_state_file());
+        let synced = state
+            .remotes
+            .get(&remote_url(&accounts.config, None))
+            .map(account_states)
+            .unwrap_or_default();
+        let warnings = overwrite_warnings(&manifest, &result.plan, &local, &synced);
+        let plan = std::mem::take(&mut result.plan);
+        apply_account_plan(&accounts, &plan, &mut manifest, None, &mut progress, &mut result).await;
+        result.plan = plan;
+        keep_downloaded_warnings(warnings, &mut result);
     }
+    trace::emit(
+        "webdav",
+        "sync_accounts_download",
+        json!({
+            "strategy": strategy,
+            "dryRun": dry_run,
+            "planned": result.plan.len(),
+            "downloaded": result.downloaded.len(),
+            "deleted": result.deleted.len(),
+            "skipped": result.skipped.len(),
+            "cancelled": result.cancelled,
+            "errors": result.errors.len(),
+        }),
+    );
+    progress.complete(&result);
+    Ok(result)
+}
 
-    let resources = match propfind(&client, &accounts_config, 1).await {
-        Ok(value) => value,
-        Err(error) if is_not_found(&error) => Vec::new(),
-        Err(error) => return Err(error),
+fn get_sync_state_file() -> std::path::PathBuf {
+    get_config_file().with_file_name(SYNC_STATE_FILE)
+}
+
+fn load_sync_state(path: &Path) -> SyncState {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_sync_state(path: &Path, state: &SyncState) -> AppResult<()> {
+    let content = serde_json::to_vec_pretty(state)
+        .map_err(|e| AppError::parse(format!("Failed to serialize sync state: {}", e)))?;
+    write_private_file_atomic(path, &content).map_err(|e| AppError::io(format!("Failed to write sync state: {}", e)))
+}
+
+/// Remote dir holding the account file `name` (a path below `accounts/`), and its file name.
+/// Names outside the account folder layout are rejected.
+fn account_file_location(accounts_config: &WebDavConfig, name: &str) -> AppResult<(WebDavConfig, String)> {
+    let segments: Vec<&str> = name.split('/').collect();
+    let Some((filename, dirs)) = segments.split_last() else {
+        return Err(AppError::path_outside_accounts(format!("{}: Invalid account path", name)));
     };
+    if dirs.len() > MAX_ACCOUNT_DIR_DEPTH
+        || segments.iter().any(|segment| segment.is_empty() || segment.starts_with('.'))
+        || !filename.ends_with(".json")
+    {
+        return Err(AppError::path_outside_accounts(format!("{}: Invalid account path", name)));
+    }
+    let config = dirs.iter().fold(accounts_config.clone(), |config, dir| scoped_config(&config, dir));
+    Ok((config, filename.to_string()))
+}
 
-    for resource in resources {
-        let Some(filename) = relative_resource_name(&resource, &accounts_config.remote_path) else {
-            continue;
-        };
-        if resource.is_collection {
-            continue;
+/// Uploads the account file `name`, encrypted when a passphrase is set, and removes the remote
+/// copy in the other form. A file stored encrypted is never replaced by plaintext.
+async fn upload_account_file(accounts: &RemoteAccounts<'_>, accounts_dir: &Path, name: &str) -> AppResult<String> {
+    let client = accounts.client;
+    let (config, filename) = account_file_location(&accounts.config, name)?;
+    let encrypted_filename = format!("{}{}", filename, ENCRYPTED_SUFFIX);
+    if accounts.cipher.is_none() && accounts.encrypted.contains(name) {
+        return Err(AppError::auth("Stored encrypted on the server; set the sync passphrase to upload it"));
+    }
+    let mut dir_config = accounts.config.clone();
+    for dir in name.split('/').take(name.matches('/').count()) {
+        dir_config = scoped_config(&dir_config, dir);
+        webdav_ensure_dir(client, &dir_config).await?;
+    }
+    let content = fs::read_to_string(accounts_dir.join(name))
+        .map_err(|e| AppError::io(format!("Failed to read file: {}", e)))?;
+    match &accounts.cipher {
+        Some(cipher) => {
+            webdav_upload(client, &config, &encrypted_filename, &cipher.encrypt(content.as_bytes())?).await?;
+            if accounts.plain.contains(name) {
+                webdav_delete(client, &config, &filename).await.map_err(|error| AppError {
+                    message: format!("Uploaded encrypted, but the plaintext copy remains: {}", error.message),
+                    ..error
+                })?;
+            }
         }
+        None => webdav_upload(client, &config, &filename, content.as_bytes()).await?,
+    }
+    Ok(content)
+}
 
-        match webdav_download(&client, &accounts_config, &filename).await {
-            Ok(content) => {
-                if serde_json::from_str::<serde_json::Value>(&content).is_err() {
-                    result.errors.push(format!("{}: Invalid JSON", filename));
-                    continue;
-                }
-                let target = accounts_dir.join(&filename);
-                let key = manifest_key("accounts/", &filename);
-                let content_hash = hash_content(&content);
-                if manifest.entries.get(&key).is_some_and(|entry| entry.hash == content_hash)
-                    && target.exists()
-                    && hash_file(&target).as_deref() == Some(content_hash.as_str())
-                {
-                    continue;
-                }
-                match fs::write(&target, &content) {
-                    Ok(()) => result.downloaded.push(filename),
-                    Err(error) => result.errors.push(format!("{}: Failed to write file: {}", target.to_string_lossy(), error)),
+/// Downloads the account file `name`, decrypting it when stored encrypted. Nothing is written
+/// unless it decrypts and parses.
+async fn download_account_file(accounts: &RemoteAccounts<'_>, accounts_dir: &Path, name: &str) -> AppResult<String> {
+    let (config, filename) = account_file_location(&accounts.config, name)?;
+    let mut content = if accounts.encrypted.contains(name) {
+        webdav_download(accounts.client, &config, &format!("{}{}", filename, ENCRYPTED_SUFFIX)).await?
+    } else {
+        webdav_download(accounts.client, &config, &filename).await?
+    };
+    if is_encrypted(&content) {
+        let Some(cipher) = &accounts.cipher else {
+            return Err(AppError::auth("Stored encrypted on the server; set the sync passphrase to download it"));
+        };
+        content = cipher.decrypt(&content)?;
+    }
+    let content = String::from_utf8(content).map_err(|_| AppError::parse("Invalid JSON"))?;
+    if serde_json::from_str::<serde_json::Value>(&content).is_err() {
+        return Err(AppError::parse("Invalid JSON"));
+    }
+    let target = accounts_dir.join(name);
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|e| AppError::io(format!("Failed to create local directory: {}", e)))?;
+    }
+    write_private_file_atomic(&target, content.as_bytes())
+        .map_err(|e| AppError::io(format!("Failed to write file: {}", e)))?;
+    Ok(content)
+}
+
+/// Removes every remote form of the account file `name`.
+async fn delete_remote_account_file(accounts: &RemoteAccounts<'_>, name: &str) -> AppResult<()> {
+    let (config, filename) = account_file_location(&accounts.config, name)?;
+    if accounts.encrypted.contains(name) {
+        webdav_delete(accounts.client, &config, &format!("{}{}", filename, ENCRYPTED_SUFFIX)).await?;
+    }
+    if accounts.plain.contains(name) || !accounts.encrypted.contains(name) {
+        webdav_delete(accounts.client, &config, &filename).await?;
+    }
+    Ok(())
+}
+
+/// Syncs account files both ways: each file moves towards the side that did not change since the
+/// last sync here, or the newer copy on a first sync. Files changed on both sides are reported as
+/// conflicts and left alone unless `strategy` or a per-file `resolutions` entry picks a side. With
+/// `dry_run` nothing is changed and `plan` lists what would be.
+#[tauri::command]
+pub async fn webdav_sync_bidirectional(
+    app: tauri::AppHandle,
+    config: Option<WebDavConfig>,
+    strategy: Option<SyncStrategy>,
+    resolutions: Option<HashMap<String, SyncSide>>,
+    propagate_deletes: Option<bool>,
+    dry_run: Option<bool>,
+) -> AppResult<SyncResult> {
+    let config = resolve_webdav_config(config)?;
+    let client = webdav_client(&config)?;
+    let mut progress = SyncProgress::start(&app)?;
+    let strategy = strategy.unwrap_or_default();
+    let resolutions = resolutions.unwrap_or_default();
+    let dry_run = dry_run.unwrap_or(false);
+    let mut accounts = RemoteAccounts::new(&client, scoped_config(&config, "accounts"), sync_cipher(&config)?);
+    let mut manifest = load_sync_manifest(&client, &config).await?;
+    let state_path = get_sync_state_file();
+    let mut state = load_sync_state(&state_path);
+    let file_states = state.remotes.entry(remote_url(&accounts.config, None)).or_default();
+
+    let local = local_account_entries();
+    let remote = remote_account_entries(&mut accounts, &manifest).await?;
+    let tombstones = merged_tombstones(&client, &config, propagate_deletes.unwrap_or(true)).await?;
+    let states = account_states(file_states);
+    let plan = plan_two_way(strategy, &local, &remote, &states, &resolutions, &tombstones);
+    let mut result = SyncResult { plan, ..SyncResult::default() };
+
+    if !dry_run {
+        webdav_ensure_dir(&client, &config).await?;
+        webdav_ensure_dir(&client, &accounts.config).await?;
+        store_tombstones(&client, &config, &tombstones, false, &mut result).await;
+
+        // Files already equal on both sides count as synced now.
+        for (name, local) in &local {
+            match (&local.hash, remote.get(name)) {
+                (Some(hash), Some(remote)) if remote.hash.as_ref() == Some(hash) => {
+                    let remote_modified_at = remote.modified_at;
+                    let entry = SyncStateEntry { hash: hash.clone(), remote_modified_at };
+                    file_states.insert(manifest_key("accounts/", name), entry);
                 }
+                _ => {}
             }
-            Err(error) => result.errors.push(format!("{}: {}", filename, error)),
+        }
+        let warnings = overwrite_warnings(&manifest, &result.plan, &local, &states);
+        let plan = std::mem::take(&mut result.plan);
+        let file_states = Some(file_states);
+        apply_account_plan(&accounts, &plan, &mut manifest, file_states, &mut progress, &mut result).await;
+        result.plan = plan;
+        keep_downloaded_warnings(warnings, &mut result);
+
+        if !result.uploaded.is_empty() || !result.deleted.is_empty() {
+            let _ = write_sync_manifest(&client, &config, &manifest).await;
+        }
+        if let Err(error) = save_sync_state(&state_path, &state) {
+            result.errors.push(error.message);
         }
     }
-
     trace::emit(
         "webdav",
-        "sync_accounts_download",
+        "sync_accounts_bidirectional",
         json!({
+            "strategy": strategy,
+            "dryRun": dry_run,
+            "planned": result.plan.len(),
+            "uploaded": result.uploaded.len(),
             "downloaded": result.downloaded.len(),
+            "conflicts": result.conflicts.len(),
+            "deleted": result.deleted.len(),
+            "cancelled": result.cancelled,
             "errors": result.errors.len(),
         }),
     );
+    progress.complete(&result);
     Ok(result)
 }
 
 #[tauri::command]
-pub async fn webdav_test_connection(config: WebDavConfig) -> AppResult<String> {
-    let client = webdav_client()?;
-    let response = client
-        .request(reqwest::Method::from_bytes(b"PROPFIND").expect("PROPFIND"), remote_url(&config, None))
-        .basic_auth(&config.username, Some(&config.password))
-        .header("Depth", "0")
-        .send()
-        .await
-        .map_err(|e| AppError::network(format!("Connection failed: {}", e)))?;
+pub async fn webdav_test_connection(config: Option<WebDavConfig>) -> AppResult<String> {
+    let config = resolve_webdav_config(config)?;
+    let client = webdav_client(&config)?;
+    let url = remote_url(&config, None);
+    let request = client
+        .request(reqwest::Method::from_bytes(b"PROPFIND").expect("PROPFIND"), url.clone())
+        .header("Depth", "0");
+    let response = client.send(&config, request).await.map_err(|e| {
+        if is_certificate_error(&e) {
+            AppError::network(format!(
+                "Certificate verification failed: {}. For a self-signed server, set a custom CA certificate or allow invalid certificates",
+                e
+            ))
+        } else {
+            AppError::network(format!("Connection failed: {}", e))
+        }
+    })?;
 
     let status = response.status();
+    let scheme = client.auth_scheme(&url).map_or("no", |scheme| scheme.name());
+    let mut details = format!("{} auth, {}", scheme, describe_route(&load_config().proxy, &url));
+    if config.allow_invalid_certs {
+        details.push_str(", certificate verification disabled");
+    }
     if status.is_success() || status.as_u16() == 207 {
-        return Ok("Connection successful".to_string());
+        return Ok(format!("Connection successful ({})", details));
     }
     if status.as_u16() == 404 {
         webdav_ensure_dir(&client, &config).await?;
-        return Ok("Connection successful, remote directory created".to_string());
+        return Ok(format!("Connection successful ({}), remote directory created", details));
     }
     if status.as_u16() == 401 {
         return Err(AppError::auth(
@@ -828,195 +1687,1132 @@ pub async fn webdav_test_connection(config: WebDavConfig) -> AppResult<String> {
     Err(http_status_error("Connection failed", status))
 }
 
-#[tauri::command]
-pub async fn webdav_sync_codex_upload(
-    config: WebDavConfig,
-    sync_config: CodexSyncConfig,
-) -> AppResult<SyncResult> {
-    let client = webdav_client()?;
-    let codex_dir = get_codex_dir();
-    let mut manifest = load_sync_manifest(&client, &config).await?;
+/// Files directly in `~/.codex` selected by `sync_config`.
+/// Remote dir and file name of `name`, a path below the dir of `config`.
+fn remote_file_location(config: &WebDavConfig, name: &str) -> (WebDavConfig, String) {
+    let (dirs, filename) = name.rsplit_once('/').unwrap_or(("", name));
+    let config = dirs
+        .split('/')
+        .filter(|dir| !dir.is_empty())
+        .fold(config.clone(), |config, dir| scoped_config(&config, dir));
+    (config, filename.to_string())
+}
 
-    let mut result = SyncResult {
-        uploaded: Vec::new(),
-        downloaded: Vec::new(),
-        errors: Vec::new(),
-    };
+/// Paths of every file below the dir of `config`.
+async fn list_remote_files(client: &WebDavClient, config: &WebDavConfig) -> AppResult<Vec<String>> {
+    let entries = list_remote_entries(client, config, SyncItemType::Account, "", "", &empty_manifest()).await?;
+    Ok(entries.into_iter().map(|entry| entry.name).collect())
+}
 
-    if let Err(error) = webdav_ensure_dir(&client, &config).await {
-        result.errors.push(format!("root dir: {}", error));
+async fn webdav_delete_dir(client: &WebDavClient, config: &WebDavConfig) -> AppResult<()> {
+    let response = client
+        .send(config, client.request(reqwest::Method::DELETE, remote_url(config, None)))
+        .await
+        .map_err(|e| AppError::network(format!("Failed to delete WebDAV directory: {}", e)))?;
+
+    if response.status().is_success() || response.status().as_u16() == 404 {
+        return Ok(());
     }
 
-    if sync_config.sync_agents_md {
-        let agents_md = codex_dir.join("AGENTS.MD");
-        if agents_md.exists() {
-            match fs::read_to_string(&agents_md) {
-                Ok(content) => {
-                    let content_hash = hash_content(&content);
-                    if manifest.entries.get("AGENTS.MD").is_none_or(|entry| entry.hash != content_hash) {
-                        match webdav_upload(&client, &config, "AGENTS.MD", &content).await {
-                            Ok(()) => {
-                                result.uploaded.push("AGENTS.MD".to_string());
-                                upsert_manifest_entry(
-                                    &mut manifest,
-                                    "AGENTS.MD".to_string(),
-                                    content_hash,
-                                    path_modified_at(&agents_md).unwrap_or_else(|| chrono::Utc::now().timestamp_millis()),
-                                );
-                            }
-                            Err(error) => result.errors.push(format!("AGENTS.MD: {}", error)),
-                        }
-                    }
-                }
-                Err(error) => result.errors.push(format!("AGENTS.MD: Failed to read file: {}", error)),
-            }
+    Err(http_status_error("Failed to delete WebDAV directory", response.status()))
+}
+
+/// Copies the dir of `from` to the dir of `to` on the server, replacing `to` when `overwrite`.
+/// Returns `false` when the server does not support COPY there.
+async fn webdav_copy_dir(client: &WebDavClient, from: &WebDavConfig, to: &WebDavConfig, overwrite: bool) -> AppResult<bool> {
+    let request = client
+        .request(reqwest::Method::from_bytes(b"COPY").expect("COPY"), remote_url(from, None))
+        .header("Destination", remote_url(to, None))
+        .header("Depth", "infinity")
+        .header("Overwrite", if overwrite { "T" } else { "F" });
+    let response = client
+        .send(from, request)
+        .await
+        .map_err(|e| AppError::network(format!("Failed to copy WebDAV directory: {}", e)))?;
+
+    match response.status().as_u16() {
+        201 | 204 => Ok(true),
+        400 | 405 | 501 | 502 => Ok(false),
+        _ => Err(http_status_error("Failed to copy WebDAV directory", response.status())),
+    }
+}
+
+/// Copies the files `names` below the dir of `from` to the same paths below `to` by downloading
+/// and re-uploading each, for servers without COPY. Files stay byte-for-byte, encrypted or not.
+async fn copy_files_by_transfer(
+    client: &WebDavClient,
+    from: &WebDavConfig,
+    to: &WebDavConfig,
+    names: &[String],
+    progress: &mut SyncProgress,
+    result: &mut SyncResult,
+) -> AppResult<()> {
+    webdav_ensure_dir(client, to).await?;
+    let dirs: BTreeSet<&str> = names
+        .iter()
+        .filter_map(|name| name.rsplit_once('/').map(|(dir, _)| dir))
+        .flat_map(|dir| dir.match_indices('/').map(|(index, _)| &dir[..index]).chain([dir]))
+        .collect();
+    for dir in dirs {
+        webdav_ensure_dir(client, &remote_file_location(to, &format!("{}/", dir)).0).await?;
+    }
+
+    progress.add_total(names.len());
+    let outcomes = run_transfers(
+        names,
+        transfer_concurrency(from),
+        |name| async move {
+            let (source, filename) = remote_file_location(from, name);
+            let content = webdav_download(client, &source, &filename).await?;
+            webdav_upload(client, &remote_file_location(to, name).0, &filename, &content).await
+        },
+        |name| (SyncPhase::Uploading, name.as_str()),
+        progress,
+        result,
+    )
+    .await;
+    for (name, outcome) in outcomes {
+        match outcome {
+            Ok(()) => result.uploaded.push(name.clone()),
+            Err(error) => result.errors.push(format!("{}: {}", name, error)),
         }
     }
+    Ok(())
+}
 
-    if sync_config.sync_config_toml {
-        let config_toml = codex_dir.join("config.toml");
-        if config_toml.exists() {
-            match fs::read_to_string(&config_toml) {
-                Ok(content) => {
-                    let content_hash = hash_content(&content);
-                    if manifest.entries.get("config.toml").is_none_or(|entry| entry.hash != content_hash) {
-                        match webdav_upload(&client, &config, "config.toml", &content).await {
-                            Ok(()) => {
-                                result.uploaded.push("config.toml".to_string());
-                                upsert_manifest_entry(
-                                    &mut manifest,
-                                    "config.toml".to_string(),
-                                    content_hash,
-                                    path_modified_at(&config_toml).unwrap_or_else(|| chrono::Utc::now().timestamp_millis()),
-                                );
-                            }
-                            Err(error) => result.errors.push(format!("config.toml: {}", error)),
-                        }
-                    }
-                }
-                Err(error) => result.errors.push(format!("config.toml: Failed to read file: {}", error)),
+fn snapshots_config(config: &WebDavConfig) -> WebDavConfig {
+    scoped_config(config, SNAPSHOTS_REMOTE_DIR)
+}
+
+async fn list_snapshots(client: &WebDavClient, config: &WebDavConfig) -> AppResult<Vec<SnapshotInfo>> {
+    let snapshots = snapshots_config(config);
+    let resources = match propfind(client, &snapshots, 1).await {
+        Ok(resources) => resources,
+        Err(error) if is_not_found(&error) => return Ok(Vec::new()),
+        Err(error) => return Err(error),
+    };
+    Ok(sorted_snapshots(
+        resources
+            .iter()
+            .filter(|resource| resource.is_collection)
+            .filter_map(|resource| relative_resource_name(resource, &snapshots)),
+    ))
+}
+
+/// Deletes the snapshots beyond the retention count of `config`, oldest first. Returns their names.
+async fn prune_snapshots(client: &WebDavClient, config: &WebDavConfig) -> AppResult<Vec<String>> {
+    let snapshots = list_snapshots(client, config).await?;
+    let keep = config.snapshot_retention.unwrap_or(DEFAULT_SNAPSHOT_RETENTION);
+    let mut pruned = Vec::new();
+    for snapshot in snapshots_to_prune(&snapshots, keep).iter().rev() {
+        webdav_delete_dir(client, &scoped_config(&snapshots_config(config), &snapshot.name)).await?;
+        pruned.push(snapshot.name.clone());
+    }
+    Ok(pruned)
+}
+
+async fn create_snapshot(
+    client: &WebDavClient,
+    config: &WebDavConfig,
+    progress: &mut SyncProgress,
+) -> AppResult<SnapshotInfo> {
+    let accounts = scoped_config(config, "accounts");
+    if !remote_exists(client, &accounts).await? {
+        return Err(AppError::not_found("There are no remote account files to snapshot"));
+    }
+    webdav_ensure_dir(client, &snapshots_config(config)).await?;
+    let snapshot = new_snapshot(chrono::Utc::now());
+    let target = scoped_config(&snapshots_config(config), &snapshot.name);
+
+    let server_side = webdav_copy_dir(client, &accounts, &target, false).await?;
+    if !server_side {
+        let names = list_remote_files(client, &accounts).await?;
+        let mut result = SyncResult::default();
+        let copied = copy_files_by_transfer(client, &accounts, &target, &names, progress, &mut result).await;
+        if copied.is_err() || !result.errors.is_empty() || result.cancelled {
+            let _ = webdav_delete_dir(client, &target).await;
+            copied?;
+            if result.cancelled {
+                return Err(AppError::external("Snapshot cancelled"));
             }
+            return Err(AppError::external(format!("Snapshot failed: {}", result.errors.join("; "))));
         }
     }
 
-    if sync_config.sync_prompts {
-        let prompts_config = scoped_config(&config, "prompts");
-        if let Err(error) = webdav_ensure_dir(&client, &prompts_config).await {
-            result.errors.push(format!("prompts dir: {}", error));
-        } else {
-            upload_dir_recursive(&client, &prompts_config, &get_prompts_dir(), "prompts/", &mut manifest, &mut result).await;
+    let pruned = prune_snapshots(client, config).await;
+    trace::emit(
+        "webdav",
+        "snapshot_created",
+        json!({
+            "name": snapshot.name,
+            "serverSide": server_side,
+            "pruned": pruned.as_ref().map(Vec::len).unwrap_or_default(),
+            "pruneError": pruned.err().map(|error| error.message),
+        }),
+    );
+    Ok(snapshot)
+}
+
+/// Writes the account files of `snapshot` into the local accounts folder, decrypted and checked
+/// like a download. Local files missing from the snapshot are left alone.
+async fn restore_snapshot_locally(
+    client: &WebDavClient,
+    config: &WebDavConfig,
+    snapshot: WebDavConfig,
+    progress: &mut SyncProgress,
+    result: &mut SyncResult,
+) -> AppResult<()> {
+    let mut accounts = RemoteAccounts::new(client, snapshot, sync_cipher(config)?);
+    let mut names: Vec<String> = remote_account_entries(&mut accounts, &empty_manifest()).await?.into_keys().collect();
+    names.sort();
+    let accounts_dir = &get_accounts_dir();
+    fs::create_dir_all(accounts_dir)
+        .map_err(|e| AppError::io(format!("Failed to create local accounts directory: {}", e)))?;
+
+    progress.add_total(names.len());
+    let accounts = &accounts;
+    let outcomes = run_transfers(
+        &names,
+        transfer_concurrency(config),
+        |name| async move { download_account_file(accounts, accounts_dir, name).await },
+        |name| (SyncPhase::Downloading, name.as_str()),
+        progress,
+        result,
+    )
+    .await;
+    for (name, outcome) in outcomes {
+        match outcome {
+            Ok(_) => result.downloaded.push(name.clone()),
+            Err(error) => result.errors.push(format!("{}: {}", name, error)),
         }
     }
+    Ok(())
+}
 
-    if sync_config.sync_skills {
-        let skills_config = scoped_config(&config, "skills");
-        if let Err(error) = webdav_ensure_dir(&client, &skills_config).await {
-            result.errors.push(format!("skills dir: {}", error));
-        } else {
-            upload_dir_recursive(&client, &skills_config, &get_skills_dir(), "skills/", &mut manifest, &mut result).await;
+/// Replaces the remote `accounts/` folder with `snapshot`. The manifest loses its account hashes,
+/// which described the replaced files.
+async fn restore_snapshot_remotely(
+    client: &WebDavClient,
+    config: &WebDavConfig,
+    snapshot: WebDavConfig,
+    progress: &mut SyncProgress,
+    result: &mut SyncResult,
+) -> AppResult<()> {
+    let accounts = scoped_config(config, "accounts");
+    let names = list_remote_files(client, &snapshot).await?;
+    if webdav_copy_dir(client, &snapshot, &accounts, true).await? {
+        result.uploaded = names;
+    } else {
+        let current = list_remote_files(client, &accounts).await?;
+        copy_files_by_transfer(client, &snapshot, &accounts, &names, progress, result).await?;
+        if result.errors.is_empty() && !result.cancelled {
+            for name in current.iter().filter(|name| !names.contains(name)) {
+                let (dir, filename) = remote_file_location(&accounts, name);
+                match webdav_delete(client, &dir, &filename).await {
+                    Ok(()) => result.deleted.push(name.clone()),
+                    Err(error) => result.errors.push(format!("{}: {}", name, error)),
+                }
+            }
         }
     }
 
-    let _ = write_sync_manifest(&client, &config, &manifest).await;
+    let mut manifest = load_sync_manifest(client, config).await?;
+    manifest.entries.retain(|key, _| !key.starts_with("accounts/"));
+    write_sync_manifest(client, config, &manifest).await
+}
+
+/// Copies the remote `accounts/` folder to `snapshots/<timestamp>/`, on the server where it
+/// supports COPY, then deletes the snapshots beyond the retention count.
+#[tauri::command]
+pub async fn webdav_create_snapshot(app: tauri::AppHandle, config: Option<WebDavConfig>) -> AppResult<SnapshotInfo> {
+    let config = resolve_webdav_config(config)?;
+    let client = webdav_client(&config)?;
+    let mut progress = SyncProgress::start(&app)?;
+    create_snapshot(&client, &config, &mut progress).await
+}
+
+/// Remote snapshots, newest first.
+#[tauri::command]
+pub async fn webdav_list_snapshots(config: Option<WebDavConfig>) -> AppResult<Vec<SnapshotInfo>> {
+    let config = resolve_webdav_config(config)?;
+    let client = webdav_client(&config)?;
+    list_snapshots(&client, &config).await
+}
+
+/// Restores the snapshot `name` into the local accounts folder or over the remote one.
+#[tauri::command]
+pub async fn webdav_restore_snapshot(
+    app: tauri::AppHandle,
+    config: Option<WebDavConfig>,
+    name: String,
+    target: SnapshotTarget,
+) -> AppResult<SyncResult> {
+    let config = resolve_webdav_config(config)?;
+    let client = webdav_client(&config)?;
+    let mut progress = SyncProgress::start(&app)?;
+    let snapshot = parse_snapshot_name(&name).ok_or_else(|| AppError::parse(format!("'{}' is not a snapshot", name)))?;
+    let snapshot = scoped_config(&snapshots_config(&config), &snapshot.name);
+    if !remote_exists(&client, &snapshot).await? {
+        return Err(AppError::not_found(format!("Snapshot '{}' does not exist", name)));
+    }
+
+    let mut result = SyncResult::default();
+    match target {
+        SnapshotTarget::Local => restore_snapshot_locally(&client, &config, snapshot, &mut progress, &mut result).await?,
+        SnapshotTarget::Remote => restore_snapshot_remotely(&client, &config, snapshot, &mut progress, &mut result).await?,
+    }
     trace::emit(
         "webdav",
-        "sync_codex_upload",
+        "snapshot_restored",
         json!({
-            "uploaded": result.uploaded.len(),
+            "name": name,
+            "target": if target == SnapshotTarget::Local { "local" } else { "remote" },
+            "restored": result.uploaded.len() + result.downloaded.len(),
+            "deleted": result.deleted.len(),
+            "cancelled": result.cancelled,
             "errors": result.errors.len(),
         }),
     );
+    progress.complete(&result);
     Ok(result)
 }
 
-#[tauri::command]
-pub async fn webdav_sync_codex_download(
-    config: WebDavConfig,
-    sync_config: CodexSyncConfig,
-) -> AppResult<SyncResult> {
-    let client = webdav_client()?;
-    let codex_dir = get_codex_dir();
-    let manifest = load_sync_manifest(&client, &config).await?;
+fn codex_root_files(sync_config: &CodexSyncConfig) -> Vec<&'static str> {
+    let mut files = Vec::new();
+    if sync_config.sync_agents_md {
+        files.push("AGENTS.MD");
+    }
+    if sync_config.sync_config_toml {
+        files.push("config.toml");
+    }
+    files
+}
 
-    let mut result = SyncResult {
-        uploaded: Vec::new(),
-        downloaded: Vec::new(),
-        errors: Vec::new(),
-    };
+/// Folders below `~/.codex` selected by `sync_config`, synced recursively.
+fn codex_dirs(sync_config: &CodexSyncConfig) -> Vec<(&'static str, SyncItemType)> {
+    let mut dirs = Vec::new();
+    if sync_config.sync_prompts {
+        dirs.push(("prompts", SyncItemType::Prompt));
+    }
+    if sync_config.sync_skills {
+        dirs.push(("skills", SyncItemType::Skill));
+    }
+    dirs
+}
 
-    if sync_config.sync_agents_md {
-        match webdav_download(&client, &config, "AGENTS.MD").await {
+/// Remote Codex folder `name` and its manifest prefix: `codex/{name}/`, or the legacy `{name}/` at
+/// the remote root when only that exists.
+async fn codex_remote_dir(client: &WebDavClient, config: &WebDavConfig, name: &str) -> AppResult<(WebDavConfig, String)> {
+    let current = scoped_config(&scoped_config(config, CODEX_REMOTE_DIR), name);
+    let legacy = scoped_config(config, name);
+    if !remote_exists(client, &current).await? && remote_exists(client, &legacy).await? {
+        return Ok((legacy, format!("{}/", name)));
+    }
+    Ok((current, format!("{}/{}/", CODEX_REMOTE_DIR, name)))
+}
+
+async fn upload_codex_files(
+    client: &WebDavClient,
+    config: &WebDavConfig,
+    codex_dir: &Path,
+    sync_config: &CodexSyncConfig,
+    manifest: &mut SyncManifest,
+    progress: &mut SyncProgress,
+    result: &mut SyncResult,
+) {
+    let codex_config = scoped_config(config, CODEX_REMOTE_DIR);
+    if let Err(error) = webdav_ensure_dir(client, &codex_config).await {
+        result.errors.push(format!("codex dir: {}", error));
+        return;
+    }
+
+    let root_files: Vec<&str> = codex_root_files(sync_config)
+        .into_iter()
+        .filter(|name| codex_dir.join(name).exists())
+        .collect();
+    progress.add_total(root_files.len());
+    let ignore = SyncIgnore::load(codex_dir);
+    for (name, _) in codex_dirs(sync_config) {
+        let local_dir = codex_dir.join(name);
+        progress.add_total(count_local_files(&local_dir, &ignore.descend(&local_dir, name)));
+    }
+
+    for name in root_files {
+        if sync_cancelled(progress, result) {
+            return;
+        }
+        let path = codex_dir.join(name);
+        progress.file(SyncPhase::Uploading, name);
+        match fs::read(&path) {
             Ok(content) => {
-                let target = codex_dir.join("AGENTS.MD");
                 let content_hash = hash_content(&content);
-                if !(manifest.entries.get("AGENTS.MD").is_some_and(|entry| entry.hash == content_hash)
-                    && target.exists()
-                    && hash_file(&target).as_deref() == Some(content_hash.as_str()))
-                {
-                    match fs::write(&target, &content) {
-                        Ok(()) => result.downloaded.push("AGENTS.MD".to_string()),
-                        Err(error) => result.errors.push(format!("AGENTS.MD: Failed to write file: {}", error)),
+                let key = manifest_key("codex/", name);
+                if manifest.entries.get(&key).is_some_and(|entry| entry.hash == content_hash) {
+                    continue;
+                }
+                match webdav_upload(client, &codex_config, name, &content).await {
+                    Ok(()) => {
+                        result.uploaded.push(name.to_string());
+                        upsert_manifest_entry(
+                            manifest,
+                            key,
+                            content_hash,
+                            path_modified_at(&path).unwrap_or_else(|| chrono::Utc::now().timestamp_millis()),
+                        );
                     }
+                    Err(error) => result.errors.push(format!("{}: {}", name, error)),
                 }
             }
-            Err(error) if is_not_found(&error) => {}
-            Err(error) => result.errors.push(format!("AGENTS.MD: {}", error)),
+            Err(error) => result.errors.push(format!("{}: Failed to read file: {}", name, error)),
         }
     }
 
-    if sync_config.sync_config_toml {
-        match webdav_download(&client, &config, "config.toml").await {
-            Ok(content) => {
-                let target = codex_dir.join("config.toml");
+    for (name, _) in codex_dirs(sync_config) {
+        if sync_cancelled(progress, result) {
+            return;
+        }
+        let dir_config = scoped_config(&codex_config, name);
+        if let Err(error) = webdav_ensure_dir(client, &dir_config).await {
+            result.errors.push(format!("{} dir: {}", name, error));
+            continue;
+        }
+        let manifest_prefix = format!("{}/{}/", CODEX_REMOTE_DIR, name);
+        let local_dir = codex_dir.join(name);
+        let dir_ignore = ignore.descend(&local_dir, name);
+        upload_dir_recursive(client, &dir_config, &local_dir, &dir_ignore, &manifest_prefix, manifest, progress, result)
+            .await;
+    }
+}
+
+async fn download_codex_files(
+    client: &WebDavClient,
+    config: &WebDavConfig,
+    codex_dir: &Path,
+    sync_config: &CodexSyncConfig,
+    manifest: &SyncManifest,
+    progress: &mut SyncProgress,
+    result: &mut SyncResult,
+) {
+    let codex_config = scoped_config(config, CODEX_REMOTE_DIR);
+    let root_files = codex_root_files(sync_config);
+    progress.add_total(root_files.len());
+
+    for name in root_files {
+        if sync_cancelled(progress, result) {
+            return;
+        }
+        progress.file(SyncPhase::Downloading, name);
+        let downloaded = match webdav_download(client, &codex_config, name).await {
+            Err(error) if is_not_found(&error) => webdav_download(client, config, name)
+                .await
+                .map(|content| (content, name.to_string())),
+            downloaded => downloaded.map(|content| (content, manifest_key("codex/", name))),
+        };
+        match downloaded {
+            Ok((content, key)) => {
+                let target = codex_dir.join(name);
                 let content_hash = hash_content(&content);
-                if !(manifest.entries.get("config.toml").is_some_and(|entry| entry.hash == content_hash)
+                if manifest.entries.get(&key).is_some_and(|entry| entry.hash == content_hash)
                     && target.exists()
-                    && hash_file(&target).as_deref() == Some(content_hash.as_str()))
+                    && hash_file(&target).as_deref() == Some(content_hash.as_str())
                 {
-                    match fs::write(&target, &content) {
-                        Ok(()) => result.downloaded.push("config.toml".to_string()),
-                        Err(error) => result.errors.push(format!("config.toml: Failed to write file: {}", error)),
-                    }
+                    continue;
+                }
+                match fs::write(&target, &content) {
+                    Ok(()) => result.downloaded.push(name.to_string()),
+                    Err(error) => result.errors.push(format!("{}: Failed to write file: {}", name, error)),
                 }
             }
             Err(error) if is_not_found(&error) => {}
-            Err(error) => result.errors.push(format!("config.toml: {}", error)),
+            Err(error) => result.errors.push(format!("{}: {}", name, error)),
         }
     }
 
-    if sync_config.sync_prompts {
-        let prompts_dir = get_prompts_dir();
-        let _ = fs::create_dir_all(&prompts_dir);
-        download_dir_recursive(
-            &client,
-            &scoped_config(&config, "prompts"),
-            &prompts_dir,
-            "prompts/",
-            &manifest,
-            &mut result,
-        ).await;
+    for (name, _) in codex_dirs(sync_config) {
+        if sync_cancelled(progress, result) {
+            return;
+        }
+        let local_dir = codex_dir.join(name);
+        let _ = fs::create_dir_all(&local_dir);
+        match codex_remote_dir(client, config, name).await {
+            Ok((dir_config, manifest_prefix)) => {
+                download_dir_recursive(client, &dir_config, &local_dir, &manifest_prefix, manifest, progress, result)
+                    .await;
+            }
+            Err(error) => result.errors.push(format!("{} dir: {}", name, error)),
+        }
     }
+}
 
-    if sync_config.sync_skills {
-        let skills_dir = get_skills_dir();
-        let _ = fs::create_dir_all(&skills_dir);
-        download_dir_recursive(
-            &client,
-            &scoped_config(&config, "skills"),
-            &skills_dir,
-            "skills/",
-            &manifest,
-            &mut result,
-        ).await;
+#[tauri::command]
+pub async fn webdav_sync_codex_upload(
+    app: tauri::AppHandle,
+    config: Option<WebDavConfig>,
+    sync_config: CodexSyncConfig,
+) -> AppResult<SyncResult> {
+    let config = resolve_webdav_config(config)?;
+    let client = webdav_client(&config)?;
+    let mut progress = SyncProgress::start(&app)?;
+    let mut manifest = load_sync_manifest(&client, &config).await?;
+
+    let mut result = SyncResult::default();
+
+    if let Err(error) = webdav_ensure_dir(&client, &config).await {
+        result.errors.push(format!("root dir: {}", error));
     }
+    let codex_dir = get_codex_dir();
+    upload_codex_files(&client, &config, &codex_dir, &sync_config, &mut manifest, &mut progress, &mut result).await;
+
+    let _ = write_sync_manifest(&client, &config, &manifest).await;
+    trace::emit(
+        "webdav",
+        "sync_codex_upload",
+        json!({
+            "uploaded": result.uploaded.len(),
+            "cancelled": result.cancelled,
+            "errors": result.errors.len(),
+        }),
+    );
+    progress.complete(&result);
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn webdav_sync_codex_download(
+    app: tauri::AppHandle,
+    config: Option<WebDavConfig>,
+    sync_config: CodexSyncConfig,
+) -> AppResult<SyncResult> {
+    let config = resolve_webdav_config(config)?;
+    let client = webdav_client(&config)?;
+    let mut progress = SyncProgress::start(&app)?;
+    let manifest = load_sync_manifest(&client, &config).await?;
+
+    let mut result = SyncResult::default();
+
+    let codex_dir = get_codex_dir();
+    download_codex_files(&client, &config, &codex_dir, &sync_config, &manifest, &mut progress, &mut result).await;
 
     trace::emit(
         "webdav",
         "sync_codex_download",
         json!({
             "downloaded": result.downloaded.len(),
+            "cancelled": result.cancelled,
             "errors": result.errors.len(),
         }),
     );
+    progress.complete(&result);
     Ok(result)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        download_account_file, download_codex_files, is_not_found, load_sync_manifest, relative_resource_name,
+        create_snapshot, list_remote_files, list_snapshots, remote_account_entries, remote_url,
+        restore_snapshot_remotely, run_transfers, scoped_config, snapshots_config, upload_account_file,
+        upload_codex_files, webdav_client, webdav_download, webdav_ensure_dir, webdav_upload, write_sync_manifest,
+        empty_manifest, keep_downloaded_warnings, overwrite_warnings, upsert_manifest_entry, CodexSyncConfig,
+        RemoteAccounts, SyncManifest, SyncResult, WebDavConfig, WebDavResource,
+    };
+    use crate::error::AppErrorCode;
+    use crate::webdav_plan::{SyncItemType, SyncPlanAction, SyncPlanItem, SyncPreviewEntry, SyncStateEntry};
+    use std::collections::HashMap;
+    use crate::webdav_auth::WebDavAuthScheme;
+    use crate::webdav_crypto::SyncCipher;
+    use crate::webdav_progress::{SyncPhase, SyncProgress};
+    use crate::webdav_propfind::{href_path, parse_propfind_resources};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::collections::{BTreeMap, BTreeSet};
+    use std::fs;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Paths of stored files and collections (with a trailing slash), as requested.
+    #[derive(Default)]
+    struct MockStore {
+        files: BTreeMap<String, Vec<u8>>,
+        collections: BTreeSet<String>,
+        /// Requests still to be refused with 423 Locked.
+        locked: usize,
+        /// Accept only Digest credentials, counting the 401 challenges sent.
+        digest: bool,
+        challenges: usize,
+        /// Requests that carried Basic credentials.
+        basic: usize,
+        /// Support COPY; refused with 405 Method Not Allowed otherwise.
+        copy: bool,
+    }
+
+    fn header_value(head: &str, name: &str) -> String {
+        head.lines()
+            .filter_map(|line| line.split_once(':'))
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.trim().to_string())
+            .unwrap_or_default()
+    }
+
+    fn remove_tree(store: &mut MockStore, prefix: &str) {
+        store.files.retain(|path, _| !path.starts_with(prefix));
+        store.collections.retain(|path| !path.starts_with(prefix));
+    }
+
+    fn copy_tree(store: &mut MockStore, path: &str, head: &str) -> u16 {
+        let source = format!("{}/", path.trim_end_matches('/'));
+        let target = format!("{}/", href_path(&header_value(head, "Destination")));
+        if !store.collections.contains(&source) {
+            return 404;
+        }
+        if store.collections.contains(&target) {
+            if header_value(head, "Overwrite") == "F" {
+                return 412;
+            }
+            remove_tree(store, &target);
+        }
+        let moved = |path: &String| format!("{}{}", target, &path[source.len()..]);
+        let collections: Vec<String> = store.collections.range(source.clone()..).take_while(|path| path.starts_with(&source)).map(moved).collect();
+        let files: Vec<(String, Vec<u8>)> = store
+            .files
+            .iter()
+            .filter(|(path, _)| path.starts_with(&source))
+            .map(|(path, content)| (moved(path), content.clone()))
+            .collect();
+        store.collections.extend(collections);
+        store.files.extend(files);
+        201
+    }
+
+    fn propfind_body(store: &MockStore, path: &str) -> Option<String> {
+        let collection = format!("{}/", path.trim_end_matches('/'));
+        let entry = |href: &str, is_collection: bool| {
+            let resource_type = if is_collection { "<d:collection/>" } else { "" };
+            format!("<d:response><d:href>{}</d:href><d:propstat><d:prop><d:resourcetype>{}</d:resourcetype></d:prop></d:propstat></d:response>", href, resource_type)
+        };
+        let mut responses = Vec::new();
+        if store.collections.contains(&collection) {
+            responses.push(entry(&collection, true));
+            let is_child = |candidate: &str| {
+                candidate
+                    .strip_prefix(&collection)
+                    .is_some_and(|rest| !rest.is_empty() && !rest.trim_end_matches('/').contains('/'))
+            };
+            responses.extend(store.collections.iter().filter(|child| is_child(child)).map(|child| entry(child, true)));
+            responses.extend(store.files.keys().filter(|child| is_child(child)).map(|child| entry(child, false)));
+        } else if store.files.contains_key(path) {
+            responses.push(entry(path, false));
+        } else {
+            return None;
+        }
+        Some(format!(r#"<?xml version="1.0"?><d:multistatus xmlns:d="DAV:">{}</d:multistatus>"#, responses.concat()))
+    }
+
+    fn respond(store: &Mutex<MockStore>, method: &str, path: &str, head: &str, body: Vec<u8>) -> (u16, Vec<u8>) {
+        let mut store = store.lock().unwrap();
+        let authorization = header_value(head, "Authorization");
+        if authorization.starts_with("Basic ") {
+            store.basic += 1;
+        }
+        if store.digest && !(authorization.starts_with(r#"Digest username="user""#) && authorization.contains(r#"nonce="n1""#)) {
+            store.challenges += 1;
+            return (401, Vec::new());
+        }
+        if store.locked > 0 {
+            store.locked -= 1;
+            return (423, Vec::new());
+        }
+        match method {
+            "MKCOL" => {
+                store.collections.insert(format!("{}/", path.trim_end_matches('/')));
+                (201, Vec::new())
+            }
+            "PUT" => {
+                store.files.insert(path.to_string(), body);
+                (201, Vec::new())
+            }
+            "DELETE" if path.ends_with('/') && store.collections.contains(path) => {
+                remove_tree(&mut store, path);
+                (204, Vec::new())
+            }
+            "DELETE" => match store.files.remove(path) {
+                Some(_) => (204, Vec::new()),
+                None => (404, Vec::new()),
+            },
+            "COPY" if store.copy => (copy_tree(&mut store, path, head), Vec::new()),
+            "GET" => match store.files.get(path) {
+                Some(content) => (200, content.clone()),
+                None => (404, Vec::new()),
+            },
+            "PROPFIND" => match propfind_body(&store, path) {
+                Some(xml) => (207, xml.into_bytes()),
+                None => (404, Vec::new()),
+            },
+            _ => (405, Vec::new()),
+        }
+    }
+
+    /// Minimal in-memory WebDAV server handling one request per connection.
+    async fn spawn_mock_webdav() -> String {
+        spawn_mock_webdav_with(Arc::default()).await
+    }
+
+    async fn spawn_mock_webdav_with(store: Arc<Mutex<MockStore>>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+        let address = listener.local_addr().expect("address");
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let store = store.clone();
+                tokio::spawn(async move {
+                    let mut request = Vec::new();
+                    let mut chunk = [0u8; 4096];
+                    let header_end = loop {
+                        let read = stream.read(&mut chunk).await.unwrap_or(0);
+                        if read == 0 {
+                            return;
+                        }
+                        request.extend_from_slice(&chunk[..read]);
+                        if let Some(index) = request.windows(4).position(|window| window == b"\r\n\r\n") {
+                            break index + 4;
+                        }
+                    };
+                    let head = String::from_utf8_lossy(&request[..header_end]).to_string();
+                    let content_length = head
+                        .lines()
+                        .filter_map(|line| line.split_once(':'))
+                        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+                        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+                        .unwrap_or(0);
+                    while request.len() < header_end + content_length {
+                        let read = stream.read(&mut chunk).await.unwrap_or(0);
+                        if read == 0 {
+                            break;
+                        }
+                        request.extend_from_slice(&chunk[..read]);
+                    }
+
+                    let mut request_line = head.lines().next().unwrap_or_default().split_whitespace();
+                    let method = request_line.next().unwrap_or_default().to_string();
+                    let path = request_line.next().unwrap_or_default().to_string();
+                    let (status, body) = respond(&store, &method, &path, &head, request[header_end..].to_vec());
+                    let extra = match status {
+                        423 => "Retry-After: 0\r\n",
+                        401 => "WWW-Authenticate: Digest realm=\"dav\", nonce=\"n1\", qop=\"auth\", algorithm=SHA-256\r\n",
+                        _ => "",
+                    };
+                    let head = format!(
+                        "HTTP/1.1 {} Mock\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n",
+                        status,
+                        body.len(),
+                        extra
+                    );
+                    let _ = stream.write_all(head.as_bytes()).await;
+                    let _ = stream.write_all(&body).await;
+                });
+            }
+        });
+        format!("http://{}", address)
+    }
+
+    fn config(url: String) -> WebDavConfig {
+        WebDavConfig {
+            url,
+            username: "user".to_string(),
+            password: "secret".to_string(),
+            remote_path: "/dav".to_string(),
+            concurrency: None,
+            allow_invalid_certs: false,
+            custom_ca_path: None,
+            sync_passphrase: None,
+            snapshot_retention: None,
+        }
+    }
+
+    fn sync_everything() -> CodexSyncConfig {
+        CodexSyncConfig {
+            sync_prompts: true,
+            sync_skills: true,
+            sync_agents_md: true,
+            sync_config_toml: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn codex_upload_then_download_restores_files_byte_for_byte() {
+        let config = config(spawn_mock_webdav().await);
+        let client = webdav_client(&config).expect("client");
+        let source = tempfile::tempdir().expect("source");
+        fs::write(source.path().join("AGENTS.MD"), "# agents").unwrap();
+        fs::write(source.path().join("config.toml"), "model = \"o3\"").unwrap();
+        fs::create_dir_all(source.path().join("prompts")).unwrap();
+        fs::write(source.path().join("prompts/review.md"), "review").unwrap();
+        fs::create_dir_all(source.path().join("skills/pdf")).unwrap();
+        fs::write(source.path().join("skills/pdf/SKILL.md"), "pdf skill").unwrap();
+        fs::create_dir_all(source.path().join("skills/pdf/assets")).unwrap();
+        let logo: Vec<u8> = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a].into_iter().chain(0..=255).collect();
+        fs::write(source.path().join("skills/pdf/assets/logo.png"), &logo).unwrap();
+        fs::create_dir_all(source.path().join("skills/pdf/node_modules/left-pad")).unwrap();
+        fs::write(source.path().join("skills/pdf/node_modules/left-pad/index.js"), "module.exports = 1").unwrap();
+        fs::write(source.path().join("skills/pdf/.syncignore"), "*.tmp\n").unwrap();
+        fs::write(source.path().join("skills/pdf/scratch.tmp"), "scratch").unwrap();
+
+        webdav_ensure_dir(&client, &config).await.expect("root dir");
+        let mut manifest = load_sync_manifest(&client, &config).await.expect("manifest");
+        let mut uploaded = SyncResult::default();
+        let mut progress = SyncProgress::detached();
+        upload_codex_files(&client, &config, source.path(), &sync_everything(), &mut manifest, &mut progress, &mut uploaded)
+            .await;
+        assert_eq!(progress.counts(), (5, 5));
+        write_sync_manifest(&client, &config, &manifest).await.expect("write manifest");
+        assert!(uploaded.errors.is_empty(), "{:?}", uploaded.errors);
+        assert_eq!(uploaded.ignored, ["skills/pdf/node_modules/", "skills/pdf/scratch.tmp"]);
+
+        let target = tempfile::tempdir().expect("target");
+        let manifest = load_sync_manifest(&client, &config).await.expect("manifest");
+        let mut downloaded = SyncResult::default();
+        let mut progress = SyncProgress::detached();
+        download_codex_files(&client, &config, target.path(), &sync_everything(), &manifest, &mut progress, &mut downloaded)
+            .await;
+        assert!(downloaded.errors.is_empty(), "{:?}", downloaded.errors);
+
+        let files = ["AGENTS.MD", "config.toml", "prompts/review.md", "skills/pdf/SKILL.md", "skills/pdf/assets/logo.png"];
+        for file in files {
+            assert_eq!(
+                fs::read(target.path().join(file)).ok(),
+                fs::read(source.path().join(file)).ok(),
+                "{}",
+                file
+            );
+        }
+    }
+
+    async fn snapshot_round_trip(store: Arc<Mutex<MockStore>>) {
+        let config = WebDavConfig {
+            snapshot_retention: Some(2),
+            ..config(spawn_mock_webdav_with(store.clone()).await)
+        };
+        let client = webdav_client(&config).expect("client");
+        let accounts = scoped_config(&config, "accounts");
+        webdav_ensure_dir(&client, &config).await.expect("root dir");
+        webdav_ensure_dir(&client, &accounts).await.expect("accounts dir");
+        webdav_ensure_dir(&client, &scoped_config(&accounts, "team")).await.expect("team dir");
+        webdav_upload(&client, &accounts, "a.json", b"{\"a\":1}").await.expect("a");
+        webdav_upload(&client, &scoped_config(&accounts, "team"), "b.json.enc", b"CRENC...").await.expect("b");
+
+        let mut names = Vec::new();
+        for _ in 0..3 {
+            let mut progress = SyncProgress::detached();
+            names.push(create_snapshot(&client, &config, &mut progress).await.expect("snapshot").name);
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+        let listed: Vec<String> = list_snapshots(&client, &config).await.expect("list").into_iter().map(|s| s.name).collect();
+        assert_eq!(listed, [names[2].clone(), names[1].clone()]);
+        let newest = scoped_config(&snapshots_config(&config), &names[2]);
+        let mut files = list_remote_files(&client, &newest).await.expect("snapshot files");
+        files.sort();
+        assert_eq!(files, ["a.json", "team/b.json.enc"]);
+
+        webdav_upload(&client, &accounts, "a.json", b"{\"a\":2}").await.expect("changed a");
+        webdav_upload(&client, &accounts, "c.json", b"{}").await.expect("c");
+        let mut progress = SyncProgress::detached();
+        let mut result = SyncResult::default();
+        restore_snapshot_remotely(&client, &config, newest, &mut progress, &mut result).await.expect("restore");
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        let mut files = list_remote_files(&client, &accounts).await.expect("accounts");
+        files.sort();
+        assert_eq!(files, ["a.json", "team/b.json.enc"]);
+        assert_eq!(webdav_download(&client, &accounts, "a.json").await.expect("a"), b"{\"a\":1}");
+    }
+
+    #[tokio::test]
+    async fn snapshots_copy_on_the_server_or_by_transfer() {
+        snapshot_round_trip(Arc::default()).await;
+        let store = Arc::new(Mutex::new(MockStore { copy: true, ..MockStore::default() }));
+        snapshot_round_trip(store).await;
+    }
+
+    #[tokio::test]
+    async fn transfers_stay_within_the_limit_and_keep_item_order() {
+        let in_flight = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let items: Vec<String> = (0..10).map(|index| format!("file-{}", index)).collect();
+        let mut progress = SyncProgress::detached();
+        let mut result = SyncResult::default();
+
+        let outcomes = run_transfers(
+            &items,
+            3,
+            |item| {
+                let (in_flight, peak) = (&in_flight, &peak);
+                async move {
+                    peak.fetch_max(in_flight.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+                    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    item.len()
+                }
+            },
+            |item| (SyncPhase::Uploading, item.as_str()),
+            &mut progress,
+            &mut result,
+        )
+        .await;
+
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+        let names: Vec<&String> = outcomes.iter().map(|(item, _)| *item).collect();
+        assert_eq!(names, items.iter().collect::<Vec<_>>());
+        assert_eq!(progress.counts(), (10, 10));
+    }
+
+    #[tokio::test]
+    async fn retries_locked_requests_then_reports_the_attempts() {
+        let store = Arc::new(Mutex::new(MockStore::default()));
+        let config = config(spawn_mock_webdav_with(store.clone()).await);
+        let client = webdav_client(&config).expect("client");
+
+        store.lock().unwrap().locked = 2;
+        webdav_upload(&client, &config, "AGENTS.MD", b"# agents").await.expect("upload after retries");
+        assert_eq!(store.lock().unwrap().files.get("/dav/AGENTS.MD").map(Vec::as_slice), Some(&b"# agents"[..]));
+
+        store.lock().unwrap().locked = 3;
+        let error = webdav_download(&client, &config, "AGENTS.MD").await.expect_err("still locked");
+        assert!(error.message.contains("HTTP 423") && error.message.ends_with("(after 3 attempts)"), "{}", error.message);
+        assert!(is_not_found(&webdav_download(&client, &config, "missing.md").await.expect_err("missing")));
+    }
+
+    #[tokio::test]
+    async fn encrypted_account_files_replace_plaintext_and_need_the_passphrase() {
+        let store = Arc::new(Mutex::new(MockStore::default()));
+        let config = config(spawn_mock_webdav_with(store.clone()).await);
+        let client = webdav_client(&config).expect("client");
+        let accounts_config = scoped_config(&config, "accounts");
+        let local = tempfile::tempdir().expect("local");
+        let account = r#"{"tokens":{"refresh_token":"rt-secret"}}"#;
+        fs::write(local.path().join("work.json"), account).unwrap();
+        webdav_ensure_dir(&client, &config).await.expect("root dir");
+        webdav_ensure_dir(&client, &accounts_config).await.expect("accounts dir");
+        webdav_upload(&client, &accounts_config, "work.json", account.as_bytes()).await.expect("plaintext");
+
+        let cipher = SyncCipher::with_iterations("passphrase", 1_000).unwrap();
+        let manifest = load_sync_manifest(&client, &config).await.expect("manifest");
+        let mut accounts = RemoteAccounts::new(&client, accounts_config.clone(), Some(cipher));
+        assert!(remote_account_entries(&mut accounts, &manifest).await.unwrap().contains_key("work.json"));
+        upload_account_file(&accounts, local.path(), "work.json").await.expect("encrypted upload");
+        {
+            let store = store.lock().unwrap();
+            assert!(!store.files.contains_key("/dav/accounts/work.json"));
+            let stored = &store.files["/dav/accounts/work.json.enc"];
+            assert!(!stored.windows(9).any(|window| window == b"rt-secret"));
+        }
+
+        let target = tempfile::tempdir().expect("target");
+        let other_machine = SyncCipher::with_iterations("passphrase", 1_000).unwrap();
+        let mut accounts = RemoteAccounts::new(&client, accounts_config.clone(), Some(other_machine));
+        let remote = remote_account_entries(&mut accounts, &manifest).await.unwrap();
+        assert_eq!(remote.keys().collect::<Vec<_>>(), vec!["work.json"]);
+        download_account_file(&accounts, target.path(), "work.json").await.expect("decrypted download");
+        assert_eq!(fs::read_to_string(target.path().join("work.json")).unwrap(), account);
+
+        let wrong = SyncCipher::with_iterations("other", 1_000).unwrap();
+        let mut accounts = RemoteAccounts::new(&client, accounts_config.clone(), Some(wrong));
+        remote_account_entries(&mut accounts, &manifest).await.unwrap();
+        let error = download_account_file(&accounts, target.path(), "work.json").await.expect_err("wrong passphrase");
+        assert_eq!(error.code, AppErrorCode::Auth);
+
+        let mut accounts = RemoteAccounts::new(&client, accounts_config, None);
+        remote_account_entries(&mut accounts, &manifest).await.unwrap();
+        assert!(download_account_file(&accounts, target.path(), "work.json").await.is_err());
+        assert!(upload_account_file(&accounts, local.path(), "work.json").await.is_err());
+    }
+
+    #[test]
+    fn custom_ca_must_be_a_readable_certificate() {
+        let dir = tempfile::tempdir().expect("dir");
+        let mut config = config("https://dav.example.com".to_string());
+        config.custom_ca_path = Some(dir.path().join("missing.pem").to_string_lossy().to_string());
+        assert_eq!(webdav_client(&config).err().map(|error| error.code), Some(AppErrorCode::Io));
+
+        let garbage = dir.path().join("garbage.pem");
+        fs::write(&garbage, "not a certificate").unwrap();
+        config.custom_ca_path = Some(garbage.to_string_lossy().to_string());
+        assert_eq!(webdav_client(&config).err().map(|error| error.code), Some(AppErrorCode::Parse));
+
+        config.custom_ca_path = Some("  ".to_string());
+        assert!(webdav_client(&config).is_ok());
+    }
+
+    #[tokio::test]
+    async fn switches_to_digest_once_per_host() {
+        let store = Arc::new(Mutex::new(MockStore {
+            digest: true,
+            ..MockStore::default()
+        }));
+        let config = config(spawn_mock_webdav_with(store.clone()).await);
+        let client = webdav_client(&config).expect("client");
+
+        webdav_ensure_dir(&client, &config).await.expect("root dir");
+        webdav_upload(&client, &config, "AGENTS.MD", b"# agents").await.expect("upload");
+        assert_eq!(webdav_download(&client, &config, "AGENTS.MD").await.expect("download"), b"# agents");
+        assert_eq!(store.lock().unwrap().challenges, 1);
+        assert_eq!(client.auth_scheme(&remote_url(&config, None)), Some(WebDavAuthScheme::Digest));
+        assert_eq!(store.lock().unwrap().basic, 0);
+    }
+
+    #[tokio::test]
+    async fn cancelled_upload_stops_before_the_next_file() {
+        let config = config(spawn_mock_webdav().await);
+        let client = webdav_client(&config).expect("client");
+        let source = tempfile::tempdir().expect("source");
+        fs::write(source.path().join("AGENTS.MD"), "# agents").unwrap();
+        fs::write(source.path().join("config.toml"), "model = \"o3\"").unwrap();
+
+        webdav_ensure_dir(&client, &config).await.expect("root dir");
+        let mut manifest = load_sync_manifest(&client, &config).await.expect("manifest");
+        let mut result = SyncResult::default();
+        let mut progress = SyncProgress::detached();
+        progress.cancel();
+        upload_codex_files(&client, &config, source.path(), &sync_everything(), &mut manifest, &mut progress, &mut result)
+            .await;
+
+        assert!(result.cancelled);
+        assert!(result.uploaded.is_empty());
+        assert!(manifest.entries.is_empty());
+    }
+
+    #[tokio::test]
+    async fn codex_download_falls_back_to_legacy_root_layout() {
+        let config = config(spawn_mock_webdav().await);
+        let client = webdav_client(&config).expect("client");
+        let prompts = scoped_config(&config, "prompts");
+        webdav_ensure_dir(&client, &config).await.expect("root dir");
+        webdav_ensure_dir(&client, &prompts).await.expect("prompts dir");
+        webdav_upload(&client, &config, "AGENTS.MD", b"# legacy").await.expect("agents");
+        webdav_upload(&client, &prompts, "old.md", b"old prompt").await.expect("prompt");
+
+        let target = tempfile::tempdir().expect("target");
+        let manifest = load_sync_manifest(&client, &config).await.expect("manifest");
+        let mut downloaded = SyncResult::default();
+        let mut progress = SyncProgress::detached();
+        download_codex_files(&client, &config, target.path(), &sync_everything(), &manifest, &mut progress, &mut downloaded)
+            .await;
+
+        assert!(downloaded.errors.is_empty(), "{:?}", downloaded.errors);
+        assert_eq!(fs::read_to_string(target.path().join("AGENTS.MD")).unwrap(), "# legacy");
+        assert_eq!(fs::read_to_string(target.path().join("prompts/old.md")).unwrap(), "old prompt");
+    }
+
+    #[test]
+    fn relative_names_skip_the_listed_dir_by_path() {
+        let config = WebDavConfig {
+            remote_path: "code revolver/accounts".to_string(),
+            ..config("https://cloud.example.com/remote.php/dav/files/me/".to_string())
+        };
+        let name = |href: &str| {
+            relative_resource_name(
+                &WebDavResource {
+                    href: href.to_string(),
+                    is_collection: false,
+                    last_modified: None,
+                    content_length: None,
+                },
+                &config,
+            )
+        };
+
+        assert_eq!(name("/remote.php/dav/files/me/code%20revolver/accounts/"), None);
+        assert_eq!(name("https://cloud.example.com/remote.php/dav/files/me/code revolver/accounts"), None);
+        assert_eq!(name("/remote.php/dav/files/me/code%20revolver/accounts/.code-revolver-sync.json"), None);
+        assert_eq!(
+            name("/remote.php/dav/files/me/code%20revolver/accounts/Tom%20&%20Jerry.json").as_deref(),
+            Some("Tom & Jerry.json")
+        );
+        assert_eq!(name("/dav/code%20revolver/accounts/"), None);
+        assert_eq!(name("/dav/code%20revolver/accounts/work/").as_deref(), Some("work"));
+    }
+
+    #[test]
+    fn traversal_hrefs_in_a_listing_are_skipped() {
+        let body = r#"<?xml version="1.0" encoding="utf-8"?>
+        <d:multistatus xmlns:d="DAV:">
+          <d:response><d:href>/dav/prompts/</d:href></d:response>
+          <d:response><d:href>/dav/prompts/..</d:href></d:response>
+          <d:response><d:href>/dav/prompts/%2E%2E/</d:href></d:response>
+          <d:response><d:href>/dav/prompts/.</d:href></d:response>
+          <d:response><d:href>/dav/prompts/..%5Cevil.md</d:href></d:response>
+          <d:response><d:href>/dav/prompts/C:evil.md</d:href></d:response>
+          <d:response><d:href>/dav/prompts/nul%00.md</d:href></d:response>
+          <d:response><d:href>https://other.example.com/elsewhere/..</d:href></d:response>
+          <d:response><d:href>/dav/prompts/review.md</d:href></d:response>
+        </d:multistatus>"#;
+        let prompts = scoped_config(&config("https://dav.example.com".to_string()), "prompts");
+        let names: Vec<String> = parse_propfind_resources(body)
+            .expect("resources")
+            .iter()
+            .filter_map(|resource| relative_resource_name(resource, &prompts))
+            .collect();
+        assert_eq!(names, ["review.md"]);
+    }
+
+    #[test]
+    fn downloads_over_newer_local_changes_from_other_devices_are_flagged() {
+        let mut laptop = SyncManifest {
+            device: "laptop".to_string(),
+            ..empty_manifest()
+        };
+        for name in ["a.json", "b.json", "c.json", "d.json"] {
+            upsert_manifest_entry(&mut laptop, format!("accounts/{}", name), "remote".to_string(), 1);
+        }
+        let uploaded_at = laptop.generated_at;
+        let stored = serde_json::to_string(&laptop).unwrap();
+        assert!(stored.contains(r#""device":"laptop""#) && stored.contains("uploadedAt"));
+        let mut manifest: SyncManifest = serde_json::from_str(&stored).unwrap();
+        manifest.device = "desktop".to_string();
+
+        let entry = |name: &str, modified_at: i64, hash: &str| {
+            let entry = SyncPreviewEntry {
+                name: name.to_string(),
+                item_type: SyncItemType::Account,
+                modified_at: Some(modified_at),
+                size: Some(1),
+                hash: Some(hash.to_string()),
+            };
+            (name.to_string(), entry)
+        };
+        let local: HashMap<String, SyncPreviewEntry> = HashMap::from([
+            entry("a.json", uploaded_at - 10, "local"),
+            entry("b.json", uploaded_at + 10, "local"),
+            entry("c.json", uploaded_at - 10, "synced"),
+            entry("d.json", uploaded_at - 10, "remote"),
+        ]);
+        let synced = HashMap::from([(
+            "c.json".to_string(),
+            SyncStateEntry { hash: "synced".to_string(), remote_modified_at: None },
+        )]);
+        let plan: Vec<SyncPlanItem> = ["a.json", "b.json", "c.json", "d.json", "e.json"]
+            .iter()
+            .map(|name| SyncPlanItem {
+                name: name.to_string(),
+                action: SyncPlanAction::Download,
+                side: None,
+                local_size: None,
+                remote_size: None,
+                local_modified_at: None,
+                remote_modified_at: Some(uploaded_at),
+            })
+            .collect();
+
+        let warnings = overwrite_warnings(&manifest, &plan, &local, &synced);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].name, "a.json");
+        assert_eq!(warnings[0].remote_device.as_deref(), Some("laptop"));
+        assert!(warnings[0].overwritten);
+        manifest.device = "laptop".to_string();
+        assert!(overwrite_warnings(&manifest, &plan, &local, &synced).is_empty());
+
+        let mut result = SyncResult::default();
+        keep_downloaded_warnings(warnings.clone(), &mut result);
+        assert!(result.conflicts.is_empty());
+        result.downloaded.push("a.json".to_string());
+        keep_downloaded_warnings(warnings, &mut result);
+        assert_eq!(result.conflicts.len(), 1);
+    }
+}
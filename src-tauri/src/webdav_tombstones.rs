@@ -0,0 +1,104 @@
+use crate::account_files::write_private_file_atomic;
+use crate::config::get_config_file;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const TOMBSTONES_FILE: &str = "sync_tombstones.json";
+/// Long enough for every synced machine to have seen a deletion.
+const TOMBSTONE_RETENTION_MS: i64 = 90 * 24 * 60 * 60 * 1000;
+
+/// Deleted account files (path below `accounts/`) with their deletion time in Unix ms, kept
+/// locally and at the remote root so a sync removes other copies instead of restoring them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Tombstones {
+    #[serde(default)]
+    pub deleted: BTreeMap<String, i64>,
+}
+
+impl Tombstones {
+    pub fn is_empty(&self) -> bool {
+        self.deleted.is_empty()
+    }
+
+    /// Keeps the later deletion of each file.
+    pub fn merge(&mut self, other: Tombstones) {
+        for (name, deleted_at) in other.deleted {
+            let entry = self.deleted.entry(name).or_insert(deleted_at);
+            *entry = (*entry).max(deleted_at);
+        }
+    }
+
+    pub fn prune(&mut self, now_ms: i64) {
+        self.deleted.retain(|_, deleted_at| now_ms - *deleted_at < TOMBSTONE_RETENTION_MS);
+    }
+
+    /// Whether the copy of `name` last modified at `modified_at` was deleted afterwards. Copies
+    /// with an unknown modification time are never treated as deleted.
+    pub fn buries(&self, name: &str, modified_at: Option<i64>) -> bool {
+        self.deleted
+            .get(name)
+            .is_some_and(|deleted_at| modified_at.is_some_and(|modified_at| modified_at <= *deleted_at))
+    }
+}
+
+pub fn get_tombstones_file() -> PathBuf {
+    get_config_file().with_file_name(TOMBSTONES_FILE)
+}
+
+pub fn load_tombstones(path: &Path) -> Tombstones {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_tombstones(path: &Path, tombstones: &Tombstones) -> Result<(), String> {
+    let content =
+        serde_json::to_vec_pretty(tombstones).map_err(|e| format!("Failed to serialize tombstones: {}", e))?;
+    write_private_file_atomic(path, &content).map_err(|e| format!("Failed to write tombstones: {}", e))
+}
+
+/// Records that the account file at `path` inside `accounts_dir` was deleted now.
+pub fn record_account_deletion(accounts_dir: &Path, path: &Path) -> Result<(), String> {
+    let Ok(relative) = path.strip_prefix(accounts_dir) else {
+        return Ok(());
+    };
+    let name = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    let tombstones_file = get_tombstones_file();
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let mut tombstones = load_tombstones(&tombstones_file);
+    tombstones.prune(now_ms);
+    tombstones.deleted.insert(name, now_ms);
+    save_tombstones(&tombstones_file, &tombstones)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Tombstones;
+
+    #[test]
+    fn merges_later_deletions_and_buries_only_older_copies() {
+        let mut tombstones = Tombstones::default();
+        tombstones.deleted.insert("work/a.json".to_string(), 5_000);
+        tombstones.merge(Tombstones {
+            deleted: [("work/a.json".to_string(), 9_000), ("b.json".to_string(), 1_000)].into(),
+        });
+
+        assert_eq!(tombstones.deleted["work/a.json"], 9_000);
+        assert!(tombstones.buries("work/a.json", Some(8_000)));
+        assert!(!tombstones.buries("work/a.json", Some(10_000)));
+        assert!(!tombstones.buries("work/a.json", None));
+        assert!(!tombstones.buries("c.json", Some(1)));
+
+        tombstones.prune(1_000 + 90 * 24 * 60 * 60 * 1000);
+        assert!(!tombstones.deleted.contains_key("b.json"));
+        assert!(tombstones.deleted.contains_key("work/a.json"));
+    }
+}
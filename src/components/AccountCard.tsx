@@ -136,6 +136,10 @@ export function AccountCard({
       return;
     }
 
+    if (result.code === 'REFRESH_DEAD') {
+      notifyError('This login has expired. Run `codex login` again, then re-import the account.', 'Token Refresh');
+      return;
+    }
     if (result.message) {
       notifyError(`Refresh failed: ${result.message}`, 'Token Refresh');
     }
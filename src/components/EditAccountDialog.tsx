@@ -29,20 +29,26 @@ export function EditAccountDialog({ isOpen, onClose, account, onSave }: EditAcco
   const [loading, setLoading] = useState(false);
   const [loadingContent, setLoadingContent] = useState(false);
   const [copied, setCopied] = useState(false);
+  // Tokens stay masked until the user explicitly reveals them; masked content is never saved.
+  const [revealed, setRevealed] = useState(false);
 
   const loadContent = useCallback(async () => {
     if (!account) return;
     setLoadingContent(true);
     setError('');
     try {
-      const content = await commands.readAccountContent(account.filePath);
+      const content = await commands.readAccountContent(account.filePath, !revealed);
       setJsonContent(content);
     } catch (e: unknown) {
       setError(toErrorMessage(e));
     } finally {
       setLoadingContent(false);
     }
-  }, [account]);
+  }, [account, revealed]);
+
+  useEffect(() => {
+    if (!isOpen) setRevealed(false);
+  }, [isOpen]);
 
   useEffect(() => {
     if (isOpen && account) {
@@ -51,7 +57,8 @@ export function EditAccountDialog({ isOpen, onClose, account, onSave }: EditAcco
   }, [isOpen, account, loadContent]);
 
   const handleSave = async () => {
-    if (!account || !jsonContent.trim()) {
+    if (!account || !revealed) return;
+    if (!jsonContent.trim()) {
       setError('Content cannot be empty');
       return;
     }
@@ -113,23 +120,28 @@ export function EditAccountDialog({ isOpen, onClose, account, onSave }: EditAcco
                 <div>
                   <div className="flex justify-between items-center mb-1.5">
                     <label className="block text-sm font-medium text-slate-400">auth.json Content</label>
-                    <Button onClick={handleCopy} variant="ghost" size="sm" className="h-7 px-2 text-primary-400 hover:text-primary-300">
-                      {copied ? (
-                        <>
-                          <svg className="w-3 h-3" fill="none" viewBox="0 0 24 24" stroke="currentColor">
-                            <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M5 13l4 4L19 7" />
-                          </svg>
-                          Copied
-                        </>
-                      ) : (
-                        <>
-                          <svg className="w-3 h-3" fill="none" viewBox="0 0 24 24" stroke="currentColor">
-                            <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M8 16H6a2 2 0 01-2-2V6a2 2 0 012-2h8a2 2 0 012 2v2m-6 12h8a2 2 0 002-2v-8a2 2 0 00-2-2h-8a2 2 0 00-2 2v8a2 2 0 002 2z" />
-                          </svg>
-                          Copy
-                        </>
-                      )}
-                    </Button>
+                    <div className="flex items-center gap-1">
+                      <Button onClick={() => setRevealed(value => !value)} variant="ghost" size="sm" className="h-7 px-2 text-primary-400 hover:text-primary-300" disabled={loadingContent}>
+                        {revealed ? 'Hide tokens' : 'Reveal to edit'}
+                      </Button>
+                      <Button onClick={handleCopy} variant="ghost" size="sm" className="h-7 px-2 text-primary-400 hover:text-primary-300">
+                        {copied ? (
+                          <>
+                            <svg className="w-3 h-3" fill="none" viewBox="0 0 24 24" stroke="currentColor">
+                              <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M5 13l4 4L19 7" />
+                            </svg>
+                            Copied
+                          </>
+                        ) : (
+                          <>
+                            <svg className="w-3 h-3" fill="none" viewBox="0 0 24 24" stroke="currentColor">
+                              <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M8 16H6a2 2 0 01-2-2V6a2 2 0 012-2h8a2 2 0 012 2v2m-6 12h8a2 2 0 002-2v-8a2 2 0 00-2-2h-8a2 2 0 00-2 2v8a2 2 0 002 2z" />
+                            </svg>
+                            Copy
+                          </>
+                        )}
+                      </Button>
+                    </div>
                   </div>
                   {loadingContent ? (
                     <div className="w-full h-[240px] rounded-xl border border-white/15 bg-white/5 flex items-center justify-center">
@@ -139,7 +151,7 @@ export function EditAccountDialog({ isOpen, onClose, account, onSave }: EditAcco
                       </svg>
                     </div>
                   ) : (
-                    <textarea value={jsonContent} onChange={(e) => setJsonContent(e.target.value)} rows={10} className="w-full resize-none rounded-md border border-white/15 bg-slate-950/60 p-3 font-mono text-xs text-white placeholder:text-slate-500 focus-visible:outline-none focus-visible:ring-2 focus-visible:ring-primary-500/60" />
+                    <textarea value={jsonContent} onChange={(e) => setJsonContent(e.target.value)} readOnly={!revealed} rows={10} className="w-full resize-none rounded-md border border-white/15 bg-slate-950/60 p-3 font-mono text-xs text-white placeholder:text-slate-500 focus-visible:outline-none focus-visible:ring-2 focus-visible:ring-primary-500/60" />
                   )}
                 </div>
 
@@ -155,7 +167,7 @@ export function EditAccountDialog({ isOpen, onClose, account, onSave }: EditAcco
 
               <div className="mt-8 flex justify-end gap-3">
                 <Button variant="outline" onClick={handleClose} disabled={loading}>Cancel</Button>
-                <Button variant="default" onClick={handleSave} disabled={loading || loadingContent || !jsonContent.trim()}>{loading ? 'Saving...' : 'Save'}</Button>
+                <Button variant="default" onClick={handleSave} disabled={loading || loadingContent || !revealed || !jsonContent.trim()}>{loading ? 'Saving...' : 'Save'}</Button>
               </div>
             </Card>
           </motion.div>
@@ -110,31 +110,47 @@ export function SyncConfirmDialog({
         syncConfigToml: sync.syncConfigToml,
       };
 
-      const result: SyncResult = { uploaded: [], downloaded: [], errors: [] };
+      const result: SyncResult = {
+        uploaded: [],
+        downloaded: [],
+        errors: [],
+        skipped: [],
+        conflicts: [],
+        deleted: [],
+        plan: [],
+        cancelled: false,
+        ignored: [],
+      };
 
       if (direction === 'upload') {
         // Upload Codex config
         const codexResult = await commands.syncCodexUpload(config, syncConfig);
         result.uploaded.push(...codexResult.uploaded);
         result.errors.push(...codexResult.errors);
+        result.ignored.push(...codexResult.ignored);
+        result.cancelled = codexResult.cancelled;
 
         // Upload account files
-        if (sync.syncAccounts) {
+        if (sync.syncAccounts && !result.cancelled) {
           const accountResult = await commands.syncAccountsUpload(config);
           result.uploaded.push(...accountResult.uploaded.map(f => `Account: ${f}`));
           result.errors.push(...accountResult.errors);
+          result.cancelled = accountResult.cancelled;
         }
       } else {
         // Download Codex config
         const codexResult = await commands.syncCodexDownload(config, syncConfig);
         result.downloaded.push(...codexResult.downloaded);
         result.errors.push(...codexResult.errors);
+        result.cancelled = codexResult.cancelled;
 
         // Download account files
-        if (sync.syncAccounts) {
+        if (sync.syncAccounts && !result.cancelled) {
           const accountResult = await commands.syncAccountsDownload(config);
           result.downloaded.push(...accountResult.downloaded.map(f => `Account: ${f}`));
           result.errors.push(...accountResult.errors);
+          result.conflicts.push(...accountResult.conflicts);
+          result.cancelled = accountResult.cancelled;
         }
       }
 
@@ -150,6 +166,12 @@ export function SyncConfirmDialog({
         uploaded: [],
         downloaded: [],
         errors: [toErrorMessage(error)],
+        skipped: [],
+        conflicts: [],
+        deleted: [],
+        plan: [],
+        cancelled: false,
+        ignored: [],
       });
     } finally {
       setSyncing(false);
@@ -269,6 +291,30 @@ export function SyncConfirmDialog({
                   </div>
                 )}
 
+                {syncResult.conflicts.some((conflict) => conflict.overwritten) && (
+                  <div className="text-xs">
+                    <div className="text-amber-400 mb-1">Local changes replaced by another device</div>
+                    <div className="text-amber-300 max-h-24 overflow-y-auto custom-scrollbar rounded-lg border border-amber-500/30 bg-amber-500/10 p-2">
+                      {syncResult.conflicts.filter((conflict) => conflict.overwritten).map((conflict, i) => (
+                        <div key={i} className="truncate">
+                          {conflict.name}{conflict.remoteDevice ? ` (uploaded by ${conflict.remoteDevice})` : ''}
+                        </div>
+                      ))}
+                    </div>
+                  </div>
+                )}
+
+                {syncResult.ignored.length > 0 && (
+                  <div className="text-xs">
+                    <div className="text-slate-400 mb-1">Ignored ({syncResult.ignored.length})</div>
+                    <div className="text-slate-500 max-h-24 overflow-y-auto custom-scrollbar rounded-lg border border-white/10 bg-white/5 p-2">
+                      {syncResult.ignored.map((item, i) => (
+                        <div key={i} className="truncate">{item}</div>
+                      ))}
+                    </div>
+                  </div>
+                )}
+
                 {syncResult.errors.length > 0 && (
                   <div className="text-xs">
                     <div className="text-rose-400 mb-1">Errors</div>
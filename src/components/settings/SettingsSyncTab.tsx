@@ -1,5 +1,6 @@
-import { AppSettings, DEFAULT_SYNC_SETTINGS, WebDavConfig } from '../../types';
-import { hasWebDavCredentials } from '../../lib/webdav';
+import { useEffect, useState } from 'react';
+import { AppSettings, DEFAULT_SYNC_SETTINGS, PasswordStorage, SnapshotInfo, SnapshotTarget, WebDavConfig } from '../../types';
+import { hasWebDavCredentials, resolveWebDavRequestConfig } from '../../lib/webdav';
 import { Button, Input } from '../ui';
 import { commands } from '../../lib/commands';
 import { useNotifications } from '../../lib/notificationState';
@@ -47,7 +48,89 @@ export function SettingsSyncTab({
   onTestConnection,
 }: SettingsSyncTabProps) {
   const sync = settings.sync || DEFAULT_SYNC_SETTINGS;
-  const { notifyError } = useNotifications();
+  const { notifyError, notifySuccess } = useNotifications();
+  const [hasPassphrase, setHasPassphrase] = useState(false);
+  const [passphrase, setPassphrase] = useState('');
+  const [passwordStorage, setPasswordStorage] = useState<PasswordStorage | null>(null);
+  const [snapshots, setSnapshots] = useState<SnapshotInfo[] | null>(null);
+  const [snapshotBusy, setSnapshotBusy] = useState(false);
+  // Restore awaiting a second click, as `${target}:${name}`
+  const [pendingRestore, setPendingRestore] = useState<string | null>(null);
+  const [deviceName, setDeviceName] = useState('');
+  // Hostname shown as the placeholder while no name is set
+  const [defaultDeviceName, setDefaultDeviceName] = useState('');
+
+  useEffect(() => {
+    commands.hasSyncPassphrase().then(setHasPassphrase).catch(() => setHasPassphrase(false));
+    Promise.all([commands.getAppConfig(), commands.getDeviceName()])
+      .then(([config, name]) => {
+        setDeviceName(config.deviceName ?? '');
+        setDefaultDeviceName(name);
+      })
+      .catch(() => setDefaultDeviceName(''));
+  }, []);
+
+  const saveDeviceName = async () => {
+    try {
+      const config = await commands.setDeviceName(deviceName.trim() || null);
+      setDeviceName(config.deviceName ?? '');
+      if (!config.deviceName) {
+        setDefaultDeviceName(await commands.getDeviceName());
+      }
+    } catch (e) {
+      notifyError(toErrorMessage(e), 'Device Name');
+    }
+  };
+
+  useEffect(() => {
+    commands.getWebDavConfig()
+      .then((stored) => setPasswordStorage(stored?.passwordStorage ?? null))
+      .catch(() => setPasswordStorage(null));
+  }, [webdav.hasStoredPassword]);
+
+  const savePassphrase = async (next: string) => {
+    try {
+      await commands.setSyncPassphrase(next);
+      setHasPassphrase(next.length > 0);
+      setPassphrase('');
+    } catch (e) {
+      notifyError(toErrorMessage(e), 'Sync Passphrase');
+    }
+  };
+
+  const runSnapshotAction = async (title: string, action: () => Promise<void>) => {
+    setSnapshotBusy(true);
+    try {
+      await action();
+      setSnapshots(await commands.listSnapshots(await resolveWebDavRequestConfig(webdav)));
+    } catch (e) {
+      notifyError(toErrorMessage(e), title);
+    } finally {
+      setSnapshotBusy(false);
+      setPendingRestore(null);
+    }
+  };
+
+  const createSnapshot = () => runSnapshotAction('Create Snapshot', async () => {
+    const snapshot = await commands.createSnapshot(await resolveWebDavRequestConfig(webdav));
+    notifySuccess(`Snapshot ${snapshot.name} created`, 'Create Snapshot');
+  });
+
+  const restoreSnapshot = (name: string, target: SnapshotTarget) => {
+    if (pendingRestore !== `${target}:${name}`) {
+      setPendingRestore(`${target}:${name}`);
+      return;
+    }
+    void runSnapshotAction('Restore Snapshot', async () => {
+      const result = await commands.restoreSnapshot(await resolveWebDavRequestConfig(webdav), name, target);
+      const restored = target === 'local' ? result.downloaded.length : result.uploaded.length;
+      if (result.errors.length > 0) {
+        notifyError(`${restored} files restored, ${result.errors.length} failed: ${result.errors[0]}`, 'Restore Snapshot');
+      } else {
+        notifySuccess(`${restored} files restored ${target === 'local' ? 'to this device' : 'to the server'}`, 'Restore Snapshot');
+      }
+    });
+  };
 
   const updateWebdav = (updates: Partial<typeof webdav>) => {
     onUpdateWebDav(updates);
@@ -145,6 +228,11 @@ export function SettingsSyncTab({
                   {loadingWebdavPassword ? 'Loading...' : showWebdavPassword ? 'Hide' : 'Show'}
                 </Button>
               </div>
+              {passwordStorage === 'file' && (
+                <p className="text-xs text-amber-400 mt-1">
+                  No system keyring is available, so the password is saved unencrypted in the app config folder.
+                </p>
+              )}
             </div>
 
             <div>
@@ -158,6 +246,30 @@ export function SettingsSyncTab({
               />
             </div>
 
+            <div>
+              <label className="block text-xs font-medium text-slate-400 mb-1">Custom CA Certificate (optional)</label>
+              <Input
+                type="text"
+                value={webdav.customCaPath ?? ''}
+                onChange={(e) => updateWebdav({ customCaPath: e.target.value })}
+                placeholder="/path/to/ca.pem"
+                className="text-sm"
+              />
+            </div>
+
+            <label className="flex items-start gap-3 cursor-pointer">
+              <input
+                type="checkbox"
+                checked={Boolean(webdav.allowInvalidCerts)}
+                onChange={(e) => updateWebdav({ allowInvalidCerts: e.target.checked })}
+                className="mt-0.5 w-4 h-4 rounded border-slate-600 text-primary-500 focus:ring-primary-500 focus:ring-offset-0 bg-slate-700"
+              />
+              <div>
+                <div className="text-sm text-white">Allow invalid certificates</div>
+                <div className="text-xs text-amber-400">Insecure: skips TLS verification. Prefer a custom CA for self-signed servers.</div>
+              </div>
+            </label>
+
             <Button
               variant="outline"
               size="sm"
@@ -258,6 +370,89 @@ export function SettingsSyncTab({
             </label>
           </div>
 
+          <div className="space-y-2">
+            <div className="font-medium text-slate-200">End-to-End Encryption</div>
+            <div className="text-xs text-slate-400">
+              {hasPassphrase
+                ? 'Account files are encrypted before upload. Use the same passphrase on every device.'
+                : 'Account files are uploaded as plaintext. Set a passphrase to encrypt them.'}
+            </div>
+            <div className="flex gap-2">
+              <Input
+                type="password"
+                value={passphrase}
+                onChange={(e) => setPassphrase(e.target.value)}
+                placeholder={hasPassphrase ? 'Stored in secure storage' : 'Sync passphrase'}
+                className="text-sm flex-1"
+              />
+              <Button variant="outline" size="sm" disabled={!passphrase} onClick={() => void savePassphrase(passphrase)}>
+                Save
+              </Button>
+              {hasPassphrase && (
+                <Button variant="outline" size="sm" onClick={() => void savePassphrase('')}>
+                  Remove
+                </Button>
+              )}
+            </div>
+          </div>
+
+          <div className="space-y-2">
+            <div className="font-medium text-slate-200">Device Name</div>
+            <div className="text-xs text-slate-400">
+              Recorded with each file this device uploads, so a download warns when it replaces a newer local change with another device's copy.
+            </div>
+            <div className="flex gap-2">
+              <Input
+                value={deviceName}
+                maxLength={64}
+                onChange={(e) => setDeviceName(e.target.value)}
+                placeholder={defaultDeviceName || 'Hostname'}
+                className="text-sm flex-1"
+              />
+              <Button variant="outline" size="sm" onClick={() => void saveDeviceName()}>
+                Save
+              </Button>
+            </div>
+          </div>
+
+          <div className="space-y-2">
+            <div className="font-medium text-slate-200">Snapshots</div>
+            <div className="text-xs text-slate-400">
+              Copies of the remote account files kept under snapshots/ on the server.
+            </div>
+            <div className="flex items-center gap-2">
+              <label className="text-xs text-slate-400">Keep</label>
+              <Input
+                type="number"
+                min={1}
+                value={webdav.snapshotRetention ?? 10}
+                onChange={(e) => updateWebdav({ snapshotRetention: Math.max(1, Number(e.target.value) || 1) })}
+                className="text-sm w-20"
+              />
+              <div className="flex-1" />
+              <Button variant="outline" size="sm" disabled={snapshotBusy} onClick={() => void runSnapshotAction('Snapshots', async () => {})}>
+                {snapshots === null ? 'Show' : 'Refresh'}
+              </Button>
+              <Button variant="outline" size="sm" disabled={snapshotBusy} onClick={() => void createSnapshot()}>
+                {snapshotBusy ? 'Working...' : 'Create Snapshot'}
+              </Button>
+            </div>
+            {snapshots !== null && snapshots.length === 0 && (
+              <div className="text-xs text-slate-500">No snapshots yet</div>
+            )}
+            {snapshots?.map((snapshot) => (
+              <div key={snapshot.name} className="flex items-center gap-2 p-2 rounded-lg border border-white/10 bg-white/5">
+                <span className="text-xs text-slate-300 flex-1">{new Date(snapshot.createdAt).toLocaleString('en-US')}</span>
+                <Button variant="outline" size="sm" disabled={snapshotBusy} onClick={() => restoreSnapshot(snapshot.name, 'local')}>
+                  {pendingRestore === `local:${snapshot.name}` ? 'Confirm' : 'Restore Here'}
+                </Button>
+                <Button variant="outline" size="sm" disabled={snapshotBusy} onClick={() => restoreSnapshot(snapshot.name, 'remote')}>
+                  {pendingRestore === `remote:${snapshot.name}` ? 'Confirm' : 'Restore Server'}
+                </Button>
+              </div>
+            ))}
+          </div>
+
           <div className="rounded-lg border border-white/10 bg-white/5 p-3">
             <div className="flex items-center justify-between">
               <span className="text-xs text-slate-400">Last Sync</span>
@@ -24,6 +24,8 @@ const { commandsMock, adaptivePollState } = vi.hoisted(() => ({
     importDefaultAccount: vi.fn(),
     getWebDavPassword: vi.fn(),
     setWebDavPassword: vi.fn(),
+    getWebDavConfig: vi.fn(),
+    setWebDavConfig: vi.fn(),
     getGatewayPlatformKey: vi.fn(),
     setGatewayPlatformKey: vi.fn(),
     testWebDavConnection: vi.fn(),
@@ -78,6 +80,7 @@ describe('useAccounts', () => {
     commandsMock.importDefaultAccount.mockResolvedValue(false);
     commandsMock.getWebDavPassword.mockResolvedValue(null);
     commandsMock.setWebDavPassword.mockResolvedValue(undefined);
+    commandsMock.getWebDavConfig.mockResolvedValue(null);
     commandsMock.getGatewayPlatformKey.mockResolvedValue(null);
     commandsMock.setGatewayPlatformKey.mockResolvedValue(undefined);
   });
@@ -23,8 +23,9 @@ import { commands } from '../lib/commands';
 import { useNotifications } from '../lib/notificationState';
 import { migrateLegacySecrets, loadStoredSettings, persistSettings } from '../lib/settingsStorage';
 import { loadUsageCache, saveUsageCache, type UsageCacheEntry } from '../lib/usageCache';
-import { toErrorMessage } from '../lib/errors';
+import { CommandError, toErrorMessage } from '../lib/errors';
 import { mapWithConcurrency } from '../lib/asyncPool';
+import { applyStoredWebDavConfig, buildWebDavRequestConfig, validateWebDavConfig } from '../lib/webdav';
 
 type AccountMutationKind = 'switch' | 'rename' | 'delete' | 'refresh-token';
 type FailedMutation =
@@ -58,6 +59,8 @@ export function useAccounts() {
 
     const [settings, setSettings] = useState<AppSettings>(() => loadStoredSettings());
     const settingsRef = useRef(settings);
+    // Last WebDAV settings saved to the backend; null until they were loaded from it
+    const savedWebDavRef = useRef<string | null>(null);
 
     useEffect(() => {
         settingsRef.current = settings;
@@ -66,7 +69,16 @@ export function useAccounts() {
     useEffect(() => {
         const migrateSecrets = async () => {
             try {
-                const next = await migrateLegacySecrets(settingsRef.current);
+                let next = await migrateLegacySecrets(settingsRef.current);
+                const stored = await commands.getWebDavConfig();
+                if (stored && next.webdav) {
+                    next = { ...next, webdav: applyStoredWebDavConfig(next.webdav, stored) };
+                }
+                if (next.webdav) {
+                    savedWebDavRef.current = stored
+                        ? JSON.stringify({ ...buildWebDavRequestConfig(next.webdav), password: '' })
+                        : '';
+                }
                 setSettings(next);
                 persistSettings(next);
             } catch (error) {
@@ -77,6 +89,18 @@ export function useAccounts() {
         void migrateSecrets();
     }, [notifyError]);
 
+    useEffect(() => {
+        const webdav = settings.webdav;
+        if (!webdav || savedWebDavRef.current === null || validateWebDavConfig(webdav)) return;
+        const request = { ...buildWebDavRequestConfig(webdav), password: '' };
+        const key = JSON.stringify(request);
+        if (key === savedWebDavRef.current) return;
+        savedWebDavRef.current = key;
+        void commands.setWebDavConfig(request).catch((error) => {
+            notifyError(toErrorMessage(error), 'WebDAV Settings');
+        });
+    }, [settings.webdav, notifyError]);
+
     const updateSettings = useCallback((newSettings: Partial<AppSettings>) => {
         const nextPassword = newSettings.webdav && Object.prototype.hasOwnProperty.call(newSettings.webdav, 'password')
             ? newSettings.webdav.password ?? ''
@@ -557,16 +581,19 @@ export function useAccounts() {
 
     const refreshAccountToken = useCallback(async (filePath: string): Promise<MutationResult> => {
         try {
-            const message = await withAccountMutationLock(filePath, 'refresh-token', async () => {
-                const result = await commands.refreshAccountToken(filePath);
+            const result = await withAccountMutationLock(filePath, 'refresh-token', async () => {
+                const refreshed = await commands.refreshAccountToken(filePath);
                 await refresh();
-                return result;
+                return refreshed;
             });
+            const message = result.activeSessionUpdated
+                ? 'Token refresh successful; active session refreshed'
+                : 'Token refresh successful';
             return { success: true, message };
         } catch (error: unknown) {
             const message = toErrorMessage(error);
             setLastFailedMutation({ kind: 'refresh-token', filePath, message });
-            return { success: false, message };
+            return { success: false, message, code: error instanceof CommandError ? error.code : undefined };
         }
     }, [refresh, withAccountMutationLock]);
 
@@ -1,13 +1,34 @@
 import { invoke } from '@tauri-apps/api/core';
 import type {
+  AccountInfo,
+  AccountRecommendation,
+  AccountUsageResult,
+  AddAccountResult,
+  ArchiveExportResult,
   BackendAppConfig,
+  BulkRefreshEntry,
   CodexSyncConfig,
+  CreditInfo,
+  ImportSummary,
+  LoginStart,
   PromptInfo,
+  ProxyConfig,
+  RefreshResult,
+  RevokeFollowUp,
+  RevokeResult,
   ScanResult,
   SkillInfo,
+  SnapshotInfo,
+  SnapshotTarget,
+  StoredWebDavConfig,
+  SwitchResult,
   SyncPreview,
   SyncResult,
+  SyncSide,
+  SyncStatus,
+  SyncStrategy,
   UsageInfo,
+  UsageSample,
 } from '../types';
 import { CommandError, toErrorMessage } from './errors';
 import {
@@ -22,7 +43,7 @@ function toCommandError(error: unknown): CommandError {
   const message = toErrorMessage(error);
   if (typeof error === 'object' && error !== null && 'code' in error) {
     const code = String((error as { code?: unknown }).code ?? '');
-    return new CommandError(message, code || undefined);
+    return new CommandError(message, code || undefined, error);
   }
   return new CommandError(message);
 }
@@ -39,32 +60,116 @@ export const commands = {
   openAccountsDir: () => invokeCommand<string>('open_accounts_dir'),
   openCodexDir: () => invokeCommand<string>('open_codex_dir'),
   scanAccounts: () => invokeCommand<ScanResult>('scan_accounts'),
-  fetchUsage: async (filePath: string) => normalizeUsageInfo(await invokeCommand<UsageInfo>('fetch_usage', { filePath })),
-  fetchActiveUsage: async () => normalizeUsageInfo(await invokeCommand<UsageInfo>('fetch_active_usage')),
-  switchAccount: (filePath: string) => invokeCommand<void>('switch_account', { filePath }),
+  fetchUsage: async (filePath: string, options?: { maxAgeSecs?: number; force?: boolean; includeRaw?: boolean }) =>
+    normalizeUsageInfo(await invokeCommand<UsageInfo>('fetch_usage', { filePath, ...options })),
+  fetchActiveUsage: async (includeRaw?: boolean) =>
+    normalizeUsageInfo(await invokeCommand<UsageInfo>('fetch_active_usage', { includeRaw })),
+  fetchAllUsage: (concurrency?: number) =>
+    invokeCommand<Record<string, AccountUsageResult>>('fetch_all_usage', { concurrency }),
+  fetchCredits: (filePath: string) => invokeCommand<CreditInfo | null>('fetch_credits', { filePath }),
+  recommendAccount: () => invokeCommand<AccountRecommendation[]>('recommend_account'),
+  getUsageHistory: (profileId: string, since?: number) =>
+    invokeCommand<UsageSample[]>('get_usage_history', { profileId, since }),
+  switchAccount: (filePath: string) => invokeCommand<SwitchResult>('switch_account', { filePath }),
   renameAccount: (oldPath: string, newName: string) => invokeCommand<void>('rename_account', { oldPath, newName }),
   getAccountsDirPath: () => invokeCommand<string>('get_accounts_dir_path'),
   getAppConfig: async () => normalizeBackendAppConfig(await invokeCommand<BackendAppConfig>('get_app_config')),
   setDebugLogging: async (enabled: boolean) =>
     normalizeBackendAppConfig(await invokeCommand<BackendAppConfig>('set_debug_logging', { enabled })),
+  setOAuthEndpoint: async (tokenRefreshUrl?: string, oauthClientId?: string, allowInsecureAuthUrl?: boolean) =>
+    normalizeBackendAppConfig(await invokeCommand<BackendAppConfig>('set_oauth_endpoint', {
+      tokenRefreshUrl,
+      oauthClientId,
+      allowInsecureAuthUrl,
+    })),
+  setUsageHistoryRetention: async (days: number) =>
+    normalizeBackendAppConfig(await invokeCommand<BackendAppConfig>('set_usage_history_retention', { days })),
+  setUsageEndpoints: async (endpoints?: string[]) =>
+    normalizeBackendAppConfig(await invokeCommand<BackendAppConfig>('set_usage_endpoints', { endpoints })),
+  setProxy: async (settings: ProxyConfig) =>
+    normalizeBackendAppConfig(await invokeCommand<BackendAppConfig>('set_proxy', { settings })),
+  getDeviceName: () => invokeCommand<string>('get_device_name'),
+  setDeviceName: async (name: string | null) =>
+    normalizeBackendAppConfig(await invokeCommand<BackendAppConfig>('set_device_name', { name })),
   setAccountsDir: (path: string) => invokeCommand<void>('set_accounts_dir', { path }),
-  addAccount: (name: string, content: string) => invokeCommand<void>('add_account', { name, content }),
-  deleteAccount: (filePath: string) => invokeCommand<void>('delete_account', { filePath }),
-  readAccountContent: (filePath: string) => invokeCommand<string>('read_account_content', { filePath }),
+  addAccount: (name: string, content: string, options?: { allowDuplicate?: boolean; overwrite?: boolean }) =>
+    invokeCommand<AddAccountResult>('add_account', { name, content, ...options }),
+  deleteAccount: (filePath: string, alsoClearActive?: boolean) =>
+    invokeCommand<void>('delete_account', { filePath, alsoClearActive }),
+  duplicateAccount: (filePath: string, newName: string) =>
+    invokeCommand<AccountInfo>('duplicate_account', { filePath, newName }),
+  moveAccount: (filePath: string, targetGroup: string) =>
+    invokeCommand<string>('move_account', { filePath, targetGroup }),
+  exportAccount: (filePath: string, targetPath: string, options?: { excludeRefreshToken?: boolean; overwrite?: boolean }) =>
+    invokeCommand<number>('export_account', { filePath, targetPath, ...options }),
+  exportAccountsArchive: (
+    targetPath: string,
+    options?: { includeArchived?: boolean; passphrase?: string; overwrite?: boolean },
+  ) => invokeCommand<ArchiveExportResult>('export_accounts_archive', { targetPath, ...options }),
+  importAccountsArchive: (path: string, options?: { overwrite?: boolean; passphrase?: string }) =>
+    invokeCommand<ImportSummary>('import_accounts_archive', { path, ...options }),
+  readAccountContent: (filePath: string, redacted?: boolean) =>
+    invokeCommand<string>('read_account_content', { filePath, redacted }),
   updateAccountContent: (filePath: string, content: string) => invokeCommand<void>('update_account_content', { filePath, content }),
-  refreshAccountToken: (filePath: string) => invokeCommand<string>('refresh_account_token', { filePath }),
+  startLogin: (overwrite?: boolean) => invokeCommand<LoginStart>('start_login', { overwrite }),
+  cancelLogin: () => invokeCommand<boolean>('cancel_login'),
+  cancelSync: () => invokeCommand<boolean>('cancel_sync'),
+  getSyncStatus: () => invokeCommand<SyncStatus>('get_sync_status'),
+  createSnapshot: (config: WebDavRequestConfig) => invokeCommand<SnapshotInfo>('webdav_create_snapshot', { config }),
+  listSnapshots: (config: WebDavRequestConfig) => invokeCommand<SnapshotInfo[]>('webdav_list_snapshots', { config }),
+  restoreSnapshot: (config: WebDavRequestConfig, name: string, target: SnapshotTarget) =>
+    invokeCommand<SyncResult>('webdav_restore_snapshot', { config, name, target }).then(normalizeSyncResult),
+  refreshAccountToken: (filePath: string) => invokeCommand<RefreshResult>('refresh_account_token', { filePath }),
+  queueRefresh: (filePath: string) => invokeCommand<boolean>('queue_refresh', { filePath }),
+  revokeAccount: (filePath: string, followUp?: RevokeFollowUp, clearActive?: boolean) =>
+    invokeCommand<RevokeResult>('revoke_account', { filePath, followUp, clearActive }),
+  refreshAllTokens: (onlyExpiring?: boolean) =>
+    invokeCommand<BulkRefreshEntry[]>('refresh_all_tokens', { onlyExpiring }),
+  restoreAccountBackup: (filePath: string) => invokeCommand<void>('restore_account_backup', { filePath }),
   importDefaultAccount: () => invokeCommand<boolean>('import_default_account'),
   getWebDavPassword: () => invokeCommand<string | null>('get_webdav_password'),
   setWebDavPassword: (password: string) => invokeCommand<void>('set_webdav_password', { password }),
+  getWebDavConfig: () => invokeCommand<StoredWebDavConfig | null>('get_webdav_config'),
+  setWebDavConfig: (config: WebDavRequestConfig) => invokeCommand<StoredWebDavConfig>('set_webdav_config', { config }),
+  hasSyncPassphrase: () => invokeCommand<boolean>('has_sync_passphrase'),
+  setSyncPassphrase: (passphrase: string) => invokeCommand<void>('set_sync_passphrase', { passphrase }),
   getGatewayPlatformKey: () => invokeCommand<string | null>('get_gateway_platform_key'),
   setGatewayPlatformKey: (platformKey: string) => invokeCommand<void>('set_gateway_platform_key', { platformKey }),
   testWebDavConnection: (config: WebDavRequestConfig) => invokeCommand<string>('webdav_test_connection', { config }),
   previewSync: (config: WebDavRequestConfig, syncConfig: CodexSyncConfig, syncAccounts: boolean) =>
     invokeCommand<SyncPreview>('webdav_sync_preview', { config, syncConfig, syncAccounts }).then(normalizeSyncPreview),
-  syncAccountsUpload: (config: WebDavRequestConfig) =>
-    invokeCommand<SyncResult>('webdav_sync_upload', { config }).then(normalizeSyncResult),
-  syncAccountsDownload: (config: WebDavRequestConfig) =>
-    invokeCommand<SyncResult>('webdav_sync_download', { config }).then(normalizeSyncResult),
+  syncAccountsUpload: (
+    config: WebDavRequestConfig,
+    strategy?: SyncStrategy,
+    propagateDeletes?: boolean,
+    dryRun?: boolean,
+  ) =>
+    invokeCommand<SyncResult>('webdav_sync_upload', { config, strategy, propagateDeletes, dryRun }).then(
+      normalizeSyncResult,
+    ),
+  syncAccountsDownload: (
+    config: WebDavRequestConfig,
+    strategy?: SyncStrategy,
+    propagateDeletes?: boolean,
+    dryRun?: boolean,
+  ) =>
+    invokeCommand<SyncResult>('webdav_sync_download', { config, strategy, propagateDeletes, dryRun }).then(
+      normalizeSyncResult,
+    ),
+  syncAccountsBidirectional: (
+    config: WebDavRequestConfig,
+    strategy?: SyncStrategy,
+    resolutions?: Record<string, SyncSide>,
+    propagateDeletes?: boolean,
+    dryRun?: boolean,
+  ) =>
+    invokeCommand<SyncResult>('webdav_sync_bidirectional', {
+      config,
+      strategy,
+      resolutions,
+      propagateDeletes,
+      dryRun,
+    }).then(normalizeSyncResult),
   syncCodexUpload: (config: WebDavRequestConfig, syncConfig: CodexSyncConfig) =>
     invokeCommand<SyncResult>('webdav_sync_codex_upload', { config, syncConfig }).then(normalizeSyncResult),
   syncCodexDownload: (config: WebDavRequestConfig, syncConfig: CodexSyncConfig) =>
@@ -51,6 +51,12 @@ describe('contracts', () => {
       uploaded: ['a'],
       downloaded: ['b'],
       errors: ['c'],
+      skipped: [],
+      conflicts: [],
+      deleted: [],
+      plan: [],
+      cancelled: false,
+      ignored: [],
     });
   });
 });
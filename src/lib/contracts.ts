@@ -47,6 +47,14 @@ export function normalizeBackendAppConfig(value: BackendAppConfig): BackendAppCo
     version: normalizeNumber(value.version) ?? 1,
     accountsDir: typeof value.accountsDir === 'string' ? value.accountsDir : undefined,
     debugLogging: Boolean(value.debugLogging),
+    tokenRefreshUrl: typeof value.tokenRefreshUrl === 'string' ? value.tokenRefreshUrl : undefined,
+    oauthClientId: typeof value.oauthClientId === 'string' ? value.oauthClientId : undefined,
+    allowInsecureAuthUrl: typeof value.allowInsecureAuthUrl === 'boolean' ? value.allowInsecureAuthUrl : undefined,
+    usageHistoryRetentionDays: normalizeNumber(value.usageHistoryRetentionDays),
+    usageEndpoints: Array.isArray(value.usageEndpoints)
+      ? value.usageEndpoints.filter((url): url is string => typeof url === 'string')
+      : undefined,
+    deviceName: typeof value.deviceName === 'string' ? value.deviceName : undefined,
   };
 }
 
@@ -55,6 +63,9 @@ export function normalizeUsageInfo(value: UsageInfo): UsageInfo {
     planType: typeof value.planType === 'string' ? value.planType : undefined,
     primaryWindow: normalizeUsageWindow(value.primaryWindow),
     secondaryWindow: normalizeUsageWindow(value.secondaryWindow),
+    fetchedAt: typeof value.fetchedAt === 'number' ? normalizeNumber(value.fetchedAt) : undefined,
+    endpointUsed: typeof value.endpointUsed === 'string' ? value.endpointUsed : undefined,
+    raw: value.raw,
   };
 }
 
@@ -82,5 +93,29 @@ export function normalizeSyncResult(value: SyncResult): SyncResult {
     uploaded: Array.isArray(value.uploaded) ? value.uploaded.map((entry) => String(entry)) : [],
     downloaded: Array.isArray(value.downloaded) ? value.downloaded.map((entry) => String(entry)) : [],
     errors: Array.isArray(value.errors) ? value.errors.map((entry) => String(entry)) : [],
+    skipped: Array.isArray(value.skipped) ? value.skipped.map((entry) => String(entry)) : [],
+    conflicts: Array.isArray(value.conflicts)
+      ? value.conflicts.map((conflict) => ({
+          name: String(conflict.name),
+          localModifiedAt: normalizeNumber(conflict.localModifiedAt ?? undefined),
+          remoteModifiedAt: normalizeNumber(conflict.remoteModifiedAt ?? undefined),
+          remoteDevice: typeof conflict.remoteDevice === 'string' ? conflict.remoteDevice : undefined,
+          overwritten: Boolean(conflict.overwritten),
+        }))
+      : [],
+    deleted: Array.isArray(value.deleted) ? value.deleted.map((entry) => String(entry)) : [],
+    plan: Array.isArray(value.plan)
+      ? value.plan.map((item) => ({
+          name: String(item.name),
+          action: item.action,
+          side: item.side ?? undefined,
+          localSize: normalizeNumber(item.localSize ?? undefined),
+          remoteSize: normalizeNumber(item.remoteSize ?? undefined),
+          localModifiedAt: normalizeNumber(item.localModifiedAt ?? undefined),
+          remoteModifiedAt: normalizeNumber(item.remoteModifiedAt ?? undefined),
+        }))
+      : [],
+    cancelled: value.cancelled === true,
+    ignored: Array.isArray(value.ignored) ? value.ignored.map((entry) => String(entry)) : [],
   };
 }
@@ -1,4 +1,4 @@
-import { confirm, message } from '@tauri-apps/plugin-dialog';
+import { confirm, message, save } from '@tauri-apps/plugin-dialog';
 import { toErrorMessage } from './errors';
 
 export async function showError(error: unknown, title = 'Code Revolver'): Promise<void> {
@@ -12,3 +12,7 @@ export async function showInfo(text: string, title = 'Code Revolver'): Promise<v
 export async function confirmAction(text: string, title = 'Confirm'): Promise<boolean> {
   return await confirm(text, { title });
 }
+
+export async function pickExportPath(defaultPath: string): Promise<string | null> {
+  return await save({ defaultPath, filters: [{ name: 'JSON', extensions: ['json'] }] });
+}
@@ -1,10 +1,13 @@
 export class CommandError extends Error {
   code?: string;
+  /** The raw error payload, for commands that return structured errors such as `UsageError`. */
+  details?: unknown;
 
-  constructor(message: string, code?: string) {
+  constructor(message: string, code?: string, details?: unknown) {
     super(message);
     this.name = 'CommandError';
     this.code = code;
+    this.details = details;
   }
 }
 
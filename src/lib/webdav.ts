@@ -1,4 +1,4 @@
-import { WebDavConfig } from '../types';
+import { StoredWebDavConfig, WebDavConfig } from '../types';
 import { commands } from './commands';
 
 export interface WebDavRequestConfig {
@@ -6,6 +6,10 @@ export interface WebDavRequestConfig {
   username: string;
   password: string;
   remotePath: string;
+  concurrency?: number;
+  allowInvalidCerts: boolean;
+  customCaPath?: string;
+  snapshotRetention?: number;
 }
 
 export function hasWebDavCredentials(config: WebDavConfig): boolean {
@@ -59,6 +63,24 @@ export function buildWebDavRequestConfig(config: WebDavConfig): WebDavRequestCon
     username: config.username.trim(),
     password: config.password,
     remotePath: normalizeWebDavRemotePath(config.remotePath),
+    concurrency: config.concurrency,
+    allowInvalidCerts: Boolean(config.allowInvalidCerts),
+    customCaPath: config.customCaPath?.trim() || undefined,
+    snapshotRetention: config.snapshotRetention,
+  };
+}
+
+export function applyStoredWebDavConfig(config: WebDavConfig, stored: StoredWebDavConfig): WebDavConfig {
+  return {
+    ...config,
+    url: stored.url,
+    username: stored.username,
+    remotePath: stored.remotePath || config.remotePath,
+    concurrency: stored.concurrency ?? undefined,
+    allowInvalidCerts: stored.allowInvalidCerts,
+    customCaPath: stored.customCaPath ?? undefined,
+    snapshotRetention: stored.snapshotRetention ?? undefined,
+    hasStoredPassword: stored.hasPassword,
   };
 }
 
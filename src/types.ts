@@ -10,6 +10,73 @@ export interface UsageInfo {
         resetsAt?: number;
     };
     planType?: string;
+    fetchedAt?: number;
+    endpointUsed?: string;
+    raw?: unknown;
+}
+
+export interface CreditGrant {
+    grantAmount: number;
+    usedAmount: number;
+    effectiveAt?: number | null;
+    expiresAt?: number | null;
+}
+
+export interface CreditInfo {
+    granted: number;
+    used: number;
+    remaining: number;
+    expiresAt?: number | null;
+    grants: CreditGrant[];
+}
+
+export type UsageErrorKind =
+    | 'unauthorized'
+    | 'forbidden'
+    | 'rate_limited'
+    | 'server_error'
+    | 'http_error'
+    | 'network'
+    | 'parse'
+    | 'local';
+
+export interface UsageError {
+    code: string;
+    kind: UsageErrorKind;
+    message: string;
+    httpStatus?: number | null;
+    retryAfterSecs?: number | null;
+    body?: string | null;
+}
+
+export interface AccountRecommendation {
+    rank: number;
+    profileId: string;
+    name: string;
+    email: string;
+    filePath: string;
+    plan: PlanKind;
+    tier: number;
+    primaryRemainingPercent?: number | null;
+    secondaryRemainingPercent?: number | null;
+    usage?: UsageInfo | null;
+    error?: string | null;
+    errorKind?: UsageErrorKind | null;
+}
+
+export interface AccountUsageResult {
+    usage: UsageInfo | null;
+    error: string | null;
+    errorKind?: UsageErrorKind | null;
+    needsRefresh: boolean;
+}
+
+export interface UsageProgress {
+    profileId: string;
+    filePath: string;
+    done: number;
+    total: number;
+    result: AccountUsageResult;
 }
 
 export interface AccountInfo {
@@ -19,6 +86,9 @@ export interface AccountInfo {
     email: string;
     planType: string;
     subscriptionEnd: string | null;
+    subscriptionEndsAt?: number | null;
+    subscriptionDaysLeft?: number | null;
+    subscriptionExpired?: boolean;
     isActive: boolean;
     filePath: string;
     authUpdatedAt: number;
@@ -27,6 +97,29 @@ export interface AccountInfo {
     lastRefresh: string;
     lastUsageUpdate?: number;
     isTokenExpired?: boolean;
+    tokenStatus?: 'valid' | 'expiring' | 'expired' | 'unknown';
+    needsRefresh?: boolean;
+    duplicateOf?: string | null;
+    notes?: string | null;
+    configOverride?: string | null;
+    refreshDead?: boolean;
+    tags?: string[];
+    isArchived?: boolean;
+    sortIndex?: number;
+    group?: string | null;
+    fileSize?: number;
+    contentHash?: string | null;
+    refreshState?: RefreshState | null;
+    lastRefreshError?: string | null;
+    coolingDownUntil?: number | null;
+    isCoolingDown?: boolean;
+    kind?: 'chatgpt' | 'apiKey';
+    apiKeyHint?: string | null;
+    lastUsedAt?: number | null;
+    switchCount?: number;
+    lastRefreshAt?: number | null;
+    isStale?: boolean;
+    plan?: PlanInfo;
     pool?: AccountPoolMetadata;
 }
 
@@ -54,15 +147,217 @@ export interface GatewaySettings {
     status: 'idle' | 'online' | 'offline';
 }
 
+export interface ScanError {
+    filePath: string;
+    reason: string;
+}
+
 export interface ScanResult {
     accounts: AccountInfo[];
     accountsDir: string;
+    errors?: ScanError[];
+    groups?: GroupInfo[];
+}
+
+export interface GroupInfo {
+    name: string;
+    path: string;
+    accountCount: number;
 }
 
 export interface BackendAppConfig {
     version: number;
     accountsDir?: string;
     debugLogging: boolean;
+    tokenRefreshThresholdMinutes?: number;
+    accountOrder?: string[];
+    switchHistoryLimit?: number;
+    staleAfterDays?: number;
+    authBackupLimit?: number;
+    switchRefreshWindowMinutes?: number;
+    preserveApiKeyOnSwitch?: boolean;
+    switchMode?: 'copy' | 'symlink';
+    postSwitchCommand?: string | null;
+    autoSwitch?: AutoSwitchConfig;
+    backgroundRefresh?: BackgroundRefreshConfig;
+    tokenRefreshUrl?: string;
+    oauthClientId?: string;
+    allowInsecureAuthUrl?: boolean;
+    usageHistoryRetentionDays?: number;
+    usageAlerts?: UsageAlertConfig;
+    usageEndpoints?: string[] | null;
+    trayUsage?: TrayUsageConfig;
+    proxy?: ProxyConfig;
+    deviceName?: string | null;  // Recorded with uploaded files; the hostname when unset
+}
+
+export type ProxyMode = 'system' | 'none' | 'manual';
+
+export interface ProxyConfig {
+    mode: ProxyMode;
+    url?: string | null;      // http://, https:// or socks5:// proxy URL (manual mode)
+    noProxy?: string | null;  // Comma-separated hosts reached directly
+}
+
+export interface TrayUsageConfig {
+    enabled: boolean;
+    intervalMinutes: number;
+}
+
+export interface BackgroundRefreshConfig {
+    enabled: boolean;
+    intervalMinutes: number;
+    leadMinutes: number;
+}
+
+export interface AutoSwitchConfig {
+    enabled: boolean;
+    thresholdPercent: number;
+    pollIntervalMinutes: number;
+    cooldownMinutes: number;
+}
+
+export interface UsageAlertConfig {
+    enabled: boolean;
+    primaryPercent?: number | null;
+    secondaryPercent?: number | null;
+    pollIntervalMinutes: number;
+}
+
+export interface UsageThresholdEvent {
+    profileId: string;
+    name: string;
+    window: 'primary' | 'secondary';
+    usedPercent: number;
+    thresholdPercent: number;
+    resetsAt?: number | null;
+}
+
+export interface AutoSwitchEvent {
+    reason: string;
+    usedPercent: number;
+    from?: string | null;
+    to: AccountInfo;
+}
+
+export interface ImportSummary {
+    imported: string[];
+    skipped: ScanError[];
+    errors: ScanError[];
+}
+
+export type RefreshState = 'queued' | 'inProgress' | 'succeeded' | 'failed';
+
+export interface RefreshStateEvent {
+    filePath: string;
+    state: RefreshState;
+    error: string | null;
+}
+
+export type RefreshErrorKind = 'expired' | 'reused' | 'invalidated' | 'network' | 'other';
+
+export interface RefreshResult {
+    expiresAt: number | null;
+    lastRefresh: string;
+    refreshTokenRotated: boolean;
+    email: string;
+    planType: string;
+    activeSessionUpdated: boolean;
+}
+
+export interface BulkRefreshEntry {
+    name: string;
+    filePath: string;
+    outcome: 'refreshed' | 'skipped' | 'failed';
+    error: string | null;
+    errorKind: RefreshErrorKind | null;
+    expiresAt: number | null;
+}
+
+export interface TokenRefreshFailedEvent {
+    name: string;
+    filePath: string;
+    error: string;
+    errorKind: RefreshErrorKind;
+}
+
+export interface BulkRefreshProgress {
+    done: number;
+    total: number;
+    name: string;
+}
+
+export type RevocationOutcome = 'revoked' | 'rejected' | 'networkError' | 'skipped';
+
+export interface TokenRevocation {
+    outcome: RevocationOutcome;
+    message: string | null;
+}
+
+export type RevokeFollowUp = 'keep' | 'archive' | 'delete';
+
+export interface RevokeResult {
+    refreshToken: TokenRevocation;
+    accessToken: TokenRevocation;
+    wasActive: boolean;
+    activeCleared: boolean;
+    followUp: RevokeFollowUp;
+    filePath: string | null;
+}
+
+export interface ArchiveExportResult {
+    count: number;
+    size: number;
+    skipped: ScanError[];
+}
+
+export interface AddAccountResult {
+    outcome: 'created' | 'updated';
+    filePath: string;
+}
+
+export interface LoginStart {
+    authUrl: string;
+    redirectUri: string;
+}
+
+export interface LoginCompleteEvent extends AddAccountResult {
+    email: string;
+}
+
+export interface LoginFailedEvent {
+    kind: 'timeout' | 'cancelled' | 'denied' | 'failed';
+    message: string;
+}
+
+export interface PermissionIssue {
+    filePath: string;
+    mode: string;
+}
+
+export interface HookResult {
+    command: string;
+    exitCode?: number | null;
+    stderr: string;
+    timedOut: boolean;
+    error?: string | null;
+}
+
+/** The hook's `HookResult` arrives later as the `post-switch-hook` event. */
+export interface SwitchResult extends AccountInfo {
+    postSwitchHookStarted: boolean;
+}
+
+export interface ActiveSyncResult {
+    filePath?: string | null;
+    updatedFields: string[];
+}
+
+export interface AuthBackup {
+    file: string;
+    createdAt: number;
+    accountId?: string | null;
+    email?: string | null;
 }
 
 export interface WebDavConfig {
@@ -72,6 +367,26 @@ export interface WebDavConfig {
     password: string;   // Session value, persisted securely outside localStorage
     hasStoredPassword?: boolean;
     remotePath: string; // Remote directory path, e.g., /code-revolver/
+    concurrency?: number; // Files transferred at once within a folder; defaults to 4
+    allowInvalidCerts?: boolean; // Skip TLS certificate checks (self-signed servers); off by default
+    customCaPath?: string;       // Extra trusted CA certificate (PEM or DER)
+    syncPassphrase?: string;     // Overrides the saved encryption passphrase for one sync; never persisted
+    snapshotRetention?: number;  // Remote snapshots kept; defaults to 10
+}
+
+export type PasswordStorage = 'keyring' | 'file';
+
+// WebDAV settings saved by the backend, used by syncs started without a config
+export interface StoredWebDavConfig {
+    url: string;
+    username: string;
+    remotePath: string;
+    concurrency?: number | null;
+    allowInvalidCerts: boolean;
+    customCaPath?: string | null;
+    snapshotRetention?: number | null;
+    hasPassword: boolean;
+    passwordStorage?: PasswordStorage | null;  // 'file' when no OS keyring was available
 }
 
 export interface SyncSettings {
@@ -186,13 +501,141 @@ export interface SyncPreview {
     conflictCount: number;
 }
 
+export type SyncStrategy = 'newer_wins' | 'local_wins' | 'remote_wins' | 'force';
+
+export type SyncSide = 'local' | 'remote';
+
+export interface SyncConflict {
+    /** Path below `accounts/`. */
+    name: string;
+    localModifiedAt?: number | null;
+    remoteModifiedAt?: number | null;
+    /** Device that last uploaded the remote copy. */
+    remoteDevice?: string | null;
+    /** The local change was downloaded over; a warning rather than an unresolved conflict. */
+    overwritten?: boolean;
+}
+
+export type SyncPlanAction = 'upload' | 'download' | 'skip' | 'delete' | 'conflict';
+
+export interface SyncPlanItem {
+    /** Path below `accounts/`. */
+    name: string;
+    action: SyncPlanAction;
+    /** Copy removed by a `delete`. */
+    side?: SyncSide | null;
+    localSize?: number | null;
+    remoteSize?: number | null;
+    localModifiedAt?: number | null;
+    remoteModifiedAt?: number | null;
+}
+
 export interface SyncResult {
     uploaded: string[];
     downloaded: string[];
     errors: string[];
+    /** Changed files the sync strategy kept on the other side. */
+    skipped: string[];
+    /** Files changed on both sides since the last bidirectional sync; neither copy was touched. */
+    conflicts: SyncConflict[];
+    /** Files removed because they were deleted on another machine after their last change. */
+    deleted: string[];
+    /** Account files the sync would change, or changed; a dry run fills only this. */
+    plan: SyncPlanItem[];
+    /** `cancel_sync` stopped the sync; the other fields list what was done before. */
+    cancelled: boolean;
+    /** Prompt and skill paths left out by `.syncignore`, the built-in patterns or the size limit. */
+    ignored: string[];
+}
+
+export type SyncPhase = 'listing' | 'uploading' | 'downloading' | 'deleting';
+
+/** Payload of `sync-progress`. */
+export interface SyncProgressEvent {
+    sessionId: number;
+    phase: SyncPhase;
+    /** File being transferred, or the remote folder being listed. */
+    name: string;
+    /** 1-based position of the file; 0 while listing. */
+    index: number;
+    /** Files known so far; grows while a download lists remote folders. */
+    total: number;
+}
+
+/** Remote copy of the `accounts/` folder under `snapshots/<name>/`. */
+export interface SnapshotInfo {
+    /** UTC timestamp, e.g. `20261016T104931.123Z`. */
+    name: string;
+    createdAt: number;
+}
+
+export type SnapshotTarget = 'local' | 'remote';
+
+/** Result of `get_sync_status`; only one sync runs at a time. */
+export interface SyncStatus {
+    state: 'idle' | 'running';
+    /** Last progress event of the running sync. */
+    progress: SyncProgressEvent | null;
+}
+
+/** Payload of `sync-complete`, and of `sync-cancelled` when the sync was cancelled. */
+export interface SyncCompleteEvent {
+    sessionId: number;
+    result: SyncResult;
 }
 
 export interface MutationResult {
     success: boolean;
     message?: string;
+    /** Backend error code, e.g. `REFRESH_DEAD` when the account must log in again. */
+    code?: string;
+}
+
+export type SwitchSource = 'ui' | 'tray' | 'cli' | 'auto';
+
+export interface SwitchHistoryEntry {
+    timestamp: number;
+    profileId: string;
+    name: string;
+    source: SwitchSource;
+}
+
+export interface UsageSample {
+    timestamp: number;
+    profileId: string;
+    primaryUsedPercent?: number | null;
+    primaryResetsAt?: number | null;
+    secondaryUsedPercent?: number | null;
+    secondaryResetsAt?: number | null;
+}
+
+export type PlanKind = 'free' | 'plus' | 'pro' | 'team' | 'business' | 'enterprise' | 'edu' | 'unknown';
+
+export interface PlanInfo {
+    raw: string;
+    kind: PlanKind;
+    displayName: string;
+    isTeamOrEnterprise: boolean;
+}
+
+export type HealthStatus = 'ok' | 'unauthorized' | 'rate_limited' | 'network_error';
+
+export interface AccountHealth {
+    status: HealthStatus;
+    httpStatus?: number | null;
+    latencyMs: number;
+    message?: string | null;
+}
+
+export interface AccountFilters {
+    planType?: string | null;
+    activeOnly?: boolean;
+    expiredOnly?: boolean;
+    includeArchived?: boolean;
+}
+
+export interface AccountStats {
+    profileId: string;
+    switchCount: number;
+    activeDurationMs: number;
 }